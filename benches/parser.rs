@@ -0,0 +1,145 @@
+//! Parser and loader benchmarks.
+
+#[macro_use]
+extern crate criterion;
+extern crate fbxcel;
+
+use std::io::Cursor;
+use criterion::{BenchmarkId, Criterion, Throughput};
+use fbxcel::parser::binary::{RootParser, Parser, ParserSource, Event, SubtreeParser};
+use fbxcel::parser::binary::{Attribute, ArrayAttribute};
+use fbxcel::loader::binary::simple::{FbxLoader, GenericNode, Result};
+use fbxcel::loader::binary::simple::fbx7400::{LoadObjects7400, ObjectProperties};
+use fbxcel::loader::binary::simple::fbx7400::{NodesBeforeObjects, ObjectPropertiesRef};
+use fbxcel::test_util::synthetic_fbx;
+
+
+/// Sizes of synthetic FBX data: (name, number of objects, number of vertices per object).
+const SIZES: &[(&str, usize, usize)] = &[
+    ("small", 16, 64),
+    ("medium", 256, 256),
+    ("large", 64, 16384),
+];
+
+
+/// Objects loader which loads objects as generic nodes.
+struct GenericObjectsLoader<R>(Vec<(ObjectProperties, Vec<GenericNode>)>, ::std::marker::PhantomData<R>);
+
+impl<R: ParserSource> LoadObjects7400 for GenericObjectsLoader<R> {
+    type Reader = R;
+    type Objects = Vec<(ObjectProperties, Vec<GenericNode>)>;
+
+    fn build(self) -> Result<Self::Objects> {
+        Ok(self.0)
+    }
+
+    fn load(
+        &mut self,
+        props: ObjectPropertiesRef<'_>,
+        subtree_parser: &mut SubtreeParser<R>,
+        _nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<()> {
+        let nodes = GenericNode::load_from_parser(subtree_parser)?.0;
+        self.0.push((props.into_owned(), nodes));
+        Ok(())
+    }
+}
+
+
+/// Reads all events and attributes.
+fn pull_parse(data: &[u8]) -> usize {
+    let mut parser = RootParser::new(Cursor::new(data));
+    let mut count = 0;
+    loop {
+        match parser.next_event().expect("Parse error") {
+            Event::EndFbx(_) => break,
+            Event::StartNode(mut node) => {
+                while let Some(attr) = node.attributes.next_attribute().expect("Parse error") {
+                    if let Attribute::Array(ArrayAttribute::F64(arr)) = attr {
+                        count += arr.into_vec().expect("Parse error").len();
+                    }
+                }
+            },
+            _ => {},
+        }
+        count += 1;
+    }
+    count
+}
+
+
+fn bench_pull_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pull_parse");
+    for &(name, objs, verts) in SIZES {
+        let data = synthetic_fbx(7400, objs, verts, false);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &data, |b, data| {
+            b.iter(|| pull_parse(data))
+        });
+    }
+    group.finish();
+}
+
+
+fn bench_generic_node(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generic_node");
+    for &(name, objs, verts) in SIZES {
+        let data = synthetic_fbx(7400, objs, verts, false);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &data, |b, data| {
+            b.iter(|| {
+                let mut parser = RootParser::new(Cursor::new(&data[..]));
+                GenericNode::load_from_parser(&mut parser).expect("Parse error")
+            })
+        });
+    }
+    group.finish();
+}
+
+
+fn bench_load_objects(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load_objects");
+    for &(name, objs, verts) in SIZES {
+        let data = synthetic_fbx(7400, objs, verts, false);
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &data, |b, data| {
+            b.iter(|| {
+                let parser = RootParser::new(Cursor::new(&data[..]));
+                match FbxLoader::load_from_parser(parser).expect("Load error") {
+                    FbxLoader::Fbx7400(loader) => {
+                        loader
+                            .load(GenericObjectsLoader(Vec::new(), Default::default()))
+                            .expect("Load error")
+                    },
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+
+fn bench_array_decompression(c: &mut Criterion) {
+    let mut group = c.benchmark_group("array_decompression");
+    for &(name, objs, verts) in SIZES {
+        for &compress in &[false, true] {
+            let data = synthetic_fbx(7400, objs, verts, compress);
+            let id = format!("{}/{}", name, if compress { "zlib" } else { "raw" });
+            group.throughput(Throughput::Elements((objs * verts * 3) as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(id), &data, |b, data| {
+                b.iter(|| pull_parse(data))
+            });
+        }
+    }
+    group.finish();
+}
+
+
+criterion_group!(
+    benches,
+    bench_pull_parse,
+    bench_generic_node,
+    bench_load_objects,
+    bench_array_decompression
+);
+criterion_main!(benches);
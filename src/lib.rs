@@ -1,6 +1,17 @@
 //! Excellent FBX loader for Rust programming language.
+//!
+//! # Features
+//!
+//! With `tracing` feature, `tracing` spans are entered for each toplevel node and each object
+//! load.
+//! Diagnostics are emitted as `log` records regardless of the feature.
+//!
+//! With `rayon` feature, objects of in-memory FBX data can be loaded in parallel (see
+//! `IndexedFile::load_objects_parallel()`).
 #![warn(missing_docs)]
 
+#[cfg(feature = "cgmath")]
+extern crate cgmath;
 #[cfg(feature = "flate2")]
 extern crate flate2;
 extern crate fnv;
@@ -8,6 +19,20 @@ extern crate fnv;
 extern crate libflate;
 #[macro_use]
 extern crate log;
+#[cfg(feature = "mint")]
+extern crate mint;
+#[cfg(feature = "nalgebra")]
+extern crate nalgebra;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "tracing")]
+#[macro_use(debug_span, info_span)]
+extern crate tracing;
 
 pub mod loader;
 pub mod parser;
+pub mod prelude;
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+pub mod test_util;
+pub mod writer;
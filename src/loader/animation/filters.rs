@@ -0,0 +1,100 @@
+//! Animation curve filters.
+//!
+//! These filters modify curves before evaluation, as `FbxAnimCurveFilterUnroll` and
+//! `FbxAnimCurveFilterResample` of FBX SDK do.
+//! Rotation curves should be unrolled before resampling, because keys interpolated across a
+//! discontinuity spin the other way around.
+
+use loader::binary::simple::fbx7400::AnimationCurve;
+use loader::time::FbxTime;
+use super::{frame_count, frame_time};
+
+
+/// Default tolerance of `unroll()`, in degrees.
+pub const DEFAULT_UNROLL_TOLERANCE: f64 = 0.25;
+
+
+/// Unrolls the Euler angles (in degrees) of consecutive samples.
+///
+/// Each value is shifted by multiples of 360 degrees so that it differs from the previous
+/// (unrolled) value by at most 180 degrees.
+/// Differences within `180 + tolerance` degrees are kept as is.
+pub fn unroll_angles(values: &mut [f64], tolerance: f64) {
+    for i in 1..values.len() {
+        let diff = values[i] - values[i - 1];
+        if diff.abs() > 180.0 + tolerance {
+            values[i] -= 360.0 * (diff / 360.0).round();
+        }
+    }
+}
+
+
+/// Unrolls the key values of the rotation curve.
+///
+/// See `unroll_angles()` for details.
+pub fn unroll(curve: &mut AnimationCurve, tolerance: f64) {
+    let mut values = curve.key_values.iter().map(|&v| v as f64).collect::<Vec<_>>();
+    unroll_angles(&mut values, tolerance);
+    for (key, value) in curve.key_values.iter_mut().zip(values) {
+        *key = value as f32;
+    }
+}
+
+
+/// Returns the curve sampled at `sample_rate` frames per second.
+///
+/// Keys are placed at the frames from `start` to `stop` (including both ends), in the same
+/// way as `bake()` samples frames.
+/// Returns `None` if `sample_rate` is not positive and finite, or the number of frames exceeds
+/// `MAX_FRAMES`.
+pub fn resample(
+    curve: &AnimationCurve,
+    start: FbxTime,
+    stop: FbxTime,
+    sample_rate: f64,
+) -> Option<AnimationCurve> {
+    let key_times = (0..frame_count(start, stop, sample_rate)?)
+        .map(|frame| frame_time(start, frame, sample_rate))
+        .collect::<Vec<_>>();
+    let key_values = key_times.iter().map(|&t| curve.evaluate(t) as f32).collect();
+    Some(AnimationCurve {
+        props: curve.props.clone(),
+        default: curve.default,
+        key_times: key_times,
+        key_values: key_values,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::{AnimationCurve, ObjectProperties};
+    use loader::time::FbxTime;
+    use super::{DEFAULT_UNROLL_TOLERANCE, resample, unroll};
+
+    #[test]
+    fn test_unroll_and_resample() {
+        const SECOND: i64 = FbxTime::TICKS_PER_SECOND;
+
+        let mut curve = AnimationCurve {
+            props: ObjectProperties {
+                id: 1,
+                name: "".to_owned(),
+                class: "AnimCurve".to_owned(),
+                subclass: "".to_owned(),
+            },
+            default: 0.0,
+            key_times: (0..4).map(|i| FbxTime(i * SECOND)).collect(),
+            key_values: vec![170.0, -170.0, 170.0, 530.0],
+        };
+        unroll(&mut curve, DEFAULT_UNROLL_TOLERANCE);
+        assert_eq!(curve.key_values, vec![170.0, 190.0, 170.0, 170.0]);
+
+        assert!(resample(&curve, FbxTime(0), FbxTime(SECOND), 0.0).is_none());
+        assert!(resample(&curve, FbxTime(i64::MIN), FbxTime(i64::MAX), 4.0).is_none());
+        let resampled = resample(&curve, FbxTime(0), FbxTime(SECOND), 4.0).expect("Valid rate");
+        assert_eq!(resampled.key_times.len(), 5);
+        assert_eq!(resampled.key_times[4], FbxTime(SECOND));
+        assert_eq!(resampled.key_values, vec![170.0, 175.0, 180.0, 185.0, 190.0]);
+    }
+}
@@ -0,0 +1,764 @@
+//! Animation evaluation and baking.
+
+use fnv::FnvHashMap;
+use loader::binary::simple::fbx7400::{Objects, Object, Connections, Properties70};
+use loader::binary::simple::fbx7400::{AnimationStack, AnimationCurve};
+use loader::math::{Matrix4, RotationOrder, Vector3, euler_to_matrix};
+use loader::time::FbxTime;
+
+pub mod filters;
+pub mod tracks;
+
+
+/// Maximum number of frames sampled by `bake()` and `filters::resample()`.
+pub const MAX_FRAMES: usize = 1 << 20;
+
+
+/// Sampled transforms of a node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedNode {
+    /// Object ID of the model.
+    pub id: i64,
+    /// Local transform matrices for each frame.
+    pub local: Vec<Matrix4>,
+    /// Global transform matrices for each frame, if requested.
+    pub global: Option<Vec<Matrix4>>,
+}
+
+
+/// Transforms sampled over the time span of an animation stack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedAnimation {
+    /// Time of the first frame.
+    pub start: FbxTime,
+    /// Number of frames per second.
+    pub sample_rate: f64,
+    /// Number of frames.
+    pub num_frames: usize,
+    /// Sampled nodes, in the order of the requested node IDs.
+    pub nodes: Vec<BakedNode>,
+}
+
+impl BakedAnimation {
+    /// Returns the time of the given frame.
+    pub fn time(&self, frame: usize) -> FbxTime {
+        frame_time(self.start, frame, self.sample_rate)
+    }
+
+    /// Returns the sampled node with the given ID.
+    pub fn node(&self, id: i64) -> Option<&BakedNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+}
+
+
+/// Bakes local transforms of the given models animated by the layer.
+///
+/// Frames are sampled at `sample_rate` frames per second over the local time span of the
+/// stack (or the reference time span if the local one is unavailable), including both ends.
+/// Components without curves use the `d|X`, `d|Y`, and `d|Z` properties of the curve node.
+/// Properties not animated by the layer use the values of the model, and missing properties
+/// use the FBX defaults (property templates in `Definitions` are not consulted).
+///
+/// Returns `None` if `sample_rate` is not positive and finite, or the number of frames exceeds
+/// `MAX_FRAMES`.
+pub fn bake(
+    objects: &Objects,
+    connections: &Connections,
+    stack: &AnimationStack,
+    layer: i64,
+    node_ids: &[i64],
+    sample_rate: f64,
+) -> Option<BakedAnimation> {
+    bake_impl(objects, connections, stack, layer, node_ids, sample_rate, false)
+}
+
+
+/// Bakes local and global transforms of the given models animated by the layer.
+///
+/// Global transforms are composed along the `Model` parents, which are also evaluated with the
+/// layer, according to `InheritType` of the models.
+/// See `bake()` for details.
+pub fn bake_with_globals(
+    objects: &Objects,
+    connections: &Connections,
+    stack: &AnimationStack,
+    layer: i64,
+    node_ids: &[i64],
+    sample_rate: f64,
+) -> Option<BakedAnimation> {
+    bake_impl(objects, connections, stack, layer, node_ids, sample_rate, true)
+}
+
+
+/// Bakes transforms.
+fn bake_impl(
+    objects: &Objects,
+    connections: &Connections,
+    stack: &AnimationStack,
+    layer: i64,
+    node_ids: &[i64],
+    sample_rate: f64,
+    global: bool,
+) -> Option<BakedAnimation> {
+    let (start, stop) = match (stack.local_start(), stack.local_stop()) {
+        (Some(start), Some(stop)) => (start, stop),
+        _ => (
+            stack.reference_start().unwrap_or_default(),
+            stack.reference_stop().unwrap_or_default(),
+        ),
+    };
+    let num_frames = frame_count(start, stop, sample_rate)?;
+    let mut baked = BakedAnimation {
+        start: start,
+        sample_rate: sample_rate,
+        num_frames: num_frames,
+        nodes: node_ids
+            .iter()
+            .map(|&id| BakedNode {
+                id: id,
+                local: Vec::with_capacity(num_frames),
+                global: if global { Some(Vec::with_capacity(num_frames)) } else { None },
+            })
+            .collect(),
+    };
+
+    let evaluator = Evaluator::new(objects, connections, layer);
+    for frame in 0..num_frames {
+        let time = baked.time(frame);
+        let mut globals = FnvHashMap::default();
+        for node in &mut baked.nodes {
+            node.local.push(evaluator.local(node.id, time));
+            if let Some(ref mut g) = node.global {
+                g.push(evaluator.global(node.id, time, &mut globals));
+            }
+        }
+    }
+    Some(baked)
+}
+
+
+/// Returns the number of frames sampled over the time span, including both ends.
+///
+/// Returns `None` if `sample_rate` is not positive and finite, or the number of frames exceeds
+/// `MAX_FRAMES`.
+fn frame_count(start: FbxTime, stop: FbxTime, sample_rate: f64) -> Option<usize> {
+    if sample_rate <= 0.0 || !sample_rate.is_finite() {
+        return None;
+    }
+    if stop < start {
+        return Some(1);
+    }
+    let frames = (stop.checked_sub(start)?.seconds() * sample_rate + 1e-6).floor();
+    if frames >= MAX_FRAMES as f64 {
+        return None;
+    }
+    Some(frames as usize + 1)
+}
+
+
+/// Returns the time of the frame sampled from `start`.
+///
+/// The time saturates instead of overflowing.
+fn frame_time(start: FbxTime, frame: usize, sample_rate: f64) -> FbxTime {
+    let offset = FbxTime::from_seconds(frame as f64 / sample_rate);
+    FbxTime(start.ticks().saturating_add(offset.ticks()))
+}
+
+
+/// Transform property animated by a curve node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Channel {
+    /// `Lcl Translation`.
+    Translation,
+    /// `Lcl Rotation`.
+    Rotation,
+    /// `Lcl Scaling`.
+    Scaling,
+}
+
+
+impl Channel {
+    /// Creates a `Channel` from the property name.
+    pub fn from_property(name: &str) -> Option<Self> {
+        match name {
+            "Lcl Translation" => Some(Channel::Translation),
+            "Lcl Rotation" => Some(Channel::Rotation),
+            "Lcl Scaling" => Some(Channel::Scaling),
+            _ => None,
+        }
+    }
+
+    /// Returns the property name.
+    pub fn property(self) -> &'static str {
+        match self {
+            Channel::Translation => "Lcl Translation",
+            Channel::Rotation => "Lcl Rotation",
+            Channel::Scaling => "Lcl Scaling",
+        }
+    }
+
+    /// Returns the non-animated value of the property.
+    fn static_value(self, props: &Properties70) -> Vector3 {
+        let default = if self == Channel::Scaling { 1.0 } else { 0.0 };
+        props.get_f64_3(self.property()).unwrap_or([default; 3])
+    }
+}
+
+
+/// How a model inherits the transform of its parent (`InheritType` property).
+///
+/// In the names, `R` and `S` are the global rotation and scaling of the parent, and `r` and
+/// `s` are the local rotation and scaling of the child.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InheritType {
+    /// Parent scaling is applied after the child rotation (FBX default).
+    #[default]
+    RrSs,
+    /// Parent scaling is applied before the child rotation, i.e. plain matrix
+    /// multiplication.
+    RSrs,
+    /// Local scaling of the parent is not inherited (segment scale compensation of Maya).
+    Rrs,
+}
+
+impl InheritType {
+    /// Creates an `InheritType` from the property value.
+    ///
+    /// Unknown values are treated as `RrSs`.
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            1 => InheritType::RSrs,
+            2 => InheritType::Rrs,
+            _ => InheritType::RrSs,
+        }
+    }
+
+    /// Returns the inherit type of the model.
+    pub fn of_model(model: &Object) -> Self {
+        model
+            .properties
+            .as_ref()
+            .and_then(|props| props.get_i64("InheritType"))
+            .map_or(InheritType::RrSs, InheritType::from_i64)
+    }
+}
+
+
+/// Returns the global transform of the child from the global transform of the parent.
+///
+/// `parent_scaling` is the local scaling (`Lcl Scaling`) of the parent, used by
+/// `InheritType::Rrs`.
+/// Global rotation and scaling of the parent are separated assuming no shear.
+pub fn inherit_transform(
+    parent_global: &Matrix4,
+    parent_scaling: Vector3,
+    local: &Matrix4,
+    inherit: InheritType,
+) -> Matrix4 {
+    if inherit == InheritType::RSrs {
+        return mul(parent_global, local);
+    }
+    let (parent_rotation, parent_rs) = split_rotation(parent_global);
+    let (local_rotation, local_rs) = split_rotation(local);
+    let parent_rs = match inherit {
+        InheritType::Rrs => mul(&parent_rs, &scaling(parent_scaling.map(|v| 1.0 / v))),
+        _ => parent_rs,
+    };
+    let mut global = [
+        parent_rotation,
+        local_rotation,
+        parent_rs,
+        local_rs,
+    ].iter()
+        .fold(IDENTITY, |acc, m| mul(&acc, m));
+    global[3] = mul(parent_global, &translation([local[3][0], local[3][1], local[3][2]]))[3];
+    global
+}
+
+
+/// Splits the rotation and scaling part of the affine matrix into the rotation matrix and the
+/// rest (the scaling matrix, possibly with shear).
+///
+/// The rotation is orthonormalized columns of the matrix, and translations are discarded.
+fn split_rotation(m: &Matrix4) -> (Matrix4, Matrix4) {
+    let mut rotation = IDENTITY;
+    for i in 0..3 {
+        let mut col = [m[i][0], m[i][1], m[i][2]];
+        for prev in &rotation[..i] {
+            let d = (0..3).map(|k| col[k] * prev[k]).sum::<f64>();
+            for (v, p) in col.iter_mut().zip(prev) {
+                *v -= d * p;
+            }
+        }
+        let len = col.iter().map(|v| v * v).sum::<f64>().sqrt();
+        if len > 0.0 {
+            rotation[i] = [col[0] / len, col[1] / len, col[2] / len, 0.0];
+        }
+    }
+    if determinant3(&rotation) < 0.0 {
+        rotation[2] = [-rotation[2][0], -rotation[2][1], -rotation[2][2], 0.0];
+    }
+    let mut rest = *m;
+    rest[3] = [0.0, 0.0, 0.0, 1.0];
+    (rotation, mul(&transpose(rotation), &rest))
+}
+
+
+/// Returns the determinant of the upper left 3x3 part of the matrix.
+fn determinant3(m: &Matrix4) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[2][1] * m[1][2]) -
+        m[1][0] * (m[0][1] * m[2][2] - m[2][1] * m[0][2]) +
+        m[2][0] * (m[0][1] * m[1][2] - m[1][1] * m[0][2])
+}
+
+
+/// Returns the non-animated local transform of the model.
+///
+/// Missing properties use the FBX defaults (property templates in `Definitions` are not
+/// consulted).
+pub fn local_transform(model: &Object) -> Matrix4 {
+    let empty = Properties70::new();
+    let props = model.properties.as_ref().unwrap_or(&empty);
+    compose_local(props, |channel| channel.static_value(props))
+}
+
+
+/// Returns the non-animated global transform of the model.
+///
+/// The transform is composed along the `Model` parents, according to `InheritType` of the
+/// models.
+pub fn global_transform(objects: &Objects, connections: &Connections, id: i64) -> Matrix4 {
+    let model = |id: i64| objects.object(id).filter(|obj| obj.class() == "Model");
+    let mut chain = model(id).into_iter().collect::<Vec<_>>();
+    let mut current = id;
+    // Parents are limited to the number of objects, to avoid infinite loops on cycles.
+    for _ in 0..objects.len() {
+        let parent = connections
+            .object_destinations(current)
+            .find_map(|parent| model(parent).map(|obj| (parent, obj)));
+        match parent {
+            Some((parent, obj)) => {
+                chain.push(obj);
+                current = parent;
+            },
+            None => break,
+        }
+    }
+
+    let mut models = chain.into_iter().rev();
+    let root = match models.next() {
+        Some(root) => root,
+        None => return IDENTITY,
+    };
+    let static_scaling = |obj: &Object| {
+        obj.properties
+            .as_ref()
+            .map_or([1.0; 3], |props| Channel::Scaling.static_value(props))
+    };
+    let (global, _) = models.fold((local_transform(root), root), |(global, parent), obj| {
+        let inherit = InheritType::of_model(obj);
+        let local = local_transform(obj);
+        let global = inherit_transform(&global, static_scaling(parent), &local, inherit);
+        (global, obj)
+    });
+    global
+}
+
+
+/// Composes the local transform from the model properties and the values of the channels.
+fn compose_local<F>(props: &Properties70, channel: F) -> Matrix4
+where
+    F: Fn(Channel) -> Vector3,
+{
+    let vec3 = |name: &str| props.get_f64_3(name).unwrap_or([0.0; 3]);
+    let rotation_active = props.get_bool("RotationActive").unwrap_or(false);
+    let (order, pre, post) = if rotation_active {
+        let order = props
+            .get_i64("RotationOrder")
+            .map_or(RotationOrder::Xyz, RotationOrder::from_i64);
+        (order, vec3("PreRotation"), vec3("PostRotation"))
+    } else {
+        (RotationOrder::Xyz, [0.0; 3], [0.0; 3])
+    };
+    let rotation_pivot = vec3("RotationPivot");
+    let scaling_pivot = vec3("ScalingPivot");
+
+    [
+        translation(channel(Channel::Translation)),
+        translation(vec3("RotationOffset")),
+        translation(rotation_pivot),
+        euler_to_matrix(pre, RotationOrder::Xyz),
+        euler_to_matrix(channel(Channel::Rotation), order),
+        transpose(euler_to_matrix(post, RotationOrder::Xyz)),
+        translation(neg(rotation_pivot)),
+        translation(vec3("ScalingOffset")),
+        translation(scaling_pivot),
+        scaling(channel(Channel::Scaling)),
+        translation(neg(scaling_pivot)),
+    ].iter()
+        .fold(IDENTITY, |acc, m| mul(&acc, m))
+}
+
+
+/// Animated values of the X, Y, and Z components of a channel.
+struct ChannelCurves {
+    /// Curves connected to `d|X`, `d|Y`, and `d|Z` of the curve node.
+    curves: [Option<AnimationCurve>; 3],
+    /// `d|X`, `d|Y`, and `d|Z` properties of the curve node.
+    defaults: [Option<f64>; 3],
+}
+
+
+/// Resolves curves of the layer, by model and property.
+fn layer_curves(
+    objects: &Objects,
+    connections: &Connections,
+    layer: i64,
+) -> FnvHashMap<(i64, Channel), ChannelCurves> {
+    let mut curves = FnvHashMap::default();
+    let curve_nodes = connections
+        .object_sources(layer)
+        .filter(|&id| objects.object(id).is_some_and(is_curve_node));
+    for curve_node in curve_nodes {
+        let node_props = objects.object(curve_node).and_then(|obj| obj.properties.as_ref());
+        for (model, property) in connections.property_destinations(curve_node) {
+            let channel = match Channel::from_property(property) {
+                Some(channel) => channel,
+                None => continue,
+            };
+            let curve = |component: &str| {
+                connections
+                    .property_sources(curve_node, component)
+                    .filter_map(|id| objects.object(id))
+                    .find(|obj| is_curve(obj))
+                    .map(AnimationCurve::from_object)
+            };
+            let default = |component: &str| node_props?.get_f64(component);
+            curves.insert(
+                (model, channel),
+                ChannelCurves {
+                    curves: [curve("d|X"), curve("d|Y"), curve("d|Z")],
+                    defaults: [default("d|X"), default("d|Y"), default("d|Z")],
+                },
+            );
+        }
+    }
+    curves
+}
+
+
+/// Evaluator of model transforms animated by a layer.
+struct Evaluator<'a> {
+    /// Objects.
+    objects: &'a Objects,
+    /// Connections.
+    connections: &'a Connections,
+    /// Curves of each component, by model and property.
+    curves: FnvHashMap<(i64, Channel), ChannelCurves>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Creates a new `Evaluator` by resolving curves of the layer.
+    fn new(objects: &'a Objects, connections: &'a Connections, layer: i64) -> Self {
+        Evaluator {
+            objects: objects,
+            connections: connections,
+            curves: layer_curves(objects, connections, layer),
+        }
+    }
+
+    /// Returns the `Model` object with the given ID.
+    fn model(&self, id: i64) -> Option<&'a Object> {
+        self.objects.object(id).filter(|obj| obj.class() == "Model")
+    }
+
+    /// Returns the value of the transform property at the given time.
+    ///
+    /// Each component is evaluated from the curve if connected, then from the `d|*` property
+    /// of the curve node, and then from the static value of the model.
+    fn channel(&self, id: i64, props: &Properties70, channel: Channel, time: FbxTime) -> Vector3 {
+        let mut value = channel.static_value(props);
+        if let Some(c) = self.curves.get(&(id, channel)) {
+            for ((v, curve), default) in value.iter_mut().zip(&c.curves).zip(&c.defaults) {
+                match (curve, *default) {
+                    (Some(curve), _) => *v = curve.evaluate(time),
+                    (None, Some(default)) => *v = default,
+                    (None, None) => {},
+                }
+            }
+        }
+        value
+    }
+
+    /// Returns the local transform of the model at the given time.
+    fn local(&self, id: i64, time: FbxTime) -> Matrix4 {
+        let empty = Properties70::new();
+        let props = self.model(id)
+            .and_then(|obj| obj.properties.as_ref())
+            .unwrap_or(&empty);
+        compose_local(props, |channel| self.channel(id, props, channel, time))
+    }
+
+    /// Returns the local scaling of the model at the given time.
+    fn scaling(&self, id: i64, time: FbxTime) -> Vector3 {
+        let empty = Properties70::new();
+        let props = self.model(id)
+            .and_then(|obj| obj.properties.as_ref())
+            .unwrap_or(&empty);
+        self.channel(id, props, Channel::Scaling, time)
+    }
+
+    /// Returns the global transform of the model at the given time.
+    ///
+    /// `cache` holds global transforms already computed for the time.
+    fn global(&self, id: i64, time: FbxTime, cache: &mut FnvHashMap<i64, Matrix4>) -> Matrix4 {
+        if let Some(m) = cache.get(&id) {
+            return *m;
+        }
+        let local = self.local(id, time);
+        let parent = self.connections
+            .object_destinations(id)
+            .find(|&parent| self.model(parent).is_some());
+        let global = match parent {
+            Some(parent) => {
+                let parent_global = self.global(parent, time, cache);
+                let inherit = self.model(id).map_or(InheritType::RrSs, InheritType::of_model);
+                inherit_transform(&parent_global, self.scaling(parent, time), &local, inherit)
+            },
+            None => local,
+        };
+        cache.insert(id, global);
+        global
+    }
+}
+
+
+/// Returns `true` if the object is an animation curve node.
+///
+/// Object nodes are named `AnimationCurveNode` while their class is `AnimCurveNode`.
+fn is_curve_node(obj: &Object) -> bool {
+    obj.class() == "AnimCurveNode" || obj.class() == "AnimationCurveNode"
+}
+
+
+/// Returns `true` if the object is an animation curve.
+///
+/// Object nodes are named `AnimationCurve` while their class is `AnimCurve`.
+fn is_curve(obj: &Object) -> bool {
+    obj.class() == "AnimCurve" || obj.class() == "AnimationCurve"
+}
+
+
+/// Identity matrix.
+const IDENTITY: Matrix4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+
+/// Multiplies column major matrices.
+fn mul(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+    let mut m = [[0.0; 4]; 4];
+    for (col, m_col) in m.iter_mut().enumerate() {
+        for (row, v) in m_col.iter_mut().enumerate() {
+            *v = (0..4).map(|k| a[k][row] * b[col][k]).sum();
+        }
+    }
+    m
+}
+
+
+/// Transposes the matrix.
+fn transpose(a: Matrix4) -> Matrix4 {
+    let mut m = a;
+    for (col, m_col) in m.iter_mut().enumerate() {
+        for (row, v) in m_col.iter_mut().enumerate() {
+            *v = a[row][col];
+        }
+    }
+    m
+}
+
+
+/// Negates the vector.
+fn neg(v: Vector3) -> Vector3 {
+    [-v[0], -v[1], -v[2]]
+}
+
+
+/// Returns the translation matrix.
+fn translation(v: Vector3) -> Matrix4 {
+    let mut m = IDENTITY;
+    m[3] = [v[0], v[1], v[2], 1.0];
+    m
+}
+
+
+/// Returns the scaling matrix.
+fn scaling(v: Vector3) -> Matrix4 {
+    let mut m = IDENTITY;
+    for (i, &s) in v.iter().enumerate() {
+        m[i][i] = s;
+    }
+    m
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::AnimationStack;
+    use loader::time::FbxTime;
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use loader::math::{Matrix4, RotationOrder, euler_to_matrix};
+    use super::{InheritType, bake, bake_with_globals, inherit_transform, mul, scaling};
+    use super::{frame_count, frame_time, translation};
+
+    #[test]
+    fn test_bake() {
+        const SECOND: i64 = FbxTime::TICKS_PER_SECOND;
+
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "AnimationStack",
+            &[Attr::I64(1), Attr::String("Take\u{0}\u{1}AnimStack"), Attr::String("")],
+        );
+        write_properties70(
+            &mut b,
+            &[
+                ("LocalStart", "KTime", "Time", "", &[Attr::I64(0)]),
+                ("LocalStop", "KTime", "Time", "", &[Attr::I64(SECOND)]),
+            ],
+        );
+        b.end_node();
+        b.node(
+            "AnimationLayer",
+            &[Attr::I64(2), Attr::String("Base\u{0}\u{1}AnimLayer"), Attr::String("")],
+        );
+        b.begin_node(
+            "Model",
+            &[Attr::I64(10), Attr::String("Parent\u{0}\u{1}Model"), Attr::String("Null")],
+        );
+        let (t, r) = ([1.0, 2.0, 3.0], [0.0, 0.0, 90.0]);
+        write_properties70(
+            &mut b,
+            &[
+                ("Lcl Translation", "Lcl Translation", "", "A", &[t[0], t[1], t[2]].map(Attr::F64)),
+                ("Lcl Rotation", "Lcl Rotation", "", "A", &[r[0], r[1], r[2]].map(Attr::F64)),
+            ],
+        );
+        b.end_node();
+        b.begin_node(
+            "Model",
+            &[Attr::I64(11), Attr::String("Child\u{0}\u{1}Model"), Attr::String("Null")],
+        );
+        write_properties70(
+            &mut b,
+            &[("Lcl Translation", "Lcl Translation", "", "A", &[0.0, 1.0, 0.0].map(Attr::F64))],
+        );
+        b.end_node();
+        b.node(
+            "AnimationCurveNode",
+            &[Attr::I64(20), Attr::String("T\u{0}\u{1}AnimCurveNode"), Attr::String("")],
+        );
+        b.begin_node(
+            "AnimationCurve",
+            &[Attr::I64(21), Attr::String("\u{0}\u{1}AnimCurve"), Attr::String("")],
+        );
+        b.node("Default", &[Attr::F64(0.0)]);
+        b.node("KeyTime", &[Attr::ArrI64(&[0, SECOND])]);
+        b.node("KeyValueFloat", &[Attr::ArrF32(&[0.0, 10.0])]);
+        b.end_node();
+        b.begin_node(
+            "AnimationCurveNode",
+            &[Attr::I64(22), Attr::String("S\u{0}\u{1}AnimCurveNode"), Attr::String("")],
+        );
+        write_properties70(&mut b, &[("d|X", "Number", "", "A", &[Attr::F64(2.0)])]);
+        b.end_node();
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(2), Attr::I64(1)]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(10), Attr::I64(0)]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(11), Attr::I64(10)]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(20), Attr::I64(2)]);
+        b.node(
+            "C",
+            &[Attr::String("OP"), Attr::I64(20), Attr::I64(10), Attr::String("Lcl Translation")],
+        );
+        b.node("C", &[Attr::String("OP"), Attr::I64(21), Attr::I64(20), Attr::String("d|X")]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(22), Attr::I64(2)]);
+        b.node(
+            "C",
+            &[Attr::String("OP"), Attr::I64(22), Attr::I64(11), Attr::String("Lcl Scaling")],
+        );
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let stack = AnimationStack::from_object(fbx.objects.object(1).expect("Should exist"));
+        let baked = bake_with_globals(&fbx.objects, &fbx.connections, &stack, 2, &[10, 11], 2.0)
+            .expect("Valid sample rate");
+        assert!(bake(&fbx.objects, &fbx.connections, &stack, 2, &[10], -1.0).is_none());
+
+        assert_eq!(baked.num_frames, 3);
+        let parent = baked.node(10).expect("Parent should be baked");
+        let xs = parent.local.iter().map(|m| m[3][0]).collect::<Vec<_>>();
+        assert_eq!(xs, vec![0.0, 5.0, 10.0]);
+        assert_eq!(parent.local[1][3][1], 2.0);
+
+        // The child at local (0, 1, 0) is rotated by 90 degrees around Z axis of the parent.
+        let child = baked.node(11).expect("Child should be baked");
+        assert_eq!(child.local[2][3], [0.0, 1.0, 0.0, 1.0]);
+        // Scaling X falls back to `d|X` of the curve node, and Y to the model value.
+        assert_eq!((child.local[0][0][0], child.local[0][1][1]), (2.0, 1.0));
+        let global = child.global.as_ref().expect("Globals should be baked")[1][3];
+        let expected = [4.0, 2.0, 3.0, 1.0];
+        assert!(global.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1e-9), "{:?}", global);
+    }
+
+    #[test]
+    fn test_frame_count_limits() {
+        let (min, max) = (FbxTime(i64::MIN), FbxTime(i64::MAX));
+        assert_eq!(frame_count(min, max, 30.0), None);
+        assert_eq!(frame_count(FbxTime(0), max, 30.0), None);
+        assert_eq!(frame_count(FbxTime(0), FbxTime(FbxTime::TICKS_PER_SECOND), f64::MAX), None);
+        assert_eq!(frame_count(max, max, 30.0), Some(1));
+        assert_eq!(frame_count(max, min, 30.0), Some(1));
+        assert_eq!(frame_time(max, 1, 30.0), max);
+    }
+
+    #[test]
+    fn test_inherit_transform() {
+        let approx = |a: &Matrix4, b: &Matrix4| {
+            a.iter().flat_map(|c| c.iter()).zip(b.iter().flat_map(|c| c.iter()))
+                .all(|(a, b)| (a - b).abs() < 1e-9)
+        };
+        // Parent scaled by (2, 1, 1), child at (1, 0, 0) rotated by 90 degrees around Z axis.
+        let parent = scaling([2.0, 1.0, 1.0]);
+        let local = mul(
+            &translation([1.0, 0.0, 0.0]),
+            &euler_to_matrix([0.0, 0.0, 90.0], RotationOrder::Xyz),
+        );
+        let global = |inherit| inherit_transform(&parent, [2.0, 1.0, 1.0], &local, inherit);
+        let expected = |x: [f64; 3], y: [f64; 3]| {
+            [
+                [x[0], x[1], x[2], 0.0],
+                [y[0], y[1], y[2], 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [2.0, 0.0, 0.0, 1.0],
+            ]
+        };
+
+        let rssr = global(InheritType::RSrs);
+        assert!(approx(&rssr, &expected([0.0, 1.0, 0.0], [-2.0, 0.0, 0.0])), "{:?}", rssr);
+        let rrss = global(InheritType::RrSs);
+        assert!(approx(&rrss, &expected([0.0, 2.0, 0.0], [-1.0, 0.0, 0.0])), "{:?}", rrss);
+        let rrs = global(InheritType::Rrs);
+        assert!(approx(&rrs, &expected([0.0, 1.0, 0.0], [-1.0, 0.0, 0.0])), "{:?}", rrs);
+        assert_eq!(InheritType::from_i64(1), InheritType::RSrs);
+    }
+}
@@ -0,0 +1,165 @@
+//! Raw keyframe tracks of model transforms.
+//!
+//! Unlike `bake()`, curves are not evaluated, and keys are passed through as they are stored
+//! in the file.
+//! This is useful for runtimes which interpolate keys by themselves.
+
+use loader::binary::simple::fbx7400::{Objects, Connections, Properties70};
+use loader::math::Vector3;
+use loader::time::FbxTime;
+use super::{Channel, layer_curves};
+
+
+/// Keys of a component.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct KeyTrack {
+    /// Key times.
+    pub times: Vec<FbxTime>,
+    /// Key values.
+    pub values: Vec<f32>,
+}
+
+
+/// Keys of the X, Y, and Z components of a transform property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelTrack {
+    /// Animated property.
+    pub channel: Channel,
+    /// Keys of each component, or `None` if the component has no curve.
+    pub components: [Option<KeyTrack>; 3],
+    /// Constant values of the components without curves.
+    ///
+    /// The `d|X`, `d|Y`, and `d|Z` properties of the curve node are used if available, and
+    /// the values of the model otherwise.
+    pub constants: Vector3,
+}
+
+
+/// Keyframe tracks of a model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelTracks {
+    /// Object ID of the model.
+    pub id: i64,
+    /// Animated properties, in the order of `Channel`.
+    pub channels: Vec<ChannelTrack>,
+}
+
+impl ModelTracks {
+    /// Returns the track of the given property.
+    pub fn channel(&self, channel: Channel) -> Option<&ChannelTrack> {
+        self.channels.iter().find(|track| track.channel == channel)
+    }
+}
+
+
+/// Returns keyframe tracks of the models animated by the layer.
+///
+/// Models are sorted by the object ID.
+/// Curve nodes connected to objects other than `Model`s are ignored.
+pub fn model_tracks(objects: &Objects, connections: &Connections, layer: i64) -> Vec<ModelTracks> {
+    let empty = Properties70::new();
+    let mut curves = layer_curves(objects, connections, layer)
+        .into_iter()
+        .filter(|&((id, _), _)| objects.object(id).is_some_and(|obj| obj.class() == "Model"))
+        .collect::<Vec<_>>();
+    curves.sort_by_key(|&(key, _)| key);
+
+    let mut tracks: Vec<ModelTracks> = Vec::new();
+    for ((id, channel), c) in curves {
+        let props = objects
+            .object(id)
+            .and_then(|obj| obj.properties.as_ref())
+            .unwrap_or(&empty);
+        let mut constants = channel.static_value(props);
+        for (v, default) in constants.iter_mut().zip(&c.defaults) {
+            if let Some(default) = *default {
+                *v = default;
+            }
+        }
+        let track = ChannelTrack {
+            channel: channel,
+            components: c.curves.map(|curve| {
+                curve.map(|curve| KeyTrack {
+                    times: curve.key_times,
+                    values: curve.key_values,
+                })
+            }),
+            constants: constants,
+        };
+        match tracks.last_mut() {
+            Some(last) if last.id == id => last.channels.push(track),
+            _ => tracks.push(ModelTracks {
+                id: id,
+                channels: vec![track],
+            }),
+        }
+    }
+    tracks
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::time::FbxTime;
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::{Channel, model_tracks};
+
+    #[test]
+    fn test_model_tracks() {
+        const SECOND: i64 = FbxTime::TICKS_PER_SECOND;
+
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.node(
+            "AnimationLayer",
+            &[Attr::I64(2), Attr::String("Base\u{0}\u{1}AnimLayer"), Attr::String("")],
+        );
+        b.begin_node(
+            "Model",
+            &[Attr::I64(10), Attr::String("Cube\u{0}\u{1}Model"), Attr::String("Mesh")],
+        );
+        write_properties70(
+            &mut b,
+            &[("Lcl Translation", "Lcl Translation", "", "A", &[1.0, 2.0, 3.0].map(Attr::F64))],
+        );
+        b.end_node();
+        b.begin_node(
+            "AnimationCurveNode",
+            &[Attr::I64(20), Attr::String("T\u{0}\u{1}AnimCurveNode"), Attr::String("")],
+        );
+        write_properties70(&mut b, &[("d|Y", "Number", "", "A", &[Attr::F64(5.0)])]);
+        b.end_node();
+        b.begin_node(
+            "AnimationCurve",
+            &[Attr::I64(21), Attr::String("\u{0}\u{1}AnimCurve"), Attr::String("")],
+        );
+        b.node("KeyTime", &[Attr::ArrI64(&[0, SECOND])]);
+        b.node("KeyValueFloat", &[Attr::ArrF32(&[0.0, 10.0])]);
+        b.end_node();
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(10), Attr::I64(0)]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(20), Attr::I64(2)]);
+        b.node(
+            "C",
+            &[Attr::String("OP"), Attr::I64(20), Attr::I64(10), Attr::String("Lcl Translation")],
+        );
+        b.node("C", &[Attr::String("OP"), Attr::I64(21), Attr::I64(20), Attr::String("d|X")]);
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let tracks = model_tracks(&fbx.objects, &fbx.connections, 2);
+
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].id, 10);
+        assert!(tracks[0].channel(Channel::Rotation).is_none());
+        let translation = tracks[0].channel(Channel::Translation).expect("Should be animated");
+        let x = translation.components[0].as_ref().expect("X should have keys");
+        assert_eq!(x.times, vec![FbxTime(0), FbxTime(SECOND)]);
+        assert_eq!(x.values, vec![0.0, 10.0]);
+        assert!(translation.components[1].is_none());
+        assert_eq!(translation.constants, [1.0, 5.0, 3.0]);
+    }
+}
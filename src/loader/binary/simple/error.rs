@@ -3,7 +3,7 @@
 use std::error;
 use std::fmt;
 use std::io;
-use parser::binary::Error as ParseError;
+use parser::binary::{Error as ParseError, ErrorKind, Event, ParserSource};
 
 
 /// Load result.
@@ -11,8 +11,20 @@ pub type Result<T> = ::std::result::Result<T, Error>;
 
 
 /// Load error.
+///
+/// New variants may be added in future, so use `kind()` to categorize errors.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
+    /// Error with the location where it happened.
+    Context {
+        /// Names of the nodes being loaded, from the toplevel node to the innermost one.
+        node_path: Vec<String>,
+        /// Start offset of the node header of the innermost node, if available.
+        offset: Option<u64>,
+        /// The error.
+        source: Box<Error>,
+    },
     /// Attribute is invalid.
     ///
     /// This includes the cases below:
@@ -21,7 +33,7 @@ pub enum Error {
     /// - The node has wrong (or unsupported) values of node attributes values.
     InvalidAttribute(String),
     /// Object load error.
-    LoadObject(Box<error::Error + Send + Sync>),
+    LoadObject(Box<dyn error::Error + Send + Sync>),
     /// Required node is missing.
     MissingNode {
         /// Parent node.
@@ -33,8 +45,14 @@ pub enum Error {
     },
     /// Parse error (including I/O error).
     Parse(ParseError),
+    /// Got an unexpected parser event.
+    ///
+    /// This has the description of the event.
+    UnexpectedEvent(String),
     /// Got an unexpected node.
     UnexpectedNode(String),
+    /// FBX version is not supported.
+    UnsupportedVersion(u32),
 }
 
 impl Error {
@@ -49,11 +67,116 @@ impl Error {
             child: child.into().map(|s| s.to_owned()),
         }
     }
+
+    /// Creates a new `Error::UnexpectedEvent` from the given event.
+    pub fn unexpected_event<R: ParserSource>(event: &Event<R>) -> Self {
+        let desc = match *event {
+            Event::StartFbx(ref header) => format!("StartFbx (version={})", header.version),
+            Event::EndFbx(_) => "EndFbx".to_owned(),
+            Event::StartNode(ref info) => format!("StartNode (name={:?})", info.name),
+            Event::RawNode(ref info) => format!("RawNode (name={:?})", info.name),
+            Event::EndNode => "EndNode".to_owned(),
+        };
+        Error::UnexpectedEvent(desc)
+    }
+
+    /// Adds the node being loaded to the context of the error.
+    ///
+    /// The node is treated as the parent of the nodes already in the context.
+    pub fn with_context(self, node_name: &str, offset: Option<u64>) -> Self {
+        match self {
+            Error::Context {
+                mut node_path,
+                offset: inner_offset,
+                source,
+            } => {
+                node_path.insert(0, node_name.to_owned());
+                Error::Context {
+                    node_path: node_path,
+                    offset: inner_offset.or(offset),
+                    source: source,
+                }
+            },
+            err => Error::Context {
+                node_path: vec![node_name.to_owned()],
+                offset: offset,
+                source: Box::new(err),
+            },
+        }
+    }
+
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            Error::Context { ref source, .. } => source.kind(),
+            Error::InvalidAttribute(_) |
+            Error::MissingNode { .. } |
+            Error::UnexpectedEvent(_) |
+            Error::UnexpectedNode(_) => ErrorKind::InvalidData,
+            Error::UnsupportedVersion(_) => ErrorKind::Unsupported,
+            Error::LoadObject(_) => ErrorKind::Other,
+            Error::Parse(ref err) => err.kind(),
+        }
+    }
+
+    /// Returns the stable machine-readable code of the error (such as `"FBX-L001"`).
+    ///
+    /// Parse errors have the codes of the parser errors (`"FBX-P..."`), and errors with
+    /// context have the codes of the inner errors.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Error::Context { ref source, .. } => source.code(),
+            Error::InvalidAttribute(_) => "FBX-L001",
+            Error::LoadObject(_) => "FBX-L002",
+            Error::MissingNode { .. } => "FBX-L003",
+            Error::UnexpectedEvent(_) => "FBX-L004",
+            Error::UnexpectedNode(_) => "FBX-L005",
+            Error::UnsupportedVersion(_) => "FBX-L006",
+            Error::Parse(ref err) => err.code(),
+        }
+    }
+
+    /// Returns names of the nodes being loaded when the error happened.
+    ///
+    /// Returns an empty slice if unknown.
+    pub fn node_path(&self) -> &[String] {
+        match *self {
+            Error::Context { ref node_path, .. } => node_path,
+            _ => &[],
+        }
+    }
+
+    /// Returns the start offset of the node header of the node being loaded, if available.
+    pub fn offset(&self) -> Option<u64> {
+        match *self {
+            Error::Context { offset, .. } => offset,
+            _ => None,
+        }
+    }
+
+    /// Returns the error without context.
+    pub fn without_context(&self) -> &Error {
+        match *self {
+            Error::Context { ref source, .. } => source.without_context(),
+            ref err => err,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            Error::Context {
+                ref node_path,
+                offset,
+                ref source,
+            } => {
+                write!(f, "{} (while loading node `{}`", source, node_path.join("/"))?;
+                if let Some(offset) = offset {
+                    write!(f, " at offset {}", offset)?;
+                }
+                f.write_str(")")
+            },
             Error::InvalidAttribute(ref name) => write!(f, "Invalid attribute for node: {}", name),
             Error::LoadObject(ref err) => write!(f, "Object load error: {}", err),
             Error::MissingNode {
@@ -66,26 +189,19 @@ impl fmt::Display for Error {
                     write!(f, "Missing node: parent={}", parent)
                 }
             },
+            Error::Parse(ref err) => write!(f, "Parse error: {}", err),
+            Error::UnexpectedEvent(ref desc) => write!(f, "Unexpected parser event: {}", desc),
             Error::UnexpectedNode(ref name) => write!(f, "Unexpected node: {}", name),
-            _ => write!(f, "{}", (self as &error::Error).description()),
+            Error::UnsupportedVersion(version) => write!(f, "Unsupported FBX version: {}", version),
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
-            Error::InvalidAttribute(_) => "Invalid node attribute",
-            Error::LoadObject(ref err) => err.description(),
-            Error::MissingNode { .. } => "Missing node",
-            Error::UnexpectedNode(_) => "Unexpected node",
-            Error::Parse(ref err) => err.description(),
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            Error::LoadObject(ref err) => Some(err.as_ref()),
+            Error::Context { ref source, .. } => Some(&**source),
+            Error::LoadObject(ref err) => Some(&**err),
             Error::Parse(ref err) => Some(err),
             _ => None,
         }
@@ -103,3 +219,22 @@ impl From<io::Error> for Error {
         Error::Parse(e.into())
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use parser::binary::Error as ParseError;
+    use super::Error;
+
+    #[test]
+    fn test_code() {
+        let err = Error::missing_node("Objects", "Geometry").with_context("Objects", Some(42));
+        assert_eq!(err.code(), "FBX-L003");
+        let parse_err = ParseError::WrongNodeEndOffset {
+            begin: 0,
+            expected_end: 10,
+            real_end: 12,
+        };
+        assert_eq!(Error::from(parse_err).code(), "FBX-P001");
+    }
+}
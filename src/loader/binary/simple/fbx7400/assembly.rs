@@ -0,0 +1,289 @@
+//! Assembly of a scene from multiple FBX files.
+
+use std::io::Read;
+use fnv::{FnvHashMap, FnvHashSet};
+use parser::binary::{RootParser, BasicSource};
+use loader::binary::simple::{Result, FbxLoader, GenericNode, OwnedAttribute};
+use loader::binary::simple::fbx7400::{Fbx7400, Objects, ObjectsLoader, Connection};
+
+
+/// Reference to an external file, read from `References` node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExternalReference {
+    /// Reference name.
+    pub name: String,
+    /// Path of the referenced file.
+    pub path: String,
+}
+
+impl ExternalReference {
+    /// Creates an `ExternalReference` from a child node of `References` node.
+    ///
+    /// The first string attribute is used as the name.
+    /// The path is taken from the `FileName` or `RelativeFilename` child node if available,
+    /// otherwise from the second string attribute.
+    pub fn from_node(node: &GenericNode) -> Option<Self> {
+        let mut strings = node.attributes.iter().filter_map(OwnedAttribute::as_str);
+        let name = strings.next()?;
+        let path = ["FileName", "RelativeFilename"]
+            .iter()
+            .filter_map(|&child_name| node.children.iter().find(|c| c.name == child_name))
+            .filter_map(|child| child.attributes.iter().filter_map(OwnedAttribute::as_str).next())
+            .next()
+            .or_else(|| strings.next())?;
+        Some(ExternalReference {
+            name: name.to_owned(),
+            path: path.to_owned(),
+        })
+    }
+}
+
+
+/// Source file of an assembled scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneSource {
+    /// Reference to the file.
+    ///
+    /// `None` for the main file.
+    pub reference: Option<ExternalReference>,
+    /// FBX version of the file.
+    pub version: u32,
+    /// Map from object IDs in the file to object IDs in the assembled scene.
+    pub id_map: FnvHashMap<i64, i64>,
+}
+
+
+/// Scene assembled from the main file and externally referenced files.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct AssembledScene {
+    /// Objects of all files.
+    pub objects: Objects,
+    /// Connections of all files, with remapped object IDs.
+    pub connections: Vec<Connection>,
+    /// Source files, in load order.
+    ///
+    /// The first one is the main file.
+    pub sources: Vec<SceneSource>,
+}
+
+
+/// Loader of a scene consisting of a main FBX file and externally referenced FBX files.
+///
+/// Referenced files are resolved by the user callback, which returns `Ok(None)` for
+/// references which should be ignored.
+/// Objects of the main file keep their IDs, and objects of referenced files get new IDs which
+/// don't collide with others.
+/// Connections to the root object (ID `0`) are kept as is, so toplevel objects of all files
+/// are attached to the single scene root.
+#[derive(Debug)]
+pub struct SceneAssembler<F> {
+    /// Resolver of external references.
+    resolver: F,
+    /// Maximum depth of nested references.
+    max_depth: usize,
+}
+
+impl<F> SceneAssembler<F> {
+    /// Default maximum depth of nested references.
+    pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+    /// Creates a new `SceneAssembler` with the given reference resolver.
+    pub fn new(resolver: F) -> Self {
+        SceneAssembler {
+            resolver: resolver,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Returns the maximum depth of nested references.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets the maximum depth of nested references.
+    ///
+    /// References deeper than this are ignored with a warning log.
+    /// `0` means referenced files are never loaded.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Loads the main file and the referenced files, and assembles them into one scene.
+    pub fn assemble<S>(&mut self, main: S) -> Result<AssembledScene>
+    where
+        S: Read,
+        F: FnMut(&ExternalReference) -> Result<Option<S>>,
+    {
+        let mut scene = AssembledScene::default();
+        let mut visited = FnvHashSet::default();
+        // Pending references with their depth.
+        let mut pending = Vec::new();
+
+        let fbx = load_fbx(main)?;
+        pending.extend(external_references(&fbx).into_iter().rev().map(|r| (r, 1)));
+        merge(&mut scene, fbx, None);
+
+        while let Some((reference, depth)) = pending.pop() {
+            if depth > self.max_depth {
+                warn!("Reference {:?} is too deep, ignoring", reference);
+                continue;
+            }
+            if !visited.insert(reference.path.clone()) {
+                debug!("Reference {:?} is already loaded", reference);
+                continue;
+            }
+            let source = match (self.resolver)(&reference)? {
+                Some(source) => source,
+                None => {
+                    warn!("Reference {:?} is not resolved, ignoring", reference);
+                    continue;
+                },
+            };
+            let fbx = load_fbx(source)?;
+            pending.extend(external_references(&fbx).into_iter().rev().map(|r| (r, depth + 1)));
+            merge(&mut scene, fbx, Some(reference));
+        }
+
+        Ok(scene)
+    }
+}
+
+
+/// FBX data with generic objects.
+type GenericFbx<S> = Fbx7400<ObjectsLoader<BasicSource<S>>>;
+
+
+/// Loads FBX data with generic objects from the given source.
+fn load_fbx<S: Read>(source: S) -> Result<GenericFbx<S>> {
+    match FbxLoader::load_from_parser(RootParser::new(source))? {
+        FbxLoader::Fbx7400(loader) => loader.load(ObjectsLoader::new()),
+    }
+}
+
+
+/// Returns external references of the FBX data.
+fn external_references<S: Read>(fbx: &GenericFbx<S>) -> Vec<ExternalReference> {
+    fbx.references
+        .nodes
+        .iter()
+        .filter_map(|node| {
+            let reference = ExternalReference::from_node(node);
+            if reference.is_none() {
+                warn!("Unsupported reference node: {:?}", node);
+            }
+            reference
+        })
+        .collect()
+}
+
+
+/// Merges the FBX data into the scene.
+fn merge<S: Read>(
+    scene: &mut AssembledScene,
+    fbx: GenericFbx<S>,
+    reference: Option<ExternalReference>,
+) {
+    let mut id_map = FnvHashMap::default();
+    if reference.is_none() {
+        for obj in fbx.objects.objects() {
+            id_map.insert(obj.id(), obj.id());
+        }
+    } else {
+        let next_id = scene
+            .objects
+            .objects()
+            .map(|obj| obj.id())
+            .max()
+            .map_or(1, |max| max + 1);
+        let mut ids = fbx.objects.objects().map(|obj| obj.id()).collect::<Vec<_>>();
+        // Assign IDs in deterministic order.
+        ids.sort();
+        id_map.extend(ids.into_iter().zip(next_id..));
+    }
+
+    for mut obj in fbx.objects.into_iter_ordered() {
+        obj.props.id = id_map[&obj.id()];
+        if let Some(old) = scene.objects.insert(obj) {
+            warn!("Duplicate object ID {}, overwriting old one", old.id());
+        }
+    }
+
+    let remap = |id: i64| if id == 0 { 0 } else { id_map.get(&id).cloned().unwrap_or(id) };
+    scene.connections.extend(fbx.connections.0.into_iter().map(|conn| Connection {
+        source: remap(conn.source),
+        destination: remap(conn.destination),
+        ..conn
+    }));
+
+    scene.sources.push(SceneSource {
+        reference: reference,
+        version: fbx.version,
+        id_map: id_map,
+    });
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use loader::binary::simple::Error;
+    use test_util::{Attr, FbxBuilder, write_toplevel_node, NODES_BEFORE_OBJECTS};
+    use super::SceneAssembler;
+
+    /// Creates FBX data with the given references and a model connected to the root.
+    fn fbx_with_refs(model_id: i64, model_name: &str, refs: &[(&str, &str)]) -> Vec<u8> {
+        let mut b = FbxBuilder::new(7400);
+        for &name in NODES_BEFORE_OBJECTS {
+            if name == "References" {
+                b.begin_node("References", &[]);
+                for &(ref_name, path) in refs {
+                    b.node("Reference", &[Attr::String(ref_name), Attr::String(path)]);
+                }
+                b.end_node();
+            } else {
+                write_toplevel_node(&mut b, name);
+            }
+        }
+        b.begin_node("Objects", &[]);
+        let name_class = format!("{}\u{0}\u{1}Model", model_name);
+        b.node("Model", &[Attr::I64(model_id), Attr::String(&name_class), Attr::String("Null")]);
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(model_id), Attr::I64(0)]);
+        b.end_node();
+        b.finish()
+    }
+
+    #[test]
+    fn test_assemble() {
+        let main = fbx_with_refs(10, "Main", &[("Ref0", "a.fbx"), ("Ref1", "missing.fbx")]);
+        let mut assembler = SceneAssembler::new(|reference: &super::ExternalReference| {
+            Ok(match reference.path.as_str() {
+                "a.fbx" => Some(Cursor::new(fbx_with_refs(10, "A", &[("Ref", "b.fbx")]))),
+                "b.fbx" => Some(Cursor::new(fbx_with_refs(1, "B", &[("Ref", "a.fbx")]))),
+                "missing.fbx" => None,
+                _ => return Err(Error::InvalidAttribute(reference.path.clone())),
+            })
+        });
+        let scene = assembler.assemble(Cursor::new(main)).expect("Assembly error");
+
+        assert_eq!(scene.sources.len(), 3);
+        assert!(scene.sources[0].reference.is_none());
+        assert_eq!(scene.objects.len(), 3);
+        assert_eq!(scene.objects.object(10).map(|obj| obj.name()), Some("Main"));
+        let a_id = scene.sources[1].id_map[&10];
+        let b_id = scene.sources[2].id_map[&1];
+        assert_eq!(scene.objects.object(a_id).map(|obj| obj.name()), Some("A"));
+        assert_eq!(scene.objects.object(b_id).map(|obj| obj.name()), Some("B"));
+        let mut roots = scene
+            .connections
+            .iter()
+            .filter(|conn| conn.destination == 0)
+            .map(|conn| conn.source)
+            .collect::<Vec<_>>();
+        roots.sort();
+        let mut expected = vec![10, a_id, b_id];
+        expected.sort();
+        assert_eq!(roots, expected);
+    }
+}
@@ -1,11 +1,18 @@
 //! `Connections` node and its children.
 
-use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
+use std::collections::HashSet;
+use parser::binary::{Parser, ParserSource, Attributes, Attribute};
+use loader::binary::simple::{Result, Error, GenericNode};
+use loader::binary::simple::fbx7400::{Documents, Objects, ObjectMap, legacy_object_id};
 
 
 /// `Connections` node.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Connections are kept in the file order, which is significant: for example, the order of
+/// `OO` connections to a model determines the material slots indexed by
+/// `LayerElementMaterial`.
+/// Queries returning multiple connections preserve this order.
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Connections(pub Vec<Connection>);
 
 impl Connections {
@@ -19,14 +26,147 @@ impl Connections {
 
         loop {
             let attrs = try_get_node_attrs!(parser, ConnectionAttrs::load);
-            connections.push(Connection::load(parser.subtree_parser(), attrs)?);
+            connections.push(Connection::load(parser.subtree_guard(), attrs)?);
+        }
+        let mut connections = Connections(connections);
+        let removed = connections.dedup();
+        if removed > 0 {
+            warn!("Removed {} duplicate connections", removed);
+        }
+        Ok(connections)
+    }
+
+    /// Removes duplicate connections, keeping the first one, and returns the number of
+    /// removed connections.
+    ///
+    /// Connections with the same ends but different kinds (such as `OO` and `OP`) are not
+    /// duplicates.
+    pub fn dedup(&mut self) -> usize {
+        let len = self.0.len();
+        let mut seen = HashSet::new();
+        self.0.retain(|c| seen.insert(c.clone()));
+        len - self.0.len()
+    }
+
+    /// Returns an iterator of connections of the given kind, in connection order.
+    pub fn of_kind(&self, kind: ConnectionKind) -> impl Iterator<Item = &Connection> + '_ {
+        self.0.iter().filter(move |c| c.kind() == kind)
+    }
+
+    /// Returns an iterator of connections from the source to the destination, in connection
+    /// order.
+    ///
+    /// The same pair may be connected by several kinds of connections (for example, both
+    /// `OO` and `OP`).
+    pub fn between(
+        &self,
+        source: i64,
+        destination: i64,
+    ) -> impl Iterator<Item = &Connection> + '_ {
+        self.0
+            .iter()
+            .filter(move |c| c.source == source && c.destination == destination)
+    }
+
+    /// Replaces object IDs of both ends by the map.
+    ///
+    /// IDs not in the map are kept as is.
+    pub fn remap_ids(&mut self, map: &ObjectMap<i64>) {
+        let id = |id: i64| map.get(&id).cloned().unwrap_or(id);
+        for c in &mut self.0 {
+            c.source = id(c.source);
+            c.destination = id(c.destination);
+        }
+    }
+
+    /// Converts the connections into a `Connections` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("Connections")
+            .with_children(self.0.iter().map(Connection::to_generic_node))
+    }
+
+    /// Returns IDs of source objects connected to the given property of the destination object.
+    pub fn property_sources<'a>(
+        &'a self,
+        destination: i64,
+        property: &'a str,
+    ) -> impl Iterator<Item = i64> + 'a {
+        self.0
+            .iter()
+            .filter(move |c| {
+                c.destination == destination && c.destination_is_prop && !c.source_is_prop &&
+                    c.property.as_ref().is_some_and(|p| p == property)
+            })
+            .map(|c| c.source)
+    }
+
+    /// Returns IDs of source objects connected to the destination object (not to its
+    /// properties), in connection order.
+    pub fn object_sources(&self, destination: i64) -> impl Iterator<Item = i64> + '_ {
+        self.0
+            .iter()
+            .filter(move |c| {
+                c.destination == destination && !c.destination_is_prop && !c.source_is_prop
+            })
+            .map(|c| c.source)
+    }
+
+    /// Returns IDs of child objects (object sources connected to the object, not to its
+    /// properties), in connection order.
+    ///
+    /// Unlike `object_sources()`, each child appears only once, at its first connection.
+    pub fn ordered_children_of(&self, id: i64) -> Vec<i64> {
+        let mut children = Vec::new();
+        for child in self.object_sources(id) {
+            if !children.contains(&child) {
+                children.push(child);
+            }
         }
-        Ok(Connections(connections))
+        children
+    }
+
+    /// Returns IDs of top-level models of the scene, i.e. models connected to the root nodes of
+    /// the documents, in document and connection order.
+    pub fn scene_roots(&self, objects: &Objects, documents: &Documents) -> Vec<i64> {
+        let mut roots = Vec::new();
+        for root_node in documents.root_nodes() {
+            let models = self.object_sources(root_node)
+                .filter(|&id| objects.object(id).is_some_and(|obj| obj.class() == "Model"));
+            for id in models {
+                if !roots.contains(&id) {
+                    roots.push(id);
+                }
+            }
+        }
+        roots
+    }
+
+    /// Returns IDs of destination objects which the source object is connected to (not to
+    /// their properties), in connection order.
+    pub fn object_destinations(&self, source: i64) -> impl Iterator<Item = i64> + '_ {
+        self.0
+            .iter()
+            .filter(move |c| {
+                c.source == source && !c.destination_is_prop && !c.source_is_prop
+            })
+            .map(|c| c.destination)
+    }
+
+    /// Returns IDs and property names of destination properties which the source object is
+    /// connected to, in connection order.
+    pub fn property_destinations(
+        &self,
+        source: i64,
+    ) -> impl Iterator<Item = (i64, &str)> + '_ {
+        self.0
+            .iter()
+            .filter(move |c| c.source == source && c.destination_is_prop && !c.source_is_prop)
+            .filter_map(|c| c.property.as_ref().map(|p| (c.destination, p.as_str())))
     }
 }
 
 
-/// Attributes read from a `C` node.
+/// Attributes read from a `C` (or legacy `Connect`) node.
 struct ConnectionAttrs {
     pub source_id: i64,
     pub destination_id: i64,
@@ -43,21 +183,16 @@ impl ConnectionAttrs {
     {
         use parser::binary::utils::AttributeValues;
 
-        if name == "C" {
-            let (ty, source_id, destination_id) =
-                <(String, i64, i64)>::from_attributes(&mut attrs)?
-                    .ok_or_else(|| Error::InvalidAttribute("C".to_owned()))?;
-            let (source_is_prop, destination_is_prop) = match ty.as_str() {
-                "OO" => (false, false),
-                "OP" => (false, true),
-                "PO" => (true, false),
-                "PP" => (true, true),
-                _ => return Err(Error::InvalidAttribute("C".to_owned())),
-            };
+        if name == "C" || name == "Connect" {
+            let invalid = || Error::InvalidAttribute(name.to_owned());
+            let ty = String::from_attributes(&mut attrs)?.ok_or_else(invalid)?;
+            let source_id = object_ref(attrs.next_attribute()?)?.ok_or_else(invalid)?;
+            let destination_id = object_ref(attrs.next_attribute()?)?.ok_or_else(invalid)?;
+            let (source_is_prop, destination_is_prop) = ConnectionKind::from_name(&ty)
+                .ok_or_else(invalid)?
+                .ends_are_props();
             let property = if attrs.rest_attributes() > 0 {
-                Some(String::from_attributes(&mut attrs)?.ok_or_else(|| {
-                    Error::InvalidAttribute("C".to_owned())
-                })?)
+                Some(String::from_attributes(&mut attrs)?.ok_or_else(invalid)?)
             } else {
                 None
             };
@@ -75,6 +210,70 @@ impl ConnectionAttrs {
 }
 
 
+/// Reads an end of a connection, either an object ID or a legacy object name.
+///
+/// See `legacy_object_id()` for legacy names.
+fn object_ref<R: ParserSource>(attr: Option<Attribute<R>>) -> Result<Option<i64>> {
+    use parser::binary::utils::AttributeValue;
+
+    Ok(match attr {
+        Some(attr @ Attribute::Special(_)) => {
+            String::from_attribute(attr)?.map(|name_class| legacy_object_id(&name_class))
+        },
+        Some(attr) => i64::from_attribute_loose(attr)?,
+        None => None,
+    })
+}
+
+
+/// Kind of a connection, i.e. whether each end is an object or a property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConnectionKind {
+    /// Object to object (`OO`), such as parenting and attachment.
+    ObjectObject,
+    /// Object to property (`OP`), such as textures and animation curve nodes bound to
+    /// properties.
+    ObjectProperty,
+    /// Property to object (`PO`).
+    PropertyObject,
+    /// Property to property (`PP`).
+    PropertyProperty,
+}
+
+impl ConnectionKind {
+    /// Creates a `ConnectionKind` from the type name (such as `OO`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "OO" => Some(ConnectionKind::ObjectObject),
+            "OP" => Some(ConnectionKind::ObjectProperty),
+            "PO" => Some(ConnectionKind::PropertyObject),
+            "PP" => Some(ConnectionKind::PropertyProperty),
+            _ => None,
+        }
+    }
+
+    /// Returns the type name.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ConnectionKind::ObjectObject => "OO",
+            ConnectionKind::ObjectProperty => "OP",
+            ConnectionKind::PropertyObject => "PO",
+            ConnectionKind::PropertyProperty => "PP",
+        }
+    }
+
+    /// Returns whether the source and the destination are properties.
+    fn ends_are_props(&self) -> (bool, bool) {
+        match *self {
+            ConnectionKind::ObjectObject => (false, false),
+            ConnectionKind::ObjectProperty => (false, true),
+            ConnectionKind::PropertyObject => (true, false),
+            ConnectionKind::PropertyProperty => (true, true),
+        }
+    }
+}
+
+
 /// `C` node.
 ///
 /// Note that "the child node will be a **source object** of the parent node", and "the parent node
@@ -96,6 +295,28 @@ pub struct Connection {
 }
 
 impl Connection {
+    /// Returns the kind of the connection.
+    pub fn kind(&self) -> ConnectionKind {
+        match (self.source_is_prop, self.destination_is_prop) {
+            (false, false) => ConnectionKind::ObjectObject,
+            (false, true) => ConnectionKind::ObjectProperty,
+            (true, false) => ConnectionKind::PropertyObject,
+            (true, true) => ConnectionKind::PropertyProperty,
+        }
+    }
+
+    /// Converts the connection into a `C` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let node = GenericNode::named("C")
+            .with_attr(self.kind().name())
+            .with_attr(self.source)
+            .with_attr(self.destination);
+        match self.property {
+            Some(ref property) => node.with_attr(property.as_str()),
+            None => node,
+        }
+    }
+
     /// Loads node contents from the parser.
     fn load<R, P>(mut parser: P, attrs: ConnectionAttrs) -> Result<Self>
     where
@@ -112,3 +333,123 @@ impl Connection {
         })
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{Attr, FbxBuilder, load_objects, NODES_BEFORE_OBJECTS, write_toplevel_node};
+    use test_util::write_nodes_before_objects;
+    use loader::binary::simple::fbx7400::legacy_object_id;
+    use super::{Connection, ConnectionKind, Connections};
+
+    #[test]
+    fn test_scene_roots() {
+        let mut b = FbxBuilder::new(7400);
+        for name in NODES_BEFORE_OBJECTS {
+            if *name != "Documents" {
+                write_toplevel_node(&mut b, name);
+                continue;
+            }
+            b.begin_node("Documents", &[]);
+            b.node("Count", &[Attr::I32(2)]);
+            for &(id, root) in &[(100, 0), (101, 200)] {
+                b.begin_node("Document", &[Attr::I64(id), Attr::String(""), Attr::String("Scene")]);
+                b.node("RootNode", &[Attr::I64(root)]);
+                b.end_node();
+            }
+            b.end_node();
+        }
+        b.begin_node("Objects", &[]);
+        for &id in &[1, 2, 3] {
+            let name_class = Attr::String("\u{0}\u{1}Model");
+            b.node("Model", &[Attr::I64(id), name_class, Attr::String("Null")]);
+        }
+        b.node("Material", &[Attr::I64(4), Attr::String("\u{0}\u{1}Material"), Attr::String("")]);
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        for &(source, destination) in &[(1, 0), (4, 0), (2, 1), (3, 200)] {
+            b.node("C", &[Attr::String("OO"), Attr::I64(source), Attr::I64(destination)]);
+        }
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        assert_eq!(fbx.documents.root_nodes(), vec![0, 200]);
+        assert_eq!(fbx.connections.scene_roots(&fbx.objects, &fbx.documents), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_duplicates_and_kinds() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.node("Objects", &[]);
+        b.begin_node("Connections", &[]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(1), Attr::I64(2)]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(1), Attr::I64(2)]);
+        b.node("C", &[Attr::String("OP"), Attr::I64(1), Attr::I64(2), Attr::String("Lcl")]);
+        b.node("C", &[Attr::String("OP"), Attr::I64(1), Attr::I64(2), Attr::String("Lcl")]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(3), Attr::I64(2)]);
+        b.end_node();
+        let data = b.finish();
+
+        let connections = load_objects(data).connections;
+        assert_eq!(connections.0.len(), 3);
+        assert_eq!(
+            connections.between(1, 2).map(|c| c.kind()).collect::<Vec<_>>(),
+            vec![ConnectionKind::ObjectObject, ConnectionKind::ObjectProperty]
+        );
+        assert_eq!(connections.of_kind(ConnectionKind::ObjectObject).count(), 2);
+        assert_eq!(connections.object_sources(2).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(connections.property_sources(2, "Lcl").collect::<Vec<_>>(), vec![1]);
+        let node = connections.0[1].to_generic_node();
+        assert_eq!(node.attributes[0].as_str(), Some("OP"));
+    }
+
+    #[test]
+    fn test_ordered_children() {
+        let connection = |source, destination| Connection {
+            source: source,
+            destination: destination,
+            property: None,
+            source_is_prop: false,
+            destination_is_prop: false,
+        };
+        let mut connections = Connections(
+            [(30, 1), (10, 2), (20, 1), (10, 1), (20, 1)]
+                .iter()
+                .map(|&(source, destination)| connection(source, destination))
+                .collect(),
+        );
+        assert_eq!(connections.object_sources(1).collect::<Vec<_>>(), vec![30, 20, 10, 20]);
+        assert_eq!(connections.ordered_children_of(1), vec![30, 20, 10]);
+        connections.dedup();
+        assert_eq!(connections.ordered_children_of(1), vec![30, 20, 10]);
+        assert_eq!(connections.ordered_children_of(2), vec![10]);
+    }
+
+    #[test]
+    fn test_legacy_names() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.node("Model", &[Attr::String("Cube\u{0}\u{1}Model"), Attr::String("Mesh")]);
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        b.node(
+            "Connect",
+            &[
+                Attr::String("OO"),
+                Attr::String("Cube\u{0}\u{1}Model"),
+                Attr::String("Scene\u{0}\u{1}Model"),
+            ],
+        );
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let id = legacy_object_id("Cube\u{0}\u{1}Model");
+        let model = fbx.objects.object(id).expect("Legacy object should be loaded");
+        assert_eq!((model.name(), model.subclass()), ("Cube", "Mesh"));
+        assert_eq!(fbx.connections.object_sources(0).collect::<Vec<_>>(), vec![id]);
+    }
+}
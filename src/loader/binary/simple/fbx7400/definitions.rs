@@ -2,19 +2,20 @@
 
 use fnv::FnvHashMap;
 use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
-use loader::binary::simple::fbx7400::{Properties70, PropertyMap, PropertyValue};
+use loader::binary::simple::{Result, Error, GenericNode};
+use loader::binary::simple::fbx7400::{Properties70, PropertyValue, EffectiveProperties};
+use loader::binary::simple::fbx7400::ObjectProperties;
 
 
 /// `Definitions` node.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Definitions {
     /// Version of the node.
     pub version: i32,
     /// Reference count?
     pub count: i32,
     /// Property templates for object types.
-    pub object_types: Vec<ObjectType>,
+    pub object_types: ObjectTypes,
 }
 
 impl Definitions {
@@ -26,7 +27,7 @@ impl Definitions {
     {
         let mut version = None;
         let mut count = None;
-        let mut object_types = Vec::new();
+        let mut object_types = ObjectTypes::new();
 
         loop {
             let node_type = try_get_node_attrs!(parser, DefinitionsChildAttrs::load);
@@ -40,7 +41,10 @@ impl Definitions {
                     parser.skip_current_node()?;
                 },
                 DefinitionsChildAttrs::ObjectType(attrs) => {
-                    object_types.push(ObjectType::load(parser.subtree_parser(), attrs)?);
+                    let object_type = ObjectType::load(parser.subtree_guard(), attrs)?;
+                    if let Some(old) = object_types.insert(object_type) {
+                        warn!("Duplicate object type `{}`, overwriting old one", old.object_type);
+                    }
                 },
             }
         }
@@ -51,32 +55,85 @@ impl Definitions {
         })
     }
 
+    /// Converts the definitions into a `Definitions` node.
+    ///
+    /// Object types are written in insertion order.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("Definitions")
+            .with_child(GenericNode::named("Version").with_attr(self.version))
+            .with_child(GenericNode::named("Count").with_attr(self.count))
+            .with_children(self.object_types.iter().map(ObjectType::to_generic_node))
+    }
+
+    /// Returns the property template for the given object type and class name.
+    ///
+    /// For example, `template_for("Material", "FbxSurfacePhong")` returns the template for
+    /// Phong materials.
+    pub fn template_for(&self, object_type: &str, class_name: &str) -> Option<&Properties70> {
+        self.object_types
+            .get(object_type)
+            .and_then(|t| t.property_template.get(class_name))
+    }
+
+    /// Returns the property template for the object.
+    ///
+    /// If the object type has only one template, it is used.
+    /// Otherwise, the class name is guessed from the object: `FbxSurfacePhong` or
+    /// `FbxSurfaceLambert` for materials (by `ShadingModel` property), and `Fbx` followed by
+    /// the subclass (such as `FbxCamera`) for others.
+    pub fn template_for_object(
+        &self,
+        props: &ObjectProperties,
+        properties: Option<&Properties70>,
+    ) -> Option<&Properties70> {
+        let templates = &self.object_types.get(&props.class)?.property_template;
+        if templates.len() == 1 {
+            return templates.values().next();
+        }
+        let class_name = if props.class == "Material" {
+            let shading_model = properties
+                .and_then(|p| p.get_string("ShadingModel"))
+                .unwrap_or("");
+            if shading_model.eq_ignore_ascii_case("lambert") {
+                "FbxSurfaceLambert".to_owned()
+            } else {
+                "FbxSurfacePhong".to_owned()
+            }
+        } else {
+            format!("Fbx{}", props.subclass)
+        };
+        templates.get(&class_name)
+    }
+
+    /// Returns the properties of the object overlaid on its property template.
+    pub fn effective_properties<'a>(
+        &'a self,
+        props: &ObjectProperties,
+        properties: Option<&'a Properties70>,
+    ) -> EffectiveProperties<'a> {
+        EffectiveProperties::new(properties, self.template_for_object(props, properties))
+    }
+
     /// Returns reference to `Properties70` object of the specified node.
+    ///
+    /// This is same as `template_for()`.
     pub fn get_properties70(&self, object_type: &str, node_type: &str) -> Option<&Properties70> {
-        self.object_types
-            .iter()
-            .find(|t| t.object_type == object_type)
-            .and_then(|t| t.property_template.get(node_type))
+        self.template_for(object_type, node_type)
     }
 
     /// Looks up and returns the property value.
-    pub fn get_property_value<'s, 'p, T, F>(
+    ///
+    /// If `props` does not have the property, the value from the property template is returned.
+    pub fn get_property_value<'p>(
         &'p self,
-        object_type: &'s str,
-        node_type: &'s str,
-        prop_name: &'s str,
+        object_type: &str,
+        node_type: &str,
+        prop_name: &str,
         props: &'p Properties70,
-        f: F,
-    ) -> Option<&'p PropertyValue<T>>
-    where
-        F: Fn(&Properties70) -> &PropertyMap<T>,
-    {
-        f(props).get(prop_name).or_else(|| {
-            self.get_properties70(object_type, node_type).and_then(
-                |v| {
-                    f(v).get(prop_name)
-                },
-            )
+    ) -> Option<&'p PropertyValue> {
+        props.get(prop_name).or_else(|| {
+            self.get_properties70(object_type, node_type)
+                .and_then(|v| v.get(prop_name))
         })
     }
 }
@@ -89,6 +146,74 @@ child_attr_loader! { DefinitionsChildAttrs {
 }}
 
 
+/// Object types keyed by type name, iterated in insertion order.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ObjectTypes {
+    /// Object types.
+    types: FnvHashMap<String, ObjectType>,
+    /// Type names in insertion order.
+    order: Vec<String>,
+}
+
+impl ObjectTypes {
+    /// Creates a new empty `ObjectTypes`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Inserts the object type and returns the old one with the same type name.
+    ///
+    /// If the type name already exists, the position in the iteration order is kept.
+    pub fn insert(&mut self, object_type: ObjectType) -> Option<ObjectType> {
+        let name = object_type.object_type.clone();
+        let old = self.types.insert(name.clone(), object_type);
+        if old.is_none() {
+            self.order.push(name);
+        }
+        old
+    }
+
+    /// Removes the object type with the given type name and returns it.
+    pub fn remove(&mut self, object_type: &str) -> Option<ObjectType> {
+        let old = self.types.remove(object_type);
+        if old.is_some() {
+            self.order.retain(|name| name != object_type);
+        }
+        old
+    }
+
+    /// Returns the object type with the given type name.
+    pub fn get(&self, object_type: &str) -> Option<&ObjectType> {
+        self.types.get(object_type)
+    }
+
+    /// Returns the object type with the given type name.
+    pub fn get_mut(&mut self, object_type: &str) -> Option<&mut ObjectType> {
+        self.types.get_mut(object_type)
+    }
+
+    /// Returns `true` if the object type with the given type name exists.
+    pub fn contains(&self, object_type: &str) -> bool {
+        self.types.contains_key(object_type)
+    }
+
+    /// Returns an iterator of object types in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &ObjectType> {
+        self.order.iter().map(move |name| &self.types[name])
+    }
+
+    /// Returns the number of object types.
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Returns `true` if there are no object types.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+
 /// An object type and property template for it.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ObjectType {
@@ -118,7 +243,7 @@ impl ObjectType {
                     parser.skip_current_node()?;
                 },
                 ObjectTypeChildAttrs::PropertyTemplate(attrs) => {
-                    let props = load_property_template(parser.subtree_parser())?;
+                    let props = load_property_template(parser.subtree_guard())?;
                     property_template.insert(attrs, props);
                 },
             }
@@ -130,6 +255,22 @@ impl ObjectType {
             property_template: property_template,
         })
     }
+
+    /// Converts the object type into an `ObjectType` node.
+    ///
+    /// Property templates are sorted by class names.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let mut templates = self.property_template.iter().collect::<Vec<_>>();
+        templates.sort_by_key(|&(class_name, _)| class_name);
+        GenericNode::named("ObjectType")
+            .with_attr(self.object_type.as_str())
+            .with_child(GenericNode::named("Count").with_attr(self.count))
+            .with_children(templates.into_iter().map(|(class_name, props)| {
+                GenericNode::named("PropertyTemplate")
+                    .with_attr(class_name.as_str())
+                    .with_child(props.to_generic_node())
+            }))
+    }
 }
 
 
@@ -152,7 +293,7 @@ where
         } else {
             Err(Error::UnexpectedNode(name.to_owned()))
         });
-        props = Some(Properties70::load(parser.subtree_parser())?);
+        props = Some(Properties70::load(parser.subtree_guard())?);
     }
     Ok(ensure_node_exists!(
         props,
@@ -160,3 +301,103 @@ where
         "Properties70"
     ))
 }
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use parser::binary::{Parser, RootParser, Event};
+    use test_util::{Attr, FbxBuilder, write_properties70};
+    use std::borrow::Cow;
+    use fnv::FnvHashMap;
+    use loader::binary::simple::fbx7400::{ObjectProperties, Properties70};
+    use super::{Definitions, ObjectType};
+
+    #[test]
+    fn test_template_for() {
+        let mut b = FbxBuilder::new(7400);
+        b.begin_node("Definitions", &[]);
+        b.node("Version", &[Attr::I32(100)]);
+        b.node("Count", &[Attr::I32(3)]);
+        for &(object_type, class_name) in &[
+            ("Material", "FbxSurfacePhong"),
+            ("Model", "FbxNode"),
+            ("GlobalSettings", ""),
+        ]
+        {
+            b.begin_node("ObjectType", &[Attr::String(object_type)]);
+            b.node("Count", &[Attr::I32(1)]);
+            if !class_name.is_empty() {
+                b.begin_node("PropertyTemplate", &[Attr::String(class_name)]);
+                let opacity = ("Opacity", "double", "Number", "", &[Attr::F64(1.0)][..]);
+                write_properties70(&mut b, &[opacity]);
+                b.end_node();
+            }
+            b.end_node();
+        }
+        b.end_node();
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartFbx(_)));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartNode(_)));
+        let defs = Definitions::load(parser.subtree_parser()).expect("Load error");
+
+        assert_eq!(
+            defs.object_types.iter().map(|t| t.object_type.as_str()).collect::<Vec<_>>(),
+            vec!["Material", "Model", "GlobalSettings"]
+        );
+        assert_eq!(defs.object_types.get("Model").map(|t| t.count), Some(1));
+        let template = defs.template_for("Material", "FbxSurfacePhong").expect("Should exist");
+        assert_eq!(template.get_f64("Opacity"), Some(1.0));
+        assert!(defs.template_for("Material", "FbxSurfaceLambert").is_none());
+        assert!(defs.template_for("Texture", "FbxFileTexture").is_none());
+    }
+
+    #[test]
+    fn test_effective_properties() {
+        let mut phong = Properties70::new();
+        phong.insert("ShadingModel", "Phong");
+        phong.insert("DiffuseColor", [0.8, 0.8, 0.8]);
+        phong.insert("Shininess", 20.0);
+        let mut lambert = Properties70::new();
+        lambert.insert("DiffuseColor", [0.5, 0.5, 0.5]);
+        let mut templates = FnvHashMap::default();
+        templates.insert("FbxSurfacePhong".to_owned(), phong);
+        templates.insert("FbxSurfaceLambert".to_owned(), lambert);
+        let mut defs = Definitions::default();
+        defs.object_types.insert(ObjectType {
+            object_type: "Material".to_owned(),
+            count: 1,
+            property_template: templates,
+        });
+
+        let mut props = Properties70::new();
+        props.insert("ShadingModel", "phong");
+        props.insert("DiffuseColor", [1.0, 0.0, 0.0]);
+        let object = ObjectProperties {
+            id: 1,
+            name: "Red".to_owned(),
+            class: "Material".to_owned(),
+            subclass: "".to_owned(),
+        };
+        let effective = defs.effective_properties(&object, Some(&props));
+        assert_eq!(effective.get_f64_3("DiffuseColor"), Some([1.0, 0.0, 0.0]));
+        assert_eq!(effective.get_f64("Shininess"), Some(20.0));
+        assert!(effective.is_overridden("DiffuseColor"));
+        assert!(!effective.is_overridden("Shininess"));
+        let mut names = effective.iter().map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, vec!["DiffuseColor", "ShadingModel", "Shininess"]);
+        let materialized = effective.materialize();
+        assert!(matches!(materialized, Cow::Owned(_)));
+        assert_eq!(materialized.len(), 3);
+        assert_eq!(materialized.get_string("ShadingModel"), Some("phong"));
+
+        let mut lambert_props = Properties70::new();
+        lambert_props.insert("ShadingModel", "Lambert");
+        lambert_props.insert("DiffuseColor", [0.0, 1.0, 0.0]);
+        let effective = defs.effective_properties(&object, Some(&lambert_props));
+        assert!(matches!(effective.materialize(), Cow::Borrowed(_)));
+    }
+}
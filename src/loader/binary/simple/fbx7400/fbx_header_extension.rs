@@ -1,29 +1,34 @@
 //! `Definitions` node and its children.
 
 use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
-use loader::binary::simple::fbx7400::Properties70;
+use loader::binary::simple::{Result, Error, GenericNode};
+use loader::binary::simple::fbx7400::{Properties70, separate_name_class, join_name_class};
 
 
 /// `FBXHeaderExtension` node.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct FbxHeaderExtension {
     /// Version of the node.
     pub fbx_header_version: i32,
     /// Version of the FBX.
     pub fbx_version: i32,
     /// Encryption type.
-    pub encryption_type: i32,
+    pub encryption_type: Option<i32>,
     /// Creation time stamp.
-    pub creation_timestamp: CreationTimeStamp,
+    pub creation_timestamp: Option<CreationTimeStamp>,
     /// Creator.
-    pub creator: String,
+    pub creator: Option<String>,
     /// Scene info.
-    pub scene_info: SceneInfo,
+    pub scene_info: Option<SceneInfo>,
+    /// Child nodes unknown to the loader (such as `OtherFlags`).
+    pub extra_nodes: Vec<GenericNode>,
 }
 
 impl FbxHeaderExtension {
     /// Loads node contents from the parser.
+    ///
+    /// Only `FBXHeaderVersion` and `FBXVersion` are required.
+    /// Unknown child nodes are loaded into `extra_nodes`.
     pub fn load<R, P>(mut parser: P) -> Result<Self>
     where
         R: ParserSource,
@@ -35,9 +40,30 @@ impl FbxHeaderExtension {
         let mut creation_timestamp = None;
         let mut creator = None;
         let mut scene_info = None;
+        let mut extra_nodes = Vec::new();
 
         loop {
-            let node_type = try_get_node_attrs!(parser, FbxHeaderExtensionChildAttrs::load);
+            let node_type = try_get_node_attrs!(parser, |name: &str, mut attrs: Attributes<R>| {
+                if FbxHeaderExtensionChildAttrs::is_known(name) {
+                    FbxHeaderExtensionChildAttrs::load(name, attrs).map(Ok)
+                } else {
+                    let attributes = attrs.collect_owned()?;
+                    Ok(Err(GenericNode {
+                        name: name.to_owned(),
+                        attributes: attributes,
+                        children: Vec::new(),
+                    }))
+                }
+            });
+            let node_type = match node_type {
+                Ok(node_type) => node_type,
+                Err(mut node) => {
+                    debug!("Unknown node `{}` in `FBXHeaderExtension`", node.name);
+                    node.children = GenericNode::load_from_parser(&mut parser.subtree_parser())?.0;
+                    extra_nodes.push(node);
+                    continue;
+                },
+            };
             match node_type {
                 FbxHeaderExtensionChildAttrs::FbxHeaderVersion(v) => {
                     fbx_header_version = Some(v);
@@ -52,14 +78,14 @@ impl FbxHeaderExtension {
                     parser.skip_current_node()?;
                 },
                 FbxHeaderExtensionChildAttrs::CreationTimeStamp => {
-                    creation_timestamp = Some(CreationTimeStamp::load(parser.subtree_parser())?);
+                    creation_timestamp = Some(CreationTimeStamp::load(parser.subtree_guard())?);
                 },
                 FbxHeaderExtensionChildAttrs::Creator(v) => {
                     creator = Some(v);
                     parser.skip_current_node()?;
                 },
                 FbxHeaderExtensionChildAttrs::SceneInfo(attrs) => {
-                    scene_info = Some(SceneInfo::load(parser.subtree_parser(), attrs)?);
+                    scene_info = Some(SceneInfo::load(parser.subtree_guard(), attrs)?);
                 },
             }
         }
@@ -70,20 +96,40 @@ impl FbxHeaderExtension {
                 "FBXHeaderVersion"
             ),
             fbx_version: ensure_node_exists!(fbx_version, "FBXHeaderExtension", "FBXVersion"),
-            encryption_type: ensure_node_exists!(
-                encryption_type,
-                "FBXHeaderExtension",
-                "EncryptionType"
-            ),
-            creation_timestamp: ensure_node_exists!(
-                creation_timestamp,
-                "FBXHeaderExtension",
-                "CreationTimeStamp"
-            ),
-            creator: ensure_node_exists!(creator, "FBXHeaderExtension", "Creator"),
-            scene_info: ensure_node_exists!(scene_info, "FBXHeaderExtension", "SceneInfo"),
+            encryption_type: encryption_type,
+            creation_timestamp: creation_timestamp,
+            creator: creator,
+            scene_info: scene_info,
+            extra_nodes: extra_nodes,
         })
     }
+
+    /// Converts the header extension into a `FBXHeaderExtension` node.
+    ///
+    /// Nodes in `extra_nodes` are put after the known nodes.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let mut node = GenericNode::named("FBXHeaderExtension")
+            .with_child(GenericNode::named("FBXHeaderVersion").with_attr(self.fbx_header_version))
+            .with_child(GenericNode::named("FBXVersion").with_attr(self.fbx_version));
+        if let Some(v) = self.encryption_type {
+            node.push_child(GenericNode::named("EncryptionType").with_attr(v));
+        }
+        if let Some(ref stamp) = self.creation_timestamp {
+            node.push_child(stamp.to_generic_node());
+        }
+        if let Some(ref creator) = self.creator {
+            node.push_child(GenericNode::named("Creator").with_attr(creator.as_str()));
+        }
+        if let Some(ref scene_info) = self.scene_info {
+            node.push_child(scene_info.to_generic_node());
+        }
+        node.with_children(self.extra_nodes.iter().cloned())
+    }
+
+    /// Returns the application metadata from the scene info.
+    pub fn application_info(&self) -> Option<ApplicationInfo> {
+        self.scene_info.as_ref().map(SceneInfo::application_info)
+    }
 }
 
 
@@ -98,7 +144,7 @@ child_attr_loader! { FbxHeaderExtensionChildAttrs {
 
 
 /// Creation time stamp.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CreationTimeStamp {
     /// Version.
     pub version: i32,
@@ -175,6 +221,23 @@ impl CreationTimeStamp {
             millisecond: ensure_node_exists!(millisecond, "CreationTimeStamp", "Millisecond"),
         })
     }
+
+    /// Converts the time stamp into a `CreationTimeStamp` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let values = [
+            ("Version", self.version),
+            ("Year", self.year),
+            ("Month", self.month),
+            ("Day", self.day),
+            ("Hour", self.hour),
+            ("Minute", self.minute),
+            ("Second", self.second),
+            ("Millisecond", self.millisecond),
+        ];
+        GenericNode::named("CreationTimeStamp").with_children(
+            values.iter().map(|&(name, v)| GenericNode::named(name).with_attr(v)),
+        )
+    }
 }
 
 
@@ -191,7 +254,7 @@ child_attr_loader! { CreationTimeStampChildAttrs {
 
 
 /// Scene info.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct SceneInfo {
     /// Object name?
     pub name: String,
@@ -228,9 +291,10 @@ impl SceneInfo {
         let mut properties = None;
 
         // Attrs.
-        let (name, class) = separate_name_class(&attrs.0)
-            .map(|(n, c)| (n.into(), c.into()))
-            .ok_or_else(|| Error::InvalidAttribute("SceneInfo".to_owned()))?;
+        let (name, class) = {
+            let name_class = separate_name_class(&attrs.0);
+            (name_class.name.into(), name_class.class.into())
+        };
         let subclass = attrs.1;
 
 
@@ -246,10 +310,10 @@ impl SceneInfo {
                     parser.skip_current_node()?;
                 },
                 SceneInfoChildAttrs::MetaData => {
-                    metadata = Some(MetaData::load(parser.subtree_parser())?);
+                    metadata = Some(MetaData::load(parser.subtree_guard())?);
                 },
                 SceneInfoChildAttrs::Properties => {
-                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                    properties = Some(Properties70::load(parser.subtree_guard())?);
                 },
             }
         }
@@ -263,6 +327,22 @@ impl SceneInfo {
             properties: ensure_node_exists!(properties, "SceneInfo", "Properties70"),
         })
     }
+
+    /// Converts the scene info into a `SceneInfo` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("SceneInfo")
+            .with_attr(join_name_class(&self.name, &self.class))
+            .with_attr(self.subclass.as_str())
+            .with_child(GenericNode::named("Type").with_attr(self.type_.as_str()))
+            .with_child(GenericNode::named("Version").with_attr(self.version))
+            .with_child(self.metadata.to_generic_node())
+            .with_child(self.properties.to_generic_node())
+    }
+
+    /// Returns the application metadata from the properties.
+    pub fn application_info(&self) -> ApplicationInfo {
+        ApplicationInfo::from_properties(&self.properties)
+    }
 }
 
 
@@ -274,8 +354,67 @@ child_attr_loader! { SceneInfoChildAttrs {
 }}
 
 
+/// Metadata about the application which created or saved the document.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ApplicationInfo {
+    /// URL of the document (`DocumentUrl`).
+    pub document_url: Option<String>,
+    /// URL of the source document (`SrcDocumentUrl`).
+    pub src_document_url: Option<String>,
+    /// Application which originally created the document (`Original|*`).
+    pub original: SavingApplication,
+    /// Original file name (`Original|FileName`).
+    pub original_file_name: Option<String>,
+    /// Application which saved the document last (`LastSaved|*`).
+    pub last_saved: SavingApplication,
+}
+
+impl ApplicationInfo {
+    /// Creates an `ApplicationInfo` from the `SceneInfo` properties.
+    pub fn from_properties(props: &Properties70) -> Self {
+        let string = |name: &str| props.get_string(name).map(str::to_owned);
+        ApplicationInfo {
+            document_url: string("DocumentUrl"),
+            src_document_url: string("SrcDocumentUrl"),
+            original: SavingApplication::from_properties(props, "Original"),
+            original_file_name: string("Original|FileName"),
+            last_saved: SavingApplication::from_properties(props, "LastSaved"),
+        }
+    }
+}
+
+
+/// Application which created or saved the document.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SavingApplication {
+    /// Application vendor (`*|ApplicationVendor`).
+    pub vendor: Option<String>,
+    /// Application name (`*|ApplicationName`).
+    pub name: Option<String>,
+    /// Application version (`*|ApplicationVersion`).
+    pub version: Option<String>,
+    /// Date and time in GMT (`*|DateTime_GMT`), such as `01/01/1970 00:00:00.000`.
+    pub date_time_gmt: Option<String>,
+}
+
+impl SavingApplication {
+    /// Creates a `SavingApplication` from the properties with the given prefix.
+    fn from_properties(props: &Properties70, prefix: &str) -> Self {
+        let string = |name: &str| {
+            props.get_string(&format!("{}|{}", prefix, name)).map(str::to_owned)
+        };
+        SavingApplication {
+            vendor: string("ApplicationVendor"),
+            name: string("ApplicationName"),
+            version: string("ApplicationVersion"),
+            date_time_gmt: string("DateTime_GMT"),
+        }
+    }
+}
+
+
 /// FBX metadata.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MetaData {
     /// Version.
     pub version: i32,
@@ -345,6 +484,19 @@ impl MetaData {
             comment: ensure_node_exists!(comment, "MetaData", "Comment"),
         })
     }
+
+    /// Converts the metadata into a `MetaData` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let string = |name: &str, v: &str| GenericNode::named(name).with_attr(v);
+        GenericNode::named("MetaData")
+            .with_child(GenericNode::named("Version").with_attr(self.version))
+            .with_child(string("Title", &self.title))
+            .with_child(string("Subject", &self.subject))
+            .with_child(string("Author", &self.author))
+            .with_child(string("Keywords", &self.keywords))
+            .with_child(string("Revision", &self.revision))
+            .with_child(string("Comment", &self.comment))
+    }
 }
 
 
@@ -359,9 +511,59 @@ child_attr_loader! { MetaDataChildAttrs {
 }}
 
 
-/// Returns `Option<(name: &'a str, class: &'a str)>`
-fn separate_name_class(name_class: &str) -> Option<(&str, &str)> {
-    name_class.find("\u{0}\u{1}").map(|sep_pos| {
-        (&name_class[0..sep_pos], &name_class[sep_pos + 2..])
-    })
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use parser::binary::{Parser, RootParser, Event};
+    use test_util::{Attr, FbxBuilder};
+    use loader::binary::simple::fbx7400::Properties70;
+    use super::{FbxHeaderExtension, ApplicationInfo};
+
+    #[test]
+    fn test_optional_and_unknown_nodes() {
+        let mut b = FbxBuilder::new(7400);
+        b.begin_node("FBXHeaderExtension", &[]);
+        b.node("FBXHeaderVersion", &[Attr::I32(1003)]);
+        b.node("FBXVersion", &[Attr::I32(7400)]);
+        b.begin_node("OtherFlags", &[]);
+        b.node("TCDefinition", &[Attr::I32(127)]);
+        b.end_node();
+        b.node("Creator", &[Attr::String("Exporter")]);
+        b.end_node();
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartFbx(_)));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartNode(_)));
+        let header = FbxHeaderExtension::load(parser.subtree_parser()).expect("Load error");
+
+        assert_eq!(header.fbx_version, 7400);
+        assert_eq!(header.creator.as_deref(), Some("Exporter"));
+        assert!(header.encryption_type.is_none());
+        assert!(header.scene_info.is_none());
+        assert_eq!(header.extra_nodes.len(), 1);
+        let other_flags = &header.extra_nodes[0];
+        assert_eq!(other_flags.name, "OtherFlags");
+        assert_eq!(other_flags.child("TCDefinition").and_then(|n| n.first_attr_as()), Some(127));
+    }
+
+    #[test]
+    fn test_application_info() {
+        let mut props = Properties70::new();
+        props.insert("DocumentUrl", "/tmp/scene.fbx");
+        props.insert("Original|ApplicationVendor", "Blender Foundation");
+        props.insert("Original|ApplicationName", "Blender (stable FBX IO)");
+        props.insert("Original|FileName", "/tmp/scene.fbx");
+        props.insert("LastSaved|ApplicationVersion", "2.79");
+        let info = ApplicationInfo::from_properties(&props);
+
+        assert_eq!(info.document_url.as_deref(), Some("/tmp/scene.fbx"));
+        assert!(info.src_document_url.is_none());
+        assert_eq!(info.original.vendor.as_deref(), Some("Blender Foundation"));
+        assert_eq!(info.original.name.as_deref(), Some("Blender (stable FBX IO)"));
+        assert!(info.original.version.is_none());
+        assert_eq!(info.original_file_name, info.document_url);
+        assert_eq!(info.last_saved.version.as_deref(), Some("2.79"));
+    }
 }
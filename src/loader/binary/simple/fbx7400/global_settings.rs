@@ -1,8 +1,9 @@
 //! `GlobalSettings` node and its children.
 
 use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
+use loader::binary::simple::{Result, Error, GenericNode};
 use loader::binary::simple::fbx7400::Properties70;
+use loader::time::{FbxTime, TimeMode};
 
 
 /// `GlobalSettings` node.
@@ -16,6 +17,10 @@ pub struct GlobalSettings {
 
 impl GlobalSettings {
     /// Loads node contents from the parser.
+    ///
+    /// Legacy `Properties60` is accepted in place of `Properties70`.
+    /// If `Version` or the properties are missing (as in some FBX 6 files), the default values
+    /// (see `default()`) are used with a warning.
     pub fn load<R, P>(mut parser: P) -> Result<Self>
     where
         R: ParserSource,
@@ -32,19 +37,143 @@ impl GlobalSettings {
                     parser.skip_current_node()?;
                 },
                 GlobalSettingsChildAttrs::Properties70 => {
-                    properties = Some(Properties70::load(parser.subtree_parser())?);
+                    properties = Some(Properties70::load(parser.subtree_guard())?);
+                },
+                GlobalSettingsChildAttrs::Properties60 => {
+                    properties = Some(Properties70::load_properties60(parser.subtree_guard())?);
                 },
             }
         }
+        let default = GlobalSettings::default();
         Ok(GlobalSettings {
-            version: ensure_node_exists!(version, "Definitions", "Version"),
-            properties: ensure_node_exists!(properties, "Definitions", "Properties70"),
+            version: version.unwrap_or_else(|| {
+                warn!("`GlobalSettings` has no `Version`, assuming {}", default.version);
+                default.version
+            }),
+            properties: properties.unwrap_or_else(|| {
+                warn!("`GlobalSettings` has no properties, using default settings");
+                default.properties
+            }),
         })
     }
+
+    /// Converts the settings into a `GlobalSettings` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("GlobalSettings")
+            .with_child(GenericNode::named("Version").with_attr(self.version))
+            .with_child(self.properties.to_generic_node())
+    }
+
+    /// Returns the global ambient color (`AmbientColor` property).
+    pub fn ambient_color(&self) -> Option<[f64; 3]> {
+        self.properties.get_f64_3("AmbientColor")
+    }
+
+    /// Returns the unit scale in centimeters per unit (`UnitScaleFactor` property).
+    pub fn unit_scale_factor(&self) -> Option<f64> {
+        self.properties.get_f64("UnitScaleFactor")
+    }
+
+    /// Returns the time mode (`TimeMode` property).
+    pub fn time_mode(&self) -> Option<TimeMode> {
+        self.properties.get_i64("TimeMode").map(TimeMode::from_i64)
+    }
+
+    /// Returns the custom frame rate (`CustomFrameRate` property).
+    pub fn custom_frame_rate(&self) -> Option<f64> {
+        self.properties.get_f64("CustomFrameRate")
+    }
+
+    /// Returns the frame rate of the scene in frames per second.
+    ///
+    /// `CustomFrameRate` is used for the custom time mode.
+    /// If `TimeMode` is missing, the default mode (30 frames per second) is assumed.
+    pub fn frame_rate(&self) -> Option<f64> {
+        self.time_mode()
+            .unwrap_or(TimeMode::Default)
+            .resolve_frame_rate(self.custom_frame_rate())
+    }
+
+    /// Returns the start and stop time of the scene (`TimeSpanStart` and `TimeSpanStop`).
+    pub fn time_span(&self) -> Option<(FbxTime, FbxTime)> {
+        let start = self.properties.get_i64("TimeSpanStart")?;
+        let stop = self.properties.get_i64("TimeSpanStop")?;
+        Some((FbxTime(start), FbxTime(stop)))
+    }
+}
+
+
+impl Default for GlobalSettings {
+    /// Returns the default settings of FBX SDK.
+    ///
+    /// The scene is Y-up, right-handed (`UpAxis` = 1, `FrontAxis` = 2, `CoordAxis` = 0, all
+    /// signs positive), in centimeters (`UnitScaleFactor` = 1), and in the default time mode
+    /// (30 frames per second).
+    fn default() -> Self {
+        let mut properties = Properties70::new();
+        for &(name, value) in &[
+            ("UpAxis", 1),
+            ("UpAxisSign", 1),
+            ("FrontAxis", 2),
+            ("FrontAxisSign", 1),
+            ("CoordAxis", 0),
+            ("CoordAxisSign", 1),
+            ("OriginalUpAxis", -1),
+            ("OriginalUpAxisSign", 1),
+            ("TimeMode", 0),
+        ] {
+            properties.insert(name, value as i64);
+        }
+        properties.insert("UnitScaleFactor", 1.0);
+        properties.insert("OriginalUnitScaleFactor", 1.0);
+        GlobalSettings {
+            version: 1000,
+            properties: properties,
+        }
+    }
 }
 
 
 child_attr_loader! { GlobalSettingsChildAttrs {
     "Version" => Version(i32),
     "Properties70" => Properties70,
+    "Properties60" => Properties60,
 }}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::{Properties70, PropertyValue};
+    use loader::time::{FbxTime, TimeMode};
+    use super::GlobalSettings;
+
+    #[test]
+    fn test_time_settings() {
+        let mut properties = Properties70::new();
+        properties.insert("TimeMode", PropertyValue::I64(14));
+        properties.insert("CustomFrameRate", PropertyValue::F64(12.5));
+        properties.insert("TimeSpanStart", PropertyValue::I64(0));
+        properties.insert("TimeSpanStop", PropertyValue::I64(FbxTime::TICKS_PER_SECOND));
+        let mut settings = GlobalSettings {
+            version: 1000,
+            properties: properties,
+        };
+        assert_eq!(settings.time_mode(), Some(TimeMode::Custom));
+        assert_eq!(settings.frame_rate(), Some(12.5));
+        let (start, stop) = settings.time_span().expect("Time span should exist");
+        assert_eq!((stop - start).seconds(), 1.0);
+
+        settings.properties.insert("TimeMode", PropertyValue::I64(10));
+        assert_eq!(settings.frame_rate(), Some(25.0));
+        settings.properties.remove("TimeMode");
+        assert_eq!(settings.frame_rate(), Some(30.0));
+    }
+
+    #[test]
+    fn test_default() {
+        let settings = GlobalSettings::default();
+        assert_eq!(settings.properties.get_i64("UpAxis"), Some(1));
+        assert_eq!(settings.unit_scale_factor(), Some(1.0));
+        assert_eq!(settings.frame_rate(), Some(30.0));
+    }
+}
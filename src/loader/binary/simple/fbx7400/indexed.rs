@@ -0,0 +1,256 @@
+//! Object index of seekable FBX 7.4 compatible data, for loading objects on demand.
+
+use std::io::{Read, Seek};
+#[cfg(feature = "rayon")]
+use std::io::Cursor;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use parser::binary::{Parser, RootParser, Event};
+use loader::binary::simple::{Result, Error};
+use loader::binary::simple::fbx7400::{LoadObject, ObjectProperties, ObjectMap};
+#[cfg(feature = "rayon")]
+use loader::binary::simple::fbx7400::{Object, ObjectLoadOptions, Objects};
+
+
+/// Location and properties of an object node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectEntry {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// Start offset of the object node header.
+    pub offset: u64,
+}
+
+
+/// Seekable FBX 7.4 compatible data with index of objects.
+///
+/// Only the attributes of object nodes are read on creation, and object contents are loaded
+/// on demand with `load_object()`.
+#[derive(Debug)]
+pub struct IndexedFile<R> {
+    /// Source reader.
+    source: R,
+    /// FBX version.
+    version: u32,
+    /// Object entries.
+    entries: ObjectMap<ObjectEntry>,
+}
+
+impl<R: Read + Seek> IndexedFile<R> {
+    /// Scans the given source and creates the object index.
+    pub fn new(mut source: R) -> Result<Self> {
+        let (version, entries) = {
+            let mut parser = RootParser::from_seekable(&mut source);
+            let version = match parser.next_event()? {
+                Event::StartFbx(header) => header.version,
+                ref ev => return Err(Error::unexpected_event(ev)),
+            };
+            let mut entries = None;
+            loop {
+                let is_objects = match parser.next_event()? {
+                    Event::StartNode(info) => info.name == "Objects",
+                    Event::EndFbx(_) => break,
+                    ref ev => return Err(Error::unexpected_event(ev)),
+                };
+                if is_objects && entries.is_none() {
+                    entries = Some(scan_objects(&mut parser)?);
+                } else {
+                    parser.skip_current_node()?;
+                }
+            }
+            let entries = entries.ok_or_else(|| Error::missing_node("(root)", "Objects"))?;
+            (version, entries)
+        };
+
+        Ok(IndexedFile {
+            source: source,
+            version: version,
+            entries: entries,
+        })
+    }
+
+    /// Returns FBX version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the object entry with the given ID.
+    pub fn entry(&self, id: i64) -> Option<&ObjectEntry> {
+        self.entries.get(&id)
+    }
+
+    /// Returns object entries.
+    pub fn entries(&self) -> &ObjectMap<ObjectEntry> {
+        &self.entries
+    }
+
+    /// Loads the object with the given ID.
+    ///
+    /// Only the subtree of the object node is read.
+    /// Returns `Ok(None)` if the object with the given ID does not exist.
+    pub fn load_object<T: LoadObject>(&mut self, id: i64) -> Result<Option<T>> {
+        let (props, offset) = match self.entries.get(&id) {
+            Some(entry) => (entry.props.clone(), entry.offset),
+            None => return Ok(None),
+        };
+        let mut parser = RootParser::from_seekable_at(&mut self.source, self.version, offset)?;
+        match parser.next_event()? {
+            Event::StartNode(_) => {},
+            ref ev => return Err(Error::unexpected_event(ev)),
+        }
+        T::load(props, parser.subtree_parser()).map(Some)
+    }
+
+    /// Returns the source reader.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: AsRef<[u8]> + Sync> IndexedFile<Cursor<T>> {
+    /// Loads all objects as `Object` in parallel.
+    ///
+    /// See `load_objects_parallel_with()`.
+    pub fn load_objects_parallel(&self) -> Result<Objects> {
+        self.load_objects_parallel_with(ObjectLoadOptions::default)
+    }
+
+    /// Loads all objects as `Object` in parallel, with the options created for each worker.
+    ///
+    /// Object subtrees are independent byte ranges, and each worker parses them with its own
+    /// parser over the in-memory data (such as a slice or a memory map).
+    /// Objects are inserted in the order of their offsets, so the result is the same as
+    /// sequential loading, except that property templates are not resolved (use
+    /// `Objects::resolve_templates()`).
+    /// Coercion warnings collected in the options are discarded.
+    pub fn load_objects_parallel_with<F>(&self, options: F) -> Result<Objects>
+    where
+        F: Fn() -> ObjectLoadOptions + Sync + Send,
+    {
+        let data = self.source.get_ref().as_ref();
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.offset);
+        let loaded = entries
+            .par_iter()
+            .map_init(options, |options, entry| {
+                let source = Cursor::new(data);
+                let mut parser = RootParser::from_seekable_at(source, self.version, entry.offset)?;
+                match parser.next_event()? {
+                    Event::StartNode(_) => {},
+                    ref ev => return Err(Error::unexpected_event(ev)),
+                }
+                Object::load_with(entry.props.clone(), parser.subtree_parser(), options)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut objects = Objects::new();
+        for object in loaded {
+            objects.insert(object);
+        }
+        Ok(objects)
+    }
+}
+
+
+/// Reads children of `Objects` node and returns the object entries.
+fn scan_objects<P, R>(parser: &mut P) -> Result<ObjectMap<ObjectEntry>>
+where
+    P: Parser<R>,
+    R: ::parser::binary::ParserSource,
+{
+    let mut entries = ObjectMap::default();
+    loop {
+        let props = try_get_node_attrs!(parser, ObjectProperties::load);
+        let offset = parser
+            .root_parser()
+            .current_node_offset()
+            .expect("Object node should be open");
+        parser.skip_current_node()?;
+        if let Some(old) = entries.insert(props.id, ObjectEntry {
+            props: props,
+            offset: offset,
+        })
+        {
+            warn!("Duplicate object ID {}, overwriting old one", old.props.id);
+        }
+    }
+    Ok(entries)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use test_util::{Attr, FbxBuilder, write_nodes_before_objects, write_properties70};
+    use loader::binary::simple::fbx7400::Object;
+    use super::IndexedFile;
+
+    #[test]
+    fn test_load_object() {
+        for &version in &[7400, 7500] {
+            let mut b = FbxBuilder::new(version);
+            write_nodes_before_objects(&mut b);
+            b.begin_node("Objects", &[]);
+            for &(id, name_class) in &[(10, "Mat0\u{0}\u{1}Material"), (20, "Mat1\u{0}\u{1}Material")] {
+                b.begin_node("Object", &[Attr::I64(id), Attr::String(name_class), Attr::String("")]);
+                b.node("Version", &[Attr::I32(102)]);
+                write_properties70(&mut b, &[("Opacity", "double", "Number", "", &[Attr::F64(0.5)])]);
+                b.end_node();
+            }
+            b.end_node();
+            b.node("Connections", &[]);
+            let data = b.finish();
+
+            let mut file = IndexedFile::new(Cursor::new(data)).expect("Failed to index");
+            assert_eq!(file.version(), version);
+            assert_eq!(file.entries().len(), 2);
+            assert_eq!(file.entry(20).map(|e| e.props.name.as_str()), Some("Mat1"));
+
+            let obj = file.load_object::<Object>(20).expect("Load error").expect("Object should exist");
+            assert_eq!(obj.id(), 20);
+            assert_eq!(obj.class(), "Material");
+            assert!(obj.properties.is_some());
+            assert!(obj.node("Version").is_some());
+            let obj = file.load_object::<Object>(10).expect("Load error").expect("Object should exist");
+            assert_eq!(obj.name(), "Mat0");
+            assert!(file.load_object::<Object>(30).expect("Load error").is_none());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_load_objects_parallel() {
+        use parser::binary::RootParser;
+        use loader::binary::simple::FbxLoader;
+        use loader::binary::simple::fbx7400::{ObjectsLoader, ObjectLoadOptions};
+        use test_util::{load_objects, synthetic_fbx};
+
+        let data = synthetic_fbx(7400, 32, 16, false);
+        let sequential = load_objects(data.clone());
+        let without_template = |obj: &Object| Object { template: None, ..obj.clone() };
+        let expected = sequential.objects
+            .iter_ordered()
+            .map(|(id, obj)| (id, without_template(obj)))
+            .collect::<Vec<_>>();
+
+        let file = IndexedFile::new(Cursor::new(&data[..])).expect("Failed to index");
+        let parallel = file.load_objects_parallel().expect("Load error");
+        let loaded = parallel.iter_ordered()
+            .map(|(id, obj)| (id, obj.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(loaded, expected);
+
+        let narrowed = file
+            .load_objects_parallel_with(|| {
+                let mut options = ObjectLoadOptions::default();
+                options.attributes.narrow_f64_arrays = true;
+                options
+            })
+            .expect("Load error");
+        assert_eq!(narrowed.len(), parallel.len());
+        let has_f32_array = narrowed.iter_ordered().any(|(_, obj)| {
+            obj.nodes.iter().any(|node| node.attributes.iter().any(|a| a.as_f32_slice().is_some()))
+        });
+        assert!(has_f32_array);
+    }
+}
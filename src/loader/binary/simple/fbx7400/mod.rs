@@ -1,13 +1,33 @@
 //! Simple FBX 7.4 binary loader.
 
+use std::io::Read;
 use parser::binary::{Parser, ParserSource, FbxFooter, Event, Attributes};
-use loader::binary::simple::{Result, Error, GenericNode};
-pub use self::connections::{Connections, Connection};
-pub use self::definitions::{Definitions, ObjectType};
+use parser::binary::{Attribute, SpecialAttributeType};
+use loader::binary::simple::{Result, Error, GenericNode, OwnedAttribute};
+pub use self::assembly::{SceneAssembler, AssembledScene, SceneSource, ExternalReference};
+pub use self::connections::{Connections, Connection, ConnectionKind};
+pub use self::definitions::{Definitions, ObjectType, ObjectTypes};
 pub use self::fbx_header_extension::{FbxHeaderExtension, CreationTimeStamp, SceneInfo};
+pub use self::fbx_header_extension::{ApplicationInfo, SavingApplication};
 pub use self::global_settings::GlobalSettings;
-pub use self::objects::{LoadObjects7400, ObjectProperties};
-pub use self::properties70::{Properties70, PropertyMap, PropertyValue};
+pub use self::indexed::{IndexedFile, ObjectEntry};
+pub use self::objects::{LoadObject, LoadObjects7400, LoadObjectsWithContext7400, ObjectsContext};
+pub use self::objects::{ObjectProperties, Object, ObjectLoadOptions, Objects, ObjectsLoader};
+pub use self::objects::ObjectPropertiesRef;
+pub use self::objects::{ObjectMap, ArrayStreams, TemplatedProperties};
+pub use self::objects::{ObjectIndex, ObjectRef, ModelKind};
+pub use self::objects::{ObjectLoader, ObjectLoaderRegistry, CustomObjects, CustomObject};
+pub use self::objects::{Mesh, MeshF32, LayerElement, MappingMode, ReferenceMode, PolygonVertex};
+pub use self::objects::{Texture, ProceduralTexture, UvTransform, Thumbnail, Video};
+pub use self::objects::{LayeredTexture, TextureLayer, BlendMode};
+pub use self::objects::{LodGroup, LodDisplayLevel};
+pub use self::objects::{Cache, CacheFileFormat, VertexCacheDeformer};
+pub use self::objects::{AnimationStack, AnimationCurve};
+pub use self::objects::{NodeAttribute, NodeAttributeKind};
+pub use self::options::{LoadOptions, ToplevelNode};
+pub use self::properties70::{Properties70, PropertyValue, PropertyNameInterner};
+pub use self::properties70::EffectiveProperties;
+pub use self::summary::SceneSummary;
 pub use self::takes::{Takes, Take};
 
 
@@ -16,14 +36,14 @@ pub use self::takes::{Takes, Take};
 /// The type of `$parser` should be `P: Parser<R> where R: ParserSource`, and
 /// the type of `$load_attr` should be `F: FnOnce(&str, &mut Attributes) -> Result<T, _>`.
 ///
-/// This will returns from the parent function on errors.
+/// This will returns from the parent function on errors (including unexpected events).
 macro_rules! try_get_node_attrs {
     ($parser:expr, $load_attr:expr) => {{
         use $crate::parser::binary::Event;
         match $parser.next_event()? {
             Event::StartNode(info) => $load_attr(info.name, info.attributes)?,
             Event::EndNode => break,
-            ev => panic!("Unexpected node event: {:?}", ev),
+            ref ev => return Err($crate::loader::binary::simple::Error::unexpected_event(ev)),
         }
     }}
 }
@@ -61,6 +81,12 @@ macro_rules! child_attr_loader {
             $($variant$(($content))*),*,
         }
         impl $enum_name {
+            /// Returns `true` if the node name is known to the loader.
+            #[allow(dead_code)]
+            pub fn is_known(name: &str) -> bool {
+                [$($node_name),*].contains(&name)
+            }
+
             pub fn load<R>(name: &str, mut attrs: Attributes<R>)
                 -> $crate::loader::binary::simple::Result<Self>
                 where R: ParserSource
@@ -79,18 +105,22 @@ macro_rules! child_attr_loader {
 }
 
 
+pub mod assembly;
 pub mod connections;
 pub mod definitions;
 pub mod fbx_header_extension;
 pub mod global_settings;
+pub mod indexed;
 pub mod objects;
+pub mod options;
 pub mod properties70;
+pub mod summary;
 pub mod takes;
 
 
 /// FBX 7.4 or later.
 #[derive(Debug, Clone, PartialEq)]
-pub struct Fbx7400<O: LoadObjects7400> {
+pub struct Fbx7400<O: LoadObjectsWithContext7400> {
     /// FBX version.
     pub version: u32,
     /// `FBXHeaderExtension`.
@@ -119,9 +149,22 @@ pub struct Fbx7400<O: LoadObjects7400> {
     pub footer: Option<FbxFooter>,
 }
 
-impl<O: LoadObjects7400> Fbx7400<O> {
+impl<O: LoadObjectsWithContext7400> Fbx7400<O> {
     /// Loads FBX 7400 (or later) structure from the given parser.
-    pub fn load_from_parser<P>(version: u32, mut parser: P, objs_loader: O) -> Result<Self>
+    pub fn load_from_parser<P>(version: u32, parser: P, objs_loader: O) -> Result<Self>
+    where
+        P: Parser<O::Reader>,
+    {
+        Self::load_from_parser_with_options(version, parser, objs_loader, &Default::default())
+    }
+
+    /// Loads FBX 7400 (or later) structure from the given parser with the given options.
+    pub fn load_from_parser_with_options<P>(
+        version: u32,
+        mut parser: P,
+        objs_loader: O,
+        options: &LoadOptions,
+    ) -> Result<Self>
     where
         P: Parser<O::Reader>,
     {
@@ -129,120 +172,125 @@ impl<O: LoadObjects7400> Fbx7400<O> {
 
         let mut objs_loader = Some(objs_loader);
         let footer;
-        let mut fbx_header_extension = None;
-        let mut file_id = None;
-        let mut creation_time = None;
-        let mut creator = None;
-        let mut global_settings = None;
-        let mut documents = None;
-        let mut references = None;
-        let mut definitions = None;
-        let mut objects_and_before = None;
+        let mut nodes = PartialNodesBeforeObjects::default();
+        let mut loaded_objs_loader = None;
         let mut connections = None;
         let mut takes = None;
         loop {
             let node_type = match parser.next_event()? {
-                Event::StartFbx(_) |
-                Event::EndNode => unreachable!(),
+                ref ev @ Event::StartFbx(_) |
+                ref ev @ Event::RawNode(_) |
+                ref ev @ Event::EndNode => return Err(Error::unexpected_event(ev)),
                 Event::EndFbx(f) => {
                     footer = f.ok();
                     break;
                 },
                 Event::StartNode(info) => NodeType::load(info.name, info.attributes)?,
             };
+            let node_type_name = node_type.name();
             debug!("node_type: {:?}", node_type);
-            match node_type {
+            let offset = parser.root_parser().current_node_offset();
+            #[cfg(feature = "tracing")]
+            let _span = info_span!("toplevel_node", name = node_type_name, offset = ?offset)
+                .entered();
+            let result = match node_type {
                 NodeType::FbxHeaderExtension => {
-                    fbx_header_extension = Some(FbxHeaderExtension::load(parser.subtree_parser())?);
+                    FbxHeaderExtension::load(parser.subtree_guard())
+                        .map(|v| nodes.fbx_header_extension = Some(v))
                 },
                 NodeType::FileId(attrs) => {
-                    file_id = Some(FileId::load(parser.subtree_parser(), attrs)?);
+                    FileId::load(parser.subtree_guard(), attrs).map(|v| nodes.file_id = Some(v))
                 },
                 NodeType::CreationTime(attrs) => {
-                    creation_time = Some(CreationTime::load(parser.subtree_parser(), attrs)?);
+                    CreationTime::load(parser.subtree_guard(), attrs)
+                        .map(|v| nodes.creation_time = Some(v))
                 },
                 NodeType::Creator(attrs) => {
-                    creator = Some(Creator::load(parser.subtree_parser(), attrs)?);
+                    Creator::load(parser.subtree_guard(), attrs).map(|v| nodes.creator = Some(v))
                 },
                 NodeType::GlobalSettings => {
-                    global_settings = Some(GlobalSettings::load(parser.subtree_parser())?);
+                    GlobalSettings::load(parser.subtree_guard())
+                        .map(|v| nodes.global_settings = Some(v))
                 },
                 NodeType::Documents => {
-                    documents = Some(Documents::load(parser.subtree_parser())?);
+                    Documents::load(parser.subtree_guard()).map(|v| nodes.documents = Some(v))
                 },
                 NodeType::References => {
-                    references = Some(References::load(parser.subtree_parser())?);
+                    References::load(parser.subtree_guard()).map(|v| nodes.references = Some(v))
                 },
                 NodeType::Definitions => {
-                    definitions = Some(Definitions::load(parser.subtree_parser())?);
+                    Definitions::load(parser.subtree_guard()).map(|v| nodes.definitions = Some(v))
                 },
                 NodeType::Objects => {
-                    if let Some(objs_loader) = objs_loader.take() {
-                        let nodes_before_objects = NodesBeforeObjects {
-                            version: version,
-                            fbx_header_extension: ensure_node_exists!(
-                                fbx_header_extension.take(),
-                                "(root)",
-                                "FBXHeaderExtension"
-                            ),
-                            file_id: ensure_node_exists!(file_id.take(), "(root)", "FileId"),
-                            creation_time: ensure_node_exists!(
-                                creation_time.take(),
-                                "(root)",
-                                "CreationTime"
-                            ),
-                            creator: ensure_node_exists!(creator.take(), "(root)", "Creator"),
-                            global_settings: ensure_node_exists!(
-                                global_settings.take(),
-                                "(root)",
-                                "GlobalSettings"
-                            ),
-                            documents: ensure_node_exists!(documents.take(), "(root)", "Documents"),
-                            references: ensure_node_exists!(
-                                references.take(),
-                                "(root)",
-                                "References"
-                            ),
-                            definitions: ensure_node_exists!(
-                                definitions.take(),
-                                "(root)",
-                                "Definitions"
-                            ),
+                    if let Some(mut objs_loader) = objs_loader.take() {
+                        // Move the nodes into `NodesBeforeObjects` if possible, so that
+                        // `LoadObjects7400` loaders can borrow them without cloning.
+                        let result = match nodes.take_all(version) {
+                            Some(all) => {
+                                let context = ObjectsContext::from_nodes_before_objects(&all);
+                                let result = load_objects(
+                                    parser.subtree_guard(),
+                                    &mut objs_loader,
+                                    &context,
+                                );
+                                nodes.restore(all);
+                                result
+                            },
+                            None => {
+                                let context = nodes.context(version);
+                                load_objects(parser.subtree_guard(), &mut objs_loader, &context)
+                            },
                         };
-                        let objects = load_objects(
-                            parser.subtree_parser(),
-                            objs_loader,
-                            &nodes_before_objects,
-                        )?;
-                        objects_and_before = Some((objects, nodes_before_objects));
+                        result.map(|()| loaded_objs_loader = Some(objs_loader))
                     } else {
                         warn!("Multiple `Objects` node found, ignoring.");
+                        Ok(())
                     }
                 },
                 NodeType::Connections => {
-                    connections = Some(Connections::load(parser.subtree_parser())?);
-                },
-                NodeType::Takes => {
-                    takes = Some(Takes::load(parser.subtree_parser())?);
+                    Connections::load(parser.subtree_guard()).map(|v| connections = Some(v))
                 },
-            }
+                NodeType::Takes => Takes::load(parser.subtree_guard()).map(|v| takes = Some(v)),
+            };
+            result.map_err(|e| e.with_context(node_type_name, offset))?;
         }
 
-        let (objects, nodes_before_objects) =
-            ensure_node_exists!(objects_and_before, "(root)", "Objects");
+        if loaded_objs_loader.is_none() {
+            check_missing_node(ToplevelNode::Objects, options)?;
+        }
+        let objs_loader = loaded_objs_loader
+            .or(objs_loader)
+            .expect("Objects loader should be available");
+        let objects = objs_loader.build(&nodes.context(version))?;
+        if takes.is_none() {
+            check_missing_node(ToplevelNode::Takes, options)?;
+        }
 
         Ok(Fbx7400 {
             version: version,
-            fbx_header_extension: nodes_before_objects.fbx_header_extension,
-            file_id: nodes_before_objects.file_id,
-            creation_time: nodes_before_objects.creation_time,
-            creator: nodes_before_objects.creator,
-            global_settings: nodes_before_objects.global_settings,
-            documents: nodes_before_objects.documents,
-            references: nodes_before_objects.references,
-            definitions: nodes_before_objects.definitions,
+            fbx_header_extension: node_or_default(
+                nodes.fbx_header_extension,
+                ToplevelNode::FbxHeaderExtension,
+                options,
+            )?,
+            file_id: node_or_default(nodes.file_id, ToplevelNode::FileId, options)?,
+            creation_time: node_or_default(
+                nodes.creation_time,
+                ToplevelNode::CreationTime,
+                options,
+            )?,
+            creator: node_or_default(nodes.creator, ToplevelNode::Creator, options)?,
+            // Missing `GlobalSettings` (as in FBX 6 files) falls back to the FBX SDK defaults.
+            global_settings: node_or_default(
+                nodes.global_settings,
+                ToplevelNode::GlobalSettings,
+                options,
+            )?,
+            documents: node_or_default(nodes.documents, ToplevelNode::Documents, options)?,
+            references: node_or_default(nodes.references, ToplevelNode::References, options)?,
+            definitions: node_or_default(nodes.definitions, ToplevelNode::Definitions, options)?,
             objects: objects,
-            connections: ensure_node_exists!(connections, "(root)", "Connections"),
+            connections: node_or_default(connections, ToplevelNode::Connections, options)?,
             takes: takes,
             footer: footer,
         })
@@ -250,6 +298,88 @@ impl<O: LoadObjects7400> Fbx7400<O> {
 }
 
 
+impl<O: LoadObjectsWithContext7400<Objects = Objects>> Fbx7400<O> {
+    /// Remaps all object IDs to the compact range `0..n` and returns the map from old IDs to
+    /// new IDs.
+    ///
+    /// IDs are numbered in the deterministic order of root node IDs of documents (so the usual
+    /// scene root `0` stays `0`), document IDs, objects in loaded order, and then the other
+    /// IDs referred by connections in file order.
+    /// `Objects`, `Connections`, and `Documents` are updated.
+    pub fn compact_ids(&mut self) -> ObjectMap<i64> {
+        let mut map = ObjectMap::default();
+        {
+            let ids = self.documents
+                .root_nodes()
+                .into_iter()
+                .chain(self.documents.document_ids())
+                .chain(self.objects.iter_ordered().map(|(id, _)| id))
+                .chain(self.connections.0.iter().flat_map(|c| vec![c.source, c.destination]));
+            for id in ids {
+                let next = map.len() as i64;
+                map.entry(id).or_insert(next);
+            }
+        }
+        self.objects.remap_ids(&map);
+        self.connections.remap_ids(&map);
+        self.documents.remap_ids(&map);
+        map
+    }
+
+    /// Returns statistics of the scene.
+    pub fn summary(&self) -> SceneSummary {
+        SceneSummary::new(&self.objects, self.takes.as_ref())
+    }
+
+    /// Converts the scene into toplevel nodes, in the order written by FBX SDK.
+    ///
+    /// `Takes` is omitted if it is `None`.
+    /// See `Objects::to_generic_node()` for objects which are not written.
+    pub fn to_generic_nodes(&self) -> Vec<GenericNode> {
+        let mut nodes = vec![
+            self.fbx_header_extension.to_generic_node(),
+            self.file_id.to_generic_node(),
+            self.creation_time.to_generic_node(),
+            self.creator.to_generic_node(),
+            self.global_settings.to_generic_node(),
+            self.documents.to_generic_node(),
+            self.references.to_generic_node(),
+            self.definitions.to_generic_node(),
+            self.objects.to_generic_node(),
+            self.connections.to_generic_node(),
+        ];
+        nodes.extend(self.takes.as_ref().map(Takes::to_generic_node));
+        nodes
+    }
+}
+
+
+/// Returns `Error::MissingNode` if the given missing node is required, or warns otherwise.
+fn check_missing_node(node: ToplevelNode, options: &LoadOptions) -> Result<()> {
+    if options.is_required(node) {
+        return Err(Error::missing_node("(root)", node.name()));
+    }
+    warn!("Toplevel node `{}` is missing, treating as empty", node.name());
+    Ok(())
+}
+
+
+/// Returns the node if available, or default value if the node is not required.
+fn node_or_default<T: Default>(
+    value: Option<T>,
+    node: ToplevelNode,
+    options: &LoadOptions,
+) -> Result<T> {
+    match value {
+        Some(value) => Ok(value),
+        None => {
+            check_missing_node(node, options)?;
+            Ok(Default::default())
+        },
+    }
+}
+
+
 /// Toplevel nodes before `Objects`.
 ///
 /// These nodes would be referred by objects loader.
@@ -276,6 +406,81 @@ pub struct NodesBeforeObjects {
 }
 
 
+/// Toplevel nodes before `Objects`, loaded so far.
+#[derive(Default)]
+struct PartialNodesBeforeObjects {
+    /// `FBXHeaderExtension`.
+    fbx_header_extension: Option<FbxHeaderExtension>,
+    /// `FileId`.
+    file_id: Option<FileId>,
+    /// `CreationTime`.
+    creation_time: Option<CreationTime>,
+    /// `Creator`.
+    creator: Option<Creator>,
+    /// `References`.
+    references: Option<References>,
+    /// `GlobalSettings`.
+    global_settings: Option<GlobalSettings>,
+    /// `Documents`.
+    documents: Option<Documents>,
+    /// `Definitions`.
+    definitions: Option<Definitions>,
+}
+
+impl PartialNodesBeforeObjects {
+    /// Returns the context borrowing the nodes.
+    fn context(&self, version: u32) -> ObjectsContext<'_> {
+        ObjectsContext::new(
+            version,
+            self.fbx_header_extension.as_ref(),
+            self.file_id.as_ref(),
+            self.creation_time.as_ref(),
+            self.creator.as_ref(),
+            self.references.as_ref(),
+            self.global_settings.as_ref(),
+            self.documents.as_ref(),
+            self.definitions.as_ref(),
+        )
+    }
+
+    /// Moves the nodes into `NodesBeforeObjects` if all of them are available.
+    ///
+    /// Nodes are left as is if some of them are unavailable.
+    fn take_all(&mut self, version: u32) -> Option<NodesBeforeObjects> {
+        let all_available = self.fbx_header_extension.is_some() && self.file_id.is_some() &&
+            self.creation_time.is_some() && self.creator.is_some() &&
+            self.references.is_some() && self.global_settings.is_some() &&
+            self.documents.is_some() && self.definitions.is_some();
+        if !all_available {
+            return None;
+        }
+        Some(NodesBeforeObjects {
+            version: version,
+            fbx_header_extension: self.fbx_header_extension.take()?,
+            file_id: self.file_id.take()?,
+            creation_time: self.creation_time.take()?,
+            creator: self.creator.take()?,
+            references: self.references.take()?,
+            global_settings: self.global_settings.take()?,
+            documents: self.documents.take()?,
+            definitions: self.definitions.take()?,
+        })
+    }
+
+    /// Moves the nodes back from `NodesBeforeObjects`.
+    fn restore(&mut self, nodes: NodesBeforeObjects) {
+        self.fbx_header_extension = Some(nodes.fbx_header_extension);
+        self.file_id = Some(nodes.file_id);
+        self.creation_time = Some(nodes.creation_time);
+        self.creator = Some(nodes.creator);
+        self.references = Some(nodes.references);
+        self.global_settings = Some(nodes.global_settings);
+        self.documents = Some(nodes.documents);
+        self.definitions = Some(nodes.definitions);
+    }
+}
+
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum NodeType {
     FbxHeaderExtension,
@@ -326,11 +531,29 @@ impl NodeType {
             _ => Err(Error::UnexpectedNode(name.to_owned())),
         }
     }
+
+    /// Returns the node name.
+    pub fn name(&self) -> &'static str {
+        let node = match *self {
+            NodeType::FbxHeaderExtension => ToplevelNode::FbxHeaderExtension,
+            NodeType::FileId(_) => ToplevelNode::FileId,
+            NodeType::CreationTime(_) => ToplevelNode::CreationTime,
+            NodeType::Creator(_) => ToplevelNode::Creator,
+            NodeType::GlobalSettings => ToplevelNode::GlobalSettings,
+            NodeType::Documents => ToplevelNode::Documents,
+            NodeType::References => ToplevelNode::References,
+            NodeType::Definitions => ToplevelNode::Definitions,
+            NodeType::Objects => ToplevelNode::Objects,
+            NodeType::Connections => ToplevelNode::Connections,
+            NodeType::Takes => ToplevelNode::Takes,
+        };
+        node.name()
+    }
 }
 
 
 /// `FileId`.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FileId(pub Vec<u8>);
 
 impl FileId {
@@ -343,11 +566,36 @@ impl FileId {
         parser.skip_current_node()?;
         Ok(FileId(attrs))
     }
+
+    /// Converts the file ID into a `FileId` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("FileId").with_attr(&self.0[..])
+    }
+
+    /// Creates a 16-byte `FileId` derived from the content.
+    ///
+    /// The same content always gives the same ID, which is useful for reproducible output.
+    /// The ID is two chained 64-bit FNV-1a hashes of the content, and not cryptographically
+    /// secure.
+    pub fn from_content(content: &[u8]) -> Self {
+        use std::hash::Hasher;
+
+        let mut hasher = ::fnv::FnvHasher::default();
+        hasher.write(content);
+        let first = hasher.finish();
+        let mut hasher = ::fnv::FnvHasher::default();
+        hasher.write(&first.to_le_bytes());
+        hasher.write(content);
+        let second = hasher.finish();
+        let mut id = first.to_le_bytes().to_vec();
+        id.extend_from_slice(&second.to_le_bytes());
+        FileId(id)
+    }
 }
 
 
 /// `CreationTime`.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct CreationTime(pub String);
 
 impl CreationTime {
@@ -360,11 +608,33 @@ impl CreationTime {
         parser.skip_current_node()?;
         Ok(CreationTime(attrs))
     }
+
+    /// Converts the creation time into a `CreationTime` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("CreationTime").with_attr(self.0.as_str())
+    }
+
+    /// Creates a `CreationTime` from the time stamp, in the format written by FBX SDK
+    /// (`"YYYY-MM-DD hh:mm:ss:mmm"`).
+    ///
+    /// Use a fixed time stamp for reproducible output.
+    pub fn from_time_stamp(stamp: &CreationTimeStamp) -> Self {
+        CreationTime(format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}:{:03}",
+            stamp.year,
+            stamp.month,
+            stamp.day,
+            stamp.hour,
+            stamp.minute,
+            stamp.second,
+            stamp.millisecond
+        ))
+    }
 }
 
 
 /// `Creator`.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Creator(pub String);
 
 impl Creator {
@@ -377,11 +647,16 @@ impl Creator {
         parser.skip_current_node()?;
         Ok(Creator(attrs))
     }
+
+    /// Converts the creator into a `Creator` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("Creator").with_attr(self.0.as_str())
+    }
 }
 
 
 /// `Documents`.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Documents {
     /// Child nodes.
     pub nodes: Vec<GenericNode>,
@@ -397,11 +672,58 @@ impl Documents {
         let nodes = GenericNode::load_from_parser(&mut parser)?.0;
         Ok(Documents { nodes: nodes })
     }
+
+    /// Converts the documents into a `Documents` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("Documents").with_children(self.nodes.iter().cloned())
+    }
+
+    /// Returns the IDs of the documents (the first attribute of `Document`), in document order.
+    pub fn document_ids(&self) -> Vec<i64> {
+        self.nodes
+            .iter()
+            .filter(|node| node.name == "Document")
+            .filter_map(|doc| doc.first_attr_as::<i64>())
+            .collect()
+    }
+
+    /// Replaces the document IDs and root node IDs by the map.
+    ///
+    /// IDs not in the map are kept as is.
+    pub fn remap_ids(&mut self, map: &ObjectMap<i64>) {
+        let remap = |node: &mut GenericNode| {
+            if let Some(&mut OwnedAttribute::I64(ref mut id)) = node.attributes.first_mut() {
+                *id = map.get(id).cloned().unwrap_or(*id);
+            }
+        };
+        for doc in self.nodes.iter_mut().filter(|node| node.name == "Document") {
+            remap(doc);
+            doc.children.iter_mut().filter(|c| c.name == "RootNode").for_each(remap);
+        }
+    }
+
+    /// Returns the root node IDs (`RootNode`) of the documents, in document order.
+    ///
+    /// Returns `[0]` if no documents have root node IDs, since top-level models are connected
+    /// to the object ID `0` in usual single document files.
+    pub fn root_nodes(&self) -> Vec<i64> {
+        let roots = self.nodes
+            .iter()
+            .filter(|node| node.name == "Document")
+            .filter_map(|doc| doc.children.iter().find(|c| c.name == "RootNode"))
+            .filter_map(|node| node.first_attr_as::<i64>())
+            .collect::<Vec<_>>();
+        if roots.is_empty() {
+            vec![0]
+        } else {
+            roots
+        }
+    }
 }
 
 
 /// `References`.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct References {
     /// Child nodes.
     pub nodes: Vec<GenericNode>,
@@ -417,37 +739,480 @@ impl References {
         let nodes = GenericNode::load_from_parser(&mut parser)?.0;
         Ok(References { nodes: nodes })
     }
+
+    /// Converts the references into a `References` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("References").with_children(self.nodes.iter().cloned())
+    }
 }
 
 
-/// Returns `Option<(name: &'a str, class: &'a str)>`
-pub fn separate_name_class(name_class: &str) -> Option<(&str, &str)> {
-    name_class.find("\u{0}\u{1}").map(|sep_pos| {
-        (&name_class[0..sep_pos], &name_class[sep_pos + 2..])
-    })
+/// Separator of name and class in object node attributes.
+const NAME_CLASS_SEPARATOR: &str = "\u{0}\u{1}";
+
+
+/// Name and class of an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NameClass<'a> {
+    /// Name.
+    pub name: &'a str,
+    /// Class.
+    pub class: &'a str,
+}
+
+
+/// Separates the name and the class of an object node attribute (`"name\x00\x01class"`).
+///
+/// Some files have attributes without the separator, and in that case the whole string is
+/// regarded as the name and the class is empty.
+pub fn separate_name_class(name_class: &str) -> NameClass<'_> {
+    match name_class.find(NAME_CLASS_SEPARATOR) {
+        Some(sep_pos) => NameClass {
+            name: &name_class[..sep_pos],
+            class: &name_class[sep_pos + NAME_CLASS_SEPARATOR.len()..],
+        },
+        None => NameClass {
+            name: name_class,
+            class: "",
+        },
+    }
+}
+
+
+/// Joins the name and the class into an object node attribute (`"name\x00\x01class"`).
+pub fn join_name_class(name: &str, class: &str) -> String {
+    [name, class].join(NAME_CLASS_SEPARATOR)
+}
+
+
+/// Returns the object ID synthesized for legacy object nodes and connections without IDs.
+///
+/// Legacy files (FBX 6.x and some 7.x exporters) refer to objects by the name and the class
+/// (`"name\x00\x01class"`) instead of IDs.
+/// The ID is the 64-bit FNV-1a hash of the string, except that the scene root
+/// (`"Scene\x00\x01Model"`) is mapped to `0` as in usual files.
+pub fn legacy_object_id(name_class: &str) -> i64 {
+    use std::hash::Hasher;
+
+    if name_class == "Scene\u{0}\u{1}Model" {
+        return 0;
+    }
+    let mut hasher = ::fnv::FnvHasher::default();
+    hasher.write(name_class.as_bytes());
+    hasher.finish() as i64
 }
 
 
 /// Loads node contents from the parser.
-fn load_objects<R, P, O>(
-    mut parser: P,
-    mut objs_loader: O,
-    nodes_before_objects: &NodesBeforeObjects,
-) -> Result<O::Objects>
+fn load_objects<R, P, O>(mut parser: P, objs_loader: &mut O, context: &ObjectsContext) -> Result<()>
 where
     R: ParserSource,
     P: Parser<R>,
-    O: LoadObjects7400<Reader = R>,
+    O: LoadObjectsWithContext7400<Reader = R>,
 {
+    // Buffers of the node name (for error context) and the string attributes, reused for all
+    // objects.
+    let mut node_name = String::new();
+    let mut name_class = String::new();
+    let mut subclass = String::new();
     loop {
-        let props = try_get_node_attrs!(parser, ObjectProperties::load);
-        let mut sub_parser = parser.subtree_parser();
-        objs_loader.load(
-            props,
-            &mut sub_parser,
-            nodes_before_objects,
-        )?;
-        sub_parser.skip_to_end()?;
-    }
-    objs_loader.build()
+        let id = match parser.next_event()? {
+            Event::StartNode(info) => {
+                node_name.clear();
+                node_name.push_str(info.name);
+                read_object_attributes(info.name, info.attributes, &mut name_class, &mut subclass)?
+            },
+            Event::EndNode => break,
+            ref ev => return Err(Error::unexpected_event(ev)),
+        };
+        let props = ObjectPropertiesRef::from_attributes(id, &name_class, &subclass);
+        let offset = parser.root_parser().current_node_offset();
+        #[cfg(feature = "tracing")]
+        let _span = debug_span!(
+            "object",
+            id = props.id,
+            class = %props.class,
+            subclass = %props.subclass,
+            name = %props.name,
+        ).entered();
+        let mut sub_parser = parser.subtree_guard();
+        objs_loader
+            .load(props, &mut sub_parser, context)
+            .and_then(|()| sub_parser.finish().map_err(Into::into))
+            .map_err(|e| e.with_context(&node_name, offset))?;
+    }
+    Ok(())
+}
+
+
+/// Reads the attributes of the object node into the buffers, and returns the object ID.
+///
+/// Legacy object nodes with only the name and class and the subclass get IDs by
+/// `legacy_object_id()`.
+fn read_object_attributes<R>(
+    node_name: &str,
+    mut attrs: Attributes<R>,
+    name_class: &mut String,
+    subclass: &mut String,
+) -> Result<i64>
+where
+    R: ParserSource,
+{
+    let id = if attrs.num_attributes() == 2 {
+        None
+    } else {
+        match attrs.convert_into::<i64>()? {
+            Some(id) => Some(id),
+            None => return Err(Error::InvalidAttribute(node_name.to_owned())),
+        }
+    };
+    if !read_string_attribute(&mut attrs, name_class)? ||
+        !read_string_attribute(&mut attrs, subclass)?
+    {
+        return Err(Error::InvalidAttribute(node_name.to_owned()));
+    }
+    Ok(id.unwrap_or_else(|| {
+        let id = legacy_object_id(name_class);
+        warn!("Object node without ID ({:?}), using synthesized ID {}", name_class, id);
+        id
+    }))
+}
+
+
+/// Reads the next string attribute into the buffer, and returns whether it is a string.
+fn read_string_attribute<R>(attrs: &mut Attributes<R>, buf: &mut String) -> Result<bool>
+where
+    R: ParserSource,
+{
+    buf.clear();
+    match attrs.next_attribute()? {
+        Some(Attribute::Special(mut attr)) if attr.value_type() == SpecialAttributeType::String => {
+            attr.reader().read_to_string(buf)?;
+            Ok(true)
+        },
+        _ => Ok(false),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::marker::PhantomData;
+    use parser::binary::{RootParser, Parser, ParserSource, BasicSource, SubtreeParser};
+    use parser::binary::ErrorKind;
+    use loader::binary::simple::{FbxLoader, GenericNode, Result, Error};
+    use test_util::{Attr, FbxBuilder, load_objects};
+    use test_util::{minimal_fbx, synthetic_fbx, write_toplevel_node};
+    use test_util::write_nodes_before_objects;
+    use super::{LoadObjects7400, LoadObjectsWithContext7400, ObjectsContext};
+    use super::{ObjectProperties, NodesBeforeObjects, LoadOptions, ToplevelNode, ObjectsLoader};
+    use super::{ObjectPropertiesRef, join_name_class, separate_name_class};
+    use super::{CreationTime, CreationTimeStamp, FileId};
+
+    /// Objects loader which only collects object properties.
+    struct PropsLoader<R>(Vec<ObjectProperties>, PhantomData<R>);
+
+    impl<R: ParserSource> LoadObjects7400 for PropsLoader<R> {
+        type Reader = R;
+        type Objects = Vec<ObjectProperties>;
+
+        fn build(self) -> Result<Self::Objects> {
+            Ok(self.0)
+        }
+
+        fn load(
+            &mut self,
+            props: ObjectPropertiesRef<'_>,
+            _subtree_parser: &mut SubtreeParser<R>,
+            _nodes_before_objects: &NodesBeforeObjects,
+        ) -> Result<()> {
+            self.0.push(props.into_owned());
+            Ok(())
+        }
+    }
+
+    type Fbx = super::Fbx7400<PropsLoader<BasicSource<Cursor<Vec<u8>>>>>;
+
+    fn load(data: Vec<u8>) -> Fbx {
+        let parser = RootParser::new(Cursor::new(data));
+        match FbxLoader::load_from_parser(parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => {
+                loader
+                    .load(PropsLoader(Vec::new(), PhantomData))
+                    .expect("Load error")
+            },
+        }
+    }
+
+    #[test]
+    fn test_load_minimal() {
+        for &version in &[7400, 7500] {
+            let fbx = load(minimal_fbx(version));
+            assert_eq!(fbx.version, version);
+            assert!(fbx.objects.is_empty());
+            assert!(fbx.footer.is_some());
+        }
+    }
+
+    #[test]
+    fn test_load_objects() {
+        for &version in &[7400, 7500] {
+            let fbx = load(synthetic_fbx(version, 3, 6, false));
+            assert_eq!(fbx.objects.len(), 3);
+            assert_eq!(fbx.objects[2].id, 1002);
+            assert_eq!(fbx.objects[2].name, "Mesh2");
+            assert_eq!(fbx.objects[2].class, "Geometry");
+            assert_eq!(fbx.objects[2].subclass, "Mesh");
+        }
+    }
+
+    #[test]
+    fn test_reproducible_metadata() {
+        let stamp = CreationTimeStamp {
+            version: 1000,
+            year: 2017,
+            month: 7,
+            day: 30,
+            hour: 12,
+            minute: 0,
+            second: 5,
+            millisecond: 7,
+        };
+        assert_eq!(CreationTime::from_time_stamp(&stamp).0, "2017-07-30 12:00:05:007");
+
+        let id = FileId::from_content(b"content");
+        assert_eq!(id.0.len(), 16);
+        assert_eq!(id, FileId::from_content(b"content"));
+        assert_ne!(id, FileId::from_content(b"other content"));
+    }
+
+    #[test]
+    fn test_compact_ids() {
+        let mut fbx = load_objects(synthetic_fbx(7400, 3, 6, false));
+        let map = fbx.compact_ids();
+
+        assert_eq!(map.len(), 4);
+        assert_eq!((map[&0], map[&1000], map[&1002]), (0, 1, 3));
+        assert_eq!(fbx.objects.sorted_ids(), vec![1, 2, 3]);
+        assert_eq!(fbx.objects.object(3).map(|obj| obj.name()), Some("Mesh2"));
+        assert_eq!(fbx.objects.ids_by_name("Mesh0"), &[1]);
+        assert_eq!(fbx.connections.object_sources(0).collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    /// Removes `Properties70` nodes, which are written in sorted order and with guessed type
+    /// names.
+    fn strip_properties(mut node: GenericNode) -> GenericNode {
+        node.remove_children("Properties70");
+        node.children = node.children.into_iter().map(strip_properties).collect();
+        node
+    }
+
+    #[test]
+    fn test_to_generic_nodes() {
+        let data = synthetic_fbx(7400, 2, 3, false);
+        let mut parser = RootParser::new(Cursor::new(data.clone()));
+        let raw = GenericNode::load_from_parser(&mut parser).expect("Parse error").0;
+        let fbx = load_objects(data);
+        let nodes = fbx.to_generic_nodes();
+
+        assert_eq!(
+            nodes.into_iter().map(strip_properties).collect::<Vec<_>>(),
+            raw.into_iter().map(strip_properties).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let fbx = load_objects(synthetic_fbx(7400, 3, 6, false));
+        let summary = fbx.summary();
+
+        assert_eq!(summary.objects.get("Geometry"), Some(&3));
+        assert_eq!(summary.meshes, 3);
+        assert_eq!((summary.vertices, summary.polygons, summary.polygon_vertices), (18, 6, 18));
+        assert_eq!((summary.clips, summary.textures, summary.embedded_media_size), (0, 0, 0));
+        assert!(summary.to_string().starts_with("Objects: 3\n  Geometry: 3\n"));
+    }
+
+    /// Objects loader which checks availability of `Definitions`.
+    struct ContextLoader<R>(usize, PhantomData<R>);
+
+    impl<R: ParserSource> LoadObjectsWithContext7400 for ContextLoader<R> {
+        type Reader = R;
+        type Objects = usize;
+
+        fn build(self, context: &ObjectsContext) -> Result<Self::Objects> {
+            assert!(context.definitions().is_some());
+            assert!(context.nodes_before_objects().is_some());
+            Ok(self.0)
+        }
+
+        fn load(
+            &mut self,
+            _props: ObjectPropertiesRef<'_>,
+            _subtree_parser: &mut SubtreeParser<R>,
+            context: &ObjectsContext,
+        ) -> Result<()> {
+            assert!(context.global_settings().is_some());
+            assert!(context.definitions().is_none());
+            assert!(context.nodes_before_objects().is_none());
+            self.0 += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_context_borrows_nodes_before_objects() {
+        let fbx = load(minimal_fbx(7400));
+        let nodes = NodesBeforeObjects {
+            version: fbx.version,
+            fbx_header_extension: fbx.fbx_header_extension,
+            file_id: fbx.file_id,
+            creation_time: fbx.creation_time,
+            creator: fbx.creator,
+            references: fbx.references,
+            global_settings: fbx.global_settings,
+            documents: fbx.documents,
+            definitions: fbx.definitions,
+        };
+        let context = ObjectsContext::from_nodes_before_objects(&nodes);
+        assert!(context.nodes_before_objects().is_some_and(|v| ::std::ptr::eq(v, &nodes)));
+        assert!(context.definitions().is_some_and(|v| ::std::ptr::eq(v, &nodes.definitions)));
+    }
+
+    #[test]
+    fn test_definitions_after_objects() {
+        let mut b = FbxBuilder::new(7400);
+        for name in &[
+            "FBXHeaderExtension",
+            "FileId",
+            "CreationTime",
+            "Creator",
+            "GlobalSettings",
+            "Documents",
+            "References",
+        ]
+        {
+            write_toplevel_node(&mut b, name);
+        }
+        b.begin_node("Objects", &[]);
+        b.node(
+            "Model",
+            &[Attr::I64(1), Attr::String("Model\u{0}\u{1}Model"), Attr::String("Null")],
+        );
+        b.end_node();
+        write_toplevel_node(&mut b, "Definitions");
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let parser = RootParser::new(Cursor::new(data.clone()));
+        let fbx = match FbxLoader::load_from_parser(parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => {
+                loader
+                    .load(ContextLoader(0, PhantomData))
+                    .expect("Load error")
+            },
+        };
+        assert_eq!(fbx.objects, 1);
+
+        // `LoadObjects7400` requires `Definitions` before `Objects`.
+        let parser = RootParser::new(Cursor::new(data));
+        match FbxLoader::load_from_parser(parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => {
+                assert!(loader.load(PropsLoader(Vec::new(), PhantomData)).is_err());
+            },
+        }
+    }
+
+    #[test]
+    fn test_missing_optional_nodes() {
+        let mut b = FbxBuilder::new(7400);
+        for name in &["FBXHeaderExtension", "FileId", "CreationTime", "Creator", "Definitions"] {
+            write_toplevel_node(&mut b, name);
+        }
+        b.node("Objects", &[]);
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let load = |options: &LoadOptions| {
+            let parser = RootParser::new(Cursor::new(data.clone()));
+            match FbxLoader::load_from_parser(parser).expect("Load error") {
+                FbxLoader::Fbx7400(loader) => {
+                    loader.load_with_options(PropsLoader(Vec::new(), PhantomData), options)
+                },
+            }
+        };
+        let fbx = load(&LoadOptions::new()).expect("Load error");
+        assert!(fbx.documents.nodes.is_empty());
+        assert!(fbx.takes.is_none());
+        assert_eq!(fbx.global_settings.properties.get_i64("UpAxis"), Some(1));
+        assert_eq!(fbx.global_settings.frame_rate(), Some(30.0));
+        assert!(load(&LoadOptions::strict()).is_err());
+        let mut options = LoadOptions::new();
+        options.set_required(ToplevelNode::Takes, true);
+        assert!(load(&options).is_err());
+    }
+
+    #[test]
+    fn test_error_context() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        let object_offset = b.position();
+        b.begin_node(
+            "Model",
+            &[Attr::I64(1), Attr::String("Model\u{0}\u{1}Model"), Attr::String("")],
+        );
+        b.begin_node("Properties70", &[]);
+        // Property without value type.
+        b.node("P", &[Attr::String("Broken")]);
+        b.end_node();
+        b.end_node();
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let parser = RootParser::new(Cursor::new(data));
+        let err = match FbxLoader::load_from_parser(parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => loader.load(ObjectsLoader::new()),
+        }.expect_err("Loading broken property should fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.node_path(), &["Objects".to_owned(), "Model".to_owned()]);
+        assert_eq!(err.offset(), Some(object_offset as u64));
+        match *err.without_context() {
+            Error::InvalidAttribute(ref name) => assert_eq!(name, "P"),
+            ref err => panic!("Unexpected error: {:?}", err),
+        }
+        assert!(::std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_unexpected_event() {
+        let mut parser = RootParser::new(Cursor::new(minimal_fbx(7400)));
+        parser.next_event().expect("Parse error");
+        match FbxLoader::load_from_parser(&mut parser) {
+            Err(Error::UnexpectedEvent(_)) => {},
+            v => panic!("Expected `UnexpectedEvent` error but got {:?}", v.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_object_properties_ref() {
+        let props = ObjectPropertiesRef::from_attributes(3, "Cube\u{0}\u{1}Model", "Mesh");
+        assert_eq!((props.name, props.class, props.subclass), ("Cube", "Model", "Mesh"));
+        let owned = props.into_owned();
+        assert_eq!(owned.name, "Cube");
+        assert_eq!(ObjectPropertiesRef::from(&owned), props);
+    }
+
+    #[test]
+    fn test_name_class() {
+        let name_class = separate_name_class("Cube\u{0}\u{1}Model");
+        assert_eq!((name_class.name, name_class.class), ("Cube", "Model"));
+        let name_class = separate_name_class("Cube");
+        assert_eq!((name_class.name, name_class.class), ("Cube", ""));
+        assert_eq!(join_name_class("Cube", "Model"), "Cube\u{0}\u{1}Model");
+        let props = ObjectPropertiesRef::from_attributes(3, "Cube", "Mesh");
+        assert_eq!((props.name, props.class), ("Cube", ""));
+    }
 }
@@ -0,0 +1,192 @@
+//! Animation objects.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::{Result, OwnedAttribute};
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+use loader::time::FbxTime;
+
+
+/// `AnimationStack` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationStack {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl AnimationStack {
+    /// Creates an `AnimationStack` from the generic object.
+    pub fn from_object(obj: &Object) -> Self {
+        AnimationStack {
+            props: obj.props.clone(),
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Returns the time property with the given name.
+    fn time(&self, name: &str) -> Option<FbxTime> {
+        self.properties.effective().get_i64(name).map(FbxTime)
+    }
+
+    /// Returns `LocalStart` property.
+    pub fn local_start(&self) -> Option<FbxTime> {
+        self.time("LocalStart")
+    }
+
+    /// Returns `LocalStop` property.
+    pub fn local_stop(&self) -> Option<FbxTime> {
+        self.time("LocalStop")
+    }
+
+    /// Returns `ReferenceStart` property.
+    pub fn reference_start(&self) -> Option<FbxTime> {
+        self.time("ReferenceStart")
+    }
+
+    /// Returns `ReferenceStop` property.
+    pub fn reference_stop(&self) -> Option<FbxTime> {
+        self.time("ReferenceStop")
+    }
+
+    /// Returns the length of the local time span.
+    pub fn local_duration(&self) -> Option<FbxTime> {
+        Some(self.local_stop()? - self.local_start()?)
+    }
+
+    /// Returns the length of the reference time span.
+    pub fn reference_duration(&self) -> Option<FbxTime> {
+        Some(self.reference_stop()? - self.reference_start()?)
+    }
+
+    /// Returns the length of the clip.
+    ///
+    /// The local time span is preferred, and the reference time span is used if the local
+    /// time span is unavailable.
+    pub fn duration(&self) -> Option<FbxTime> {
+        self.local_duration().or_else(|| self.reference_duration())
+    }
+}
+
+impl LoadObject for AnimationStack {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(AnimationStack::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+/// `AnimationCurve` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationCurve {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Default`.
+    pub default: f64,
+    /// `KeyTime`.
+    pub key_times: Vec<FbxTime>,
+    /// `KeyValueFloat`.
+    pub key_values: Vec<f32>,
+}
+
+impl AnimationCurve {
+    /// Creates an `AnimationCurve` from the generic object.
+    ///
+    /// Missing or invalid child nodes are treated as default values.
+    /// If the numbers of key times and key values differ, extra keys are ignored.
+    pub fn from_object(obj: &Object) -> Self {
+        let attr = |name: &str| obj.node(name).and_then(|node| node.attributes.first());
+        let mut key_times = attr("KeyTime")
+            .and_then(OwnedAttribute::as_i64_slice)
+            .map_or_else(Vec::new, |v| v.iter().cloned().map(FbxTime).collect());
+        let mut key_values = attr("KeyValueFloat")
+            .and_then(OwnedAttribute::as_f32_slice)
+            .map_or_else(Vec::new, <[f32]>::to_vec);
+        let len = ::std::cmp::min(key_times.len(), key_values.len());
+        key_times.truncate(len);
+        key_values.truncate(len);
+        AnimationCurve {
+            props: obj.props.clone(),
+            default: attr("Default").and_then(OwnedAttribute::as_f64).unwrap_or(0.0),
+            key_times: key_times,
+            key_values: key_values,
+        }
+    }
+
+    /// Evaluates the curve at the given time.
+    ///
+    /// Keys are interpolated linearly, and values before the first key and after the last
+    /// key are clamped.
+    /// Tangents and interpolation modes of the keys are not supported yet.
+    /// Returns `default` if the curve has no keys.
+    pub fn evaluate(&self, time: FbxTime) -> f64 {
+        let next = self.key_times.partition_point(|&t| t <= time);
+        if next == 0 {
+            return self.key_values.first().map_or(self.default, |&v| v as f64);
+        }
+        if next == self.key_times.len() {
+            return self.key_values[next - 1] as f64;
+        }
+        let (t0, t1) = (self.key_times[next - 1], self.key_times[next]);
+        let (v0, v1) = (self.key_values[next - 1] as f64, self.key_values[next] as f64);
+        let ratio = (time - t0).ticks() as f64 / (t1 - t0).ticks() as f64;
+        v0 + (v1 - v0) * ratio
+    }
+}
+
+impl LoadObject for AnimationCurve {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(AnimationCurve::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::time::FbxTime;
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::AnimationStack;
+
+    #[test]
+    fn test_stack_time_span() {
+        const SECOND: i64 = FbxTime::TICKS_PER_SECOND;
+
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "AnimationStack",
+            &[Attr::I64(1), Attr::String("Walk\u{0}\u{1}AnimStack"), Attr::String("")],
+        );
+        write_properties70(
+            &mut b,
+            &[
+                ("LocalStart", "KTime", "Time", "", &[Attr::I64(SECOND)]),
+                ("LocalStop", "KTime", "Time", "", &[Attr::I64(3 * SECOND)]),
+                ("ReferenceStart", "KTime", "Time", "", &[Attr::I64(0)]),
+                ("ReferenceStop", "KTime", "Time", "", &[Attr::I64(4 * SECOND)]),
+            ],
+        );
+        b.end_node();
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let objects = load_objects(data).objects;
+        let stack = AnimationStack::from_object(objects.object(1).expect("Should exist"));
+
+        assert_eq!(stack.local_start(), Some(FbxTime(SECOND)));
+        assert_eq!(stack.reference_stop(), Some(FbxTime(4 * SECOND)));
+        assert_eq!(stack.local_duration().map(FbxTime::seconds), Some(2.0));
+        assert_eq!(stack.reference_duration().map(FbxTime::seconds), Some(4.0));
+        assert_eq!(stack.duration(), stack.local_duration());
+    }
+}
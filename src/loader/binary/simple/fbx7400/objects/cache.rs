@@ -0,0 +1,201 @@
+//! `Cache` object and `VertexCacheDeformer`.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::Result;
+use loader::binary::simple::fbx7400::Connections;
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object, Objects};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+
+
+/// Format of an external cache file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheFileFormat {
+    /// 3ds Max point cache (`.pc2`).
+    MaxPointCacheV2,
+    /// Maya cache (`.mc` with `.xml` description).
+    MayaCache,
+    /// Alembic (`.abc`).
+    Alembic,
+    /// Unknown format.
+    Unknown(i64),
+}
+
+impl CacheFileFormat {
+    /// Creates a `CacheFileFormat` from the value of `CacheFileType` property.
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            1 => CacheFileFormat::MaxPointCacheV2,
+            2 => CacheFileFormat::MayaCache,
+            3 => CacheFileFormat::Alembic,
+            v => CacheFileFormat::Unknown(v),
+        }
+    }
+}
+
+
+/// `Cache` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cache {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `CacheFileName` property (relative path).
+    pub file_name: Option<String>,
+    /// `CacheFileAbsoluteName` property.
+    pub absolute_file_name: Option<String>,
+    /// `CacheFileType` property.
+    pub format: Option<CacheFileFormat>,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl Cache {
+    /// Creates a `Cache` from the generic object.
+    ///
+    /// Missing or invalid properties are treated as `None`.
+    pub fn from_object(obj: &Object) -> Self {
+        let props = obj.properties_with_template();
+        let string = |name: &str| props.get_string(name).map(str::to_owned);
+        Cache {
+            props: obj.props.clone(),
+            file_name: string("CacheFileName"),
+            absolute_file_name: string("CacheFileAbsoluteName"),
+            format: props.get_i64("CacheFileType").map(CacheFileFormat::from_i64),
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Returns the path of the cache file.
+    ///
+    /// The absolute path is preferred, and the relative path is used if the absolute path is
+    /// missing or empty.
+    pub fn path(&self) -> Option<&str> {
+        self.absolute_file_name
+            .as_deref()
+            .filter(|s| !s.is_empty())
+            .or(self.file_name.as_deref())
+    }
+}
+
+impl LoadObject for Cache {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(Cache::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+/// `Deformer` object with `VertexCacheDeformer` subclass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VertexCacheDeformer {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Channel` property (name of the channel in the cache file).
+    pub channel: Option<String>,
+    /// `CacheSet` property.
+    pub cache_set: Option<String>,
+    /// `Active` property.
+    pub active: bool,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl VertexCacheDeformer {
+    /// Creates a `VertexCacheDeformer` from the generic object.
+    ///
+    /// Missing or invalid properties are treated as `None`, and the deformer is active by
+    /// default.
+    pub fn from_object(obj: &Object) -> Self {
+        let props = obj.properties_with_template();
+        let string = |name: &str| props.get_string(name).map(str::to_owned);
+        VertexCacheDeformer {
+            props: obj.props.clone(),
+            channel: string("Channel"),
+            cache_set: string("CacheSet"),
+            active: props.get_bool("Active").unwrap_or(true),
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Returns the `Cache` object connected to the deformer.
+    pub fn cache(&self, objects: &Objects, connections: &Connections) -> Option<Cache> {
+        connections
+            .object_sources(self.props.id)
+            .filter_map(|id| objects.object(id))
+            .find(|obj| obj.class() == "Cache")
+            .map(Cache::from_object)
+    }
+}
+
+impl LoadObject for VertexCacheDeformer {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(VertexCacheDeformer::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::ObjectRef;
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::{VertexCacheDeformer, CacheFileFormat};
+
+    #[test]
+    fn test_vertex_cache() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "Deformer",
+            &[
+                Attr::I64(1),
+                Attr::String("Sim\u{0}\u{1}Deformer"),
+                Attr::String("VertexCacheDeformer"),
+            ],
+        );
+        write_properties70(
+            &mut b,
+            &[
+                ("Channel", "KString", "", "", &[Attr::String("clothShape")]),
+                ("CacheSet", "KString", "", "", &[Attr::String("set1")]),
+            ],
+        );
+        b.end_node();
+        b.begin_node(
+            "Cache",
+            &[Attr::I64(2), Attr::String("SimCache\u{0}\u{1}Cache"), Attr::String("")],
+        );
+        write_properties70(
+            &mut b,
+            &[
+                ("CacheFileName", "KString", "", "", &[Attr::String("cache/sim.pc2")]),
+                ("CacheFileAbsoluteName", "KString", "", "", &[Attr::String("")]),
+                ("CacheFileType", "enum", "", "", &[Attr::I32(1)]),
+            ],
+        );
+        b.end_node();
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        b.node("C", &[Attr::String("OO"), Attr::I64(2), Attr::I64(1)]);
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        assert!(matches!(fbx.objects.get(2), Some(ObjectRef::Cache(_))));
+
+        let deformer =
+            VertexCacheDeformer::from_object(fbx.objects.object(1).expect("Should exist"));
+        assert_eq!(deformer.channel.as_deref(), Some("clothShape"));
+        assert_eq!(deformer.cache_set.as_deref(), Some("set1"));
+        assert!(deformer.active);
+        let cache = deformer.cache(&fbx.objects, &fbx.connections).expect("Cache should exist");
+        assert_eq!(cache.format, Some(CacheFileFormat::MaxPointCacheV2));
+        assert_eq!(cache.path(), Some("cache/sim.pc2"));
+    }
+}
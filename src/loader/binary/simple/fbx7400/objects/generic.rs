@@ -0,0 +1,1070 @@
+//! Generic object and objects loader.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use fnv::FnvHashMap;
+use parser::binary::{Parser, ParserSource, Event, SubtreeParser};
+use parser::binary::{Attributes, Attribute, ArrayAttribute, FromArrayStream};
+use parser::binary::utils::{CoercionWarning, StrictCoercion};
+use loader::binary::simple::{Result, Error, GenericNode, OwnedAttributeOptions, LargeBinaries};
+use loader::binary::simple::fbx7400::{Properties70, PropertyNameInterner, PropertyValue};
+use loader::binary::simple::fbx7400::{Definitions, EffectiveProperties};
+use loader::binary::simple::fbx7400::objects::{LoadObject, LoadObjectsWithContext7400};
+use loader::binary::simple::fbx7400::objects::{ObjectsContext, ObjectPropertiesRef};
+use loader::binary::simple::fbx7400::objects::{ObjectProperties, ObjectIndex, ObjectRef};
+use loader::binary::simple::fbx7400::objects::{CustomObjects, ObjectLoaderRegistry};
+
+
+/// A type of map from object ID to object.
+pub type ObjectMap<T> = FnvHashMap<i64, T>;
+
+
+/// Options for loading `Object`s.
+#[derive(Default, Debug)]
+pub struct ObjectLoadOptions {
+    /// Options for attributes of child nodes.
+    ///
+    /// `Properties70` is not affected.
+    pub attributes: OwnedAttributeOptions,
+    /// Interner of `Properties70` property names, if names should be shared among objects.
+    pub property_names: Option<PropertyNameInterner>,
+}
+
+
+/// Sinks of array attributes streamed during object loading.
+///
+/// Used by `Object::load_with_streams()`.
+#[derive(Default)]
+pub struct ArrayStreams<'a> {
+    /// Sinks by child node name.
+    sinks: Vec<(&'a str, ArrayStream<'a>)>,
+}
+
+impl<'a> ArrayStreams<'a> {
+    /// Creates a new empty `ArrayStreams`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Streams the first attribute of the child node with the given name as `f64` array.
+    ///
+    /// `f32` arrays are also accepted and converted.
+    pub fn set_f64(&mut self, name: &'a str, sink: &'a mut dyn FromArrayStream<f64>) {
+        self.set(name, ArrayStream::F64(sink));
+    }
+
+    /// Streams the first attribute of the child node with the given name as `i32` array.
+    pub fn set_i32(&mut self, name: &'a str, sink: &'a mut dyn FromArrayStream<i32>) {
+        self.set(name, ArrayStream::I32(sink));
+    }
+
+    /// Sets the sink for the child node with the given name.
+    fn set(&mut self, name: &'a str, stream: ArrayStream<'a>) {
+        match self.sinks.iter_mut().find(|&&mut (n, _)| n == name) {
+            Some(entry) => entry.1 = stream,
+            None => self.sinks.push((name, stream)),
+        }
+    }
+
+    /// Returns `true` if no sinks are set.
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Streams the first attribute into the sink for the node, if available.
+    fn stream<R: ParserSource>(&mut self, name: &str, attrs: &mut Attributes<R>) -> Result<()> {
+        let stream = match self.sinks.iter_mut().find(|&&mut (n, _)| n == name) {
+            Some(&mut (_, ref mut stream)) => stream,
+            None => return Ok(()),
+        };
+        let array = match attrs.next_attribute()? {
+            Some(Attribute::Array(array)) => array,
+            _ => return Err(Error::InvalidAttribute(name.to_owned())),
+        };
+        match (stream, array) {
+            (&mut ArrayStream::F64(ref mut sink), ArrayAttribute::F64(reader)) => {
+                reader.stream_into(&mut **sink)?;
+            },
+            (&mut ArrayStream::F64(ref mut sink), ArrayAttribute::F32(reader)) => {
+                reader.stream_into(&mut WidenF32(&mut **sink))?;
+            },
+            (&mut ArrayStream::I32(ref mut sink), ArrayAttribute::I32(reader)) => {
+                reader.stream_into(&mut **sink)?;
+            },
+            _ => return Err(Error::InvalidAttribute(name.to_owned())),
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for ArrayStreams<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.sinks.iter().map(|&(name, _)| name)).finish()
+    }
+}
+
+
+/// Sink of an array attribute.
+enum ArrayStream<'a> {
+    /// `f64` array.
+    F64(&'a mut dyn FromArrayStream<f64>),
+    /// `i32` array.
+    I32(&'a mut dyn FromArrayStream<i32>),
+}
+
+
+/// Adapter streaming `f32` elements into `f64` sink.
+struct WidenF32<'a>(&'a mut dyn FromArrayStream<f64>);
+
+impl<'a> FromArrayStream<f32> for WidenF32<'a> {
+    fn begin(&mut self, num_elements: u64) {
+        self.0.begin(num_elements);
+    }
+
+    fn extend_chunk(&mut self, chunk: &[f32]) -> io::Result<()> {
+        let mut buf = [0.0f64; 256];
+        for part in chunk.chunks(buf.len()) {
+            for (dest, &src) in buf.iter_mut().zip(part) {
+                *dest = src as f64;
+            }
+            self.0.extend_chunk(&buf[0..part.len()])?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Object node loaded without interpretation of its class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70` child node.
+    ///
+    /// Legacy `Properties60` is loaded here if available.
+    pub properties: Option<Properties70>,
+    /// Child nodes except `Properties70` (and `Properties60`).
+    pub nodes: Vec<GenericNode>,
+    /// Property template of the object.
+    ///
+    /// Set by `Objects::resolve_templates()`, which `ObjectsLoader` calls after loading.
+    pub template: Option<Arc<Properties70>>,
+}
+
+impl Object {
+    /// Loads node contents from the parser.
+    pub fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Object::load_with(props, parser, &mut ObjectLoadOptions::default())
+    }
+
+    /// Loads node contents from the parser with the given options.
+    pub fn load_with<R, P>(
+        props: ObjectProperties,
+        parser: P,
+        options: &mut ObjectLoadOptions,
+    ) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Object::load_with_streams(props, parser, options, &mut ArrayStreams::new())
+    }
+
+    /// Loads node contents from the parser, streaming arrays of child nodes into the sinks.
+    ///
+    /// Only direct child nodes of the object are streamed, and streamed attributes are omitted
+    /// from the loaded child nodes.
+    /// Returns `Error::InvalidAttribute` if the first attribute of the child node with a sink
+    /// is missing or has incompatible type.
+    pub fn load_with_streams<R, P>(
+        props: ObjectProperties,
+        mut parser: P,
+        options: &mut ObjectLoadOptions,
+        streams: &mut ArrayStreams<'_>,
+    ) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut properties = None;
+        let mut nodes = Vec::new();
+        if let Some(ref mut strict) = options.attributes.strict_coercion {
+            strict.enter(format!("{}({})", props.class, props.id));
+        }
+
+        loop {
+            let node = match parser.next_event()? {
+                Event::StartNode(mut info) => {
+                    if info.name == "Properties70" || info.name == "Properties60" {
+                        Err(info.name == "Properties60")
+                    } else {
+                        let rest_attributes = info.attributes.rest_attributes();
+                        streams.stream(info.name, &mut info.attributes)?;
+                        if let Some(ref mut strict) = options.attributes.strict_coercion {
+                            strict.enter(info.name);
+                            if info.attributes.rest_attributes() != rest_attributes {
+                                // Streamed attributes are not narrowed.
+                                strict.push_attribute(None);
+                            }
+                        }
+                        let attrs = info.attributes.collect_owned_with(&mut options.attributes)?;
+                        Ok((info.name.to_owned(), attrs))
+                    }
+                },
+                Event::EndNode => break,
+                ref ev => return Err(Error::unexpected_event(ev)),
+            };
+            match node {
+                Err(legacy) => {
+                    let subtree = parser.subtree_guard();
+                    properties = Some(match (options.property_names.as_mut(), legacy) {
+                        (Some(interner), false) => {
+                            Properties70::load_with_interner(subtree, interner)?
+                        },
+                        (Some(interner), true) => {
+                            Properties70::load_properties60_with_interner(subtree, interner)?
+                        },
+                        (None, false) => Properties70::load(subtree)?,
+                        (None, true) => Properties70::load_properties60(subtree)?,
+                    });
+                },
+                Ok((name, attrs)) => {
+                    let children = GenericNode::load_from_parser_with(
+                        &mut parser.subtree_parser(),
+                        &mut options.attributes,
+                    )?.0;
+                    nodes.push(GenericNode {
+                        name: name,
+                        attributes: attrs,
+                        children: children,
+                    });
+                    if let Some(ref mut strict) = options.attributes.strict_coercion {
+                        strict.leave();
+                    }
+                },
+            }
+        }
+        if let Some(ref mut strict) = options.attributes.strict_coercion {
+            strict.leave();
+        }
+
+        Ok(Object {
+            props: props,
+            properties: properties,
+            nodes: nodes,
+            template: None,
+        })
+    }
+
+    /// Returns object ID.
+    pub fn id(&self) -> i64 {
+        self.props.id
+    }
+
+    /// Returns object name.
+    pub fn name(&self) -> &str {
+        &self.props.name
+    }
+
+    /// Returns object class.
+    pub fn class(&self) -> &str {
+        &self.props.class
+    }
+
+    /// Returns object subclass.
+    pub fn subclass(&self) -> &str {
+        &self.props.subclass
+    }
+
+    /// Returns the first child node with the given name.
+    pub fn node(&self, name: &str) -> Option<&GenericNode> {
+        self.nodes.iter().find(|node| node.name == name)
+    }
+
+    /// Returns the properties of the object overlaid on its property template.
+    pub fn effective_properties<'a>(
+        &'a self,
+        definitions: &'a Definitions,
+    ) -> EffectiveProperties<'a> {
+        definitions.effective_properties(&self.props, self.properties.as_ref())
+    }
+
+    /// Returns the properties of the object overlaid on its resolved property template.
+    ///
+    /// Same as `effective_properties()`, but uses `template` instead of `Definitions`.
+    pub fn properties_with_template(&self) -> EffectiveProperties<'_> {
+        EffectiveProperties::new(self.properties.as_ref(), self.template.as_deref())
+    }
+
+    /// Returns user-defined properties (properties with `U` flag).
+    ///
+    /// For `Model` objects, these are custom properties authored in DCC tools (such as
+    /// Blender custom properties and Maya extra attributes).
+    pub fn user_properties(&self) -> BTreeMap<&str, &PropertyValue> {
+        self.properties.as_ref().map_or_else(BTreeMap::new, Properties70::user_properties)
+    }
+
+    /// Converts the object into an object node.
+    ///
+    /// `Properties70` is put after the leading `Type` and `Version` nodes.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let mut children = self.nodes.clone();
+        if let Some(ref properties) = self.properties {
+            let pos = children
+                .iter()
+                .take_while(|node| node.name == "Type" || node.name == "Version")
+                .count();
+            children.insert(pos, properties.to_generic_node());
+        }
+        GenericNode {
+            name: self.props.class.clone(),
+            attributes: self.props.to_attributes(),
+            children: children,
+        }
+    }
+}
+
+impl LoadObject for Object {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Object::load(props, parser)
+    }
+}
+
+
+/// `Properties70` of a typed object, with its property template.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TemplatedProperties {
+    /// `Properties70` child node.
+    pub properties: Option<Properties70>,
+    /// Property template (see `Object::template`).
+    pub template: Option<Arc<Properties70>>,
+}
+
+impl TemplatedProperties {
+    /// Creates a `TemplatedProperties` from the generic object.
+    pub fn from_object(obj: &Object) -> Self {
+        TemplatedProperties {
+            properties: obj.properties.clone(),
+            template: obj.template.clone(),
+        }
+    }
+
+    /// Returns the properties overlaid on the property template.
+    pub fn effective(&self) -> EffectiveProperties<'_> {
+        EffectiveProperties::new(self.properties.as_ref(), self.template.as_deref())
+    }
+
+    /// Returns user-defined properties (properties with `U` flag).
+    pub fn user_properties(&self) -> BTreeMap<&str, &PropertyValue> {
+        self.properties.as_ref().map_or_else(BTreeMap::new, Properties70::user_properties)
+    }
+}
+
+
+/// Objects.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Objects {
+    /// Objects.
+    objects: ObjectMap<Object>,
+    /// Index of objects by name and class.
+    index: ObjectIndex,
+    /// Objects loaded by user defined loaders.
+    custom: CustomObjects,
+    /// Object IDs in insertion order.
+    order: Vec<i64>,
+    /// Lossy attribute conversions detected in the strict coercion mode.
+    coercion_warnings: Vec<CoercionWarning>,
+}
+
+impl Objects {
+    /// Creates a new empty `Objects`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Inserts the object and returns the old object with the same ID.
+    ///
+    /// The custom object with the same ID is removed.
+    pub fn insert(&mut self, object: Object) -> Option<Object> {
+        let old = self.objects.remove(&object.id());
+        match old {
+            Some(ref old) => {
+                self.index.remove(&old.props);
+                self.custom.remove(old.id());
+            },
+            None => self.order.push(object.id()),
+        }
+        self.index.insert(&object.props);
+        self.objects.insert(object.id(), object);
+        old
+    }
+
+    /// Removes the object with the given ID and returns it.
+    pub fn remove(&mut self, id: i64) -> Option<Object> {
+        self.custom.remove(id);
+        let old = self.objects.remove(&id);
+        if let Some(ref old) = old {
+            self.index.remove(&old.props);
+            self.order.retain(|&v| v != id);
+        }
+        old
+    }
+
+    /// Replaces object IDs by the map.
+    ///
+    /// IDs not in the map are kept as is, and the loaded order is preserved.
+    /// The map should be injective, otherwise objects mapped to the same ID are lost.
+    pub fn remap_ids(&mut self, map: &ObjectMap<i64>) {
+        let mut objects = ::std::mem::take(&mut self.objects);
+        let order = ::std::mem::take(&mut self.order);
+        self.index = ObjectIndex::new();
+        self.custom.remap_ids(map);
+        for old_id in order {
+            let mut object = objects.remove(&old_id).expect("Ordered ID should exist");
+            object.props.id = map.get(&old_id).cloned().unwrap_or(old_id);
+            self.insert(object);
+        }
+    }
+
+    /// Converts the objects into an `Objects` node, in loaded order.
+    ///
+    /// Objects loaded by user defined loaders (see `custom_objects()`) are not written, since
+    /// they are opaque to this crate.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("Objects")
+            .with_children(self.iter_ordered().map(|(_, obj)| obj.to_generic_node()))
+    }
+
+    /// Sets `template` of all objects to their property templates in the definitions.
+    ///
+    /// Each template is cloned once and shared among objects.
+    pub fn resolve_templates(&mut self, definitions: &Definitions) {
+        let mut shared: FnvHashMap<*const Properties70, Arc<Properties70>> =
+            FnvHashMap::default();
+        for object in self.objects.values_mut() {
+            object.template = definitions
+                .template_for_object(&object.props, object.properties.as_ref())
+                .map(|template| {
+                    shared
+                        .entry(template as *const _)
+                        .or_insert_with(|| Arc::new(template.clone()))
+                        .clone()
+                });
+        }
+    }
+
+    /// Returns the object with the given ID.
+    pub fn object(&self, id: i64) -> Option<&Object> {
+        self.objects.get(&id)
+    }
+
+    /// Returns the object with the given ID, categorized by its class.
+    pub fn get(&self, id: i64) -> Option<ObjectRef<'_>> {
+        self.objects.get(&id).map(ObjectRef::new)
+    }
+
+    /// Returns an iterator of all objects with IDs, categorized by their classes.
+    ///
+    /// Objects are iterated in arbitrary order.
+    pub fn iter_all(&self) -> impl Iterator<Item = (i64, ObjectRef<'_>)> {
+        self.objects.iter().map(|(&id, obj)| (id, ObjectRef::new(obj)))
+    }
+
+    /// Returns an iterator of objects with IDs, in insertion (i.e. loaded) order.
+    ///
+    /// Unlike `objects()`, the order is stable between runs.
+    /// Replacing an object by `insert()` keeps its position.
+    pub fn iter_ordered(&self) -> impl Iterator<Item = (i64, &Object)> {
+        self.order.iter().map(move |&id| (id, &self.objects[&id]))
+    }
+
+    /// Converts the objects into an iterator of objects, in insertion (i.e. loaded) order.
+    ///
+    /// Objects loaded by user defined loaders are dropped.
+    pub fn into_iter_ordered(self) -> impl Iterator<Item = Object> {
+        let mut objects = self.objects;
+        self.order
+            .into_iter()
+            .map(move |id| objects.remove(&id).expect("Ordered object should exist"))
+    }
+
+    /// Returns object IDs sorted in ascending order.
+    pub fn sorted_ids(&self) -> Vec<i64> {
+        let mut ids = self.objects.keys().cloned().collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Returns an iterator of objects with IDs, sorted by IDs.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (i64, &Object)> {
+        self.sorted_ids().into_iter().map(move |id| (id, &self.objects[&id]))
+    }
+
+    /// Returns the number of objects.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Returns `true` if there are no objects.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Returns an iterator of objects in arbitrary order.
+    pub fn objects(&self) -> ::std::collections::hash_map::Values<'_, i64, Object> {
+        self.objects.values()
+    }
+
+    /// Returns the object map.
+    pub fn object_map(&self) -> &ObjectMap<Object> {
+        &self.objects
+    }
+
+    /// Returns the index of objects by name and class.
+    pub fn index(&self) -> &ObjectIndex {
+        &self.index
+    }
+
+    /// Returns IDs of objects with the given name, in loaded order.
+    pub fn ids_by_name(&self, name: &str) -> &[i64] {
+        self.index.ids_by_name(name)
+    }
+
+    /// Returns IDs of objects with the given class and subclass, in loaded order.
+    pub fn ids_by_class(&self, class: &str, subclass: &str) -> &[i64] {
+        self.index.ids_by_class(class, subclass)
+    }
+
+    /// Returns objects with the given name, in loaded order.
+    pub fn find_by_name<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Object> + 'a {
+        let ids = self.index.ids_by_name(name);
+        ids.iter().filter_map(move |id| self.objects.get(id))
+    }
+
+    /// Returns the first `Model` object with the given name.
+    pub fn find_model_by_name(&self, name: &str) -> Option<&Object> {
+        self.find_by_name(name).find(|obj| obj.class() == "Model")
+    }
+
+    /// Returns the object loaded by the user defined loader, if it has the type `T`.
+    pub fn custom<T: ::std::any::Any>(&self, id: i64) -> Option<&T> {
+        self.custom.get(id)
+    }
+
+    /// Returns objects loaded by user defined loaders.
+    pub fn custom_objects(&self) -> &CustomObjects {
+        &self.custom
+    }
+
+    /// Returns objects loaded by user defined loaders.
+    pub fn custom_objects_mut(&mut self) -> &mut CustomObjects {
+        &mut self.custom
+    }
+
+    /// Returns lossy attribute conversions detected while loading.
+    ///
+    /// Always empty unless `ObjectsLoader::set_strict_coercion()` is enabled.
+    pub fn coercion_warnings(&self) -> &[CoercionWarning] {
+        &self.coercion_warnings
+    }
+}
+
+
+/// Objects loader which loads all objects as `Object`.
+///
+/// Objects with loaders registered in the `ObjectLoaderRegistry` are additionally loaded by
+/// them, and the results are available from `Objects::custom()`.
+#[derive(Debug)]
+pub struct ObjectsLoader<R> {
+    /// Objects.
+    objects: Objects,
+    /// User defined object loaders.
+    registry: ObjectLoaderRegistry,
+    /// Options for loading objects.
+    options: ObjectLoadOptions,
+    /// Dummy.
+    _r: PhantomData<R>,
+}
+
+impl<R> ObjectsLoader<R> {
+    /// Creates a new `ObjectsLoader`.
+    pub fn new() -> Self {
+        Self::with_registry(ObjectLoaderRegistry::new())
+    }
+
+    /// Creates a new `ObjectsLoader` with the user defined object loaders.
+    pub fn with_registry(registry: ObjectLoaderRegistry) -> Self {
+        ObjectsLoader {
+            objects: Objects::new(),
+            registry: registry,
+            options: ObjectLoadOptions::default(),
+            _r: PhantomData,
+        }
+    }
+
+    /// Returns the user defined object loaders.
+    pub fn registry(&self) -> &ObjectLoaderRegistry {
+        &self.registry
+    }
+
+    /// Sets whether to load `f64` array attributes as `f32` arrays.
+    ///
+    /// If enabled, arrays such as vertices and normals are converted during the streaming read,
+    /// so the whole `f64` array is never allocated.
+    /// `Properties70` is not affected.
+    /// Use `MeshF32` to get vertices of meshes without widening them again.
+    pub fn set_narrow_f64_arrays(&mut self, narrow: bool) {
+        self.options.attributes.narrow_f64_arrays = narrow;
+    }
+
+    /// Returns whether `f64` array attributes are loaded as `f32` arrays.
+    pub fn narrow_f64_arrays(&self) -> bool {
+        self.options.attributes.narrow_f64_arrays
+    }
+
+    /// Sets whether to detect lossy attribute conversions.
+    ///
+    /// If enabled, `f64` values which are not representable in `f32` are reported by
+    /// `Objects::coercion_warnings()` with the node paths (such as
+    /// `"Objects/Geometry(42)/Vertices"`), when arrays are narrowed.
+    /// Narrowed arrays are converted element by element, which is slower.
+    pub fn set_strict_coercion(&mut self, strict: bool) {
+        if !strict {
+            self.options.attributes.strict_coercion = None;
+        } else if self.options.attributes.strict_coercion.is_none() {
+            let mut coercion = StrictCoercion::new();
+            coercion.enter("Objects");
+            self.options.attributes.strict_coercion = Some(coercion);
+        }
+    }
+
+    /// Returns whether lossy attribute conversions are detected.
+    pub fn strict_coercion(&self) -> bool {
+        self.options.attributes.strict_coercion.is_some()
+    }
+
+    /// Sets the handling of large binary attributes (such as `Video` contents), and returns
+    /// the old one.
+    ///
+    /// Large binaries are loaded as `OwnedAttribute::BinaryRef` instead of
+    /// `OwnedAttribute::Binary`.
+    /// If `None`, all binaries are loaded into memory (this is the default).
+    pub fn set_large_binaries(
+        &mut self,
+        large_binaries: Option<LargeBinaries>,
+    ) -> Option<LargeBinaries> {
+        ::std::mem::replace(&mut self.options.attributes.large_binaries, large_binaries)
+    }
+
+    /// Returns the handling of large binary attributes.
+    pub fn large_binaries(&self) -> Option<&LargeBinaries> {
+        self.options.attributes.large_binaries.as_ref()
+    }
+
+    /// Sets whether to share `Properties70` property names among objects.
+    pub fn set_intern_property_names(&mut self, intern: bool) {
+        if !intern {
+            self.options.property_names = None;
+        } else if self.options.property_names.is_none() {
+            self.options.property_names = Some(PropertyNameInterner::new());
+        }
+    }
+
+    /// Returns the interner of property names, if enabled.
+    pub fn property_names(&self) -> Option<&PropertyNameInterner> {
+        self.options.property_names.as_ref()
+    }
+}
+
+impl<R> Default for ObjectsLoader<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: ParserSource> LoadObjectsWithContext7400 for ObjectsLoader<R> {
+    type Reader = R;
+    type Objects = Objects;
+
+    fn build(mut self, context: &ObjectsContext) -> Result<Self::Objects> {
+        let mut objects = self.objects;
+        if let Some(ref mut strict) = self.options.attributes.strict_coercion {
+            objects.coercion_warnings = strict.take_warnings();
+        }
+        if let Some(definitions) = context.definitions() {
+            objects.resolve_templates(definitions);
+        }
+        Ok(objects)
+    }
+
+    fn load(
+        &mut self,
+        props: ObjectPropertiesRef<'_>,
+        subtree_parser: &mut SubtreeParser<R>,
+        _context: &ObjectsContext,
+    ) -> Result<()> {
+        let object = Object::load_with(props.into_owned(), subtree_parser, &mut self.options)?;
+        let custom = match self.registry.get(object.class(), object.subclass()) {
+            Some(loader) => Some(loader.load(&object)?),
+            None => None,
+        };
+        let id = object.id();
+        if let Some(old) = self.objects.insert(object) {
+            warn!("Duplicate object ID {}, overwriting old one", old.id());
+        }
+        if let Some(custom) = custom {
+            self.objects.custom.insert(id, custom);
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::{Cursor, Write};
+    use std::sync::{Arc, Mutex};
+    use parser::binary::RootParser;
+    use loader::binary::simple::{FbxLoader, LargeBinaries};
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use test_util::{NODES_BEFORE_OBJECTS, write_toplevel_node};
+    use loader::binary::simple::fbx7400::{ObjectRef, ObjectLoaderRegistry, Object};
+    use loader::binary::simple::fbx7400::{NodeAttribute, ObjectProperties};
+    use loader::material::StandardMaterial;
+    use parser::binary::utils::CoercionLoss;
+    use super::{ObjectsLoader, Objects};
+
+    #[test]
+    fn test_load_objects() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &(id, name_class, subclass) in &[
+            (1, "Camera001\u{0}\u{1}NodeAttribute", "Camera"),
+            (2, "Camera001\u{0}\u{1}Model", "Camera"),
+            (3, "Armature\u{0}\u{1}Model", "Null"),
+            (4, "Bone\u{0}\u{1}Model", "LimbNode"),
+        ]
+        {
+            b.begin_node(
+                "Object",
+                &[Attr::I64(id), Attr::String(name_class), Attr::String(subclass)],
+            );
+            b.node("Version", &[Attr::I32(232)]);
+            write_properties70(&mut b, &[("Visibility", "Visibility", "", "A", &[Attr::F64(1.0)])]);
+            b.end_node();
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let objects = fbx.objects;
+
+        assert_eq!(objects.len(), 4);
+        assert_eq!(objects.ids_by_name("Camera001"), &[1, 2]);
+        assert_eq!(objects.ids_by_class("Model", "Camera"), &[2]);
+        assert!(objects.ids_by_name("Nonexistent").is_empty());
+        let model = objects.find_model_by_name("Camera001").expect("Model should be found");
+        assert_eq!(model.id(), 2);
+        assert_eq!(model.node("Version").map(|node| node.attributes.len()), Some(1));
+        assert!(model.properties.is_some());
+        let mut models = objects.index().ids_by_class_any_subclass("Model").collect::<Vec<_>>();
+        models.sort();
+        assert_eq!(models, vec![2, 3, 4]);
+        match objects.get(1) {
+            Some(ObjectRef::NodeAttribute(obj)) => assert_eq!(obj.subclass(), "Camera"),
+            v => panic!("Unexpected object: {:?}", v),
+        }
+        assert!(objects.get(5).is_none());
+        assert_eq!(
+            objects.iter_all().filter(|&(_, obj)| matches!(obj, ObjectRef::Model(_))).count(),
+            3
+        );
+    }
+
+    /// User defined object type.
+    #[derive(Debug, PartialEq)]
+    struct Marker {
+        version: usize,
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &(id, name_class, subclass) in &[
+            (1, "Marker\u{0}\u{1}Model", "Marker"),
+            (2, "Null\u{0}\u{1}Model", "Null"),
+        ]
+        {
+            b.node("Model", &[Attr::I64(id), Attr::String(name_class), Attr::String(subclass)]);
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let mut registry = ObjectLoaderRegistry::new();
+        registry.register("Model", Some("Marker"), |obj: &Object| {
+            Ok(Box::new(Marker { version: obj.nodes.len() }) as Box<_>)
+        });
+        let parser = RootParser::new(Cursor::new(data));
+        let objects = match FbxLoader::load_from_parser(parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => {
+                loader.load(ObjectsLoader::with_registry(registry)).expect("Load error")
+            },
+        }.objects;
+
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects.custom::<Marker>(1), Some(&Marker { version: 0 }));
+        assert!(objects.custom::<Marker>(2).is_none());
+        assert!(objects.custom::<String>(1).is_none());
+        assert_eq!(objects.custom_objects().len(), 1);
+    }
+
+    #[test]
+    fn test_intern_property_names() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &id in &[1, 2] {
+            b.begin_node(
+                "Model",
+                &[Attr::I64(id), Attr::String("Null\u{0}\u{1}Model"), Attr::String("Null")],
+            );
+            write_properties70(&mut b, &[("Visibility", "Visibility", "", "A", &[Attr::F64(1.0)])]);
+            b.end_node();
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let mut objs_loader = ObjectsLoader::new();
+        objs_loader.set_intern_property_names(true);
+        let parser = RootParser::new(Cursor::new(data));
+        let objects = match FbxLoader::load_from_parser(parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => loader.load(objs_loader).expect("Load error"),
+        }.objects;
+
+        let name = |id| {
+            let props = objects.object(id).and_then(|obj| obj.properties.as_ref());
+            let (name, _) = props.and_then(|props| props.iter().next()).expect("Should exist");
+            name
+        };
+        assert_eq!(name(1), "Visibility");
+        assert_eq!(name(1).as_ptr(), name(2).as_ptr());
+    }
+
+    #[test]
+    fn test_resolve_templates() {
+        let mut b = FbxBuilder::new(7400);
+        for name in NODES_BEFORE_OBJECTS.iter().filter(|&&name| name != "Definitions") {
+            write_toplevel_node(&mut b, name);
+        }
+        b.begin_node("Definitions", &[]);
+        b.node("Version", &[Attr::I32(100)]);
+        b.node("Count", &[Attr::I32(4)]);
+        let diffuse = &[Attr::F64(0.25), Attr::F64(0.5), Attr::F64(1.0)];
+        let size = &[Attr::F64(100.0)];
+        for &(object_type, class_name, template) in &[
+            ("Material", "FbxSurfacePhong", ("DiffuseColor", "Color", &diffuse[..])),
+            ("NodeAttribute", "FbxNull", ("Size", "double", &size[..])),
+        ]
+        {
+            b.begin_node("ObjectType", &[Attr::String(object_type)]);
+            b.node("Count", &[Attr::I32(2)]);
+            b.begin_node("PropertyTemplate", &[Attr::String(class_name)]);
+            let (name, type_, value) = template;
+            write_properties70(&mut b, &[(name, type_, "", "A", value)]);
+            b.end_node();
+            b.end_node();
+        }
+        b.end_node();
+        b.begin_node("Objects", &[]);
+        for &(id, name_class) in &[(1, "Red\u{0}\u{1}Material"), (2, "Gray\u{0}\u{1}Material")] {
+            b.begin_node("Material", &[Attr::I64(id), Attr::String(name_class), Attr::String("")]);
+            b.node("ShadingModel", &[Attr::String("phong")]);
+            if id == 1 {
+                let red = &[Attr::F64(1.0), Attr::F64(0.0), Attr::F64(0.0)];
+                write_properties70(&mut b, &[("DiffuseColor", "Color", "", "A", red)]);
+            }
+            b.end_node();
+        }
+        b.node(
+            "NodeAttribute",
+            &[Attr::I64(3), Attr::String("\u{0}\u{1}NodeAttribute"), Attr::String("Null")],
+        );
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let object = |id| fbx.objects.object(id).expect("Should exist");
+
+        let template = |id| object(id).template.clone().expect("Template should be resolved");
+        assert!(Arc::ptr_eq(&template(1), &template(2)));
+        let material = |id| StandardMaterial::from_object(object(id), &fbx.connections);
+        assert_eq!(material(1).base_color, [1.0, 0.0, 0.0]);
+        assert_eq!(material(2).base_color, [0.25, 0.5, 1.0]);
+        assert_eq!(NodeAttribute::from_object(object(3)).size(), Some(100.0));
+    }
+
+    #[test]
+    fn test_deterministic_order() {
+        let mut objects = Objects::new();
+        for &id in &[30, 10, 20, 40] {
+            objects.insert(Object {
+                props: ObjectProperties {
+                    id: id,
+                    name: String::new(),
+                    class: "Model".to_owned(),
+                    subclass: "Null".to_owned(),
+                },
+                properties: None,
+                nodes: Vec::new(),
+                template: None,
+            });
+        }
+        objects.remove(40);
+        let replaced = objects.object(30).cloned().expect("Should exist");
+        objects.insert(replaced);
+
+        let ordered = objects.iter_ordered().map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(ordered, vec![30, 10, 20]);
+        let sorted = objects.iter_sorted().map(|(id, obj)| (id, obj.id())).collect::<Vec<_>>();
+        assert_eq!(sorted, vec![(10, 10), (20, 20), (30, 30)]);
+        let consumed = objects.into_iter_ordered().map(|obj| obj.id()).collect::<Vec<_>>();
+        assert_eq!(consumed, ordered);
+    }
+
+    /// Sink shared with the test.
+    #[derive(Debug, Clone, Default)]
+    struct SharedSink(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedSink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().expect("Poisoned").write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_large_binaries() {
+        let content = (0..64).collect::<Vec<u8>>();
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &id in &[1, 2] {
+            b.begin_node(
+                "Video",
+                &[Attr::I64(id), Attr::String("Video\u{0}\u{1}Video"), Attr::String("Clip")],
+            );
+            b.node("Content", &[Attr::Binary(&content)]);
+            b.node("Small", &[Attr::Binary(&[1, 2])]);
+            b.end_node();
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let load = |large: LargeBinaries| {
+            let mut objs_loader = ObjectsLoader::new();
+            objs_loader.set_large_binaries(Some(large));
+            let parser = RootParser::from_seekable(Cursor::new(data.clone()));
+            match FbxLoader::load_from_parser(parser).expect("Load error") {
+                FbxLoader::Fbx7400(loader) => loader.load(objs_loader).expect("Load error"),
+            }.objects
+        };
+        let content_ref = |objects: &Objects, id: i64| {
+            let obj = objects.object(id).expect("Should exist");
+            let small = obj.node("Small").and_then(|n| n.attributes[0].as_binary());
+            assert_eq!(small, Some(&[1, 2][..]));
+            let content = obj.node("Content").and_then(|n| n.attributes[0].as_binary_ref());
+            content.expect("Content should not be loaded")
+        };
+
+        let objects = load(LargeBinaries::reference(16));
+        for &id in &[1, 2] {
+            let r = content_ref(&objects, id);
+            assert_eq!(&data[r.offset as usize..(r.offset + r.len) as usize], &content[..]);
+        }
+
+        let sink = SharedSink::default();
+        let objects = load(LargeBinaries::stream(16, sink.clone()));
+        let streamed = sink.0.lock().expect("Poisoned");
+        assert_eq!(streamed.len(), 128);
+        for &id in &[1, 2] {
+            let r = content_ref(&objects, id);
+            assert_eq!(&streamed[r.offset as usize..(r.offset + r.len) as usize], &content[..]);
+        }
+    }
+
+    #[test]
+    fn test_strict_coercion() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "Geometry",
+            &[Attr::I64(42), Attr::String("Tri\u{0}\u{1}Geometry"), Attr::String("Mesh")],
+        );
+        b.node("Vertices", &[Attr::ArrF64(&[0.0, 0.5, 1.0, 0.1, 0.2, 1e-3])]);
+        b.begin_node("LayerElementNormal", &[Attr::I32(0)]);
+        b.node("Normals", &[Attr::ArrF64(&[0.0, 0.6, 0.8])]);
+        b.end_node();
+        b.end_node();
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let load = |strict: bool| {
+            let mut objs_loader = ObjectsLoader::new();
+            objs_loader.set_narrow_f64_arrays(true);
+            objs_loader.set_strict_coercion(strict);
+            let parser = RootParser::new(Cursor::new(data.clone()));
+            match FbxLoader::load_from_parser(parser).expect("Load error") {
+                FbxLoader::Fbx7400(loader) => loader.load(objs_loader).expect("Load error"),
+            }.objects
+        };
+
+        let objects = load(true);
+        let warnings = objects.coercion_warnings()
+            .iter()
+            .map(|w| (w.path.as_str(), w.attribute, w.loss))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                (
+                    "Objects/Geometry(42)/Vertices",
+                    0,
+                    CoercionLoss::InexactF32 { value: 0.1, count: 3 },
+                ),
+                (
+                    "Objects/Geometry(42)/LayerElementNormal/Normals",
+                    0,
+                    CoercionLoss::InexactF32 { value: 0.6, count: 2 },
+                ),
+            ]
+        );
+        let lenient = load(false);
+        assert!(lenient.coercion_warnings().is_empty());
+        assert_eq!(lenient, Objects { coercion_warnings: Vec::new(), ..objects });
+    }
+}
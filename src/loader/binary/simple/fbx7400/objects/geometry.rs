@@ -0,0 +1,887 @@
+//! `Geometry` object.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use parser::binary::{Parser, ParserSource, FromArrayStream};
+use loader::binary::simple::{Result, Error, GenericNode, OwnedAttribute};
+use loader::binary::simple::fbx7400::Properties70;
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object, Objects};
+use loader::binary::simple::fbx7400::objects::{ArrayStreams, ObjectLoadOptions};
+use loader::math::{Aabb, Vector3};
+
+
+/// Mapping mode of a layer element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MappingMode {
+    /// One value for each control point (`ByControlPoint`, `ByVertex`, or `ByVertice`).
+    ByControlPoint,
+    /// One value for each polygon vertex.
+    ByPolygonVertex,
+    /// One value for each polygon.
+    ByPolygon,
+    /// One value for each edge.
+    ByEdge,
+    /// One value for the whole mesh.
+    AllSame,
+}
+
+impl MappingMode {
+    /// Creates a `MappingMode` from the value of `MappingInformationType` node.
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "ByControlPoint" | "ByVertex" | "ByVertice" => Some(MappingMode::ByControlPoint),
+            "ByPolygonVertex" => Some(MappingMode::ByPolygonVertex),
+            "ByPolygon" => Some(MappingMode::ByPolygon),
+            "ByEdge" => Some(MappingMode::ByEdge),
+            "AllSame" => Some(MappingMode::AllSame),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of `MappingInformationType` node for the mapping mode.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            MappingMode::ByControlPoint => "ByControlPoint",
+            MappingMode::ByPolygonVertex => "ByPolygonVertex",
+            MappingMode::ByPolygon => "ByPolygon",
+            MappingMode::ByEdge => "ByEdge",
+            MappingMode::AllSame => "AllSame",
+        }
+    }
+}
+
+
+/// Reference mode of a layer element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceMode {
+    /// Values are referred directly by the mapping.
+    Direct,
+    /// Values are referred through the index array (`IndexToDirect` or `Index`).
+    IndexToDirect,
+}
+
+impl ReferenceMode {
+    /// Creates a `ReferenceMode` from the value of `ReferenceInformationType` node.
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "Direct" => Some(ReferenceMode::Direct),
+            "IndexToDirect" | "Index" => Some(ReferenceMode::IndexToDirect),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of `ReferenceInformationType` node for the reference mode.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ReferenceMode::Direct => "Direct",
+            ReferenceMode::IndexToDirect => "IndexToDirect",
+        }
+    }
+}
+
+
+/// Layer element (such as `LayerElementNormal` and `LayerElementUV`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerElement<T> {
+    /// Layer element index (the node attribute).
+    pub index: i32,
+    /// Name of the layer element (such as UV set name).
+    pub name: String,
+    /// Mapping mode.
+    pub mapping: MappingMode,
+    /// Reference mode.
+    pub reference: ReferenceMode,
+    /// Direct values.
+    pub direct: Vec<T>,
+    /// Indices into `direct`, for `ReferenceMode::IndexToDirect`.
+    pub indices: Vec<i32>,
+}
+
+impl<T> LayerElement<T> {
+    /// Returns the value for the given polygon vertex.
+    ///
+    /// `polygon_vertex` is the index into polygon vertex indices, `control_point` is the control
+    /// point of the polygon vertex, and `polygon` is the index of the polygon.
+    /// Returns `None` if the value is unavailable (for example, out of range index or
+    /// `ByEdge` mapping).
+    pub fn get(&self, polygon_vertex: usize, control_point: usize, polygon: usize) -> Option<&T> {
+        let index = match self.mapping {
+            MappingMode::ByControlPoint => control_point,
+            MappingMode::ByPolygonVertex => polygon_vertex,
+            MappingMode::ByPolygon => polygon,
+            MappingMode::AllSame => 0,
+            MappingMode::ByEdge => return None,
+        };
+        let index = match self.reference {
+            ReferenceMode::Direct => index,
+            ReferenceMode::IndexToDirect => {
+                let i = *self.indices.get(index)?;
+                if i < 0 {
+                    return None;
+                }
+                i as usize
+            },
+        };
+        self.direct.get(index)
+    }
+
+    /// Reorders `ByPolygonVertex` values so that the `i`-th value is the `order[i]`-th one.
+    ///
+    /// Values after `order.len()` are left as is.
+    /// Elements with other mapping modes or with too few values are not modified.
+    fn reorder_polygon_vertices(&mut self, order: &[usize])
+    where
+        T: Clone,
+    {
+        fn reorder<T: Clone>(values: &mut Vec<T>, order: &[usize]) {
+            if values.len() < order.len() {
+                return;
+            }
+            let mut reordered = order.iter().map(|&i| values[i].clone()).collect::<Vec<_>>();
+            reordered.extend_from_slice(&values[order.len()..]);
+            *values = reordered;
+        }
+
+        if self.mapping != MappingMode::ByPolygonVertex {
+            return;
+        }
+        match self.reference {
+            ReferenceMode::Direct => reorder(&mut self.direct, order),
+            ReferenceMode::IndexToDirect => reorder(&mut self.indices, order),
+        }
+    }
+}
+
+
+/// Polygon vertex of a mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PolygonVertex {
+    /// Index into polygon vertex indices.
+    pub polygon_vertex: usize,
+    /// Control point index.
+    pub control_point: usize,
+    /// Polygon index.
+    pub polygon: usize,
+}
+
+
+/// `Geometry` object with `Mesh` subclass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// Control points.
+    pub vertices: Vec<[f64; 3]>,
+    /// Polygon vertex indices.
+    ///
+    /// The last index of each polygon is stored as bitwise negation (`!index`).
+    pub polygon_vertex_index: Vec<i32>,
+    /// Normals (`LayerElementNormal`).
+    pub normals: Vec<LayerElement<[f64; 3]>>,
+    /// UV sets (`LayerElementUV`).
+    pub uv_sets: Vec<LayerElement<[f64; 2]>>,
+    /// Vertex colors (`LayerElementColor`).
+    pub colors: Vec<LayerElement<[f64; 4]>>,
+    /// Material indices (`LayerElementMaterial`).
+    pub materials: Vec<LayerElement<i32>>,
+    /// `BBoxMin` property.
+    pub bbox_min: Option<Vector3>,
+    /// `BBoxMax` property.
+    pub bbox_max: Option<Vector3>,
+}
+
+impl Mesh {
+    /// Creates a `Mesh` from the generic object.
+    ///
+    /// `f32` arrays (loaded with `ObjectsLoader::set_narrow_f64_arrays()`) are widened into
+    /// `f64`. Use `MeshF32` to borrow them without widening.
+    pub fn from_object(obj: &Object) -> Result<Self> {
+        let vertices = chunks(&required_f64_array(obj, "Vertices")?);
+        let polygon_vertex_index = required_child(obj, "PolygonVertexIndex")
+            .and_then(|node| i32_array(node).ok_or_else(|| invalid(node)))?
+            .to_vec();
+        Mesh::from_object_with_arrays(obj, vertices, polygon_vertex_index)
+    }
+
+    /// Loads a `Mesh`, streaming `Vertices` and `PolygonVertexIndex` into the given sinks.
+    ///
+    /// The arrays are not materialized, so `vertices` and `polygon_vertex_index` of the
+    /// returned mesh are empty.
+    /// Layer elements are loaded as usual.
+    pub fn load_streamed<R, P>(
+        props: ObjectProperties,
+        parser: P,
+        vertices: &mut dyn FromArrayStream<f64>,
+        polygon_vertex_index: &mut dyn FromArrayStream<i32>,
+    ) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        let mut streams = ArrayStreams::new();
+        streams.set_f64("Vertices", vertices);
+        streams.set_i32("PolygonVertexIndex", polygon_vertex_index);
+        let mut options = ObjectLoadOptions::default();
+        let obj = Object::load_with_streams(props, parser, &mut options, &mut streams)?;
+        required_child(&obj, "Vertices")?;
+        required_child(&obj, "PolygonVertexIndex")?;
+        Mesh::from_object_with_arrays(&obj, Vec::new(), Vec::new())
+    }
+
+    /// Creates a `Mesh` from the generic object and the already loaded arrays.
+    fn from_object_with_arrays(
+        obj: &Object,
+        vertices: Vec<[f64; 3]>,
+        polygon_vertex_index: Vec<i32>,
+    ) -> Result<Self> {
+        let mut mesh = Mesh {
+            props: obj.props.clone(),
+            vertices: vertices,
+            polygon_vertex_index: polygon_vertex_index,
+            normals: Vec::new(),
+            uv_sets: Vec::new(),
+            colors: Vec::new(),
+            materials: Vec::new(),
+            bbox_min: obj.properties.as_ref().and_then(|p| p.get_f64_3("BBoxMin")),
+            bbox_max: obj.properties.as_ref().and_then(|p| p.get_f64_3("BBoxMax")),
+        };
+        for node in &obj.nodes {
+            match node.name.as_str() {
+                "LayerElementNormal" => {
+                    let normals = load_layer_element(node, "Normals", Some("NormalsIndex"), f64s)?;
+                    mesh.normals.push(normals);
+                },
+                "LayerElementUV" => {
+                    let uv = load_layer_element(node, "UV", Some("UVIndex"), f64s)?;
+                    mesh.uv_sets.push(uv);
+                },
+                "LayerElementColor" => {
+                    let colors = load_layer_element(node, "Colors", Some("ColorIndex"), f64s)?;
+                    mesh.colors.push(colors);
+                },
+                "LayerElementMaterial" => {
+                    // Values of `LayerElementMaterial` are indices into the materials of the
+                    // model, so they are treated as direct values.
+                    mesh.materials.push(load_layer_element(node, "Materials", None, |node| {
+                        i32_array(node).map(<[i32]>::to_vec)
+                    })?)
+                },
+                _ => {},
+            }
+        }
+        Ok(mesh)
+    }
+
+    /// Converts the mesh into a generic object.
+    ///
+    /// `GeometryVersion`, `Version` of layer elements, and `Layer` nodes are written with the
+    /// values used by FBX 7.4 exporters, and `BBoxMin` and `BBoxMax` are written as properties.
+    /// Material layer elements are written as `IndexToDirect`, as FBX SDK does.
+    pub fn to_object(&self) -> Object {
+        let mut nodes = vec![
+            GenericNode::named("GeometryVersion").with_attr(124),
+            GenericNode::named("Vertices").with_attr(flatten(&self.vertices)),
+            GenericNode::named("PolygonVertexIndex").with_attr(&self.polygon_vertex_index[..]),
+        ];
+        let mut layers = BTreeMap::new();
+        {
+            let mut push = |node: GenericNode, index: i32| {
+                let element = GenericNode::named("LayerElement")
+                    .with_child(GenericNode::named("Type").with_attr(node.name.as_str()))
+                    .with_child(GenericNode::named("TypedIndex").with_attr(index));
+                layers.entry(index).or_insert_with(Vec::new).push(element);
+                nodes.push(node);
+            };
+            for e in &self.normals {
+                let values = ("Normals", flatten(&e.direct).into());
+                let index = Some("NormalsIndex");
+                push(layer_element_node(e, "LayerElementNormal", values, index), e.index);
+            }
+            for e in &self.uv_sets {
+                let values = ("UV", flatten(&e.direct).into());
+                push(layer_element_node(e, "LayerElementUV", values, Some("UVIndex")), e.index);
+            }
+            for e in &self.colors {
+                let values = ("Colors", flatten(&e.direct).into());
+                let index = Some("ColorIndex");
+                push(layer_element_node(e, "LayerElementColor", values, index), e.index);
+            }
+            for e in &self.materials {
+                let values = ("Materials", e.direct[..].into());
+                push(layer_element_node(e, "LayerElementMaterial", values, None), e.index);
+            }
+        }
+        nodes.extend(layers.into_iter().map(|(index, elements)| {
+            GenericNode::named("Layer")
+                .with_attr(index)
+                .with_child(GenericNode::named("Version").with_attr(100))
+                .with_children(elements)
+        }));
+
+        let mut properties = Properties70::new();
+        if let Some(min) = self.bbox_min {
+            properties.insert("BBoxMin", min);
+        }
+        if let Some(max) = self.bbox_max {
+            properties.insert("BBoxMax", max);
+        }
+        Object {
+            props: self.props.clone(),
+            properties: if properties.is_empty() { None } else { Some(properties) },
+            nodes: nodes,
+            template: None,
+        }
+    }
+
+    /// Returns the bounding box of the mesh in its local space.
+    ///
+    /// The bounding box is computed from the vertices, and `BBoxMin` and `BBoxMax` properties
+    /// are used if there are no vertices (e.g. vertices are streamed by `load_streamed()`).
+    pub fn compute_aabb(&self) -> Option<Aabb> {
+        let properties = match (self.bbox_min, self.bbox_max) {
+            (Some(min), Some(max)) => Some(Aabb { min: min, max: max }),
+            _ => None,
+        };
+        Aabb::from_points(&self.vertices).or(properties)
+    }
+
+    /// Returns an iterator of polygon vertices.
+    ///
+    /// Incomplete polygon at the tail (i.e. polygon vertices after the last negative index) is
+    /// ignored.
+    pub fn polygon_vertices(&self) -> impl Iterator<Item = PolygonVertex> + '_ {
+        let complete_len = self.polygon_vertex_index
+            .iter()
+            .rposition(|&i| i < 0)
+            .map_or(0, |pos| pos + 1);
+        let mut polygon = 0;
+        self.polygon_vertex_index[..complete_len]
+            .iter()
+            .enumerate()
+            .map(move |(polygon_vertex, &i)| {
+                let (control_point, is_last) = if i < 0 { (!i, true) } else { (i, false) };
+                let pv = PolygonVertex {
+                    polygon_vertex: polygon_vertex,
+                    control_point: control_point as usize,
+                    polygon: polygon,
+                };
+                if is_last {
+                    polygon += 1;
+                }
+                pv
+            })
+    }
+
+    /// Returns an iterator of UV set names and UV sets, in layer element order.
+    pub fn uv_sets(&self) -> impl Iterator<Item = (&str, &LayerElement<[f64; 2]>)> {
+        self.uv_sets.iter().map(|uv| (uv.name.as_str(), uv))
+    }
+
+    /// Returns the UV set with the given name.
+    pub fn uv_set(&self, name: &str) -> Option<&LayerElement<[f64; 2]>> {
+        self.uv_sets.iter().find(|uv| uv.name == name)
+    }
+
+    /// Returns the UV set used by the given texture.
+    ///
+    /// The UV set is chosen by `UVSet` property of the texture (or its property template).
+    /// If the property is missing or `"default"`, or no UV sets have the name, the first UV set
+    /// is returned.
+    /// Returns `None` if the texture is not found or the mesh has no UV sets.
+    pub fn uv_set_for_texture(
+        &self,
+        objects: &Objects,
+        texture_id: i64,
+    ) -> Option<&LayerElement<[f64; 2]>> {
+        let texture = objects.object(texture_id)?;
+        let name = texture.properties_with_template().get_string("UVSet");
+        name.and_then(|name| self.uv_set(name))
+            .or_else(|| self.uv_sets.first())
+    }
+
+    /// Returns UVs of the given UV set for each polygon vertex.
+    pub fn polygon_vertex_uvs(&self, uv_set: &LayerElement<[f64; 2]>) -> Vec<Option<[f64; 2]>> {
+        self.polygon_vertices()
+            .map(|pv| uv_set.get(pv.polygon_vertex, pv.control_point, pv.polygon).cloned())
+            .collect()
+    }
+
+    /// Reverses the vertex order (winding) of each polygon.
+    ///
+    /// `ByPolygonVertex` layer elements are reordered consistently.
+    /// Incomplete polygon at the tail is left as is.
+    pub fn flip_winding(&mut self) {
+        let mut order = Vec::with_capacity(self.polygon_vertex_index.len());
+        let mut start = 0;
+        for pv in self.polygon_vertices() {
+            if self.polygon_vertex_index[pv.polygon_vertex] < 0 {
+                order.extend((start..pv.polygon_vertex + 1).rev());
+                start = pv.polygon_vertex + 1;
+            }
+        }
+        let mut polygon_vertex_index = order
+            .iter()
+            .map(|&i| self.polygon_vertex_index[i])
+            .map(|i| if i < 0 { !i } else { i })
+            .collect::<Vec<_>>();
+        let mut start = 0;
+        for (pos, &old) in order.iter().enumerate() {
+            // The first vertex of the original polygon becomes the last one.
+            if old == start {
+                polygon_vertex_index[pos] = !polygon_vertex_index[pos];
+                start = pos + 1;
+            }
+        }
+        polygon_vertex_index.extend_from_slice(&self.polygon_vertex_index[order.len()..]);
+        self.polygon_vertex_index = polygon_vertex_index;
+
+        for normals in &mut self.normals {
+            normals.reorder_polygon_vertices(&order);
+        }
+        for uv in &mut self.uv_sets {
+            uv.reorder_polygon_vertices(&order);
+        }
+        for colors in &mut self.colors {
+            colors.reorder_polygon_vertices(&order);
+        }
+        for materials in &mut self.materials {
+            materials.reorder_polygon_vertices(&order);
+        }
+    }
+
+    /// Negates all normals.
+    pub fn negate_normals(&mut self) {
+        for normal in self.normals.iter_mut().flat_map(|normals| normals.direct.iter_mut()) {
+            for v in normal.iter_mut() {
+                *v = -*v;
+            }
+        }
+    }
+}
+
+/// `Geometry` object with `Mesh` subclass, borrowing `f32` arrays of the generic object.
+///
+/// This is for objects loaded with `ObjectsLoader::set_narrow_f64_arrays()`, and gives the
+/// narrowed arrays as is, without widening them into `f64` as `Mesh` does.
+/// Layer elements are available through `Mesh` or the generic object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshF32<'a> {
+    /// Properties common to object nodes.
+    pub props: &'a ObjectProperties,
+    /// Coordinates of control points (`[x0, y0, z0, x1, y1, z1, ...]`).
+    pub vertices: &'a [f32],
+    /// Polygon vertex indices.
+    ///
+    /// The last index of each polygon is stored as bitwise negation (`!index`).
+    pub polygon_vertex_index: &'a [i32],
+}
+
+impl<'a> MeshF32<'a> {
+    /// Creates a `MeshF32` from the generic object.
+    ///
+    /// Returns `Error::InvalidAttribute` if `Vertices` is not a `f32` array.
+    pub fn from_object(obj: &'a Object) -> Result<Self> {
+        let vertices = required_child(obj, "Vertices")
+            .and_then(|node| f32_array(node).ok_or_else(|| invalid(node)))?;
+        let polygon_vertex_index = required_child(obj, "PolygonVertexIndex")
+            .and_then(|node| i32_array(node).ok_or_else(|| invalid(node)))?;
+        Ok(MeshF32 {
+            props: &obj.props,
+            vertices: vertices,
+            polygon_vertex_index: polygon_vertex_index,
+        })
+    }
+
+    /// Returns an iterator of control points.
+    ///
+    /// Extra coordinates at the tail are ignored.
+    pub fn control_points(&self) -> impl Iterator<Item = [f32; 3]> + 'a {
+        self.vertices.chunks_exact(3).map(|v| [v[0], v[1], v[2]])
+    }
+}
+
+
+impl LoadObject for Mesh {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Mesh::from_object(&Object::load(props, parser)?)
+    }
+}
+
+
+/// Loads a layer element.
+///
+/// If `index_name` is `None`, the values are treated as direct values regardless of the
+/// reference mode.
+fn load_layer_element<T, F>(
+    node: &GenericNode,
+    values_name: &str,
+    index_name: Option<&str>,
+    load_values: F,
+) -> Result<LayerElement<T>>
+where
+    F: FnOnce(&GenericNode) -> Option<Vec<T>>,
+{
+    let child = |name: &str| node.children.iter().find(|c| c.name == name);
+    let child_str = |name: &str| -> Result<&str> {
+        let c = child(name).ok_or_else(|| Error::missing_node(node.name.as_str(), name))?;
+        string(c).ok_or_else(|| invalid(c))
+    };
+    let index = node.first_attr_as::<i32>().ok_or_else(|| invalid(node))?;
+    let name = child("Name").and_then(string).unwrap_or("").to_owned();
+    let mapping = MappingMode::from_name(child_str("MappingInformationType")?)
+        .ok_or_else(|| Error::InvalidAttribute("MappingInformationType".to_owned()))?;
+    // Some exporters omit `ReferenceInformationType` (for example, for `AllSame` materials),
+    // and FBX SDK treats it as `Direct`.
+    let reference = match child("ReferenceInformationType") {
+        Some(_) => ReferenceMode::from_name(child_str("ReferenceInformationType")?)
+            .ok_or_else(|| Error::InvalidAttribute("ReferenceInformationType".to_owned()))?,
+        None => ReferenceMode::Direct,
+    };
+    let values = child(values_name)
+        .ok_or_else(|| Error::missing_node(node.name.as_str(), values_name))?;
+    let direct = load_values(values).ok_or_else(|| invalid(values))?;
+    let (reference, indices) = match (reference, index_name) {
+        (ReferenceMode::IndexToDirect, Some(index_name)) => {
+            let indices = child(index_name)
+                .ok_or_else(|| Error::missing_node(node.name.as_str(), index_name))?;
+            let indices = i32_array(indices).ok_or_else(|| invalid(indices))?.to_vec();
+            (ReferenceMode::IndexToDirect, indices)
+        },
+        _ => (ReferenceMode::Direct, Vec::new()),
+    };
+
+    Ok(LayerElement {
+        index: index,
+        name: name,
+        mapping: mapping,
+        reference: reference,
+        direct: direct,
+        indices: indices,
+    })
+}
+
+
+/// Creates a layer element node.
+///
+/// If `index_name` is `None`, the reference mode is written as `IndexToDirect` without the index
+/// node (see `load_layer_element()`).
+fn layer_element_node<T>(
+    element: &LayerElement<T>,
+    node_name: &str,
+    values: (&str, OwnedAttribute),
+    index_name: Option<&str>,
+) -> GenericNode {
+    let reference = match index_name {
+        Some(_) => element.reference,
+        None => ReferenceMode::IndexToDirect,
+    };
+    let mut node = GenericNode::named(node_name)
+        .with_attr(element.index)
+        .with_child(GenericNode::named("Version").with_attr(101))
+        .with_child(GenericNode::named("Name").with_attr(element.name.as_str()))
+        .with_child(GenericNode::named("MappingInformationType").with_attr(element.mapping.name()))
+        .with_child(GenericNode::named("ReferenceInformationType").with_attr(reference.name()))
+        .with_child(GenericNode::named(values.0).with_attr(values.1));
+    if let (ReferenceMode::IndexToDirect, Some(index_name)) = (element.reference, index_name) {
+        node.push_child(GenericNode::named(index_name).with_attr(&element.indices[..]));
+    }
+    node
+}
+
+
+/// Flattens the fixed size arrays into a `f64` array.
+fn flatten<A: AsRef<[f64]>>(values: &[A]) -> Vec<f64> {
+    values.iter().flat_map(|v| v.as_ref().iter().cloned()).collect()
+}
+
+
+/// Returns the child node of the object, or `Error::MissingNode`.
+fn required_child<'a>(obj: &'a Object, name: &str) -> Result<&'a GenericNode> {
+    obj.node(name).ok_or_else(|| Error::missing_node(obj.class(), name))
+}
+
+
+/// Returns the `f64` array of the child node of the object.
+fn required_f64_array<'a>(obj: &'a Object, name: &str) -> Result<Cow<'a, [f64]>> {
+    let node = required_child(obj, name)?;
+    f64_array(node).ok_or_else(|| invalid(node))
+}
+
+
+/// Returns `Error::InvalidAttribute` for the node.
+fn invalid(node: &GenericNode) -> Error {
+    Error::InvalidAttribute(node.name.clone())
+}
+
+
+/// Returns the first attribute as `f64` array.
+///
+/// `f32` arrays (loaded with `ObjectsLoader::set_narrow_f64_arrays()`) are also accepted.
+fn f64_array(node: &GenericNode) -> Option<Cow<'_, [f64]>> {
+    let attr = node.attributes.first()?;
+    match attr.as_f64_slice() {
+        Some(v) => Some(Cow::Borrowed(v)),
+        None => attr.as_f32_slice().map(|v| Cow::Owned(v.iter().map(|&v| v as f64).collect())),
+    }
+}
+
+
+/// Returns the first attribute as `f32` array.
+fn f32_array(node: &GenericNode) -> Option<&[f32]> {
+    node.attributes.first()?.as_f32_slice()
+}
+
+
+/// Returns the first attribute as `i32` array.
+fn i32_array(node: &GenericNode) -> Option<&[i32]> {
+    node.first_attr_as()
+}
+
+
+/// Returns the first attribute as string.
+fn string(node: &GenericNode) -> Option<&str> {
+    node.first_attr_as()
+}
+
+
+/// Returns the first attribute as `f64` array, split into fixed size chunks.
+fn f64s<A: FixedArray>(node: &GenericNode) -> Option<Vec<A>> {
+    f64_array(node).map(|values| chunks(&values))
+}
+
+
+/// Splits the values into fixed size arrays.
+///
+/// Extra values at the tail are ignored.
+fn chunks<A: FixedArray>(values: &[f64]) -> Vec<A> {
+    values.chunks_exact(A::LEN).map(A::from_slice).collect()
+}
+
+
+/// Fixed size array of `f64`.
+trait FixedArray: Sized {
+    /// Length of the array.
+    const LEN: usize;
+    /// Creates an array from the slice with length `LEN`.
+    fn from_slice(s: &[f64]) -> Self;
+}
+
+macro_rules! impl_fixed_array {
+    ($($len:expr),*) => {
+        $(
+            impl FixedArray for [f64; $len] {
+                const LEN: usize = $len;
+                fn from_slice(s: &[f64]) -> Self {
+                    let mut arr = [0.0; $len];
+                    arr.copy_from_slice(s);
+                    arr
+                }
+            }
+        )*
+    }
+}
+
+impl_fixed_array!(2, 3, 4);
+
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Cursor;
+    use std::sync::Arc;
+    use parser::binary::{RootParser, Parser, Event, FromArrayStream};
+    use loader::binary::simple::FbxLoader;
+    use loader::binary::simple::fbx7400::{ObjectsLoader, ObjectProperties, Properties70};
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::{Mesh, MeshF32, ReferenceMode};
+
+    /// Writes `LayerElementUV` node.
+    fn write_uv(b: &mut FbxBuilder, index: i32, name: &str, uv: &[f64], uv_index: &[i32]) {
+        b.begin_node("LayerElementUV", &[Attr::I32(index)]);
+        b.node("Version", &[Attr::I32(101)]);
+        b.node("Name", &[Attr::String(name)]);
+        b.node("MappingInformationType", &[Attr::String("ByPolygonVertex")]);
+        b.node("ReferenceInformationType", &[Attr::String("IndexToDirect")]);
+        b.node("UV", &[Attr::ArrF64(uv)]);
+        b.node("UVIndex", &[Attr::ArrI32(uv_index)]);
+        b.end_node();
+    }
+
+    #[test]
+    fn test_uv_sets() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "Geometry",
+            &[Attr::I64(1), Attr::String("Tri\u{0}\u{1}Geometry"), Attr::String("Mesh")],
+        );
+        b.node("Vertices", &[Attr::ArrF64(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])]);
+        b.node("PolygonVertexIndex", &[Attr::ArrI32(&[0, 1, !2])]);
+        write_uv(&mut b, 0, "map1", &[0.0, 0.0, 1.0, 0.0, 0.0, 1.0], &[0, 1, 2]);
+        write_uv(&mut b, 1, "UVChannel_2", &[0.5, 0.5], &[0, 0, 0]);
+        b.begin_node("LayerElementMaterial", &[Attr::I32(0)]);
+        b.node("MappingInformationType", &[Attr::String("AllSame")]);
+        b.node("Materials", &[Attr::ArrI32(&[0])]);
+        b.end_node();
+        b.end_node();
+        for &(id, name_class, uv_set) in &[
+            (2, "Lightmap\u{0}\u{1}Texture", "UVChannel_2"),
+            (3, "Diffuse\u{0}\u{1}Texture", "default"),
+        ]
+        {
+            b.begin_node("Texture", &[Attr::I64(id), Attr::String(name_class), Attr::String("")]);
+            write_properties70(&mut b, &[("UVSet", "KString", "", "", &[Attr::String(uv_set)])]);
+            b.end_node();
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let mut objects = load_objects(data).objects;
+        let mesh = Mesh::from_object(objects.object(1).expect("Mesh should exist"))
+            .expect("Invalid mesh");
+
+        assert_eq!(mesh.vertices.len(), 3);
+        assert_eq!(mesh.materials[0].reference, ReferenceMode::Direct);
+        assert_eq!(mesh.materials[0].direct, vec![0]);
+        assert_eq!(
+            mesh.uv_sets().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["map1", "UVChannel_2"]
+        );
+        let lightmap = mesh.uv_set_for_texture(&objects, 2).expect("UV set should be found");
+        assert_eq!(lightmap.name, "UVChannel_2");
+        assert_eq!(mesh.polygon_vertex_uvs(lightmap), vec![Some([0.5, 0.5]); 3]);
+        let diffuse = mesh.uv_set_for_texture(&objects, 3).expect("UV set should be found");
+        assert_eq!(diffuse.name, "map1");
+        assert_eq!(mesh.polygon_vertex_uvs(diffuse)[2], Some([0.0, 1.0]));
+        assert!(mesh.uv_set_for_texture(&objects, 4).is_none());
+
+        let mut templated = objects.object(3).expect("Texture should exist").clone();
+        templated.props.id = 5;
+        templated.properties = None;
+        let mut template = Properties70::new();
+        template.insert("UVSet", "UVChannel_2");
+        templated.template = Some(Arc::new(template));
+        objects.insert(templated);
+        let lightmap = mesh.uv_set_for_texture(&objects, 5).expect("UV set should be found");
+        assert_eq!(lightmap.name, "UVChannel_2");
+
+        let lowered = mesh.to_object();
+        assert!(lowered.node("LayerElementUV").and_then(|n| n.child("UVIndex")).is_some());
+        assert_eq!(Mesh::from_object(&lowered).expect("Invalid mesh"), mesh);
+    }
+
+    #[test]
+    fn test_narrow_f64_arrays() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "Geometry",
+            &[Attr::I64(1), Attr::String("Tri\u{0}\u{1}Geometry"), Attr::String("Mesh")],
+        );
+        let vertices = (0..600).map(|i| i as f64 * 0.5).collect::<Vec<_>>();
+        b.node("Vertices", &[Attr::ArrF64(&vertices)]);
+        b.node("PolygonVertexIndex", &[Attr::ArrI32(&[0, 1, !2])]);
+        b.end_node();
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let parser = RootParser::new(Cursor::new(data));
+        let mut objs_loader = ObjectsLoader::new();
+        objs_loader.set_narrow_f64_arrays(true);
+        let objects = match FbxLoader::load_from_parser(parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => loader.load(objs_loader).expect("Load error"),
+        }.objects;
+        let obj = objects.object(1).expect("Mesh should exist");
+        let narrowed = obj.node("Vertices")
+            .and_then(|node| node.attributes[0].as_f32_slice())
+            .expect("Vertices should be loaded as `f32` array");
+        assert_eq!(narrowed.len(), 600);
+        assert_eq!(narrowed[599], 299.5);
+
+        let mesh = Mesh::from_object(obj).expect("Invalid mesh");
+        assert_eq!(mesh.vertices.len(), 200);
+        assert_eq!(mesh.vertices[199], [298.5, 299.0, 299.5]);
+
+        let mesh = MeshF32::from_object(obj).expect("Invalid mesh");
+        assert!(::std::ptr::eq(mesh.vertices, narrowed));
+        assert_eq!(mesh.polygon_vertex_index, &[0, 1, !2]);
+        assert_eq!(mesh.control_points().nth(199), Some([298.5, 299.0, 299.5]));
+        let lowered = Mesh::from_object(obj).expect("Invalid mesh").to_object();
+        assert!(MeshF32::from_object(&lowered).is_err());
+    }
+
+    /// Sink recording the sum and the number of chunks.
+    #[derive(Default)]
+    struct Summary {
+        expected: u64,
+        len: usize,
+        sum: f64,
+        chunks: usize,
+    }
+
+    impl FromArrayStream<f64> for Summary {
+        fn begin(&mut self, num_elements: u64) {
+            self.expected = num_elements;
+        }
+
+        fn extend_chunk(&mut self, chunk: &[f64]) -> io::Result<()> {
+            self.len += chunk.len();
+            self.sum += chunk.iter().sum::<f64>();
+            self.chunks += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_load_streamed() {
+        let mut b = FbxBuilder::new(7400);
+        // Cover compressed arrays if a zlib backend is available.
+        b.set_compress_arrays(cfg!(any(feature = "flate2", feature = "libflate")));
+        b.begin_node(
+            "Geometry",
+            &[Attr::I64(1), Attr::String("Big\u{0}\u{1}Geometry"), Attr::String("Mesh")],
+        );
+        let vertices = (0..3000).map(|i| i as f64).collect::<Vec<_>>();
+        b.node("Vertices", &[Attr::ArrF64(&vertices)]);
+        b.node("PolygonVertexIndex", &[Attr::ArrI32(&[0, 1, !2])]);
+        b.begin_node("LayerElementMaterial", &[Attr::I32(0)]);
+        b.node("MappingInformationType", &[Attr::String("AllSame")]);
+        b.node("ReferenceInformationType", &[Attr::String("IndexToDirect")]);
+        b.node("Materials", &[Attr::ArrI32(&[0])]);
+        b.end_node();
+        b.end_node();
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartFbx(_)));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartNode(_)));
+        let props = ObjectProperties {
+            id: 1,
+            name: "Big".to_owned(),
+            class: "Geometry".to_owned(),
+            subclass: "Mesh".to_owned(),
+        };
+        let mut summary = Summary::default();
+        let mut indices = Vec::new();
+        let mesh = Mesh::load_streamed(props, parser.subtree_guard(), &mut summary, &mut indices)
+            .expect("Load error");
+
+        assert!(mesh.vertices.is_empty());
+        assert!(mesh.polygon_vertex_index.is_empty());
+        assert_eq!(mesh.materials.len(), 1);
+        assert_eq!(summary.expected, 3000);
+        assert_eq!(summary.len, 3000);
+        assert_eq!(summary.sum, (0..3000).sum::<i32>() as f64);
+        assert!(summary.chunks > 1);
+        assert_eq!(indices, vec![0, 1, !2]);
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::EndFbx(_)));
+    }
+}
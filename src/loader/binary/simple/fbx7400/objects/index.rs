@@ -0,0 +1,83 @@
+//! Index of objects by name and class.
+
+use fnv::FnvHashMap;
+use loader::binary::simple::fbx7400::objects::ObjectProperties;
+
+
+/// Index of objects by name and by (class, subclass).
+///
+/// IDs are stored in insertion order.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ObjectIndex {
+    /// Object IDs by name.
+    by_name: FnvHashMap<String, Vec<i64>>,
+    /// Object IDs by class and subclass.
+    by_class: FnvHashMap<String, FnvHashMap<String, Vec<i64>>>,
+}
+
+impl ObjectIndex {
+    /// Creates a new empty `ObjectIndex`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds the object to the index.
+    pub fn insert(&mut self, props: &ObjectProperties) {
+        self.by_name
+            .entry(props.name.clone())
+            .or_default()
+            .push(props.id);
+        self.by_class
+            .entry(props.class.clone())
+            .or_default()
+            .entry(props.subclass.clone())
+            .or_default()
+            .push(props.id);
+    }
+
+    /// Removes the object from the index.
+    pub fn remove(&mut self, props: &ObjectProperties) {
+        let id = props.id;
+        if let Some(ids) = self.by_name.get_mut(&props.name) {
+            ids.retain(|&v| v != id);
+        }
+        if let Some(ids) = self.by_class
+            .get_mut(&props.class)
+            .and_then(|subclasses| subclasses.get_mut(&props.subclass))
+        {
+            ids.retain(|&v| v != id);
+        }
+    }
+
+    /// Returns IDs of objects with the given name.
+    pub fn ids_by_name(&self, name: &str) -> &[i64] {
+        self.by_name.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns IDs of objects with the given class and subclass.
+    pub fn ids_by_class(&self, class: &str, subclass: &str) -> &[i64] {
+        self.by_class
+            .get(class)
+            .and_then(|subclasses| subclasses.get(subclass))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns IDs of objects with the given class and any subclass.
+    ///
+    /// IDs are grouped by subclass in arbitrary order.
+    pub fn ids_by_class_any_subclass<'a>(&'a self, class: &str) -> impl Iterator<Item = i64> + 'a {
+        self.by_class
+            .get(class)
+            .into_iter()
+            .flat_map(|subclasses| subclasses.values())
+            .flat_map(|ids| ids.iter().cloned())
+    }
+
+    /// Returns an iterator of all names and IDs of objects with the names.
+    pub fn names(&self) -> impl Iterator<Item = (&str, &[i64])> {
+        self.by_name
+            .iter()
+            .filter(|&(_, ids)| !ids.is_empty())
+            .map(|(name, ids)| (name.as_str(), ids.as_slice()))
+    }
+}
@@ -0,0 +1,260 @@
+//! `LayeredTexture` object.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::{Result, GenericNode};
+use loader::binary::simple::fbx7400::Connections;
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+
+
+/// Blend mode of a texture layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Translucent.
+    Translucent,
+    /// Additive.
+    Additive,
+    /// Modulate.
+    Modulate,
+    /// Modulate2.
+    Modulate2,
+    /// Over.
+    Over,
+    /// Normal.
+    Normal,
+    /// Dissolve.
+    Dissolve,
+    /// Darken.
+    Darken,
+    /// Color burn.
+    ColorBurn,
+    /// Linear burn.
+    LinearBurn,
+    /// Darker color.
+    DarkerColor,
+    /// Lighten.
+    Lighten,
+    /// Screen.
+    Screen,
+    /// Color dodge.
+    ColorDodge,
+    /// Linear dodge.
+    LinearDodge,
+    /// Lighter color.
+    LighterColor,
+    /// Soft light.
+    SoftLight,
+    /// Hard light.
+    HardLight,
+    /// Vivid light.
+    VividLight,
+    /// Linear light.
+    LinearLight,
+    /// Pin light.
+    PinLight,
+    /// Hard mix.
+    HardMix,
+    /// Difference.
+    Difference,
+    /// Exclusion.
+    Exclusion,
+    /// Subtract.
+    Subtract,
+    /// Divide.
+    Divide,
+    /// Hue.
+    Hue,
+    /// Saturation.
+    Saturation,
+    /// Color.
+    Color,
+    /// Luminosity.
+    Luminosity,
+    /// Overlay.
+    Overlay,
+    /// Unknown blend mode.
+    Unknown(i32),
+}
+
+impl BlendMode {
+    /// Creates a `BlendMode` from the value of `BlendModes` array.
+    pub fn from_i32(v: i32) -> Self {
+        const MODES: [BlendMode; 31] = [
+            BlendMode::Translucent,
+            BlendMode::Additive,
+            BlendMode::Modulate,
+            BlendMode::Modulate2,
+            BlendMode::Over,
+            BlendMode::Normal,
+            BlendMode::Dissolve,
+            BlendMode::Darken,
+            BlendMode::ColorBurn,
+            BlendMode::LinearBurn,
+            BlendMode::DarkerColor,
+            BlendMode::Lighten,
+            BlendMode::Screen,
+            BlendMode::ColorDodge,
+            BlendMode::LinearDodge,
+            BlendMode::LighterColor,
+            BlendMode::SoftLight,
+            BlendMode::HardLight,
+            BlendMode::VividLight,
+            BlendMode::LinearLight,
+            BlendMode::PinLight,
+            BlendMode::HardMix,
+            BlendMode::Difference,
+            BlendMode::Exclusion,
+            BlendMode::Subtract,
+            BlendMode::Divide,
+            BlendMode::Hue,
+            BlendMode::Saturation,
+            BlendMode::Color,
+            BlendMode::Luminosity,
+            BlendMode::Overlay,
+        ];
+        if v < 0 {
+            return BlendMode::Unknown(v);
+        }
+        MODES.get(v as usize).cloned().unwrap_or(BlendMode::Unknown(v))
+    }
+}
+
+
+/// Layer of a layered texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureLayer {
+    /// Object ID of the texture.
+    pub texture: i64,
+    /// Blend mode.
+    pub blend_mode: BlendMode,
+    /// Alpha.
+    pub alpha: f64,
+}
+
+
+/// `LayeredTexture` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayeredTexture {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// Blend modes of layers (`BlendModes`).
+    pub blend_modes: Vec<BlendMode>,
+    /// Alphas of layers (`Alphas`).
+    pub alphas: Vec<f64>,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl LayeredTexture {
+    /// Creates a `LayeredTexture` from the generic object.
+    ///
+    /// Missing or invalid child nodes are treated as empty arrays.
+    pub fn from_object(obj: &Object) -> Self {
+        let blend_modes = obj.node("BlendModes").map_or_else(Vec::new, |node| {
+            i32_values(node).into_iter().map(BlendMode::from_i32).collect()
+        });
+        let alphas = obj.node("Alphas").map_or_else(Vec::new, f64_values);
+        LayeredTexture {
+            props: obj.props.clone(),
+            blend_modes: blend_modes,
+            alphas: alphas,
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Returns layers of the texture, from the bottom to the top.
+    ///
+    /// Child textures are resolved by the connections, in connection order.
+    /// If `BlendModes` or `Alphas` is shorter than the number of child textures,
+    /// `BlendMode::Normal` and `1.0` are used for the rest layers.
+    pub fn layers(&self, connections: &Connections) -> Vec<TextureLayer> {
+        connections
+            .object_sources(self.props.id)
+            .enumerate()
+            .map(|(i, texture)| TextureLayer {
+                texture: texture,
+                blend_mode: self.blend_modes.get(i).cloned().unwrap_or(BlendMode::Normal),
+                alpha: self.alphas.get(i).cloned().unwrap_or(1.0),
+            })
+            .collect()
+    }
+}
+
+impl LoadObject for LayeredTexture {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(LayeredTexture::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+/// Returns the attributes as `i32` values, accepting both an array and scalars.
+fn i32_values(node: &GenericNode) -> Vec<i32> {
+    let mut values = Vec::new();
+    for attr in &node.attributes {
+        match attr.as_i32_slice() {
+            Some(v) => values.extend_from_slice(v),
+            None => values.extend(attr.as_i32()),
+        }
+    }
+    values
+}
+
+
+/// Returns the attributes as `f64` values, accepting both an array and scalars.
+fn f64_values(node: &GenericNode) -> Vec<f64> {
+    let mut values = Vec::new();
+    for attr in &node.attributes {
+        match attr.get_loose::<Vec<f64>>() {
+            Some(v) => values.extend(v),
+            None => values.extend(attr.as_f64()),
+        }
+    }
+    values
+}
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects};
+    use super::{LayeredTexture, BlendMode};
+
+    #[test]
+    fn test_layers() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "LayeredTexture",
+            &[Attr::I64(1), Attr::String("Layered\u{0}\u{1}LayeredTexture"), Attr::String("")],
+        );
+        b.node("LayeredTexture", &[Attr::I32(101)]);
+        b.node("BlendModes", &[Attr::ArrI32(&[0, 1])]);
+        b.node("Alphas", &[Attr::ArrF64(&[1.0, 0.5])]);
+        b.end_node();
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        for &(source, destination) in &[(10, 1), (11, 1), (12, 1), (1, 2)] {
+            b.node("C", &[Attr::String("OO"), Attr::I64(source), Attr::I64(destination)]);
+        }
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let layered = LayeredTexture::from_object(fbx.objects.object(1).expect("Should exist"));
+        assert_eq!(layered.blend_modes, vec![BlendMode::Translucent, BlendMode::Additive]);
+
+        let layers = layered.layers(&fbx.connections);
+        assert_eq!(
+            layers.iter().map(|l| (l.texture, l.blend_mode, l.alpha)).collect::<Vec<_>>(),
+            vec![
+                (10, BlendMode::Translucent, 1.0),
+                (11, BlendMode::Additive, 0.5),
+                (12, BlendMode::Normal, 1.0),
+            ]
+        );
+    }
+}
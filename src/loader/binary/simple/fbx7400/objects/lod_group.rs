@@ -0,0 +1,177 @@
+//! `NodeAttribute` object with `LodGroup` subclass.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::Result;
+use loader::binary::simple::fbx7400::Connections;
+use loader::binary::simple::fbx7400::EffectiveProperties;
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object, Objects};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+
+
+/// Display level of a LOD level (`DisplayLevels|LevelN` property).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LodDisplayLevel {
+    /// Displayed according to the thresholds.
+    UseLod,
+    /// Always shown.
+    Show,
+    /// Always hidden.
+    Hide,
+    /// Unknown value.
+    Unknown(i64),
+}
+
+impl LodDisplayLevel {
+    /// Creates a `LodDisplayLevel` from the property value.
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            0 => LodDisplayLevel::UseLod,
+            1 => LodDisplayLevel::Show,
+            2 => LodDisplayLevel::Hide,
+            v => LodDisplayLevel::Unknown(v),
+        }
+    }
+}
+
+
+/// `NodeAttribute` object with `LodGroup` subclass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LodGroup {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl LodGroup {
+    /// Creates a `LodGroup` from the generic object.
+    pub fn from_object(obj: &Object) -> Self {
+        LodGroup {
+            props: obj.props.clone(),
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Returns whether the thresholds are percentages of the screen size (instead of
+    /// distances).
+    pub fn thresholds_used_as_percentage(&self) -> bool {
+        self.properties.effective()
+            .get_bool("ThresholdsUsedAsPercentage")
+            .unwrap_or(false)
+    }
+
+    /// Returns the thresholds (`Thresholds|LevelN` properties).
+    ///
+    /// The `N`-th threshold is the boundary between level `N` and level `N + 1`, so there is
+    /// usually one less threshold than levels.
+    pub fn thresholds(&self) -> Vec<f64> {
+        self.levels("Thresholds", |props, name| props.get_f64(name))
+    }
+
+    /// Returns the display levels (`DisplayLevels|LevelN` properties).
+    pub fn display_levels(&self) -> Vec<LodDisplayLevel> {
+        self.levels("DisplayLevels", |props, name| props.get_i64(name))
+            .into_iter()
+            .map(LodDisplayLevel::from_i64)
+            .collect()
+    }
+
+    /// Returns values of `<prefix>|Level0`, `<prefix>|Level1`, ... until missing.
+    fn levels<T, F>(&self, prefix: &str, get: F) -> Vec<T>
+    where
+        F: Fn(&EffectiveProperties, &str) -> Option<T>,
+    {
+        let props = self.properties.effective();
+        (0..)
+            .map_while(|i| get(&props, &format!("{}|Level{}", prefix, i)))
+            .collect()
+    }
+
+    /// Returns the ID of the model which has the LOD group.
+    pub fn model(&self, objects: &Objects, connections: &Connections) -> Option<i64> {
+        connections
+            .object_destinations(self.props.id)
+            .find(|&id| objects.object(id).is_some_and(|obj| obj.class() == "Model"))
+    }
+
+    /// Returns IDs of the LOD level models, from the most detailed level.
+    ///
+    /// The levels are the child models of the LOD group model, in connection order.
+    pub fn level_models(&self, objects: &Objects, connections: &Connections) -> Vec<i64> {
+        let model = match self.model(objects, connections) {
+            Some(model) => model,
+            None => return Vec::new(),
+        };
+        connections
+            .object_sources(model)
+            .filter(|&id| objects.object(id).is_some_and(|obj| obj.class() == "Model"))
+            .collect()
+    }
+}
+
+impl LoadObject for LodGroup {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(LodGroup::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::{LodDisplayLevel, LodGroup};
+
+    #[test]
+    fn test_lod_group() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "NodeAttribute",
+            &[Attr::I64(10), Attr::String("\u{0}\u{1}NodeAttribute"), Attr::String("LodGroup")],
+        );
+        write_properties70(
+            &mut b,
+            &[
+                ("ThresholdsUsedAsPercentage", "bool", "", "", &[Attr::I32(0)]),
+                ("Thresholds|Level0", "Distance", "", "", &[Attr::F64(10.0)]),
+                ("Thresholds|Level1", "Distance", "", "", &[Attr::F64(50.0)]),
+                ("DisplayLevels|Level0", "enum", "", "", &[Attr::I32(0)]),
+                ("DisplayLevels|Level1", "enum", "", "", &[Attr::I32(2)]),
+            ],
+        );
+        b.end_node();
+        for &(id, name_class, subclass) in &[
+            (1, "Tree\u{0}\u{1}Model", "LodGroup"),
+            (2, "Tree_LOD0\u{0}\u{1}Model", "Mesh"),
+            (3, "Tree_LOD1\u{0}\u{1}Model", "Mesh"),
+            (4, "Tree_LOD2\u{0}\u{1}Model", "Mesh"),
+        ]
+        {
+            b.node(
+                "Model",
+                &[Attr::I64(id), Attr::String(name_class), Attr::String(subclass)],
+            );
+        }
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        for &(source, destination) in &[(1, 0), (10, 1), (2, 1), (3, 1), (4, 1)] {
+            b.node("C", &[Attr::String("OO"), Attr::I64(source), Attr::I64(destination)]);
+        }
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let lod = LodGroup::from_object(fbx.objects.object(10).expect("Should exist"));
+
+        assert!(!lod.thresholds_used_as_percentage());
+        assert_eq!(lod.thresholds(), vec![10.0, 50.0]);
+        assert_eq!(lod.display_levels(), vec![LodDisplayLevel::UseLod, LodDisplayLevel::Hide]);
+        assert_eq!(lod.model(&fbx.objects, &fbx.connections), Some(1));
+        assert_eq!(lod.level_models(&fbx.objects, &fbx.connections), vec![2, 3, 4]);
+    }
+}
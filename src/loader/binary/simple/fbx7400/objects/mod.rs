@@ -0,0 +1,431 @@
+//! Objects.
+
+use std::cell::OnceCell;
+use parser::binary::{Parser, ParserSource, Attributes, SubtreeParser};
+use parser::binary::Error as ParseError;
+use loader::binary::simple::{Result, Error, OwnedAttribute};
+use loader::binary::simple::fbx7400::{NodesBeforeObjects, FbxHeaderExtension, FileId};
+use loader::binary::simple::fbx7400::{CreationTime, Creator, References, GlobalSettings};
+use loader::binary::simple::fbx7400::{Documents, Definitions};
+use loader::binary::simple::fbx7400::{NameClass, join_name_class, separate_name_class};
+use loader::binary::simple::fbx7400::legacy_object_id;
+pub use self::animation::{AnimationStack, AnimationCurve};
+pub use self::cache::{Cache, CacheFileFormat, VertexCacheDeformer};
+pub use self::generic::{Object, ObjectLoadOptions, Objects, ObjectsLoader, ObjectMap};
+pub use self::generic::{ArrayStreams, TemplatedProperties};
+pub use self::geometry::{Mesh, MeshF32, LayerElement, MappingMode, ReferenceMode, PolygonVertex};
+pub use self::index::ObjectIndex;
+pub use self::layered_texture::{LayeredTexture, TextureLayer, BlendMode};
+pub use self::lod_group::{LodGroup, LodDisplayLevel};
+pub use self::model::ModelKind;
+pub use self::node_attribute::{NodeAttribute, NodeAttributeKind};
+pub use self::object_ref::ObjectRef;
+pub use self::registry::{ObjectLoader, ObjectLoaderRegistry, CustomObjects, CustomObject};
+pub use self::texture::{Texture, ProceduralTexture, UvTransform};
+pub use self::thumbnail::Thumbnail;
+pub use self::video::Video;
+
+pub mod animation;
+pub mod cache;
+pub mod generic;
+pub mod geometry;
+pub mod index;
+pub mod layered_texture;
+pub mod lod_group;
+pub mod model;
+pub mod node_attribute;
+pub mod object_ref;
+pub mod registry;
+pub mod texture;
+pub mod thumbnail;
+pub mod video;
+
+
+/// A trait for types which can be loaded from a single object node.
+pub trait LoadObject: Sized {
+    /// Loads the object from the subtree parser of the object node.
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>;
+}
+
+
+/// A trait for objects nodes loader of FBX 7.4 compatible data.
+///
+/// This requires all toplevel nodes in `NodesBeforeObjects` to appear before `Objects`.
+/// To load files without such restriction, use `LoadObjectsWithContext7400`.
+pub trait LoadObjects7400: Sized {
+    /// Reader type.
+    type Reader: ParserSource;
+
+    /// Result objects.
+    type Objects;
+
+    /// Builds objects from the loader.
+    fn build(self) -> Result<Self::Objects>;
+
+    /// Loads an object.
+    fn load(
+        &mut self,
+        props: ObjectPropertiesRef<'_>,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        nodes_before_objects: &NodesBeforeObjects,
+    ) -> Result<()>;
+}
+
+
+/// A trait for objects nodes loader of FBX 7.4 compatible data, with lazy access to other
+/// toplevel nodes.
+///
+/// Toplevel nodes may appear after `Objects` node (for example, some exporters write
+/// `Definitions` after `Objects`).
+/// Such nodes are unavailable from the context in `load()`, but available in `build()`, which
+/// is called after all toplevel nodes are loaded.
+///
+/// This is implemented for all `LoadObjects7400` types.
+pub trait LoadObjectsWithContext7400: Sized {
+    /// Reader type.
+    type Reader: ParserSource;
+
+    /// Result objects.
+    type Objects;
+
+    /// Builds objects from the loader.
+    fn build(self, context: &ObjectsContext) -> Result<Self::Objects>;
+
+    /// Loads an object.
+    fn load(
+        &mut self,
+        props: ObjectPropertiesRef<'_>,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        context: &ObjectsContext,
+    ) -> Result<()>;
+}
+
+impl<T: LoadObjects7400> LoadObjectsWithContext7400 for T {
+    type Reader = T::Reader;
+    type Objects = T::Objects;
+
+    fn build(self, _context: &ObjectsContext) -> Result<Self::Objects> {
+        LoadObjects7400::build(self)
+    }
+
+    fn load(
+        &mut self,
+        props: ObjectPropertiesRef<'_>,
+        subtree_parser: &mut SubtreeParser<Self::Reader>,
+        context: &ObjectsContext,
+    ) -> Result<()> {
+        let nodes_before_objects = context.nodes_before_objects().ok_or_else(|| {
+            Error::missing_node("(root)", context.missing_node())
+        })?;
+        LoadObjects7400::load(self, props, subtree_parser, nodes_before_objects)
+    }
+}
+
+
+/// Toplevel nodes available to objects loader.
+///
+/// Nodes are `None` if they are not loaded yet.
+#[derive(Debug, Clone)]
+pub struct ObjectsContext<'a> {
+    /// FBX version.
+    version: u32,
+    /// `FBXHeaderExtension`.
+    fbx_header_extension: Option<&'a FbxHeaderExtension>,
+    /// `FileId`.
+    file_id: Option<&'a FileId>,
+    /// `CreationTime`.
+    creation_time: Option<&'a CreationTime>,
+    /// `Creator`.
+    creator: Option<&'a Creator>,
+    /// `References`.
+    references: Option<&'a References>,
+    /// `GlobalSettings`.
+    global_settings: Option<&'a GlobalSettings>,
+    /// `Documents`.
+    documents: Option<&'a Documents>,
+    /// `Definitions`.
+    definitions: Option<&'a Definitions>,
+    /// `NodesBeforeObjects` borrowed from the caller.
+    nodes_before_objects: Option<&'a NodesBeforeObjects>,
+    /// Lazily cloned `NodesBeforeObjects`, if not borrowed.
+    cloned_nodes_before_objects: OnceCell<Option<NodesBeforeObjects>>,
+}
+
+impl<'a> ObjectsContext<'a> {
+    /// Creates a new `ObjectsContext`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        version: u32,
+        fbx_header_extension: Option<&'a FbxHeaderExtension>,
+        file_id: Option<&'a FileId>,
+        creation_time: Option<&'a CreationTime>,
+        creator: Option<&'a Creator>,
+        references: Option<&'a References>,
+        global_settings: Option<&'a GlobalSettings>,
+        documents: Option<&'a Documents>,
+        definitions: Option<&'a Definitions>,
+    ) -> Self {
+        ObjectsContext {
+            version: version,
+            fbx_header_extension: fbx_header_extension,
+            file_id: file_id,
+            creation_time: creation_time,
+            creator: creator,
+            references: references,
+            global_settings: global_settings,
+            documents: documents,
+            definitions: definitions,
+            nodes_before_objects: None,
+            cloned_nodes_before_objects: OnceCell::new(),
+        }
+    }
+
+    /// Creates a new `ObjectsContext` with all nodes in `NodesBeforeObjects` available.
+    ///
+    /// `nodes_before_objects()` returns the given nodes without cloning.
+    pub fn from_nodes_before_objects(nodes: &'a NodesBeforeObjects) -> Self {
+        ObjectsContext {
+            nodes_before_objects: Some(nodes),
+            ..ObjectsContext::new(
+                nodes.version,
+                Some(&nodes.fbx_header_extension),
+                Some(&nodes.file_id),
+                Some(&nodes.creation_time),
+                Some(&nodes.creator),
+                Some(&nodes.references),
+                Some(&nodes.global_settings),
+                Some(&nodes.documents),
+                Some(&nodes.definitions),
+            )
+        }
+    }
+
+    /// Returns FBX version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns `FBXHeaderExtension` if available.
+    pub fn fbx_header_extension(&self) -> Option<&'a FbxHeaderExtension> {
+        self.fbx_header_extension
+    }
+
+    /// Returns `FileId` if available.
+    pub fn file_id(&self) -> Option<&'a FileId> {
+        self.file_id
+    }
+
+    /// Returns `CreationTime` if available.
+    pub fn creation_time(&self) -> Option<&'a CreationTime> {
+        self.creation_time
+    }
+
+    /// Returns `Creator` if available.
+    pub fn creator(&self) -> Option<&'a Creator> {
+        self.creator
+    }
+
+    /// Returns `References` if available.
+    pub fn references(&self) -> Option<&'a References> {
+        self.references
+    }
+
+    /// Returns `GlobalSettings` if available.
+    pub fn global_settings(&self) -> Option<&'a GlobalSettings> {
+        self.global_settings
+    }
+
+    /// Returns `Documents` if available.
+    pub fn documents(&self) -> Option<&'a Documents> {
+        self.documents
+    }
+
+    /// Returns `Definitions` if available.
+    pub fn definitions(&self) -> Option<&'a Definitions> {
+        self.definitions
+    }
+
+    /// Returns `NodesBeforeObjects` if all of its nodes are available.
+    ///
+    /// Unless the context is created by `from_nodes_before_objects()`, this clones the nodes
+    /// at the first call, so use other accessors if possible.
+    pub fn nodes_before_objects(&self) -> Option<&NodesBeforeObjects> {
+        if let Some(nodes) = self.nodes_before_objects {
+            return Some(nodes);
+        }
+        self.cloned_nodes_before_objects
+            .get_or_init(|| {
+                Some(NodesBeforeObjects {
+                    version: self.version,
+                    fbx_header_extension: self.fbx_header_extension?.clone(),
+                    file_id: self.file_id?.clone(),
+                    creation_time: self.creation_time?.clone(),
+                    creator: self.creator?.clone(),
+                    references: self.references?.clone(),
+                    global_settings: self.global_settings?.clone(),
+                    documents: self.documents?.clone(),
+                    definitions: self.definitions?.clone(),
+                })
+            })
+            .as_ref()
+    }
+
+    /// Returns the name of the first unavailable node in `NodesBeforeObjects`.
+    fn missing_node(&self) -> Option<&'static str> {
+        if self.fbx_header_extension.is_none() {
+            Some("FBXHeaderExtension")
+        } else if self.file_id.is_none() {
+            Some("FileId")
+        } else if self.creation_time.is_none() {
+            Some("CreationTime")
+        } else if self.creator.is_none() {
+            Some("Creator")
+        } else if self.references.is_none() {
+            Some("References")
+        } else if self.global_settings.is_none() {
+            Some("GlobalSettings")
+        } else if self.documents.is_none() {
+            Some("Documents")
+        } else if self.definitions.is_none() {
+            Some("Definitions")
+        } else {
+            None
+        }
+    }
+}
+
+
+/// Properties common to object nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectProperties {
+    /// ID.
+    pub id: i64,
+    /// Name.
+    pub name: String,
+    /// Class.
+    pub class: String,
+    /// Subclass.
+    pub subclass: String,
+}
+
+impl ObjectProperties {
+    /// Returns the attributes of the object node (ID, name and class, and subclass).
+    pub fn to_attributes(&self) -> Vec<OwnedAttribute> {
+        vec![
+            self.id.into(),
+            join_name_class(&self.name, &self.class).into(),
+            self.subclass.as_str().into(),
+        ]
+    }
+
+    /// Loads `ObjectProperties` in the same manner as usual child node attributes.
+    ///
+    /// Legacy object nodes with only the name and class and the subclass get IDs by
+    /// `legacy_object_id()`.
+    pub fn load<R>(name: &str, mut attrs: Attributes<R>) -> Result<ObjectProperties>
+    where
+        R: ParserSource,
+    {
+        use parser::binary::utils::AttributeValues;
+
+        Self::from_attributes(&mut attrs)?.ok_or_else(|| {
+            Error::InvalidAttribute(name.to_owned())
+        })
+    }
+}
+
+impl ::parser::binary::utils::AttributeValues for ObjectProperties {
+    fn from_attributes<R>(
+        attrs: &mut Attributes<R>,
+    ) -> ::std::result::Result<Option<Self>, ParseError>
+    where
+        R: ParserSource,
+    {
+        let (id, name_class, subclass) = if attrs.num_attributes() == 2 {
+            // Legacy object node without ID.
+            let (name_class, subclass) = match <(String, String)>::from_attributes(attrs)? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            let id = legacy_object_id(&name_class);
+            warn!("Object node without ID ({:?}), using synthesized ID {}", name_class, id);
+            (id, name_class, subclass)
+        } else {
+            match <(i64, String, String)>::from_attributes(attrs)? {
+                Some(v) => v,
+                None => return Ok(None),
+            }
+        };
+        // Reuse the buffer of `name_class` as `name` to avoid an allocation.
+        let mut name = name_class;
+        let (name_len, class) = {
+            let name_class = separate_name_class(&name);
+            (name_class.name.len(), name_class.class.to_owned())
+        };
+        name.truncate(name_len);
+        Ok(Some(ObjectProperties {
+            id: id,
+            name: name,
+            class: class,
+            subclass: subclass,
+        }))
+    }
+}
+
+
+/// Borrowed properties common to object nodes.
+///
+/// Objects loaders get this with the name and the class borrowing the attribute buffers, so
+/// they can inspect object nodes (for example, to filter them) before allocating
+/// `ObjectProperties`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectPropertiesRef<'a> {
+    /// ID.
+    pub id: i64,
+    /// Name.
+    pub name: &'a str,
+    /// Class.
+    pub class: &'a str,
+    /// Subclass.
+    pub subclass: &'a str,
+}
+
+impl<'a> ObjectPropertiesRef<'a> {
+    /// Creates an `ObjectPropertiesRef` from the attributes of the object node.
+    ///
+    /// `name` and `class` borrow `name_class` (`"name\x00\x01class"`).
+    /// See `separate_name_class()` for attributes without the separator.
+    pub fn from_attributes(id: i64, name_class: &'a str, subclass: &'a str) -> Self {
+        let NameClass { name, class } = separate_name_class(name_class);
+        ObjectPropertiesRef {
+            id: id,
+            name: name,
+            class: class,
+            subclass: subclass,
+        }
+    }
+
+    /// Creates owned `ObjectProperties`.
+    pub fn into_owned(self) -> ObjectProperties {
+        ObjectProperties {
+            id: self.id,
+            name: self.name.to_owned(),
+            class: self.class.to_owned(),
+            subclass: self.subclass.to_owned(),
+        }
+    }
+}
+
+impl<'a> From<&'a ObjectProperties> for ObjectPropertiesRef<'a> {
+    fn from(props: &'a ObjectProperties) -> Self {
+        ObjectPropertiesRef {
+            id: props.id,
+            name: &props.name,
+            class: &props.class,
+            subclass: &props.subclass,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! `Model` object.
+
+use loader::binary::simple::fbx7400::objects::ObjectProperties;
+
+
+/// Subclass of a `Model` object.
+///
+/// Objects loaders can route models to their own maps by matching on this, and handle
+/// subclasses unknown to this crate with `ModelKind::Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ModelKind {
+    /// `Camera`.
+    Camera,
+    /// `CameraSwitcher`.
+    CameraSwitcher,
+    /// `Light`.
+    Light,
+    /// `LimbNode`.
+    LimbNode,
+    /// `Line`.
+    Line,
+    /// `LodGroup`.
+    LodGroup,
+    /// `Marker`.
+    Marker,
+    /// `Mesh`.
+    Mesh,
+    /// `Null`.
+    Null,
+    /// `Nurbs`.
+    Nurbs,
+    /// `NurbsCurve`.
+    NurbsCurve,
+    /// `NurbsSurface`.
+    NurbsSurface,
+    /// `Patch`.
+    Patch,
+    /// `Root`.
+    Root,
+    /// Other subclass.
+    Other(String),
+}
+
+impl ModelKind {
+    /// Creates a `ModelKind` from the subclass name.
+    pub fn from_subclass(subclass: &str) -> Self {
+        match subclass {
+            "Camera" => ModelKind::Camera,
+            "CameraSwitcher" => ModelKind::CameraSwitcher,
+            "Light" => ModelKind::Light,
+            "LimbNode" => ModelKind::LimbNode,
+            "Line" => ModelKind::Line,
+            "LodGroup" => ModelKind::LodGroup,
+            "Marker" => ModelKind::Marker,
+            "Mesh" => ModelKind::Mesh,
+            "Null" => ModelKind::Null,
+            "Nurbs" => ModelKind::Nurbs,
+            "NurbsCurve" => ModelKind::NurbsCurve,
+            "NurbsSurface" => ModelKind::NurbsSurface,
+            "Patch" => ModelKind::Patch,
+            "Root" => ModelKind::Root,
+            _ => ModelKind::Other(subclass.to_owned()),
+        }
+    }
+
+    /// Creates a `ModelKind` from the object properties.
+    ///
+    /// Returns `None` if the object is not a `Model`.
+    pub fn from_props(props: &ObjectProperties) -> Option<Self> {
+        if props.class == "Model" {
+            Some(ModelKind::from_subclass(&props.subclass))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the subclass name.
+    pub fn subclass(&self) -> &str {
+        match *self {
+            ModelKind::Camera => "Camera",
+            ModelKind::CameraSwitcher => "CameraSwitcher",
+            ModelKind::Light => "Light",
+            ModelKind::LimbNode => "LimbNode",
+            ModelKind::Line => "Line",
+            ModelKind::LodGroup => "LodGroup",
+            ModelKind::Marker => "Marker",
+            ModelKind::Mesh => "Mesh",
+            ModelKind::Null => "Null",
+            ModelKind::Nurbs => "Nurbs",
+            ModelKind::NurbsCurve => "NurbsCurve",
+            ModelKind::NurbsSurface => "NurbsSurface",
+            ModelKind::Patch => "Patch",
+            ModelKind::Root => "Root",
+            ModelKind::Other(ref s) => s,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use fnv::FnvHashMap;
+    use loader::binary::simple::fbx7400::objects::ObjectProperties;
+    use super::ModelKind;
+
+    fn props(id: i64, class: &str, subclass: &str) -> ObjectProperties {
+        ObjectProperties {
+            id: id,
+            name: "".to_owned(),
+            class: class.to_owned(),
+            subclass: subclass.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_route_models() {
+        let mut routed = FnvHashMap::<ModelKind, Vec<i64>>::default();
+        for p in &[
+            props(1, "Model", "CameraSwitcher"),
+            props(2, "Model", "Marker"),
+            props(3, "Model", "FancyRig"),
+            props(4, "Model", "Marker"),
+            props(5, "NodeAttribute", "Marker"),
+        ] {
+            if let Some(kind) = ModelKind::from_props(p) {
+                routed.entry(kind).or_default().push(p.id);
+            }
+        }
+        assert_eq!(routed[&ModelKind::CameraSwitcher], vec![1]);
+        assert_eq!(routed[&ModelKind::Marker], vec![2, 4]);
+        let other = ModelKind::Other("FancyRig".to_owned());
+        assert_eq!(routed[&other], vec![3]);
+        assert_eq!(other.subclass(), "FancyRig");
+        assert_eq!(routed.len(), 3);
+    }
+}
@@ -0,0 +1,191 @@
+//! `NodeAttribute` object.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::{Result, GenericNode};
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+
+
+/// Subclass of a `NodeAttribute` object.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NodeAttributeKind {
+    /// `Boundary`.
+    Boundary,
+    /// `Camera`.
+    Camera,
+    /// `CameraSwitcher`.
+    CameraSwitcher,
+    /// `Light`.
+    Light,
+    /// `LimbNode` (skeleton).
+    LimbNode,
+    /// `LodGroup` (see `LodGroup` for its properties).
+    LodGroup,
+    /// `Marker`.
+    Marker,
+    /// `Null`.
+    Null,
+    /// `Root` (skeleton root).
+    Root,
+    /// Other subclass.
+    Other(String),
+}
+
+impl NodeAttributeKind {
+    /// Creates a `NodeAttributeKind` from the subclass name.
+    pub fn from_subclass(subclass: &str) -> Self {
+        match subclass {
+            "Boundary" => NodeAttributeKind::Boundary,
+            "Camera" => NodeAttributeKind::Camera,
+            "CameraSwitcher" => NodeAttributeKind::CameraSwitcher,
+            "Light" => NodeAttributeKind::Light,
+            "LimbNode" => NodeAttributeKind::LimbNode,
+            "LodGroup" => NodeAttributeKind::LodGroup,
+            "Marker" => NodeAttributeKind::Marker,
+            "Null" => NodeAttributeKind::Null,
+            "Root" => NodeAttributeKind::Root,
+            _ => NodeAttributeKind::Other(subclass.to_owned()),
+        }
+    }
+
+    /// Returns the subclass name.
+    pub fn subclass(&self) -> &str {
+        match *self {
+            NodeAttributeKind::Boundary => "Boundary",
+            NodeAttributeKind::Camera => "Camera",
+            NodeAttributeKind::CameraSwitcher => "CameraSwitcher",
+            NodeAttributeKind::Light => "Light",
+            NodeAttributeKind::LimbNode => "LimbNode",
+            NodeAttributeKind::LodGroup => "LodGroup",
+            NodeAttributeKind::Marker => "Marker",
+            NodeAttributeKind::Null => "Null",
+            NodeAttributeKind::Root => "Root",
+            NodeAttributeKind::Other(ref s) => s,
+        }
+    }
+}
+
+
+/// `NodeAttribute` object.
+///
+/// All subclasses share this type, and the subclass is distinguished by `kind`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeAttribute {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// Subclass.
+    pub kind: NodeAttributeKind,
+    /// `TypeFlags` (such as `"Null"` and `"Skeleton"`).
+    pub type_flags: Vec<String>,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl NodeAttribute {
+    /// Creates a `NodeAttribute` from the generic object.
+    pub fn from_object(obj: &Object) -> Self {
+        let type_flags = obj.node("TypeFlags").map_or_else(Vec::new, |node| {
+            node.attributes
+                .iter()
+                .filter_map(|attr| attr.as_str().map(str::to_owned))
+                .collect()
+        });
+        NodeAttribute {
+            props: obj.props.clone(),
+            kind: NodeAttributeKind::from_subclass(obj.subclass()),
+            type_flags: type_flags,
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Converts the node attribute into a generic object.
+    pub fn to_object(&self) -> Object {
+        let mut nodes = Vec::new();
+        if !self.type_flags.is_empty() {
+            nodes.push(self.type_flags.iter().fold(GenericNode::named("TypeFlags"), |node, f| {
+                node.with_attr(f.as_str())
+            }));
+        }
+        Object {
+            props: self.props.clone(),
+            properties: self.properties.properties.clone(),
+            nodes: nodes,
+            template: self.properties.template.clone(),
+        }
+    }
+
+    /// Returns the `Size` property (limb length of `LimbNode`, or size of `Null` and
+    /// `Marker`).
+    pub fn size(&self) -> Option<f64> {
+        self.properties.effective().get_f64("Size")
+    }
+
+    /// Returns the `Look` property (display style of `Null` and `Marker`).
+    pub fn look(&self) -> Option<i64> {
+        self.properties.effective().get_i64("Look")
+    }
+}
+
+impl LoadObject for NodeAttribute {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(NodeAttribute::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::{NodeAttribute, NodeAttributeKind};
+
+    #[test]
+    fn test_node_attribute_kinds() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &(id, subclass, flag) in &[
+            (1, "Null", "Null"),
+            (2, "LimbNode", "Skeleton"),
+            (3, "Marker", "Marker"),
+            (4, "CameraSwitcher", "CameraSwitcher"),
+            (5, "Boundary", "Boundary"),
+        ]
+        {
+            b.begin_node(
+                "NodeAttribute",
+                &[Attr::I64(id), Attr::String("\u{0}\u{1}NodeAttribute"), Attr::String(subclass)],
+            );
+            write_properties70(&mut b, &[("Size", "double", "Number", "", &[Attr::F64(2.0)])]);
+            b.node("TypeFlags", &[Attr::String(flag)]);
+            b.end_node();
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let objects = load_objects(data).objects;
+        let attrs = (1..6)
+            .map(|id| NodeAttribute::from_object(objects.object(id).expect("Should exist")))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            attrs.iter().map(|a| a.kind.clone()).collect::<Vec<_>>(),
+            vec![
+                NodeAttributeKind::Null,
+                NodeAttributeKind::LimbNode,
+                NodeAttributeKind::Marker,
+                NodeAttributeKind::CameraSwitcher,
+                NodeAttributeKind::Boundary,
+            ]
+        );
+        assert!(attrs.iter().all(|a| a.size() == Some(2.0)));
+        assert_eq!(attrs[1].type_flags, vec!["Skeleton".to_owned()]);
+        assert_eq!(attrs[2].kind.subclass(), "Marker");
+        let roundtrip = NodeAttribute::from_object(&attrs[1].to_object());
+        assert_eq!(roundtrip, attrs[1]);
+    }
+}
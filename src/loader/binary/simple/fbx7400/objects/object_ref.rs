@@ -0,0 +1,112 @@
+//! Object reference categorized by class.
+
+use loader::binary::simple::fbx7400::objects::{Object, ModelKind};
+
+
+/// Reference to an object, categorized by its class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectRef<'a> {
+    /// `AnimationCurve`.
+    AnimationCurve(&'a Object),
+    /// `AnimationCurveNode`.
+    AnimationCurveNode(&'a Object),
+    /// `AnimationLayer`.
+    AnimationLayer(&'a Object),
+    /// `AnimationStack`.
+    AnimationStack(&'a Object),
+    /// `Cache` (external cache file such as point cache).
+    Cache(&'a Object),
+    /// `CollectionExclusive` (such as display layers).
+    Collection(&'a Object),
+    /// `Deformer` (such as skin and blend shape).
+    Deformer(&'a Object),
+    /// `Geometry`.
+    Geometry(&'a Object),
+    /// `LayeredTexture`.
+    LayeredTexture(&'a Object),
+    /// `Material`.
+    Material(&'a Object),
+    /// `Model`.
+    Model(&'a Object),
+    /// `NodeAttribute`.
+    NodeAttribute(&'a Object),
+    /// `Pose`.
+    Pose(&'a Object),
+    /// `ProceduralTexture`.
+    ProceduralTexture(&'a Object),
+    /// `SubDeformer` (such as cluster and blend shape channel).
+    SubDeformer(&'a Object),
+    /// `Texture`.
+    Texture(&'a Object),
+    /// `Thumbnail`.
+    Thumbnail(&'a Object),
+    /// `Video`.
+    Video(&'a Object),
+    /// Object with unknown class.
+    Unknown(&'a Object),
+}
+
+impl<'a> ObjectRef<'a> {
+    /// Creates a new `ObjectRef` from the given object.
+    pub fn new(object: &'a Object) -> Self {
+        match object.class() {
+            "AnimationCurve" => ObjectRef::AnimationCurve(object),
+            "AnimationCurveNode" => ObjectRef::AnimationCurveNode(object),
+            "AnimationLayer" => ObjectRef::AnimationLayer(object),
+            "AnimationStack" => ObjectRef::AnimationStack(object),
+            "Cache" => ObjectRef::Cache(object),
+            "CollectionExclusive" => ObjectRef::Collection(object),
+            "Deformer" => ObjectRef::Deformer(object),
+            "Geometry" => ObjectRef::Geometry(object),
+            "LayeredTexture" => ObjectRef::LayeredTexture(object),
+            "Material" => ObjectRef::Material(object),
+            "Model" => ObjectRef::Model(object),
+            "NodeAttribute" => ObjectRef::NodeAttribute(object),
+            "Pose" => ObjectRef::Pose(object),
+            "ProceduralTexture" => ObjectRef::ProceduralTexture(object),
+            "SubDeformer" => ObjectRef::SubDeformer(object),
+            "Texture" => ObjectRef::Texture(object),
+            "Thumbnail" => ObjectRef::Thumbnail(object),
+            "Video" => ObjectRef::Video(object),
+            _ => ObjectRef::Unknown(object),
+        }
+    }
+
+    /// Returns the referred object.
+    pub fn object(&self) -> &'a Object {
+        match *self {
+            ObjectRef::AnimationCurve(obj) |
+            ObjectRef::AnimationCurveNode(obj) |
+            ObjectRef::AnimationLayer(obj) |
+            ObjectRef::AnimationStack(obj) |
+            ObjectRef::Cache(obj) |
+            ObjectRef::Collection(obj) |
+            ObjectRef::Deformer(obj) |
+            ObjectRef::Geometry(obj) |
+            ObjectRef::LayeredTexture(obj) |
+            ObjectRef::Material(obj) |
+            ObjectRef::Model(obj) |
+            ObjectRef::NodeAttribute(obj) |
+            ObjectRef::Pose(obj) |
+            ObjectRef::ProceduralTexture(obj) |
+            ObjectRef::SubDeformer(obj) |
+            ObjectRef::Texture(obj) |
+            ObjectRef::Thumbnail(obj) |
+            ObjectRef::Video(obj) |
+            ObjectRef::Unknown(obj) => obj,
+        }
+    }
+
+    /// Returns object ID.
+    pub fn id(&self) -> i64 {
+        self.object().id()
+    }
+
+    /// Returns the subclass of the model, or `None` if the object is not a model.
+    pub fn model_kind(&self) -> Option<ModelKind> {
+        match *self {
+            ObjectRef::Model(obj) => Some(ModelKind::from_subclass(obj.subclass())),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Registry of user defined object loaders.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+use fnv::FnvHashMap;
+use loader::binary::simple::Result;
+use loader::binary::simple::fbx7400::objects::{Object, ObjectMap};
+
+
+/// Object loaded by a user defined loader.
+pub type CustomObject = Box<dyn Any + Send + Sync>;
+
+
+/// A trait for user defined loaders of objects with specific class and subclass.
+///
+/// This is implemented for closures of type `Fn(&Object) -> Result<CustomObject>`.
+pub trait ObjectLoader: Send + Sync {
+    /// Loads the custom object from the generic object.
+    fn load(&self, object: &Object) -> Result<CustomObject>;
+}
+
+impl<F> ObjectLoader for F
+where
+    F: Fn(&Object) -> Result<CustomObject> + Send + Sync,
+{
+    fn load(&self, object: &Object) -> Result<CustomObject> {
+        self(object)
+    }
+}
+
+
+/// Registry of object loaders keyed by class and subclass.
+#[derive(Default)]
+pub struct ObjectLoaderRegistry {
+    /// Loaders by class and subclass (`None` for any subclass).
+    loaders: FnvHashMap<(String, Option<String>), Box<dyn ObjectLoader>>,
+}
+
+impl ObjectLoaderRegistry {
+    /// Creates a new empty `ObjectLoaderRegistry`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the loader for objects with the given class and subclass, and returns the
+    /// old loader for the same key.
+    ///
+    /// If `subclass` is `None`, the loader is used for all subclasses without their own
+    /// loaders.
+    pub fn register<L>(
+        &mut self,
+        class: &str,
+        subclass: Option<&str>,
+        loader: L,
+    ) -> Option<Box<dyn ObjectLoader>>
+    where
+        L: ObjectLoader + 'static,
+    {
+        let key = (class.to_owned(), subclass.map(str::to_owned));
+        self.loaders.insert(key, Box::new(loader))
+    }
+
+    /// Returns the loader for the given class and subclass.
+    pub fn get(&self, class: &str, subclass: &str) -> Option<&dyn ObjectLoader> {
+        self.loaders
+            .get(&(class.to_owned(), Some(subclass.to_owned())))
+            .or_else(|| self.loaders.get(&(class.to_owned(), None)))
+            .map(|loader| &**loader)
+    }
+
+    /// Returns the number of registered loaders.
+    pub fn len(&self) -> usize {
+        self.loaders.len()
+    }
+
+    /// Returns `true` if no loaders are registered.
+    pub fn is_empty(&self) -> bool {
+        self.loaders.is_empty()
+    }
+}
+
+impl fmt::Debug for ObjectLoaderRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.loaders.keys()).finish()
+    }
+}
+
+
+/// Objects loaded by user defined loaders.
+///
+/// Values are shared on clone, and two maps are equal if they have the same IDs and share the
+/// same values.
+#[derive(Default, Clone)]
+pub struct CustomObjects(ObjectMap<Arc<dyn Any + Send + Sync>>);
+
+impl CustomObjects {
+    /// Creates a new empty `CustomObjects`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Inserts the object.
+    pub fn insert(&mut self, id: i64, object: CustomObject) {
+        self.0.insert(id, Arc::from(object));
+    }
+
+    /// Removes the object.
+    pub fn remove(&mut self, id: i64) {
+        self.0.remove(&id);
+    }
+
+    /// Returns the object with the given ID if it has the type `T`.
+    pub fn get<T: Any>(&self, id: i64) -> Option<&T> {
+        self.0.get(&id).and_then(|obj| (**obj).downcast_ref())
+    }
+
+    /// Returns an iterator of IDs and objects of the type `T`, in arbitrary order.
+    pub fn iter<T: Any>(&self) -> impl Iterator<Item = (i64, &T)> {
+        self.0
+            .iter()
+            .filter_map(|(&id, obj)| (**obj).downcast_ref().map(|obj| (id, obj)))
+    }
+
+    /// Replaces object IDs by the map.
+    ///
+    /// IDs not in the map are kept as is.
+    pub fn remap_ids(&mut self, map: &ObjectMap<i64>) {
+        self.0 = self.0
+            .drain()
+            .map(|(id, obj)| (map.get(&id).cloned().unwrap_or(id), obj))
+            .collect();
+    }
+
+    /// Returns the number of objects.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no objects.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for CustomObjects {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl PartialEq for CustomObjects {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() &&
+            self.0.iter().all(|(id, obj)| {
+                other.0.get(id).is_some_and(|other| Arc::ptr_eq(obj, other))
+            })
+    }
+}
@@ -0,0 +1,409 @@
+//! `Texture` object.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::{Result, GenericNode, OwnedAttribute};
+use loader::binary::simple::fbx7400::EffectiveProperties;
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+use loader::math::{Matrix3, Vector2};
+
+
+/// `Texture` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `TextureName`.
+    pub texture_name: Option<String>,
+    /// `Media` (name of the `Video` object).
+    pub media: Option<String>,
+    /// `FileName` (absolute path).
+    pub file_name: Option<String>,
+    /// `RelativeFilename`.
+    pub relative_filename: Option<String>,
+    /// `ModelUVTranslation`.
+    pub model_uv_translation: Vector2,
+    /// `ModelUVScaling`.
+    pub model_uv_scaling: Vector2,
+    /// `Texture_Alpha_Source`.
+    pub alpha_source: Option<String>,
+    /// `Cropping` (left, top, right, bottom).
+    pub cropping: [i32; 4],
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl Texture {
+    /// Creates a `Texture` from the generic object.
+    ///
+    /// Missing or invalid child nodes are treated as default values.
+    pub fn from_object(obj: &Object) -> Self {
+        let string = |name: &str| obj.node(name).and_then(GenericNode::first_attr_as::<String>);
+        let f64_2 = |name: &str, default: Vector2| {
+            obj.node(name)
+                .and_then(|node| {
+                    let attr = |i: usize| node.attributes.get(i).and_then(OwnedAttribute::as_f64);
+                    Some([attr(0)?, attr(1)?])
+                })
+                .unwrap_or(default)
+        };
+        let mut cropping = [0; 4];
+        if let Some(node) = obj.node("Cropping") {
+            for (dest, attr) in cropping.iter_mut().zip(&node.attributes) {
+                if let Some(v) = attr.as_i32() {
+                    *dest = v;
+                }
+            }
+        }
+
+        Texture {
+            props: obj.props.clone(),
+            texture_name: string("TextureName"),
+            media: string("Media"),
+            file_name: string("FileName"),
+            relative_filename: string("RelativeFilename"),
+            model_uv_translation: f64_2("ModelUVTranslation", [0.0, 0.0]),
+            model_uv_scaling: f64_2("ModelUVScaling", [1.0, 1.0]),
+            alpha_source: string("Texture_Alpha_Source"),
+            cropping: cropping,
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Converts the texture into a generic object.
+    ///
+    /// `Type` and `Version` nodes are written with the values used by FBX 7.4 exporters.
+    pub fn to_object(&self) -> Object {
+        let mut nodes = vec![
+            GenericNode::named("Type").with_attr("TextureVideoClip"),
+            GenericNode::named("Version").with_attr(202),
+        ];
+        let mut push_string = |name: &str, value: &Option<String>| {
+            if let Some(ref value) = *value {
+                nodes.push(GenericNode::named(name).with_attr(value.as_str()));
+            }
+        };
+        push_string("TextureName", &self.texture_name);
+        push_string("Media", &self.media);
+        push_string("FileName", &self.file_name);
+        push_string("RelativeFilename", &self.relative_filename);
+        nodes.push(
+            GenericNode::named("ModelUVTranslation")
+                .with_attr(self.model_uv_translation[0])
+                .with_attr(self.model_uv_translation[1]),
+        );
+        nodes.push(
+            GenericNode::named("ModelUVScaling")
+                .with_attr(self.model_uv_scaling[0])
+                .with_attr(self.model_uv_scaling[1]),
+        );
+        if let Some(ref alpha_source) = self.alpha_source {
+            nodes.push(
+                GenericNode::named("Texture_Alpha_Source").with_attr(alpha_source.as_str()),
+            );
+        }
+        nodes.push(self.cropping.iter().fold(GenericNode::named("Cropping"), |node, &v| {
+            node.with_attr(v)
+        }));
+        Object {
+            props: self.props.clone(),
+            properties: self.properties.properties.clone(),
+            nodes: nodes,
+            template: self.properties.template.clone(),
+        }
+    }
+
+    /// Converts the texture into a `Texture` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        self.to_object().to_generic_node()
+    }
+
+    /// Returns the name of the UV set used by the texture.
+    pub fn uv_set(&self) -> Option<&str> {
+        self.properties.effective().get_string("UVSet")
+    }
+
+    /// Returns the UV transform of the texture.
+    pub fn uv_transform(&self) -> UvTransform {
+        let base = UvTransform {
+            model_uv_translation: self.model_uv_translation,
+            model_uv_scaling: self.model_uv_scaling,
+            ..Default::default()
+        };
+        uv_transform(self.properties.effective(), base)
+    }
+}
+
+impl LoadObject for Texture {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(Texture::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+/// `ProceduralTexture` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProceduralTexture {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl ProceduralTexture {
+    /// Creates a `ProceduralTexture` from the generic object.
+    pub fn from_object(obj: &Object) -> Self {
+        ProceduralTexture {
+            props: obj.props.clone(),
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Returns the application specific data of the texture (`Blob` property).
+    pub fn blob(&self) -> Option<&[u8]> {
+        self.properties.effective().get_binary("Blob")
+    }
+
+    /// Returns the UV transform of the texture.
+    ///
+    /// Procedural textures use the same transform properties as `Texture`.
+    pub fn uv_transform(&self) -> UvTransform {
+        uv_transform(self.properties.effective(), UvTransform::default())
+    }
+}
+
+impl LoadObject for ProceduralTexture {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(ProceduralTexture::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+/// UV transform of a texture.
+///
+/// UV is transformed in the following order:
+///
+/// 1. swapped if `swap` is true,
+/// 2. scaled by `model_uv_scaling` and translated by `model_uv_translation`,
+/// 3. scaled by `scaling` around `scaling_pivot`,
+/// 4. rotated by `rotation` around `rotation_pivot`,
+/// 5. translated by `translation`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvTransform {
+    /// Translation (`Translation` property).
+    pub translation: Vector2,
+    /// Rotation in degrees, counterclockwise (W component of `Rotation` property).
+    pub rotation: f64,
+    /// Scaling (`Scaling` property).
+    pub scaling: Vector2,
+    /// Rotation pivot (`TextureRotationPivot` property).
+    pub rotation_pivot: Vector2,
+    /// Scaling pivot (`TextureScalingPivot` property).
+    pub scaling_pivot: Vector2,
+    /// `ModelUVTranslation`.
+    pub model_uv_translation: Vector2,
+    /// `ModelUVScaling`.
+    pub model_uv_scaling: Vector2,
+    /// Whether U and V are swapped (`UVSwap` property).
+    pub swap: bool,
+}
+
+impl Default for UvTransform {
+    fn default() -> Self {
+        UvTransform {
+            translation: [0.0, 0.0],
+            rotation: 0.0,
+            scaling: [1.0, 1.0],
+            rotation_pivot: [0.0, 0.0],
+            scaling_pivot: [0.0, 0.0],
+            model_uv_translation: [0.0, 0.0],
+            model_uv_scaling: [1.0, 1.0],
+            swap: false,
+        }
+    }
+}
+
+impl UvTransform {
+    /// Returns `true` if the transform is identity.
+    pub fn is_identity(&self) -> bool {
+        self.matrix() == IDENTITY
+    }
+
+    /// Returns the transform as a column major 3x3 matrix for homogeneous 2D coordinates.
+    pub fn matrix(&self) -> Matrix3 {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let rotation = [[cos, sin, 0.0], [-sin, cos, 0.0], [0.0, 0.0, 1.0]];
+        let swap = if self.swap {
+            [[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]
+        } else {
+            IDENTITY
+        };
+        [
+            translate(self.translation),
+            translate(self.rotation_pivot),
+            rotation,
+            translate(neg(self.rotation_pivot)),
+            translate(self.scaling_pivot),
+            scale(self.scaling),
+            translate(neg(self.scaling_pivot)),
+            translate(self.model_uv_translation),
+            scale(self.model_uv_scaling),
+            swap,
+        ].iter()
+            .fold(IDENTITY, |acc, m| mul(&acc, m))
+    }
+
+    /// Transforms the UV.
+    pub fn apply(&self, uv: Vector2) -> Vector2 {
+        let m = self.matrix();
+        [
+            m[0][0] * uv[0] + m[1][0] * uv[1] + m[2][0],
+            m[0][1] * uv[0] + m[1][1] * uv[1] + m[2][1],
+        ]
+    }
+}
+
+
+/// Reads the UV transform from the texture properties.
+///
+/// Missing properties are taken from `base`.
+fn uv_transform(props: EffectiveProperties, base: UvTransform) -> UvTransform {
+    let xy = |name: &str, default: Vector2| {
+        props.get_f64_3(name).map_or(default, |v| [v[0], v[1]])
+    };
+    UvTransform {
+        translation: xy("Translation", base.translation),
+        rotation: props.get_f64_3("Rotation").map_or(base.rotation, |v| v[2]),
+        scaling: xy("Scaling", base.scaling),
+        rotation_pivot: xy("TextureRotationPivot", base.rotation_pivot),
+        scaling_pivot: xy("TextureScalingPivot", base.scaling_pivot),
+        swap: props.get_bool("UVSwap").unwrap_or(base.swap),
+        ..base
+    }
+}
+
+
+/// Identity matrix.
+const IDENTITY: Matrix3 = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+
+/// Returns the translation matrix.
+fn translate(v: Vector2) -> Matrix3 {
+    [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [v[0], v[1], 1.0]]
+}
+
+
+/// Returns the scaling matrix.
+fn scale(v: Vector2) -> Matrix3 {
+    [[v[0], 0.0, 0.0], [0.0, v[1], 0.0], [0.0, 0.0, 1.0]]
+}
+
+
+/// Returns the negated vector.
+fn neg(v: Vector2) -> Vector2 {
+    [-v[0], -v[1]]
+}
+
+
+/// Multiplies the column major matrices.
+fn mul(a: &Matrix3, b: &Matrix3) -> Matrix3 {
+    let mut m = [[0.0; 3]; 3];
+    for (col, b_col) in m.iter_mut().zip(b) {
+        for (row, v) in col.iter_mut().enumerate() {
+            *v = (0..3).map(|k| a[k][row] * b_col[k]).sum();
+        }
+    }
+    m
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::OwnedAttribute;
+    use loader::binary::simple::fbx7400::{ObjectProperties, Properties70, TemplatedProperties};
+    use super::{Texture, UvTransform};
+
+    fn assert_near(actual: [f64; 2], expected: [f64; 2]) {
+        assert!(
+            (actual[0] - expected[0]).abs() < 1e-9 && (actual[1] - expected[1]).abs() < 1e-9,
+            "actual = {:?}, expected = {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_uv_transform() {
+        assert!(UvTransform::default().is_identity());
+        assert_near(UvTransform::default().apply([0.25, 0.75]), [0.25, 0.75]);
+
+        let tiled = UvTransform {
+            translation: [0.5, 0.0],
+            scaling: [2.0, 3.0],
+            ..Default::default()
+        };
+        assert!(!tiled.is_identity());
+        assert_near(tiled.apply([1.0, 1.0]), [2.5, 3.0]);
+
+        let rotated = UvTransform {
+            rotation: 90.0,
+            rotation_pivot: [0.5, 0.5],
+            ..Default::default()
+        };
+        assert_near(rotated.apply([1.0, 0.5]), [0.5, 1.0]);
+
+        let model = UvTransform {
+            scaling: [2.0, 2.0],
+            scaling_pivot: [1.0, 1.0],
+            model_uv_translation: [0.5, 0.0],
+            model_uv_scaling: [1.0, -1.0],
+            swap: true,
+            ..Default::default()
+        };
+        // swap: (0, 1) -> model: (1.5, 0) -> scaling around (1, 1): (2, -1).
+        assert_near(model.apply([0.0, 1.0]), [2.0, -1.0]);
+    }
+
+    #[test]
+    fn test_to_object() {
+        let mut properties = Properties70::new();
+        properties.insert("UVSet", "map1");
+        properties.insert("Scaling", [2.0, 2.0, 1.0]);
+        let texture = Texture {
+            props: ObjectProperties {
+                id: 1,
+                name: "Diffuse".to_owned(),
+                class: "Texture".to_owned(),
+                subclass: "".to_owned(),
+            },
+            texture_name: Some("Diffuse".to_owned()),
+            media: None,
+            file_name: Some("C:/diffuse.png".to_owned()),
+            relative_filename: Some("diffuse.png".to_owned()),
+            model_uv_translation: [0.5, 0.0],
+            model_uv_scaling: [1.0, 2.0],
+            alpha_source: Some("None".to_owned()),
+            cropping: [0, 1, 2, 3],
+            properties: TemplatedProperties {
+                properties: Some(properties),
+                template: None,
+            },
+        };
+        let node = texture.to_generic_node();
+        assert_eq!(node.name, "Texture");
+        assert_eq!(node.attributes[1], OwnedAttribute::from("Diffuse\u{0}\u{1}Texture"));
+        assert_eq!(
+            node.children.iter().map(|c| c.name.as_str()).take(3).collect::<Vec<_>>(),
+            vec!["Type", "Version", "Properties70"]
+        );
+        assert_eq!(Texture::from_object(&texture.to_object()), texture);
+    }
+}
@@ -0,0 +1,113 @@
+//! `Thumbnail` object.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::Result;
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+
+
+/// `Thumbnail` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thumbnail {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Version`.
+    pub version: Option<i32>,
+    /// `Format` (`0` for RGB, `1` for RGBA).
+    pub format: Option<i32>,
+    /// `Size` (width and height).
+    pub size: Option<[i32; 2]>,
+    /// `Encoding` (`0` for raw pixels).
+    pub encoding: Option<i32>,
+    /// `ImageData`.
+    pub image_data: Vec<u8>,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl Thumbnail {
+    /// Creates a `Thumbnail` from the generic object.
+    ///
+    /// Missing or invalid child nodes are treated as `None` or empty data.
+    pub fn from_object(obj: &Object) -> Self {
+        let i32_attr = |name: &str, index: usize| {
+            obj.node(name).and_then(|node| node.attributes.get(index)?.as_i32())
+        };
+        let size = match (i32_attr("Size", 0), i32_attr("Size", 1)) {
+            (Some(width), Some(height)) => Some([width, height]),
+            _ => None,
+        };
+        let image_data = obj.node("ImageData")
+            .and_then(|node| node.first_attr_as::<Vec<u8>>())
+            .unwrap_or_default();
+        Thumbnail {
+            props: obj.props.clone(),
+            version: i32_attr("Version", 0),
+            format: i32_attr("Format", 0),
+            size: size,
+            encoding: i32_attr("Encoding", 0),
+            image_data: image_data,
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+}
+
+impl LoadObject for Thumbnail {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(Thumbnail::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::{ObjectRef, ProceduralTexture};
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::Thumbnail;
+
+    #[test]
+    fn test_thumbnail_and_procedural_texture() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "Thumbnail",
+            &[Attr::I64(1), Attr::String("Thumb\u{0}\u{1}Thumbnail"), Attr::String("")],
+        );
+        b.node("Version", &[Attr::I32(100)]);
+        b.node("Format", &[Attr::I32(0)]);
+        b.node("Size", &[Attr::I32(1), Attr::I32(2)]);
+        b.node("Encoding", &[Attr::I32(0)]);
+        b.node("ImageData", &[Attr::Binary(&[1, 2, 3, 4, 5, 6])]);
+        b.end_node();
+        b.begin_node(
+            "ProceduralTexture",
+            &[Attr::I64(2), Attr::String("Noise\u{0}\u{1}ProceduralTexture"), Attr::String("")],
+        );
+        write_properties70(&mut b, &[("Blob", "Blob", "", "", &[Attr::Binary(&[42])])]);
+        b.end_node();
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let objects = load_objects(data).objects;
+
+        let thumbnail = match objects.get(1) {
+            Some(ObjectRef::Thumbnail(obj)) => Thumbnail::from_object(obj),
+            v => panic!("Unexpected object: {:?}", v),
+        };
+        assert_eq!(thumbnail.size, Some([1, 2]));
+        assert_eq!(thumbnail.format, Some(0));
+        assert_eq!(thumbnail.image_data, vec![1, 2, 3, 4, 5, 6]);
+        let procedural = match objects.get(2) {
+            Some(ObjectRef::ProceduralTexture(obj)) => ProceduralTexture::from_object(obj),
+            v => panic!("Unexpected object: {:?}", v),
+        };
+        assert_eq!(procedural.blob(), Some(&[42][..]));
+        assert!(procedural.uv_transform().is_identity());
+    }
+}
@@ -0,0 +1,186 @@
+//! `Video` object.
+
+use parser::binary::{Parser, ParserSource};
+use loader::binary::simple::{Result, GenericNode};
+use loader::binary::simple::fbx7400::objects::{LoadObject, ObjectProperties, Object};
+use loader::binary::simple::fbx7400::objects::TemplatedProperties;
+
+
+/// `Video` object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Video {
+    /// Properties common to object nodes.
+    pub props: ObjectProperties,
+    /// `Type` (usually `Clip`).
+    pub video_type: Option<String>,
+    /// `FileName` (absolute path).
+    pub file_name: Option<String>,
+    /// `RelativeFilename`.
+    pub relative_filename: Option<String>,
+    /// `Content` (embedded file content).
+    ///
+    /// `None` if the content is not embedded.
+    pub content: Option<Vec<u8>>,
+    /// `Properties70` child node and its property template.
+    pub properties: TemplatedProperties,
+}
+
+impl Video {
+    /// Creates a `Video` from the generic object.
+    ///
+    /// The content may be split into multiple attributes of the `Content` node, and they are
+    /// concatenated.
+    /// String attributes (as written by ASCII FBX exporters) are decoded as base64.
+    /// The content is `None` if the `Content` node is missing or empty, or has attributes
+    /// which are not loaded into memory (see `ObjectsLoader::set_large_binaries()`) or invalid.
+    pub fn from_object(obj: &Object) -> Self {
+        let string = |name: &str| obj.node(name).and_then(GenericNode::first_attr_as::<String>);
+        Video {
+            props: obj.props.clone(),
+            video_type: string("Type"),
+            file_name: string("FileName").or_else(|| string("Filename")),
+            relative_filename: string("RelativeFilename"),
+            content: obj.node("Content").and_then(content),
+            properties: TemplatedProperties::from_object(obj),
+        }
+    }
+
+    /// Returns the path of the file (`Path` property), or `FileName` if unavailable.
+    pub fn path(&self) -> Option<&str> {
+        self.properties.effective()
+            .get_string("Path")
+            .or(self.file_name.as_deref())
+    }
+}
+
+impl LoadObject for Video {
+    fn load<R, P>(props: ObjectProperties, parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        Ok(Video::from_object(&Object::load(props, parser)?))
+    }
+}
+
+
+/// Returns the concatenated content of the `Content` node.
+///
+/// Base64 strings may be split at any position, so consecutive string attributes are
+/// concatenated before decoding.
+fn content(node: &GenericNode) -> Option<Vec<u8>> {
+    let mut content = Vec::new();
+    let mut text = String::new();
+    for attr in &node.attributes {
+        if let Some(v) = attr.as_binary() {
+            content.extend(decode_base64(&text)?);
+            text.clear();
+            content.extend_from_slice(v);
+        } else {
+            text.push_str(attr.as_str()?);
+        }
+    }
+    content.extend(decode_base64(&text)?);
+    if content.is_empty() {
+        None
+    } else {
+        Some(content)
+    }
+}
+
+
+/// Decodes the base64 string.
+///
+/// Whitespaces are ignored, and `None` is returned for invalid strings.
+fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let chars = s.bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    let data = match chars.iter().position(|&c| c == b'=') {
+        Some(pos) if chars[pos..].iter().all(|&c| c == b'=') && chars.len() % 4 == 0 => {
+            &chars[..pos]
+        },
+        Some(_) => return None,
+        None => &chars[..],
+    };
+    if data.len() % 4 == 1 {
+        return None;
+    }
+    let mut result = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut bits = 0;
+        for &c in chunk {
+            bits = (bits << 6) | value(c)?;
+        }
+        bits <<= 6 * (4 - chunk.len());
+        let bytes = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        result.extend_from_slice(&bytes[..chunk.len() - 1]);
+    }
+    Some(result)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::{Video, decode_base64};
+
+    #[test]
+    fn test_video() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        let contents: [&[Attr]; 4] = [
+            &[Attr::Binary(&[1, 2, 3]), Attr::Binary(&[4, 5])],
+            &[Attr::String("AQID"), Attr::String("BAU=")],
+            &[],
+            &[Attr::String("AQIDB"), Attr::String("AU="), Attr::Binary(&[6])],
+        ];
+        for (id, content) in (1..).zip(&contents) {
+            b.begin_node(
+                "Video",
+                &[Attr::I64(id), Attr::String("Tex\u{0}\u{1}Video"), Attr::String("Clip")],
+            );
+            b.node("Type", &[Attr::String("Clip")]);
+            write_properties70(
+                &mut b,
+                &[("Path", "KString", "XRefUrl", "", &[Attr::String("/tmp/tex.png")])],
+            );
+            b.node("RelativeFilename", &[Attr::String("tex.png")]);
+            if !content.is_empty() {
+                b.node("Content", content);
+            }
+            b.end_node();
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let objects = load_objects(data).objects;
+        let video = |id: i64| Video::from_object(objects.object(id).expect("Should exist"));
+
+        let embedded = video(1);
+        assert_eq!(embedded.video_type.as_deref(), Some("Clip"));
+        assert_eq!(embedded.path(), Some("/tmp/tex.png"));
+        assert_eq!(embedded.content, Some(vec![1, 2, 3, 4, 5]));
+        assert_eq!(video(2).content, Some(vec![1, 2, 3, 4, 5]));
+        assert_eq!(video(3).content, None);
+        assert_eq!(video(3).relative_filename.as_deref(), Some("tex.png"));
+        assert_eq!(video(4).content, Some(vec![1, 2, 3, 4, 5, 6]));
+
+        assert_eq!(decode_base64("TWFu\nTWE="), Some(b"ManMa".to_vec()));
+        assert_eq!(decode_base64("TQ=="), Some(b"M".to_vec()));
+        assert_eq!(decode_base64("T=Q="), None);
+    }
+}
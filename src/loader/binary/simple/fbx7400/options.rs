@@ -0,0 +1,122 @@
+//! Load options.
+
+use fnv::FnvHashSet;
+
+
+/// Toplevel node of FBX 7.4 compatible data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ToplevelNode {
+    /// `FBXHeaderExtension`.
+    FbxHeaderExtension,
+    /// `FileId`.
+    FileId,
+    /// `CreationTime`.
+    CreationTime,
+    /// `Creator`.
+    Creator,
+    /// `GlobalSettings`.
+    GlobalSettings,
+    /// `Documents`.
+    Documents,
+    /// `References`.
+    References,
+    /// `Definitions`.
+    Definitions,
+    /// `Objects`.
+    Objects,
+    /// `Connections`.
+    Connections,
+    /// `Takes`.
+    Takes,
+}
+
+impl ToplevelNode {
+    /// All toplevel nodes in usual order.
+    pub const ALL: [ToplevelNode; 11] = [
+        ToplevelNode::FbxHeaderExtension,
+        ToplevelNode::FileId,
+        ToplevelNode::CreationTime,
+        ToplevelNode::Creator,
+        ToplevelNode::GlobalSettings,
+        ToplevelNode::Documents,
+        ToplevelNode::References,
+        ToplevelNode::Definitions,
+        ToplevelNode::Objects,
+        ToplevelNode::Connections,
+        ToplevelNode::Takes,
+    ];
+
+    /// Returns the node name.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ToplevelNode::FbxHeaderExtension => "FBXHeaderExtension",
+            ToplevelNode::FileId => "FileId",
+            ToplevelNode::CreationTime => "CreationTime",
+            ToplevelNode::Creator => "Creator",
+            ToplevelNode::GlobalSettings => "GlobalSettings",
+            ToplevelNode::Documents => "Documents",
+            ToplevelNode::References => "References",
+            ToplevelNode::Definitions => "Definitions",
+            ToplevelNode::Objects => "Objects",
+            ToplevelNode::Connections => "Connections",
+            ToplevelNode::Takes => "Takes",
+        }
+    }
+}
+
+
+/// Options for loading FBX 7.4 compatible data.
+///
+/// If a toplevel node is not required and missing, it is treated as an empty node (i.e. loaded
+/// as `Default::default()` value) with a warning log.
+/// Missing required nodes cause `Error::MissingNode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// Required toplevel nodes.
+    required_nodes: FnvHashSet<ToplevelNode>,
+}
+
+impl LoadOptions {
+    /// Creates a new `LoadOptions` with the default settings.
+    ///
+    /// By default, only `Objects` and `Connections` are required.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Creates a new `LoadOptions` which requires all toplevel nodes except `Takes`.
+    pub fn strict() -> Self {
+        LoadOptions {
+            required_nodes: ToplevelNode::ALL
+                .iter()
+                .cloned()
+                .filter(|&node| node != ToplevelNode::Takes)
+                .collect(),
+        }
+    }
+
+    /// Returns whether the given toplevel node is required.
+    pub fn is_required(&self, node: ToplevelNode) -> bool {
+        self.required_nodes.contains(&node)
+    }
+
+    /// Sets whether the given toplevel node is required.
+    pub fn set_required(&mut self, node: ToplevelNode, required: bool) {
+        if required {
+            self.required_nodes.insert(node);
+        } else {
+            self.required_nodes.remove(&node);
+        }
+    }
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        LoadOptions {
+            required_nodes: [ToplevelNode::Objects, ToplevelNode::Connections]
+                .iter()
+                .cloned()
+                .collect(),
+        }
+    }
+}
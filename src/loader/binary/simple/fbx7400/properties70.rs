@@ -1,37 +1,24 @@
 //! `Properties70` node and its children.
 
-use std::fmt;
-use fnv::{FnvHashSet, FnvHashMap};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use fnv::{FnvHashMap, FnvHashSet};
 use parser::binary::{Parser, ParserSource, Attributes};
 use parser::binary::{Attribute, PrimitiveAttribute};
-use loader::binary::simple::{Result, Error};
-
-
-/// A type of map from property name to value of the specific type.
-pub type PropertyMap<T> = FnvHashMap<String, PropertyValue<T>>;
+use loader::binary::simple::{Result, Error, GenericNode, OwnedAttribute};
 
 
 /// Struct to store `Properties70` node data.
-#[derive(Default, Clone, PartialEq)]
+///
+/// Properties are stored in a single map from property name to value.
+/// Property names can be shared among objects by loading them with `PropertyNameInterner`.
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct Properties70 {
-    /// Properties without values.
-    pub values_empty: FnvHashSet<String>,
-    /// Values with `i64`, `i32`, or `i16` type.
-    pub values_i64: PropertyMap<i64>,
-    /// Values with `f64` or `f32` type.
-    pub values_f64: PropertyMap<f64>,
-    /// Values with `[f64; 2]` type.
-    pub values_f64_2: PropertyMap<[f64; 2]>,
-    /// Values with `[f64; 3]` type.
-    pub values_f64_3: PropertyMap<[f64; 3]>,
-    /// Values with `[f64; 4]` type.
-    pub values_f64_4: PropertyMap<[f64; 4]>,
-    /// Values with `[[f64; 4]; 4]` type.
-    pub values_f64_4x4: PropertyMap<[[f64; 4]; 4]>,
-    /// Values with `String` type.
-    pub values_string: PropertyMap<String>,
-    /// Values with `Vec<u8>` type (called "blob").
-    pub values_binary: PropertyMap<Vec<u8>>,
+    /// Property values.
+    values: FnvHashMap<Arc<str>, PropertyValue>,
+    /// Names of user-defined properties (properties with `U` flag).
+    user_defined: FnvHashSet<Arc<str>>,
 }
 
 impl Properties70 {
@@ -46,66 +33,540 @@ impl Properties70 {
         R: ParserSource,
         P: Parser<R>,
     {
-        load_properties70(parser)
+        load_properties(parser, None, PropertiesFormat::V70)
+    }
+
+    /// Loads a node from the parser, sharing property names through the given interner.
+    pub fn load_with_interner<R, P>(parser: P, interner: &mut PropertyNameInterner) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        load_properties(parser, Some(interner), PropertiesFormat::V70)
+    }
+
+    /// Loads a legacy `Properties60` node from the parser.
+    ///
+    /// `Property` nodes of `Properties60` have no label attribute, and otherwise they are
+    /// loaded in the same way as `P` nodes.
+    /// Type names (which differ from `Properties70`, such as `Color` for `ColorRGB`) are not
+    /// kept, so the result is the same as the equivalent `Properties70`.
+    pub fn load_properties60<R, P>(parser: P) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        load_properties(parser, None, PropertiesFormat::V60)
+    }
+
+    /// Loads a legacy `Properties60` node from the parser, sharing property names through the
+    /// given interner.
+    pub fn load_properties60_with_interner<R, P>(
+        parser: P,
+        interner: &mut PropertyNameInterner,
+    ) -> Result<Self>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        load_properties(parser, Some(interner), PropertiesFormat::V60)
+    }
+
+    /// Inserts the property and returns the old value with the same name.
+    pub fn insert<S, V>(&mut self, name: S, value: V) -> Option<PropertyValue>
+    where
+        S: Into<Arc<str>>,
+        V: Into<PropertyValue>,
+    {
+        self.values.insert(name.into(), value.into())
+    }
+
+    /// Removes the property and returns its value.
+    pub fn remove(&mut self, name: &str) -> Option<PropertyValue> {
+        self.user_defined.remove(name);
+        self.values.remove(name)
+    }
+
+    /// Returns `true` if the property is user-defined (i.e. has `U` flag).
+    pub fn is_user_defined(&self, name: &str) -> bool {
+        self.user_defined.contains(name)
+    }
+
+    /// Sets whether the existing property is user-defined.
+    ///
+    /// Returns `false` if the property does not exist.
+    pub fn set_user_defined(&mut self, name: &str, user_defined: bool) -> bool {
+        let name = match self.values.get_key_value(name) {
+            Some((name, _)) => name.clone(),
+            None => return false,
+        };
+        if user_defined {
+            self.user_defined.insert(name);
+        } else {
+            self.user_defined.remove(&name);
+        }
+        true
+    }
+
+    /// Returns user-defined properties (such as custom properties authored in DCC tools).
+    pub fn user_properties(&self) -> BTreeMap<&str, &PropertyValue> {
+        self.user_defined
+            .iter()
+            .filter_map(|name| self.values.get(name).map(|value| (&**name, value)))
+            .collect()
+    }
+
+    /// Returns the value of the property.
+    pub fn get(&self, name: &str) -> Option<&PropertyValue> {
+        self.values.get(name)
+    }
+
+    /// Returns `true` if the property exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Returns an iterator of property names and values in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PropertyValue)> {
+        self.values.iter().map(|(name, value)| (&**name, value))
+    }
+
+    /// Returns the number of properties.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no properties.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Converts the properties into a `Properties70` node.
+    ///
+    /// Property type names, labels and flags other than `U` are not kept by the loader, so
+    /// typical type names are guessed from the value types, labels are left empty, and flags
+    /// are `"U"` for user-defined properties and empty otherwise.
+    /// `P` nodes are sorted by property names.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let p = |name: &str, type_name: &str, label: &str| -> GenericNode {
+            let flags = if self.is_user_defined(name) { "U" } else { "" };
+            GenericNode::named("P")
+                .with_attr(name)
+                .with_attr(type_name)
+                .with_attr(label)
+                .with_attr(flags)
+        };
+        fn with_f64s(mut node: GenericNode, values: &[f64]) -> GenericNode {
+            node.attributes.extend(values.iter().map(|&v| OwnedAttribute::F64(v)));
+            node
+        }
+
+        let mut nodes = self.values
+            .iter()
+            .map(|(name, value)| match *value {
+                PropertyValue::Empty => p(name, "Compound", ""),
+                PropertyValue::I64(v) => {
+                    if v >= i64::from(i32::MIN) && v <= i64::from(i32::MAX) {
+                        p(name, "int", "Integer").with_attr(v as i32)
+                    } else {
+                        p(name, "ULongLong", "").with_attr(v)
+                    }
+                },
+                PropertyValue::F64(v) => p(name, "double", "Number").with_attr(v),
+                PropertyValue::F64x2(ref v) => with_f64s(p(name, "Vector2D", "Vector2"), v),
+                PropertyValue::F64x3(ref v) => with_f64s(p(name, "Vector3D", "Vector"), v),
+                PropertyValue::F64x4(ref v) => with_f64s(p(name, "ColorAndAlpha", ""), v),
+                PropertyValue::F64x4x4(ref v) => {
+                    let values = v.iter().flat_map(|col| col.iter().cloned()).collect::<Vec<_>>();
+                    with_f64s(p(name, "Matrix", ""), &values)
+                },
+                PropertyValue::String(ref v) => p(name, "KString", "").with_attr(v.as_str()),
+                PropertyValue::Binary(ref v) => p(name, "Blob", "").with_attr(v.as_slice()),
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by(|a, b| a.first_attr_as::<&str>().cmp(&b.first_attr_as::<&str>()));
+        GenericNode::named("Properties70").with_children(nodes)
+    }
+
+    /// Returns the integer value of the property.
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(PropertyValue::as_i64)
+    }
+
+    /// Returns the boolean value of the property.
+    ///
+    /// Boolean properties are stored as integers.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get_i64(name).map(|v| v != 0)
+    }
+
+    /// Returns the numeric value of the property.
+    ///
+    /// Integer values are converted to `f64`.
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        self.get(name).and_then(PropertyValue::as_f64)
+    }
+
+    /// Returns the `[f64; 2]` value of the property.
+    pub fn get_f64_2(&self, name: &str) -> Option<[f64; 2]> {
+        self.get(name).and_then(PropertyValue::as_f64_2)
+    }
+
+    /// Returns the `[f64; 3]` value of the property.
+    pub fn get_f64_3(&self, name: &str) -> Option<[f64; 3]> {
+        self.get(name).and_then(PropertyValue::as_f64_3)
+    }
+
+    /// Returns the `[f64; 4]` value of the property.
+    pub fn get_f64_4(&self, name: &str) -> Option<[f64; 4]> {
+        self.get(name).and_then(PropertyValue::as_f64_4)
+    }
+
+    /// Returns the `[[f64; 4]; 4]` value of the property.
+    pub fn get_f64_4x4(&self, name: &str) -> Option<[[f64; 4]; 4]> {
+        self.get(name).and_then(PropertyValue::as_f64_4x4)
+    }
+
+    /// Returns the string value of the property.
+    pub fn get_string(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(PropertyValue::as_str)
+    }
+
+    /// Returns the binary value (called "blob") of the property.
+    pub fn get_binary(&self, name: &str) -> Option<&[u8]> {
+        self.get(name).and_then(PropertyValue::as_binary)
     }
 }
 
-impl fmt::Debug for Properties70 {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut format = f.debug_struct("Properties70");
-        macro_rules! show {
-            ($field:ident) => {
-                if !self.$field.is_empty() {
-                    format.field(stringify!($field), &self.$field);
+
+/// Properties of an object overlaid on its property template.
+///
+/// Values in the object's `Properties70` take precedence over the template.
+/// Nothing is cloned unless `materialize()` needs to merge both.
+#[derive(Debug, Clone, Copy)]
+pub struct EffectiveProperties<'a> {
+    /// Properties of the object.
+    properties: Option<&'a Properties70>,
+    /// Property template.
+    template: Option<&'a Properties70>,
+}
+
+impl<'a> EffectiveProperties<'a> {
+    /// Creates a new `EffectiveProperties`.
+    pub fn new(properties: Option<&'a Properties70>, template: Option<&'a Properties70>) -> Self {
+        EffectiveProperties {
+            properties: properties,
+            template: template,
+        }
+    }
+
+    /// Returns the properties of the object.
+    pub fn properties(&self) -> Option<&'a Properties70> {
+        self.properties
+    }
+
+    /// Returns the property template.
+    pub fn template(&self) -> Option<&'a Properties70> {
+        self.template
+    }
+
+    /// Returns the effective value of the property.
+    pub fn get(&self, name: &str) -> Option<&'a PropertyValue> {
+        self.properties
+            .and_then(|props| props.get(name))
+            .or_else(|| self.template.and_then(|template| template.get(name)))
+    }
+
+    /// Returns `true` if the object has its own value of the property.
+    pub fn is_overridden(&self, name: &str) -> bool {
+        self.properties.is_some_and(|props| props.contains(name))
+    }
+
+    /// Returns an iterator of effective property names and values.
+    ///
+    /// Properties of the object are iterated first, then properties only in the template.
+    /// Each group is iterated in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a PropertyValue)> + 'a {
+        let properties = self.properties;
+        let inherited = self.template
+            .into_iter()
+            .flat_map(Properties70::iter)
+            .filter(move |&(name, _)| !properties.is_some_and(|props| props.contains(name)));
+        properties.into_iter().flat_map(Properties70::iter).chain(inherited)
+    }
+
+    /// Returns the effective properties as a single `Properties70`.
+    ///
+    /// If one of the object properties and the template is enough, it is returned without
+    /// cloning.
+    pub fn materialize(&self) -> Cow<'a, Properties70> {
+        match (self.properties, self.template) {
+            (None, None) => Cow::Owned(Properties70::new()),
+            (Some(props), None) | (None, Some(props)) => Cow::Borrowed(props),
+            (Some(props), Some(template)) => {
+                if template.values.keys().all(|name| props.values.contains_key(name)) {
+                    return Cow::Borrowed(props);
                 }
-            }
+                let mut merged = template.clone();
+                merged.values.extend(
+                    props.values.iter().map(|(name, value)| (name.clone(), value.clone())),
+                );
+                Cow::Owned(merged)
+            },
         }
-        show!(values_empty);
-        show!(values_i64);
-        show!(values_f64);
-        show!(values_f64_2);
-        show!(values_f64_3);
-        show!(values_f64_4);
-        show!(values_f64_4x4);
-        show!(values_string);
-        show!(values_binary);
-        format.finish()
+    }
+
+    /// Returns the effective integer value of the property.
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.get(name).and_then(PropertyValue::as_i64)
+    }
+
+    /// Returns the effective boolean value of the property.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.get_i64(name).map(|v| v != 0)
+    }
+
+    /// Returns the effective numeric value of the property.
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        self.get(name).and_then(PropertyValue::as_f64)
+    }
+
+    /// Returns the effective `[f64; 2]` value of the property.
+    pub fn get_f64_2(&self, name: &str) -> Option<[f64; 2]> {
+        self.get(name).and_then(PropertyValue::as_f64_2)
+    }
+
+    /// Returns the effective `[f64; 3]` value of the property.
+    pub fn get_f64_3(&self, name: &str) -> Option<[f64; 3]> {
+        self.get(name).and_then(PropertyValue::as_f64_3)
+    }
+
+    /// Returns the effective `[f64; 4]` value of the property.
+    pub fn get_f64_4(&self, name: &str) -> Option<[f64; 4]> {
+        self.get(name).and_then(PropertyValue::as_f64_4)
+    }
+
+    /// Returns the effective `[[f64; 4]; 4]` value of the property.
+    pub fn get_f64_4x4(&self, name: &str) -> Option<[[f64; 4]; 4]> {
+        self.get(name).and_then(PropertyValue::as_f64_4x4)
+    }
+
+    /// Returns the effective string value of the property.
+    pub fn get_string(&self, name: &str) -> Option<&'a str> {
+        self.get(name).and_then(PropertyValue::as_str)
+    }
+
+    /// Returns the effective binary value of the property.
+    pub fn get_binary(&self, name: &str) -> Option<&'a [u8]> {
+        self.get(name).and_then(PropertyValue::as_binary)
     }
 }
 
 
-/// A type of property value and its metadata.
+/// Pool of property names shared among `Properties70`s.
 ///
-/// Type, label, and flags will be ignored currently.
+/// Property names such as `Lcl Translation` repeat across many objects, and this deduplicates
+/// them into shared `Arc<str>`s.
+#[derive(Default, Debug, Clone)]
+pub struct PropertyNameInterner {
+    /// Interned names.
+    names: FnvHashSet<Arc<str>>,
+}
+
+impl PropertyNameInterner {
+    /// Creates a new empty `PropertyNameInterner`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the shared string for the given name.
+    pub fn intern(&mut self, name: &str) -> Arc<str> {
+        if let Some(interned) = self.names.get(name) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(name);
+        self.names.insert(interned.clone());
+        interned
+    }
+
+    /// Returns the number of interned names.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if no names are interned.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+
+/// Value of a property.
+///
+/// Type and label will be ignored currently, and flags are only used to find user-defined
+/// properties.
 #[derive(Debug, Clone, PartialEq)]
-pub struct PropertyValue<T>(T);
+pub enum PropertyValue {
+    /// No values.
+    Empty,
+    /// `i64`, `i32`, or `i16` value.
+    I64(i64),
+    /// `f64` or `f32` value.
+    F64(f64),
+    /// `[f64; 2]` value.
+    F64x2([f64; 2]),
+    /// `[f64; 3]` value.
+    F64x3([f64; 3]),
+    /// `[f64; 4]` value.
+    F64x4([f64; 4]),
+    /// `[[f64; 4]; 4]` value.
+    ///
+    /// This is boxed to keep the other values small.
+    F64x4x4(Box<[[f64; 4]; 4]>),
+    /// `String` value.
+    String(String),
+    /// `Vec<u8>` value (called "blob").
+    Binary(Vec<u8>),
+}
+
+impl PropertyValue {
+    /// Returns `true` if the property has no values.
+    pub fn is_empty(&self) -> bool {
+        *self == PropertyValue::Empty
+    }
+
+    /// Returns the integer value.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            PropertyValue::I64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric value.
+    ///
+    /// Integer values are converted to `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            PropertyValue::I64(v) => Some(v as f64),
+            PropertyValue::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the `[f64; 2]` value.
+    pub fn as_f64_2(&self) -> Option<[f64; 2]> {
+        match *self {
+            PropertyValue::F64x2(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the `[f64; 3]` value.
+    pub fn as_f64_3(&self) -> Option<[f64; 3]> {
+        match *self {
+            PropertyValue::F64x3(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the `[f64; 4]` value.
+    pub fn as_f64_4(&self) -> Option<[f64; 4]> {
+        match *self {
+            PropertyValue::F64x4(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the `[[f64; 4]; 4]` value.
+    pub fn as_f64_4x4(&self) -> Option<[[f64; 4]; 4]> {
+        match *self {
+            PropertyValue::F64x4x4(ref v) => Some(**v),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            PropertyValue::String(ref v) => Some(v),
+            _ => None,
+        }
+    }
 
-impl<T> PropertyValue<T> {
-    /// Creates a new `PropertyValue` with the given value.
-    pub fn new(v: T) -> Self {
-        PropertyValue(v)
+    /// Returns the binary value.
+    pub fn as_binary(&self) -> Option<&[u8]> {
+        match *self {
+            PropertyValue::Binary(ref v) => Some(v),
+            _ => None,
+        }
     }
+}
 
-    /// Returns the value.
-    pub fn value(&self) -> &T {
-        &self.0
+macro_rules! impl_property_value_from {
+    ($($t:ty => $variant:ident, |$v:ident| $conv:expr;)*) => {
+        $(
+            impl From<$t> for PropertyValue {
+                fn from($v: $t) -> Self {
+                    PropertyValue::$variant($conv)
+                }
+            }
+        )*
     }
+}
 
-    /// Returns the value with the ownership.
-    pub fn take_value(self) -> T {
-        self.0
+impl_property_value_from! {
+    i64 => I64, |v| v;
+    f64 => F64, |v| v;
+    [f64; 2] => F64x2, |v| v;
+    [f64; 3] => F64x3, |v| v;
+    [f64; 4] => F64x4, |v| v;
+    [[f64; 4]; 4] => F64x4x4, |v| Box::new(v);
+    String => String, |v| v;
+    Vec<u8> => Binary, |v| v;
+}
+
+impl<'a> From<&'a str> for PropertyValue {
+    fn from(v: &'a str) -> Self {
+        PropertyValue::String(v.to_owned())
     }
 }
 
-impl<T> From<T> for PropertyValue<T> {
-    fn from(v: T) -> Self {
-        PropertyValue::new(v)
+
+/// Format of property nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PropertiesFormat {
+    /// `Properties70` with `P` nodes.
+    V70,
+    /// Legacy `Properties60` with `Property` nodes.
+    V60,
+}
+
+impl PropertiesFormat {
+    /// Returns the name of the properties node.
+    fn node_name(self) -> &'static str {
+        match self {
+            PropertiesFormat::V70 => "Properties70",
+            PropertiesFormat::V60 => "Properties60",
+        }
+    }
+
+    /// Returns the name of the property nodes.
+    fn property_node_name(self) -> &'static str {
+        match self {
+            PropertiesFormat::V70 => "P",
+            PropertiesFormat::V60 => "Property",
+        }
     }
 }
 
 
-/// Loads a `Properties70` node.
-fn load_properties70<R, P>(mut parser: P) -> Result<Properties70>
+/// Loads a `Properties70` or `Properties60` node.
+fn load_properties<R, P>(
+    mut parser: P,
+    mut interner: Option<&mut PropertyNameInterner>,
+    format: PropertiesFormat,
+) -> Result<Properties70>
 where
     R: ParserSource,
     P: Parser<R>,
@@ -113,10 +574,15 @@ where
     let mut props = Properties70::new();
 
     loop {
-        try_get_node_attrs!(parser, |name: &str, attrs| if name == "P" {
-            load_property(&mut props, attrs)
+        try_get_node_attrs!(parser, |name: &str, attrs| if name == format.property_node_name() {
+            load_property(&mut props, attrs, interner.as_deref_mut(), format)
         } else {
-            warn!("Expected `P` node but got `{}` in `Properties70`", name);
+            warn!(
+                "Expected `{}` node but got `{}` in `{}`",
+                format.property_node_name(),
+                name,
+                format.node_name()
+            );
             Err(Error::UnexpectedNode(name.to_owned()))
         });
         parser.skip_current_node()?;
@@ -125,22 +591,39 @@ where
 }
 
 
-/// Loads a `P` node in `Properties70`.
-fn load_property<R>(props: &mut Properties70, mut attrs: Attributes<R>) -> Result<()>
+/// Loads a `P` node in `Properties70` (or a `Property` node in `Properties60`).
+fn load_property<R>(
+    props: &mut Properties70,
+    mut attrs: Attributes<R>,
+    interner: Option<&mut PropertyNameInterner>,
+    format: PropertiesFormat,
+) -> Result<()>
 where
     R: ParserSource,
 {
     use parser::binary::utils::AttributeValues;
 
-    // `type_name`, `label`, `flags` are `String`s, but ignore here because they are currently
+    // `type_name` and `label` are `String`s, but ignore here because they are currently
     // unused.
-    let (name, _type_name, _label, _flags) =
-        <(String, (), (), ())>::from_attributes(&mut attrs)?
-            .ok_or_else(|| Error::InvalidAttribute("P".to_owned()))?;
+    let header = match format {
+        PropertiesFormat::V70 => <(String, (), (), String)>::from_attributes(&mut attrs)?
+            .map(|(name, _type_name, _label, flags)| (name, flags)),
+        PropertiesFormat::V60 => <(String, (), String)>::from_attributes(&mut attrs)?
+            .map(|(name, _type_name, flags)| (name, flags)),
+    };
+    let (name, flags) = header
+        .ok_or_else(|| Error::InvalidAttribute(format.property_node_name().to_owned()))?;
+    let name = match interner {
+        Some(interner) => interner.intern(&name),
+        None => Arc::from(name),
+    };
+    if flags.contains('U') {
+        props.user_defined.insert(name.clone());
+    }
 
     if attrs.rest_attributes() == 0 {
         // Empty attribute.
-        props.values_empty.insert(name);
+        props.insert(name, PropertyValue::Empty);
         return Ok(());
     }
 
@@ -149,23 +632,23 @@ where
     // `5i16, 5i16, 5i16`. However, it seems that it can be regarded as single `5i16`.
     let first: f64 = match attrs.next_attribute()? {
         None => {
-            props.values_empty.insert(name);
+            props.insert(name, PropertyValue::Empty);
             return Ok(());
         },
         Some(Attribute::Primitive(PrimitiveAttribute::I16(val))) => {
-            props.values_i64.insert(name, (val as i64).into());
+            props.insert(name, val as i64);
             return Ok(());
         },
         Some(Attribute::Primitive(PrimitiveAttribute::I32(val))) => {
-            props.values_i64.insert(name, (val as i64).into());
+            props.insert(name, val as i64);
             return Ok(());
         },
         Some(Attribute::Primitive(PrimitiveAttribute::I64(val))) => {
-            props.values_i64.insert(name, val.into());
+            props.insert(name, val);
             return Ok(());
         },
         Some(Attribute::Primitive(PrimitiveAttribute::F32(val))) => {
-            props.values_f64.insert(name, (val as f64).into());
+            props.insert(name, val as f64);
             return Ok(());
         },
         Some(Attribute::Primitive(PrimitiveAttribute::F64(val))) => val,
@@ -178,15 +661,15 @@ where
                 SpecialAttributeType::String => {
                     match String::from_utf8(vec) {
                         Ok(val) => {
-                            props.values_string.insert(name, val.into());
+                            props.insert(name, val);
                         },
                         Err(err) => {
-                            props.values_binary.insert(name, err.into_bytes().into());
+                            props.insert(name, err.into_bytes());
                         },
                     }
                 },
                 SpecialAttributeType::Binary => {
-                    props.values_binary.insert(name, vec.into());
+                    props.insert(name, vec);
                 },
             }
             return Ok(());
@@ -202,7 +685,7 @@ where
 fn load_property_rest_f64s<R>(
     props: &mut Properties70,
     mut attrs: Attributes<R>,
-    name: String,
+    name: Arc<str>,
     first: f64,
 ) -> Result<()>
 where
@@ -212,31 +695,25 @@ where
 
     match attrs.rest_attributes() {
         0 => {
-            props.values_f64.insert(name, first.into());
+            props.insert(name, first);
             Ok(())
         },
         1 => {
             let second = attrs.convert_into()?.ok_or_else(invalid_attr)?;
-            props.values_f64_2.insert(name, [first, second].into());
+            props.insert(name, [first, second]);
             Ok(())
         },
         2 => {
             let second = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
             let third = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
-            props.values_f64_3.insert(
-                name,
-                [first, second, third].into(),
-            );
+            props.insert(name, [first, second, third]);
             Ok(())
         },
         3 => {
             let second = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
             let third = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
             let fourth = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
-            props.values_f64_4.insert(
-                name,
-                [first, second, third, fourth].into(),
-            );
+            props.insert(name, [first, second, third, fourth]);
             Ok(())
         },
         15 => {
@@ -256,10 +733,7 @@ where
                 let t: (f64, f64, f64, f64) = attrs.convert_into()?.ok_or_else(&invalid_attr)?;
                 [t.0, t.1, t.2, t.3]
             };
-            props.values_f64_4x4.insert(
-                name,
-                [vec1, vec2, vec3, vec4].into(),
-            );
+            props.insert(name, [vec1, vec2, vec3, vec4]);
             Ok(())
         },
         n => {
@@ -272,3 +746,117 @@ where
         },
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use parser::binary::{Parser, RootParser, Event};
+    use test_util::{Attr, FbxBuilder, write_properties70};
+    use super::{Properties70, PropertyValue};
+
+    #[test]
+    fn test_load() {
+        let mut b = FbxBuilder::new(7400);
+        write_properties70(
+            &mut b,
+            &[
+                ("Visibility", "bool", "", "", &[Attr::I32(1)]),
+                ("Intensity", "Number", "", "A", &[Attr::F32(0.5)]),
+                (
+                    "Color",
+                    "ColorRGB",
+                    "Color",
+                    "",
+                    &[Attr::F64(1.0), Attr::F64(0.5), Attr::F64(0.0)],
+                ),
+                ("UVSet", "KString", "", "", &[Attr::String("map1")]),
+                ("Compound", "Compound", "", "", &[]),
+            ],
+        );
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartFbx(_)));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartNode(_)));
+        let props = Properties70::load(parser.subtree_parser()).expect("Load error");
+
+        assert_eq!(props.len(), 5);
+        assert_eq!(props.get_bool("Visibility"), Some(true));
+        assert_eq!(props.get_f64("Visibility"), Some(1.0));
+        assert_eq!(props.get_f64("Intensity"), Some(0.5));
+        assert_eq!(props.get_f64_3("Color"), Some([1.0, 0.5, 0.0]));
+        assert_eq!(props.get_f64_4("Color"), None);
+        assert_eq!(props.get_string("UVSet"), Some("map1"));
+        assert_eq!(props.get("Compound"), Some(&PropertyValue::Empty));
+        assert!(::std::mem::size_of::<PropertyValue>() <= 40);
+    }
+
+    #[test]
+    fn test_load_properties60() {
+        let mut b = FbxBuilder::new(6100);
+        b.begin_node("Properties60", &[]);
+        b.node(
+            "Property",
+            &[
+                Attr::String("Lcl Translation"),
+                Attr::String("Lcl Translation"),
+                Attr::String("A+"),
+                Attr::F64(1.0),
+                Attr::F64(2.0),
+                Attr::F64(3.0),
+            ],
+        );
+        b.node(
+            "Property",
+            &[Attr::String("Custom"), Attr::String("int"), Attr::String("A+U"), Attr::I32(4)],
+        );
+        b.end_node();
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartFbx(_)));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartNode(_)));
+        let props = Properties70::load_properties60(parser.subtree_parser()).expect("Load error");
+
+        assert_eq!(props.len(), 2);
+        assert_eq!(props.get_f64_3("Lcl Translation"), Some([1.0, 2.0, 3.0]));
+        assert_eq!(props.get_i64("Custom"), Some(4));
+        assert!(props.is_user_defined("Custom"));
+        assert!(!props.is_user_defined("Lcl Translation"));
+    }
+
+    #[test]
+    fn test_user_properties() {
+        let mut b = FbxBuilder::new(7400);
+        write_properties70(
+            &mut b,
+            &[
+                ("Visibility", "bool", "", "A", &[Attr::I32(1)]),
+                ("SpawnWeight", "double", "Number", "A+U", &[Attr::F64(0.25)]),
+                ("Tag", "KString", "", "U", &[Attr::String("enemy")]),
+            ],
+        );
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartFbx(_)));
+        assert!(matches!(parser.next_event().expect("Parse error"), Event::StartNode(_)));
+        let mut props = Properties70::load(parser.subtree_parser()).expect("Load error");
+
+        let user = props.user_properties();
+        assert_eq!(user.keys().cloned().collect::<Vec<_>>(), vec!["SpawnWeight", "Tag"]);
+        assert_eq!(user["SpawnWeight"], &PropertyValue::F64(0.25));
+        assert!(!props.is_user_defined("Visibility"));
+        let node = props.to_generic_node();
+        let tag = node.children
+            .iter()
+            .find(|p| p.first_attr_as::<&str>() == Some("Tag"))
+            .expect("Should exist");
+        assert_eq!(tag.attributes[3].as_str(), Some("U"));
+        assert!(props.set_user_defined("Visibility", true));
+        assert!(!props.set_user_defined("Missing", true));
+        props.remove("Tag");
+        assert_eq!(props.user_properties().len(), 2);
+    }
+}
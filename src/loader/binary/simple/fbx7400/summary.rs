@@ -0,0 +1,98 @@
+//! Scene statistics.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use loader::binary::simple::fbx7400::{Objects, Object, Takes, Video};
+use loader::clips::clips;
+use loader::time::FbxTime;
+
+
+/// Statistics of a scene.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SceneSummary {
+    /// Number of objects by class.
+    pub objects: BTreeMap<String, usize>,
+    /// Number of `Geometry` objects with `Mesh` subclass.
+    pub meshes: usize,
+    /// Total number of control points of meshes.
+    pub vertices: usize,
+    /// Total number of polygons of meshes.
+    pub polygons: usize,
+    /// Total number of polygon vertices of meshes.
+    pub polygon_vertices: usize,
+    /// Number of animation clips (see `loader::clips::clips()`).
+    pub clips: usize,
+    /// Total duration of animation clips.
+    pub clip_duration: FbxTime,
+    /// Number of `Texture` objects.
+    pub textures: usize,
+    /// Total size of embedded media (`Content` of `Video` objects), in bytes.
+    pub embedded_media_size: usize,
+}
+
+impl SceneSummary {
+    /// Collects statistics of the objects and takes.
+    ///
+    /// Mesh arrays not loaded into memory (such as streamed ones) are not counted.
+    pub fn new(objects: &Objects, takes: Option<&Takes>) -> Self {
+        let mut summary = SceneSummary::default();
+        for (_, obj) in objects.iter_ordered() {
+            *summary.objects.entry(obj.class().to_owned()).or_insert(0) += 1;
+            match obj.class() {
+                "Geometry" if obj.subclass() == "Mesh" => summary.add_mesh(obj),
+                "Texture" => summary.textures += 1,
+                "Video" => {
+                    summary.embedded_media_size += Video::from_object(obj)
+                        .content
+                        .map_or(0, |content| content.len());
+                },
+                _ => {},
+            }
+        }
+        let clips = clips(objects, takes);
+        summary.clips = clips.len();
+        summary.clip_duration = clips
+            .iter()
+            .filter_map(|clip| clip.time_span())
+            .filter(|&(start, stop)| start < stop)
+            .fold(FbxTime(0), |sum, (start, stop)| sum + (stop - start));
+        summary
+    }
+
+    /// Adds the counts of the mesh.
+    fn add_mesh(&mut self, obj: &Object) {
+        let attr = |name: &str| obj.node(name).and_then(|node| node.attributes.first());
+        let num_coords = attr("Vertices").and_then(|attr| {
+            attr.as_f64_slice()
+                .map(<[f64]>::len)
+                .or_else(|| attr.as_f32_slice().map(<[f32]>::len))
+        });
+        let indices = attr("PolygonVertexIndex").and_then(|attr| attr.as_i32_slice());
+        self.meshes += 1;
+        self.vertices += num_coords.map_or(0, |n| n / 3);
+        if let Some(indices) = indices {
+            self.polygons += indices.iter().filter(|&&i| i < 0).count();
+            self.polygon_vertices += indices.len();
+        }
+    }
+}
+
+impl fmt::Display for SceneSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Objects: {}", self.objects.values().sum::<usize>())?;
+        for (class, count) in &self.objects {
+            writeln!(f, "  {}: {}", class, count)?;
+        }
+        writeln!(
+            f,
+            "Meshes: {} ({} vertices, {} polygons, {} polygon vertices)",
+            self.meshes,
+            self.vertices,
+            self.polygons,
+            self.polygon_vertices
+        )?;
+        writeln!(f, "Clips: {} ({} seconds)", self.clips, self.clip_duration.seconds())?;
+        writeln!(f, "Textures: {}", self.textures)?;
+        write!(f, "Embedded media: {} bytes", self.embedded_media_size)
+    }
+}
@@ -1,7 +1,7 @@
 //! `Takes` node and its children.
 
 use parser::binary::{Parser, ParserSource, Attributes};
-use loader::binary::simple::{Result, Error};
+use loader::binary::simple::{Result, Error, GenericNode};
 
 
 /// `Takes` node.
@@ -31,7 +31,7 @@ impl Takes {
                     parser.skip_current_node()?;
                 },
                 TakesChildAttrs::Take(attrs) => {
-                    takes.push(Take::load(parser.subtree_parser(), attrs)?);
+                    takes.push(Take::load(parser.subtree_guard(), attrs)?);
                 },
             }
         }
@@ -40,6 +40,13 @@ impl Takes {
             takes: takes,
         })
     }
+
+    /// Converts the takes into a `Takes` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        GenericNode::named("Takes")
+            .with_child(GenericNode::named("Current").with_attr(self.current.as_str()))
+            .with_children(self.takes.iter().map(Take::to_generic_node))
+    }
 }
 
 
@@ -95,6 +102,18 @@ impl Take {
             reference_time: ensure_node_exists!(reference_time, "Take", "ReferenceTime"),
         })
     }
+
+    /// Converts the take into a `Take` node.
+    pub fn to_generic_node(&self) -> GenericNode {
+        let time = |name: &str, (start, stop): (i64, i64)| {
+            GenericNode::named(name).with_attr(start).with_attr(stop)
+        };
+        GenericNode::named("Take")
+            .with_attr(self.name.as_str())
+            .with_child(GenericNode::named("FileName").with_attr(self.filename.as_str()))
+            .with_child(time("LocalTime", self.local_time))
+            .with_child(time("ReferenceTime", self.reference_time))
+    }
 }
 
 
@@ -1,7 +1,9 @@
 //! Generic node and node attribute.
 
-use parser::binary::{Parser, ParserSource, Event, Attributes, Attribute, FbxFooter};
-use parser::binary::Result as ParseResult;
+use parser::binary::{Parser, ParserSource, Event, FbxFooter, DynParser, OwnedEvent};
+use parser::binary::{Error as ParseError, Result as ParseResult};
+pub use parser::binary::{OwnedAttribute, FromOwnedAttribute};
+pub use parser::binary::{OwnedAttributeOptions, LargeBinaries, BinaryRef};
 
 
 /// Generic FBX node.
@@ -21,12 +23,77 @@ impl GenericNode {
         Default::default()
     }
 
+    /// Creates a new `GenericNode` with the given name and no attributes and children.
+    pub fn named<S: Into<String>>(name: S) -> Self {
+        GenericNode {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Appends the attribute and returns the node.
+    pub fn with_attr<T: Into<OwnedAttribute>>(mut self, attr: T) -> Self {
+        self.push_attr(attr);
+        self
+    }
+
+    /// Appends the child node and returns the node.
+    pub fn with_child(mut self, child: GenericNode) -> Self {
+        self.push_child(child);
+        self
+    }
+
+    /// Appends the child nodes and returns the node.
+    pub fn with_children<I>(mut self, children: I) -> Self
+    where
+        I: IntoIterator<Item = GenericNode>,
+    {
+        self.children.extend(children);
+        self
+    }
+
+    /// Appends the attribute.
+    pub fn push_attr<T: Into<OwnedAttribute>>(&mut self, attr: T) {
+        self.attributes.push(attr.into());
+    }
+
+    /// Appends the child node.
+    pub fn push_child(&mut self, child: GenericNode) {
+        self.children.push(child);
+    }
+
+    /// Returns the first child node with the given name.
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut GenericNode> {
+        self.children.iter_mut().find(|child| child.name == name)
+    }
+
+    /// Removes all child nodes with the given name, and returns them.
+    pub fn remove_children(&mut self, name: &str) -> Vec<GenericNode> {
+        let (removed, kept) = ::std::mem::take(&mut self.children)
+            .into_iter()
+            .partition(|child| child.name == name);
+        self.children = kept;
+        removed
+    }
+
     /// Loads all sibling nodes from the given parser.
     ///
     /// This reads N `StartNode` and N+1 { `EndNode` or `EndFbx` }.
     pub fn load_from_parser<R, P>(
         parser: &mut P,
     ) -> ParseResult<(Vec<GenericNode>, Option<FbxFooter>)>
+    where
+        R: ParserSource,
+        P: Parser<R>,
+    {
+        GenericNode::load_from_parser_with(parser, &mut OwnedAttributeOptions::default())
+    }
+
+    /// Loads all sibling nodes from the given parser with the given attribute options.
+    pub fn load_from_parser_with<R, P>(
+        parser: &mut P,
+        options: &mut OwnedAttributeOptions,
+    ) -> ParseResult<(Vec<GenericNode>, Option<FbxFooter>)>
     where
         R: ParserSource,
         P: Parser<R>,
@@ -41,14 +108,21 @@ impl GenericNode {
                     break;
                 },
                 Event::EndNode => break,
-                Event::StartNode(node) => {
+                Event::RawNode(_) => return Err(ParseError::RawNodeUnsupported),
+                Event::StartNode(mut node) => {
                     let name = node.name.to_owned();
-                    let attrs = OwnedAttribute::load_attrs_from_parser_event(node.attributes)?;
+                    if let Some(ref mut strict) = options.strict_coercion {
+                        strict.enter(node.name);
+                    }
+                    let attrs = node.attributes.collect_owned_with(options)?;
                     (name, attrs)
                 },
             };
-            let children = GenericNode::load_from_parser(&mut parser.subtree_parser())?
-                .0;
+            let children =
+                GenericNode::load_from_parser_with(&mut parser.subtree_parser(), options)?.0;
+            if let Some(ref mut strict) = options.strict_coercion {
+                strict.leave();
+            }
             let node = GenericNode {
                 name: name,
                 attributes: attrs,
@@ -59,94 +133,287 @@ impl GenericNode {
         nodes.shrink_to_fit();
         Ok((nodes, footer))
     }
-}
 
+    /// Loads all sibling nodes from the given parser trait object.
+    ///
+    /// Same as `load_from_parser()`, but not generic over the parser.
+    pub fn load_from_dyn_parser(
+        parser: &mut dyn DynParser,
+    ) -> ParseResult<(Vec<GenericNode>, Option<FbxFooter>)> {
+        let mut nodes = Vec::new();
+        let mut footer = None;
+        loop {
+            let node = match parser.next_owned_event()? {
+                OwnedEvent::StartFbx(_) => continue,
+                OwnedEvent::EndFbx(f) => {
+                    footer = f.ok();
+                    break;
+                },
+                OwnedEvent::EndNode => break,
+                OwnedEvent::RawNode(_) => return Err(ParseError::RawNodeUnsupported),
+                OwnedEvent::StartNode(node) => node,
+            };
+            let children = GenericNode::load_from_dyn_parser(&mut *parser.dyn_subtree_parser())?.0;
+            nodes.push(GenericNode {
+                name: node.name,
+                attributes: node.attributes,
+                children: children,
+            });
+        }
+        nodes.shrink_to_fit();
+        Ok((nodes, footer))
+    }
 
-/// Owned node attribute.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub enum OwnedAttribute {
-    /// `bool`.
-    Bool(bool),
-    /// `i16`.
-    I16(i16),
-    /// `i32`.
-    I32(i32),
-    /// `i64`.
-    I64(i64),
-    /// `f32`.
-    F32(f32),
-    /// `f64`.
-    F64(f64),
-    /// `Box<[bool]>`.
-    ArrBool(Box<[bool]>),
-    /// `Box<[i32]>`.
-    ArrI32(Box<[i32]>),
-    /// `Box<[i64]>`.
-    ArrI64(Box<[i64]>),
-    /// `Box<[f32]>`.
-    ArrF32(Box<[f32]>),
-    /// `Box<[f64]>`.
-    ArrF64(Box<[f64]>),
-    /// `String`.
-    String(::std::result::Result<String, Vec<u8>>),
-    /// `Box<[u8]>`.
-    Binary(Box<[u8]>),
-}
+    /// Returns an iterator of child nodes with the given name.
+    pub fn children_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a GenericNode> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
 
-impl OwnedAttribute {
-    /// Loads `OwnedAttribute`s from `parser::binary::Attributes`.
-    pub fn load_attrs_from_parser_event<R>(mut attrs: Attributes<R>) -> ParseResult<Vec<Self>>
-    where
-        R: ParserSource,
-    {
-        let mut result = Vec::with_capacity(attrs.num_attributes() as usize);
-        while let Some(attr) = attrs.next_attribute()? {
-            result.push(Self::load_from_parser_event(attr)?);
+    /// Returns the first child node with the given name.
+    pub fn child(&self, name: &str) -> Option<&GenericNode> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    /// Returns the first descendant node at the given path of node names.
+    ///
+    /// For example, `node.find_path(&["LayerElementUV", "UV"])` returns the first `UV` node in
+    /// `LayerElementUV` children.
+    /// All children with the same name are searched, so a later `LayerElementUV` is used if
+    /// the first one has no `UV` node.
+    /// Returns the node itself if the path is empty.
+    pub fn find_path(&self, path: &[&str]) -> Option<&GenericNode> {
+        match path.split_first() {
+            None => Some(self),
+            Some((name, rest)) => {
+                self.children
+                    .iter()
+                    .filter(|child| child.name == *name)
+                    .filter_map(|child| child.find_path(rest))
+                    .next()
+            },
         }
-        Ok(result)
     }
 
-    /// Loads an `OwnedAttribute` from `parser::binary::Attribute`.
-    pub fn load_from_parser_event<R>(attr: Attribute<R>) -> ::std::io::Result<Self>
+    /// Returns all descendant nodes at the given path of node names, in depth-first order.
+    pub fn find_path_all(&self, path: &[&str]) -> Vec<&GenericNode> {
+        let mut nodes = vec![self];
+        for name in path {
+            nodes = nodes
+                .into_iter()
+                .flat_map(|node| node.children.iter().filter(move |c| c.name == *name))
+                .collect();
+        }
+        nodes
+    }
+
+    /// Returns the attribute at the given index as `T`.
+    ///
+    /// Returns `None` if the attribute doesn't exist or has a different type.
+    pub fn attr_as<'a, T: FromOwnedAttribute<'a>>(&'a self, index: usize) -> Option<T> {
+        self.attributes.get(index).and_then(T::from_owned_attribute)
+    }
+
+    /// Returns the first attribute as `T`.
+    ///
+    /// Returns `None` if the node has no attributes or the first one has a different type.
+    pub fn first_attr_as<'a, T: FromOwnedAttribute<'a>>(&'a self) -> Option<T> {
+        self.attr_as(0)
+    }
+
+    /// Returns an iterator of descendant nodes (excluding the node itself) in depth-first
+    /// pre-order, with their depth (`1` for children).
+    pub fn descendants(&self) -> Descendants<'_> {
+        Descendants {
+            stack: vec![(1, self.children.iter())],
+        }
+    }
+
+    /// Calls the visitor for each descendant node (excluding the node itself) in depth-first
+    /// pre-order.
+    ///
+    /// Children of a node are skipped if the visitor returns `false` for the node.
+    pub fn visit<F>(&self, mut visitor: F)
     where
-        R: ParserSource,
+        F: FnMut(&GenericNode) -> bool,
     {
-        use parser::binary::{PrimitiveAttribute, ArrayAttribute, SpecialAttributeType};
-        Ok(match attr {
-            Attribute::Primitive(PrimitiveAttribute::Bool(v)) => OwnedAttribute::Bool(v),
-            Attribute::Primitive(PrimitiveAttribute::I16(v)) => OwnedAttribute::I16(v),
-            Attribute::Primitive(PrimitiveAttribute::I32(v)) => OwnedAttribute::I32(v),
-            Attribute::Primitive(PrimitiveAttribute::I64(v)) => OwnedAttribute::I64(v),
-            Attribute::Primitive(PrimitiveAttribute::F32(v)) => OwnedAttribute::F32(v),
-            Attribute::Primitive(PrimitiveAttribute::F64(v)) => OwnedAttribute::F64(v),
-            Attribute::Array(ArrayAttribute::Bool(arr)) => {
-                OwnedAttribute::ArrBool(arr.into_vec()?.into_boxed_slice())
-            },
-            Attribute::Array(ArrayAttribute::I32(arr)) => {
-                OwnedAttribute::ArrI32(arr.into_vec()?.into_boxed_slice())
-            },
-            Attribute::Array(ArrayAttribute::I64(arr)) => {
-                OwnedAttribute::ArrI64(arr.into_vec()?.into_boxed_slice())
-            },
-            Attribute::Array(ArrayAttribute::F32(arr)) => {
-                OwnedAttribute::ArrF32(arr.into_vec()?.into_boxed_slice())
-            },
-            Attribute::Array(ArrayAttribute::F64(arr)) => {
-                OwnedAttribute::ArrF64(arr.into_vec()?.into_boxed_slice())
-            },
-            Attribute::Special(v) => {
-                match v.value_type() {
-                    SpecialAttributeType::Binary => {
-                        OwnedAttribute::Binary(v.into_vec()?.into_boxed_slice())
-                    },
-                    SpecialAttributeType::String => {
-                        OwnedAttribute::String(match String::from_utf8(v.into_vec()?) {
-                            Ok(s) => Ok(s),
-                            Err(e) => Err(e.into_bytes()),
-                        })
+        fn visit_children<F>(node: &GenericNode, visitor: &mut F)
+        where
+            F: FnMut(&GenericNode) -> bool,
+        {
+            for child in &node.children {
+                if visitor(child) {
+                    visit_children(child, visitor);
+                }
+            }
+        }
+        visit_children(self, &mut visitor);
+    }
+}
+
+
+/// Iterator of descendant nodes of a `GenericNode`.
+///
+/// See `GenericNode::descendants()`.
+#[derive(Debug, Clone)]
+pub struct Descendants<'a> {
+    /// Stack of depths and sibling iterators.
+    stack: Vec<(usize, ::std::slice::Iter<'a, GenericNode>)>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = (usize, &'a GenericNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, node) = {
+                let &mut (depth, ref mut iter) = self.stack.last_mut()?;
+                match iter.next() {
+                    Some(node) => (depth, node),
+                    None => {
+                        self.stack.pop();
+                        continue;
                     },
                 }
-            },
-        })
+            };
+            self.stack.push((depth + 1, node.children.iter()));
+            return Some((depth, node));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use parser::binary::RootParser;
+    use test_util::{Attr, FbxBuilder};
+    use super::{GenericNode, OwnedAttribute};
+
+    fn do_test_load_attributes(version: u32, compress_arrays: bool) {
+        let mut b = FbxBuilder::new(version);
+        b.set_compress_arrays(compress_arrays);
+        b.begin_node(
+            "Node",
+            &[
+                Attr::Bool(true),
+                Attr::Bool(false),
+                Attr::I16(-2),
+                Attr::I32(3),
+                Attr::I64(-4),
+                Attr::F32(5.5),
+                Attr::F64(-6.25),
+                Attr::ArrBool(&[true, false]),
+                Attr::ArrI32(&[1, -2, 3]),
+                Attr::ArrI64(&[]),
+                Attr::ArrF32(&[0.5]),
+                Attr::ArrF64(&[1.5, 2.5]),
+                Attr::String("str\u{0}\u{1}ing"),
+                Attr::Binary(&[0, 1, 2]),
+            ],
+        );
+        b.node("Child", &[]);
+        b.end_node();
+        b.node("Sibling", &[Attr::I32(42)]);
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        let (nodes, footer) = GenericNode::load_from_parser(&mut parser).expect("Parse error");
+        assert!(parser.warnings().is_empty(), "{:?}", parser.warnings());
+        assert_eq!(footer.expect("Footer should be loaded").version, version);
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes[0].attributes,
+            vec![
+                OwnedAttribute::Bool(true),
+                OwnedAttribute::Bool(false),
+                OwnedAttribute::I16(-2),
+                OwnedAttribute::I32(3),
+                OwnedAttribute::I64(-4),
+                OwnedAttribute::F32(5.5),
+                OwnedAttribute::F64(-6.25),
+                OwnedAttribute::ArrBool(vec![true, false].into_boxed_slice()),
+                OwnedAttribute::ArrI32(vec![1, -2, 3].into_boxed_slice()),
+                OwnedAttribute::ArrI64(vec![].into_boxed_slice()),
+                OwnedAttribute::ArrF32(vec![0.5].into_boxed_slice()),
+                OwnedAttribute::ArrF64(vec![1.5, 2.5].into_boxed_slice()),
+                OwnedAttribute::String(Ok("str\u{0}\u{1}ing".to_owned())),
+                OwnedAttribute::Binary(vec![0, 1, 2].into_boxed_slice()),
+            ]
+        );
+        assert_eq!(nodes[0].children.len(), 1);
+        assert_eq!(nodes[0].children[0].name, "Child");
+        assert_eq!(nodes[1].name, "Sibling");
+        assert_eq!(nodes[1].attributes, vec![OwnedAttribute::I32(42)]);
+    }
+
+    fn node(
+        name: &str,
+        attributes: Vec<OwnedAttribute>,
+        children: Vec<GenericNode>,
+    ) -> GenericNode {
+        GenericNode {
+            name: name.to_owned(),
+            attributes: attributes,
+            children: children,
+        }
+    }
+
+    #[test]
+    fn test_query() {
+        let uv = |values: Vec<f64>| {
+            node("UV", vec![OwnedAttribute::ArrF64(values.into_boxed_slice())], vec![])
+        };
+        let root = node(
+            "Geometry",
+            vec![OwnedAttribute::I64(42), OwnedAttribute::String(Ok("Mesh".to_owned()))],
+            vec![
+                node("LayerElementUV", vec![OwnedAttribute::I32(0)], vec![]),
+                node("LayerElementUV", vec![OwnedAttribute::I32(1)], vec![uv(vec![0.5])]),
+                node("LayerElementUV", vec![OwnedAttribute::I32(2)], vec![uv(vec![1.0])]),
+            ],
+        );
+
+        assert_eq!(root.first_attr_as::<i64>(), Some(42));
+        assert_eq!(root.first_attr_as::<i32>(), None);
+        assert_eq!(root.attr_as::<&str>(1), Some("Mesh"));
+        assert_eq!(root.attr_as::<String>(2), None);
+        assert_eq!(root.children_named("LayerElementUV").count(), 3);
+        let first_uv = root.find_path(&["LayerElementUV", "UV"]).expect("UV should be found");
+        assert_eq!(first_uv.first_attr_as::<&[f64]>(), Some(&[0.5][..]));
+        assert_eq!(root.find_path_all(&["LayerElementUV", "UV"]).len(), 2);
+        assert!(root.find_path(&["UV"]).is_none());
+        assert_eq!(root.find_path(&[]), Some(&root));
+        assert_eq!(
+            root.descendants().map(|(depth, node)| (depth, node.name.as_str())).collect::<Vec<_>>(),
+            vec![
+                (1, "LayerElementUV"),
+                (1, "LayerElementUV"),
+                (2, "UV"),
+                (1, "LayerElementUV"),
+                (2, "UV"),
+            ]
+        );
+        let mut visited = 0;
+        root.visit(|node| {
+            visited += 1;
+            node.first_attr_as::<i32>() != Some(1)
+        });
+        assert_eq!(visited, 4);
+    }
+
+    #[test]
+    fn test_load_attributes() {
+        do_test_load_attributes(7400, false);
+        do_test_load_attributes(7500, false);
+    }
+
+    #[cfg(any(feature = "flate2", feature = "libflate"))]
+    #[test]
+    fn test_load_compressed_attributes() {
+        do_test_load_attributes(7400, true);
+        do_test_load_attributes(7500, true);
     }
 }
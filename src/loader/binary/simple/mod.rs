@@ -1,8 +1,9 @@
 //! Simple binary loader.
 
-use parser::binary::{Parser, ParserSource, Event};
+use parser::binary::{Parser, ParserSource, Event, FbxVersion};
 pub use self::error::{Result, Error};
-pub use self::generic::{GenericNode, OwnedAttribute};
+pub use self::generic::{GenericNode, OwnedAttribute, FromOwnedAttribute, Descendants};
+pub use self::generic::{OwnedAttributeOptions, LargeBinaries, BinaryRef};
 
 pub mod error;
 pub mod generic;
@@ -37,10 +38,27 @@ impl<R: ParserSource, P: Parser<R>> FbxLoader7400<R, P> {
     /// Load FBX 7.4 compatible data.
     pub fn load<O>(self, objs_loader: O) -> Result<fbx7400::Fbx7400<O>>
     where
-        O: fbx7400::LoadObjects7400<Reader = R>,
+        O: fbx7400::LoadObjectsWithContext7400<Reader = R>,
     {
         fbx7400::Fbx7400::load_from_parser(self.inner.version, self.inner.parser, objs_loader)
     }
+
+    /// Load FBX 7.4 compatible data with the given options.
+    pub fn load_with_options<O>(
+        self,
+        objs_loader: O,
+        options: &fbx7400::LoadOptions,
+    ) -> Result<fbx7400::Fbx7400<O>>
+    where
+        O: fbx7400::LoadObjectsWithContext7400<Reader = R>,
+    {
+        fbx7400::Fbx7400::load_from_parser_with_options(
+            self.inner.version,
+            self.inner.parser,
+            objs_loader,
+            options,
+        )
+    }
 }
 
 
@@ -54,31 +72,23 @@ pub enum FbxLoader<R, P> {
 impl<R: ParserSource, P: Parser<R>> FbxLoader<R, P> {
     /// Loads FBX structure from the given parser.
     ///
-    /// # Panics
-    /// Panics if the parser has already emitted some event (i.e. if the given parser didn't return
-    /// the `StartFbx` first).
+    /// Returns `Error::UnexpectedEvent` if the parser has already emitted some event (i.e. if the
+    /// given parser didn't return the `StartFbx` first).
     pub fn load_from_parser(mut parser: P) -> Result<Self> {
         let version = match parser.next_event()? {
             Event::StartFbx(header) => header.version,
-            ev => {
-                panic!(
-                    "FBX binary parser should return `StartFbx` as the first event but got \
-                        `{:?}`",
-                    ev
-                )
-            },
+            ref ev => return Err(Error::unexpected_event(ev)),
         };
         let inner = FbxLoaderInner {
             version: version,
             parser: parser,
             _r: Default::default(),
         };
-        match version {
-            7400...7599 => Ok(FbxLoader::Fbx7400(FbxLoader7400::new(inner))),
-            _ => {
-                error!("Unsupported FBX version: {}", version);
-                unimplemented!()
-            },
+        if FbxVersion(version).is_supported_by_simple_loader() {
+            Ok(FbxLoader::Fbx7400(FbxLoader7400::new(inner)))
+        } else {
+            error!("Unsupported FBX version: {}", version);
+            Err(Error::UnsupportedVersion(version))
         }
     }
 }
@@ -0,0 +1,101 @@
+//! Bounding boxes of scenes.
+
+use loader::animation::global_transform;
+use loader::binary::simple::fbx7400::{Objects, Connections, Mesh};
+use loader::math::Aabb;
+
+
+/// Returns the bounding box of all meshes in the scene, in the global space.
+///
+/// Each mesh model is transformed by its non-animated global transform.
+/// Geometries which fail to load as meshes are ignored.
+/// Returns `None` if the scene has no meshes with bounds.
+pub fn scene_aabb(objects: &Objects, connections: &Connections) -> Option<Aabb> {
+    let mut result: Option<Aabb> = None;
+    let models = objects
+        .iter_ordered()
+        .filter(|&(_, obj)| obj.class() == "Model" && obj.subclass() == "Mesh");
+    for (id, _) in models {
+        let meshes = connections
+            .object_sources(id)
+            .filter_map(|source| objects.object(source))
+            .filter(|obj| obj.class() == "Geometry" && obj.subclass() == "Mesh")
+            .filter_map(|obj| Mesh::from_object(obj).ok());
+        for mesh in meshes {
+            let aabb = match mesh.compute_aabb() {
+                Some(aabb) => aabb.transform(&global_transform(objects, connections, id)),
+                None => continue,
+            };
+            result = Some(result.map_or(aabb, |r| r.union(&aabb)));
+        }
+    }
+    result
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::Mesh;
+    use loader::math::Aabb;
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::scene_aabb;
+
+    #[test]
+    fn test_scene_aabb() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "Geometry",
+            &[Attr::I64(1), Attr::String("Tri\u{0}\u{1}Geometry"), Attr::String("Mesh")],
+        );
+        let bbox_min = [-1.0, -1.0, -1.0].map(Attr::F64);
+        let bbox_max = [1.0, 1.0, 1.0].map(Attr::F64);
+        write_properties70(
+            &mut b,
+            &[
+                ("BBoxMin", "Vector3D", "Vector", "", &bbox_min),
+                ("BBoxMax", "Vector3D", "Vector", "", &bbox_max),
+            ],
+        );
+        b.node("Vertices", &[Attr::ArrF64(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0])]);
+        b.node("PolygonVertexIndex", &[Attr::ArrI32(&[0, 1, !2])]);
+        b.end_node();
+        for &(id, x) in &[(10, 0.0), (11, 10.0)] {
+            b.begin_node(
+                "Model",
+                &[Attr::I64(id), Attr::String("\u{0}\u{1}Model"), Attr::String("Mesh")],
+            );
+            let translation = [x, 0.0, 0.0].map(Attr::F64);
+            write_properties70(
+                &mut b,
+                &[("Lcl Translation", "Lcl Translation", "", "A", &translation)],
+            );
+            b.end_node();
+        }
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        for &(source, destination) in &[(10, 0), (11, 10), (1, 10), (1, 11)] {
+            b.node("C", &[Attr::String("OO"), Attr::I64(source), Attr::I64(destination)]);
+        }
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let mut mesh = Mesh::from_object(fbx.objects.object(1).expect("Should exist"))
+            .expect("Invalid mesh");
+        assert_eq!(mesh.bbox_max, Some([1.0, 1.0, 1.0]));
+        let local = Aabb {
+            min: [0.0, 0.0, 0.0],
+            max: [1.0, 2.0, 0.0],
+        };
+        assert_eq!(mesh.compute_aabb(), Some(local));
+        mesh.vertices.clear();
+        assert_eq!(mesh.compute_aabb().map(|aabb| aabb.min), Some([-1.0, -1.0, -1.0]));
+
+        // The child model is translated by its parent and itself.
+        let scene = scene_aabb(&fbx.objects, &fbx.connections).expect("Scene should have bounds");
+        assert_eq!(scene.min, [0.0, 0.0, 0.0]);
+        assert_eq!(scene.max, [11.0, 2.0, 0.0]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Animation clips from animation stacks and legacy takes.
+//!
+//! Files may have both `AnimationStack` objects and the legacy `Takes` node, and their names
+//! are not always consistent.
+//! `clips()` reconciles them into a single list.
+
+use loader::binary::simple::fbx7400::{AnimationStack, Objects, Take, Takes};
+use loader::time::FbxTime;
+
+
+/// Animation clip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clip {
+    /// Name of the clip.
+    ///
+    /// The name of the animation stack is used if available.
+    pub name: String,
+    /// Object ID of the animation stack, if the clip has one.
+    pub stack: Option<i64>,
+    /// Index of the take in `Takes::takes`, if the clip has one.
+    pub take: Option<usize>,
+    /// Local time span (start and stop).
+    pub local_time: Option<(FbxTime, FbxTime)>,
+    /// Reference time span (start and stop).
+    pub reference_time: Option<(FbxTime, FbxTime)>,
+    /// Whether the clip is the current take (`Takes::current`).
+    pub is_default: bool,
+}
+
+impl Clip {
+    /// Returns the time span of the clip.
+    ///
+    /// The local time span is preferred, and the reference time span is used if the local
+    /// time span is unavailable.
+    pub fn time_span(&self) -> Option<(FbxTime, FbxTime)> {
+        self.local_time.or(self.reference_time)
+    }
+}
+
+
+/// Returns the animation clips of the file.
+///
+/// Animation stacks come first in object order, followed by takes without corresponding
+/// stacks.
+/// A take corresponds to the stack with the same name, and if exactly one stack and one take
+/// are left unmatched, they are regarded as the same clip.
+/// Time spans of stacks are preferred, and those of takes fill missing ones.
+pub fn clips(objects: &Objects, takes: Option<&Takes>) -> Vec<Clip> {
+    let stacks = objects
+        .iter_ordered()
+        .filter(|&(_, obj)| obj.class() == "AnimStack" || obj.class() == "AnimationStack")
+        .map(|(_, obj)| AnimationStack::from_object(obj))
+        .collect::<Vec<_>>();
+    let take_list = takes.map_or(&[][..], |takes| &takes.takes[..]);
+
+    let mut take_for_stack = stacks
+        .iter()
+        .map(|stack| take_list.iter().position(|take| take.name == stack.props.name))
+        .collect::<Vec<_>>();
+    let unmatched_stacks = (0..stacks.len())
+        .filter(|&i| take_for_stack[i].is_none())
+        .collect::<Vec<_>>();
+    let unmatched_takes = (0..take_list.len())
+        .filter(|i| !take_for_stack.contains(&Some(*i)))
+        .collect::<Vec<_>>();
+    if unmatched_stacks.len() == 1 && unmatched_takes.len() == 1 {
+        take_for_stack[unmatched_stacks[0]] = Some(unmatched_takes[0]);
+    }
+
+    let mut clips = stacks
+        .iter()
+        .zip(&take_for_stack)
+        .map(|(stack, &take)| {
+            let take_clip = take.map(|i| take_clip(i, &take_list[i]));
+            let span = |start, stop| Some((start?, stop?));
+            let local = span(stack.local_start(), stack.local_stop());
+            let reference = span(stack.reference_start(), stack.reference_stop());
+            Clip {
+                name: stack.props.name.clone(),
+                stack: Some(stack.props.id),
+                take: take,
+                local_time: local.or_else(|| take_clip.as_ref()?.local_time),
+                reference_time: reference.or_else(|| take_clip.as_ref()?.reference_time),
+                is_default: false,
+            }
+        })
+        .collect::<Vec<_>>();
+    clips.extend(
+        take_list
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| !take_for_stack.contains(&Some(i)))
+            .map(|(i, take)| take_clip(i, take)),
+    );
+
+    if let Some(takes) = takes {
+        let current = clips.iter_mut().find(|clip| {
+            clip.name == takes.current ||
+                clip.take.is_some_and(|i| take_list[i].name == takes.current)
+        });
+        if let Some(clip) = current {
+            clip.is_default = true;
+        }
+    }
+    clips
+}
+
+
+/// Creates a clip from the take.
+fn take_clip(index: usize, take: &Take) -> Clip {
+    let span = |(start, stop): (i64, i64)| Some((FbxTime(start), FbxTime(stop)));
+    Clip {
+        name: take.name.clone(),
+        stack: None,
+        take: Some(index),
+        local_time: span(take.local_time),
+        reference_time: span(take.reference_time),
+        is_default: false,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::{Take, Takes};
+    use loader::time::FbxTime;
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::clips;
+
+    #[test]
+    fn test_clips() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &(id, name_class, stop) in &[
+            (1, "Walk\u{0}\u{1}AnimStack", Some(10)),
+            (2, "Armature|Run\u{0}\u{1}AnimStack", None),
+        ]
+        {
+            b.begin_node(
+                "AnimationStack",
+                &[Attr::I64(id), Attr::String(name_class), Attr::String("")],
+            );
+            if let Some(stop) = stop {
+                write_properties70(
+                    &mut b,
+                    &[
+                        ("LocalStart", "KTime", "Time", "", &[Attr::I64(0)]),
+                        ("LocalStop", "KTime", "Time", "", &[Attr::I64(stop)]),
+                    ],
+                );
+            }
+            b.end_node();
+        }
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let objects = load_objects(data).objects;
+        let take = |name: &str, stop: i64| Take {
+            name: name.to_owned(),
+            filename: String::new(),
+            local_time: (0, stop),
+            reference_time: (0, stop),
+        };
+        let mut takes = Takes {
+            current: "Run".to_owned(),
+            takes: vec![take("Walk", 20), take("Run", 30)],
+        };
+
+        let result = clips(&objects, Some(&takes));
+        let summary = result
+            .iter()
+            .map(|c| (c.name.as_str(), c.stack, c.take, c.is_default))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("Walk", Some(1), Some(0), false),
+                ("Armature|Run", Some(2), Some(1), true),
+            ]
+        );
+        assert_eq!(result[0].time_span(), Some((FbxTime(0), FbxTime(10))));
+        assert_eq!(result[0].reference_time, Some((FbxTime(0), FbxTime(20))));
+        assert_eq!(result[1].time_span(), Some((FbxTime(0), FbxTime(30))));
+
+        takes.takes.push(take("Idle", 40));
+        let names = clips(&objects, Some(&takes))
+            .into_iter()
+            .map(|c| c.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Walk", "Armature|Run", "Run", "Idle"]);
+    }
+}
@@ -0,0 +1,391 @@
+//! Unit and axis conversion of cameras, lights, and meshes.
+//!
+//! Distances of a scene are scaled by `ConvertOptions::unit_scale`, and camera and light
+//! parameters are converted so that the converted scene is rendered consistently.
+
+use loader::binary::simple::fbx7400::{GlobalSettings, Mesh, NodeAttribute, Properties70};
+use loader::math::{Matrix4, Vector3};
+
+
+/// Millimeters per inch.
+const MM_PER_INCH: f64 = 25.4;
+
+
+/// Rotation applied to a camera looking at -Z (with +Y up) to look at +X as FBX cameras do.
+///
+/// Post-multiply this to the global transform of a camera model to get the view transform for
+/// renderers using -Z forward cameras.
+pub const CAMERA_VIEW_CORRECTION: Matrix4 = [
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [-1.0, 0.0, 0.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+
+/// Rotation applied to a light pointing at -Z to point at -Y as FBX lights do.
+///
+/// Post-multiply this to the global transform of a light model for renderers using -Z forward
+/// lights.
+pub const LIGHT_DIRECTION_CORRECTION: Matrix4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 0.0, -1.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+
+/// Options of the conversion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvertOptions {
+    /// Scale multiplied to distances (for example, `0.01` converts centimeters into meters).
+    pub unit_scale: f64,
+    /// Whether camera clip planes and focus distance are scaled.
+    pub convert_cameras: bool,
+    /// Whether light intensity and attenuation distances are converted.
+    ///
+    /// Intensity of a light with decay is scaled so that the illuminance at the scaled
+    /// distances is unchanged.
+    pub convert_lights: bool,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        ConvertOptions {
+            unit_scale: 1.0,
+            convert_cameras: true,
+            convert_lights: true,
+        }
+    }
+}
+
+impl ConvertOptions {
+    /// Creates options to convert the scene units into meters.
+    ///
+    /// `UnitScaleFactor` (centimeters per unit) of the global settings is used, and `1.0` is
+    /// assumed if it is missing.
+    pub fn to_meters(settings: &GlobalSettings) -> Self {
+        ConvertOptions {
+            unit_scale: settings.unit_scale_factor().unwrap_or(1.0) * 0.01,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the scale for cameras.
+    fn camera_scale(&self) -> f64 {
+        if self.convert_cameras { self.unit_scale } else { 1.0 }
+    }
+
+    /// Returns the scale for lights.
+    fn light_scale(&self) -> f64 {
+        if self.convert_lights { self.unit_scale } else { 1.0 }
+    }
+}
+
+
+/// Converted camera parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraParams {
+    /// Horizontal field of view in degrees.
+    pub horizontal_fov: f64,
+    /// Vertical field of view in degrees.
+    pub vertical_fov: f64,
+    /// Aspect ratio (width / height).
+    pub aspect_ratio: f64,
+    /// Near clip plane distance.
+    pub near: f64,
+    /// Far clip plane distance.
+    pub far: f64,
+    /// Focus distance.
+    pub focus_distance: Option<f64>,
+    /// Focal length in millimeters.
+    pub focal_length: Option<f64>,
+    /// Film aperture (width and height) in millimeters.
+    pub film_size: Option<[f64; 2]>,
+}
+
+
+/// Converts the camera node attribute.
+///
+/// Field of view is computed from the focal length and the film aperture if available, and
+/// `FieldOfView` (assumed to be horizontal) is used otherwise.
+pub fn convert_camera(attr: &NodeAttribute, options: &ConvertOptions) -> CameraParams {
+    let empty = Properties70::new();
+    let props = attr.properties.properties.as_ref().unwrap_or(&empty);
+    let scale = options.camera_scale();
+
+    let film_size = match (props.get_f64("FilmWidth"), props.get_f64("FilmHeight")) {
+        (Some(w), Some(h)) if w > 0.0 && h > 0.0 => Some([w * MM_PER_INCH, h * MM_PER_INCH]),
+        _ => None,
+    };
+    let focal_length = props.get_f64("FocalLength").filter(|&v| v > 0.0);
+    let aspect_ratio = match (props.get_f64("AspectWidth"), props.get_f64("AspectHeight")) {
+        (Some(w), Some(h)) if w > 0.0 && h > 0.0 => w / h,
+        _ => film_size.map_or(4.0 / 3.0, |s| s[0] / s[1]),
+    };
+    let fov = |size: f64, focal: f64| (size / (2.0 * focal)).atan().to_degrees() * 2.0;
+    let (horizontal_fov, vertical_fov) = match (film_size, focal_length) {
+        (Some(size), Some(focal)) => (fov(size[0], focal), fov(size[1], focal)),
+        _ => {
+            let h = props.get_f64("FieldOfView").unwrap_or(40.0);
+            let v = ((h / 2.0).to_radians().tan() / aspect_ratio).atan().to_degrees() * 2.0;
+            (h, v)
+        },
+    };
+
+    CameraParams {
+        horizontal_fov: horizontal_fov,
+        vertical_fov: vertical_fov,
+        aspect_ratio: aspect_ratio,
+        near: props.get_f64("NearPlane").unwrap_or(10.0) * scale,
+        far: props.get_f64("FarPlane").unwrap_or(4000.0) * scale,
+        focus_distance: props.get_f64("FocusDistance").map(|v| v * scale),
+        focal_length: focal_length,
+        film_size: film_size,
+    }
+}
+
+
+/// Type of a light (`LightType` property).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LightType {
+    /// Point light.
+    Point,
+    /// Directional light.
+    Directional,
+    /// Spot light.
+    Spot,
+    /// Area light.
+    Area,
+    /// Volume light.
+    Volume,
+    /// Unknown type.
+    Unknown(i64),
+}
+
+impl LightType {
+    /// Creates a `LightType` from the value of `LightType` property.
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            0 => LightType::Point,
+            1 => LightType::Directional,
+            2 => LightType::Spot,
+            3 => LightType::Area,
+            4 => LightType::Volume,
+            v => LightType::Unknown(v),
+        }
+    }
+}
+
+
+/// Converted light parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightParams {
+    /// Type of the light.
+    pub light_type: LightType,
+    /// Color.
+    pub color: Vector3,
+    /// Intensity, where `1.0` corresponds to FBX intensity `100`.
+    pub intensity: f64,
+    /// Exponent of the distance decay (`0` for none, `1` for linear, `2` for quadratic, and
+    /// `3` for cubic).
+    pub decay: i32,
+    /// Distance where the decay starts.
+    pub decay_start: f64,
+    /// Far attenuation range, if enabled.
+    pub far_attenuation: Option<[f64; 2]>,
+    /// Inner cone angle of spot lights in degrees.
+    pub inner_angle: f64,
+    /// Outer cone angle of spot lights in degrees.
+    pub outer_angle: f64,
+}
+
+
+/// Converts the light node attribute.
+pub fn convert_light(attr: &NodeAttribute, options: &ConvertOptions) -> LightParams {
+    let empty = Properties70::new();
+    let props = attr.properties.properties.as_ref().unwrap_or(&empty);
+    let scale = options.light_scale();
+
+    let light_type = LightType::from_i64(props.get_i64("LightType").unwrap_or(0));
+    let decay = match props.get_i64("DecayType").unwrap_or(0) {
+        v @ 0..=3 => v as i32,
+        _ => 0,
+    };
+    // With the distances scaled by `scale`, `intensity / distance^decay` is kept by scaling the
+    // intensity by `scale^decay`. Directional lights have no decay.
+    let intensity_scale = match light_type {
+        LightType::Directional => 1.0,
+        _ => scale.powi(decay),
+    };
+    let far_attenuation = if props.get_bool("EnableFarAttenuation").unwrap_or(false) {
+        let start = props.get_f64("FarAttenuationStart").unwrap_or(0.0);
+        let end = props.get_f64("FarAttenuationEnd").unwrap_or(0.0);
+        Some([start * scale, end * scale])
+    } else {
+        None
+    };
+
+    LightParams {
+        light_type: light_type,
+        color: props.get_f64_3("Color").unwrap_or([1.0; 3]),
+        intensity: props.get_f64("Intensity").unwrap_or(100.0) * 0.01 * intensity_scale,
+        decay: decay,
+        decay_start: props.get_f64("DecayStart").unwrap_or(0.0) * scale,
+        far_attenuation: far_attenuation,
+        inner_angle: props.get_f64("InnerAngle").unwrap_or(0.0),
+        outer_angle: props.get_f64("OuterAngle").unwrap_or(45.0),
+    }
+}
+
+
+/// Returns whether the transform changes handedness (i.e. it contains a reflection).
+pub fn is_handedness_flip(transform: &Matrix4) -> bool {
+    let m = |col: usize, row: usize| transform[col][row];
+    let det = m(0, 0) * (m(1, 1) * m(2, 2) - m(2, 1) * m(1, 2))
+        - m(1, 0) * (m(0, 1) * m(2, 2) - m(2, 1) * m(0, 2))
+        + m(2, 0) * (m(0, 1) * m(1, 2) - m(1, 1) * m(0, 2));
+    det < 0.0
+}
+
+
+/// Fixes the polygon winding of the mesh transformed by the given axis conversion.
+///
+/// If the transform changes handedness, the winding of the polygons is flipped so that front
+/// faces stay front, and normals are also negated if `negate_normals` is true (use it when the
+/// normals are not transformed together with the vertices).
+/// Returns whether the mesh is modified.
+pub fn fix_winding(mesh: &mut Mesh, transform: &Matrix4, negate_normals: bool) -> bool {
+    if !is_handedness_flip(transform) {
+        return false;
+    }
+    mesh.flip_winding();
+    if negate_normals {
+        mesh.negate_normals();
+    }
+    true
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::{NodeAttribute, NodeAttributeKind, ObjectProperties};
+    use loader::binary::simple::fbx7400::{Properties70, PropertyValue, TemplatedProperties};
+    use loader::binary::simple::fbx7400::{LayerElement, MappingMode, Mesh, ReferenceMode};
+    use super::{ConvertOptions, LightType, convert_camera, convert_light, fix_winding};
+
+    fn attr(kind: NodeAttributeKind, props: &[(&str, PropertyValue)]) -> NodeAttribute {
+        let mut properties = Properties70::new();
+        for &(name, ref value) in props {
+            properties.insert(name, value.clone());
+        }
+        NodeAttribute {
+            props: ObjectProperties {
+                id: 1,
+                name: String::new(),
+                class: "NodeAttribute".to_owned(),
+                subclass: kind.subclass().to_owned(),
+            },
+            kind: kind,
+            type_flags: Vec::new(),
+            properties: TemplatedProperties {
+                properties: Some(properties),
+                template: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_convert_camera_and_light() {
+        let options = ConvertOptions {
+            unit_scale: 0.01,
+            ..Default::default()
+        };
+
+        let camera = attr(
+            NodeAttributeKind::Camera,
+            &[
+                ("FilmWidth", PropertyValue::F64(36.0 / 25.4)),
+                ("FilmHeight", PropertyValue::F64(24.0 / 25.4)),
+                ("FocalLength", PropertyValue::F64(18.0)),
+                ("NearPlane", PropertyValue::F64(10.0)),
+            ],
+        );
+        let params = convert_camera(&camera, &options);
+        assert!((params.horizontal_fov - 90.0).abs() < 1e-9);
+        assert!((params.aspect_ratio - 1.5).abs() < 1e-9);
+        assert!((params.near - 0.1).abs() < 1e-12);
+
+        let light = attr(
+            NodeAttributeKind::Light,
+            &[
+                ("LightType", PropertyValue::I64(0)),
+                ("Intensity", PropertyValue::F64(200.0)),
+                ("DecayType", PropertyValue::I64(2)),
+                ("DecayStart", PropertyValue::F64(100.0)),
+            ],
+        );
+        let params = convert_light(&light, &options);
+        assert_eq!(params.light_type, LightType::Point);
+        assert!((params.intensity - 2.0e-4).abs() < 1e-12);
+        assert!((params.decay_start - 1.0).abs() < 1e-12);
+        let unscaled = convert_light(
+            &light,
+            &ConvertOptions {
+                convert_lights: false,
+                ..options
+            },
+        );
+        assert_eq!(unscaled.intensity, 2.0);
+    }
+
+    #[test]
+    fn test_fix_winding() {
+        let normal = |values: Vec<[f64; 3]>, mapping, indices: Vec<i32>| LayerElement {
+            index: 0,
+            name: String::new(),
+            mapping: mapping,
+            reference: if indices.is_empty() {
+                ReferenceMode::Direct
+            } else {
+                ReferenceMode::IndexToDirect
+            },
+            direct: values,
+            indices: indices,
+        };
+        let mut mesh = Mesh {
+            props: ObjectProperties {
+                id: 1,
+                name: String::new(),
+                class: "Geometry".to_owned(),
+                subclass: "Mesh".to_owned(),
+            },
+            vertices: vec![[0.0; 3]; 5],
+            polygon_vertex_index: vec![0, 1, !2, 1, 3, 4, !2, 0],
+            normals: vec![
+                normal(vec![[0.0, 0.0, 1.0]], MappingMode::ByControlPoint, vec![]),
+                normal(vec![[1.0, 0.0, 0.0]; 2], MappingMode::ByPolygonVertex, (0..7).collect()),
+            ],
+            uv_sets: Vec::new(),
+            colors: Vec::new(),
+            materials: Vec::new(),
+            bbox_min: None,
+            bbox_max: None,
+        };
+
+        let identity = [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        assert!(!fix_winding(&mut mesh, &identity, true));
+        let mut mirror = identity;
+        mirror[0][0] = -1.0;
+        assert!(fix_winding(&mut mesh, &mirror, true));
+
+        assert_eq!(mesh.polygon_vertex_index, vec![2, 1, !0, 2, 4, 3, !1, 0]);
+        assert_eq!(mesh.normals[0].direct, vec![[0.0, 0.0, -1.0]]);
+        assert_eq!(mesh.normals[1].indices, vec![2, 1, 0, 6, 5, 4, 3]);
+    }
+}
@@ -0,0 +1,301 @@
+//! Material interpretation.
+
+use loader::binary::simple::fbx7400::{Object, Connections, Properties70, PropertyValue};
+
+
+/// Shading model of a material.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaterialKind {
+    /// Lambert material.
+    Lambert,
+    /// Phong material.
+    Phong,
+    /// Stingray PBS material of Maya.
+    StingrayPbs,
+    /// Physical Material of 3ds Max.
+    PhysicalMaterial,
+    /// Unknown shading model, interpreted as Lambert or Phong.
+    Unknown,
+}
+
+
+/// Normalized material description with metallic-roughness model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardMaterial {
+    /// Object ID of the material.
+    pub id: i64,
+    /// Material name.
+    pub name: String,
+    /// Interpreted shading model.
+    pub kind: MaterialKind,
+    /// Base color (linear RGB).
+    pub base_color: [f64; 3],
+    /// Base color texture.
+    pub base_color_texture: Option<i64>,
+    /// Opacity.
+    pub opacity: f64,
+    /// Metallic factor.
+    pub metallic: f64,
+    /// Metallic texture.
+    pub metallic_texture: Option<i64>,
+    /// Roughness factor.
+    pub roughness: f64,
+    /// Roughness texture.
+    pub roughness_texture: Option<i64>,
+    /// Emissive color, multiplied by its intensity.
+    pub emissive: [f64; 3],
+    /// Emissive texture.
+    pub emissive_texture: Option<i64>,
+    /// Normal map texture.
+    pub normal_texture: Option<i64>,
+}
+
+impl StandardMaterial {
+    /// Interprets the `Material` object.
+    ///
+    /// Textures connected to the material properties are resolved using the connections.
+    /// Properties missing in the object are taken from its property template, if resolved.
+    pub fn from_object(obj: &Object, connections: &Connections) -> Self {
+        let props = obj.properties_with_template().materialize();
+        let props = &*props;
+        let binding = Binding {
+            id: obj.id(),
+            connections: connections,
+        };
+        let kind = material_kind(obj, props);
+        let mut material = StandardMaterial {
+            id: obj.id(),
+            name: obj.name().to_owned(),
+            kind: kind,
+            base_color: [0.8, 0.8, 0.8],
+            base_color_texture: None,
+            opacity: 1.0,
+            metallic: 0.0,
+            metallic_texture: None,
+            roughness: 1.0,
+            roughness_texture: None,
+            emissive: [0.0, 0.0, 0.0],
+            emissive_texture: None,
+            normal_texture: None,
+        };
+        match kind {
+            MaterialKind::StingrayPbs => material.load_stingray_pbs(props, &binding),
+            MaterialKind::PhysicalMaterial => material.load_physical_material(props, &binding),
+            MaterialKind::Lambert | MaterialKind::Phong | MaterialKind::Unknown => {
+                material.load_lambert_phong(props, &binding)
+            },
+        }
+        material
+    }
+
+    /// Loads properties of Lambert and Phong materials.
+    fn load_lambert_phong(&mut self, props: &Properties70, binding: &Binding) {
+        if let Some(color) = props.get_f64_3("DiffuseColor") {
+            let factor = props.get_f64("DiffuseFactor").unwrap_or(1.0);
+            self.base_color = scale(color, factor);
+        }
+        self.base_color_texture = binding.texture("DiffuseColor");
+        if let Some(color) = props.get_f64_3("EmissiveColor") {
+            let factor = props.get_f64("EmissiveFactor").unwrap_or(1.0);
+            self.emissive = scale(color, factor);
+        }
+        self.emissive_texture = binding.texture("EmissiveColor");
+        self.opacity = match props.get_f64("Opacity") {
+            Some(opacity) => opacity,
+            None => {
+                let factor = props.get_f64("TransparencyFactor").unwrap_or(0.0);
+                let color = props.get_f64_3("TransparentColor").unwrap_or([1.0, 1.0, 1.0]);
+                1.0 - factor * (color[0] + color[1] + color[2]) / 3.0
+            },
+        };
+        if let Some(shininess) = props
+            .get_f64("ShininessExponent")
+            .or_else(|| props.get_f64("Shininess"))
+        {
+            // Approximation of Blinn-Phong exponent to roughness.
+            self.roughness = (2.0 / (shininess.max(0.0) + 2.0)).sqrt();
+        }
+        self.normal_texture = binding
+            .texture("NormalMap")
+            .or_else(|| binding.texture("Bump"));
+    }
+
+    /// Loads properties of Stingray PBS material.
+    fn load_stingray_pbs(&mut self, props: &Properties70, binding: &Binding) {
+        if let Some(color) = props.get_f64_3("Maya|base_color") {
+            self.base_color = color;
+        }
+        self.metallic = props.get_f64("Maya|metallic").unwrap_or(0.0);
+        self.roughness = props.get_f64("Maya|roughness").unwrap_or(1.0);
+        if let Some(color) = props.get_f64_3("Maya|emissive") {
+            let intensity = props.get_f64("Maya|emissive_intensity").unwrap_or(1.0);
+            self.emissive = scale(color, intensity);
+        }
+        let use_map = |name: &str| props.get_f64(name).is_none_or(|v| v != 0.0);
+        let map = |use_name: &str, tex_name: &str| if use_map(use_name) {
+            binding.texture(tex_name)
+        } else {
+            None
+        };
+        self.base_color_texture = map("Maya|use_color_map", "Maya|TEX_color_map");
+        self.metallic_texture = map("Maya|use_metallic_map", "Maya|TEX_metallic_map");
+        self.roughness_texture = map("Maya|use_roughness_map", "Maya|TEX_roughness_map");
+        self.emissive_texture = map("Maya|use_emissive_map", "Maya|TEX_emissive_map");
+        self.normal_texture = map("Maya|use_normal_map", "Maya|TEX_normal_map");
+    }
+
+    /// Loads properties of 3ds Max Physical Material.
+    fn load_physical_material(&mut self, props: &Properties70, binding: &Binding) {
+        const PREFIX: &str = "3dsMax|Parameters|";
+        let name = |s: &str| format!("{}{}", PREFIX, s);
+
+        if let Some(color) = props.get_f64_4(&name("base_color")) {
+            let weight = props.get_f64(&name("base_weight")).unwrap_or(1.0);
+            self.base_color = scale([color[0], color[1], color[2]], weight);
+        }
+        self.metallic = props.get_f64(&name("metalness")).unwrap_or(0.0);
+        let roughness = props.get_f64(&name("roughness")).unwrap_or(0.0);
+        self.roughness = if props.get_bool(&name("roughness_inv")).unwrap_or(false) {
+            1.0 - roughness
+        } else {
+            roughness
+        };
+        if let Some(color) = props.get_f64_4(&name("emit_color")) {
+            let emission = props.get_f64(&name("emission")).unwrap_or(0.0);
+            self.emissive = scale([color[0], color[1], color[2]], emission);
+        }
+        self.opacity = 1.0 - props.get_f64(&name("transparency")).unwrap_or(0.0);
+        self.base_color_texture = binding.texture(&name("base_color_map"));
+        self.metallic_texture = binding.texture(&name("metalness_map"));
+        self.roughness_texture = binding.texture(&name("roughness_map"));
+        self.emissive_texture = binding.texture(&name("emit_color_map"));
+        self.normal_texture = binding.texture(&name("bump_map"));
+    }
+}
+
+
+/// Texture bindings of a material.
+struct Binding<'a> {
+    /// Material ID.
+    id: i64,
+    /// Connections.
+    connections: &'a Connections,
+}
+
+impl<'a> Binding<'a> {
+    /// Returns the first texture connected to the given property.
+    fn texture(&self, property: &str) -> Option<i64> {
+        self.connections.property_sources(self.id, property).next()
+    }
+}
+
+
+/// Detects the shading model of the material.
+fn material_kind(obj: &Object, props: &Properties70) -> MaterialKind {
+    let has_prefix = |prefix: &str| {
+        props.iter().any(|(name, value)| {
+            let numeric = matches!(
+                *value,
+                PropertyValue::F64(_) | PropertyValue::F64x3(_) | PropertyValue::F64x4(_)
+            );
+            numeric && name.starts_with(prefix)
+        })
+    };
+    if has_prefix("Maya|base_color") || has_prefix("Maya|metallic") {
+        return MaterialKind::StingrayPbs;
+    }
+    if has_prefix("3dsMax|Parameters|base_color") {
+        return MaterialKind::PhysicalMaterial;
+    }
+    let shading_model = obj.node("ShadingModel").and_then(|node| node.first_attr_as::<&str>());
+    match shading_model.map(str::to_lowercase) {
+        Some(ref s) if s == "lambert" => MaterialKind::Lambert,
+        Some(ref s) if s == "phong" => MaterialKind::Phong,
+        _ => MaterialKind::Unknown,
+    }
+}
+
+
+/// Multiplies the color by the factor.
+fn scale(color: [f64; 3], factor: f64) -> [f64; 3] {
+    [color[0] * factor, color[1] * factor, color[2] * factor]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::{GenericNode, OwnedAttribute};
+    use loader::binary::simple::fbx7400::{Object, ObjectProperties, Properties70};
+    use loader::binary::simple::fbx7400::{Connections, Connection};
+    use super::{StandardMaterial, MaterialKind};
+
+    fn material(id: i64, shading_model: &str, props: Properties70) -> Object {
+        Object {
+            props: ObjectProperties {
+                id: id,
+                name: "Mat".to_owned(),
+                class: "Material".to_owned(),
+                subclass: "".to_owned(),
+            },
+            properties: Some(props),
+            nodes: vec![
+                GenericNode {
+                    name: "ShadingModel".to_owned(),
+                    attributes: vec![OwnedAttribute::String(Ok(shading_model.to_owned()))],
+                    children: vec![],
+                },
+            ],
+            template: None,
+        }
+    }
+
+    fn texture_connection(texture: i64, material: i64, property: &str) -> Connection {
+        Connection {
+            source: texture,
+            destination: material,
+            property: Some(property.to_owned()),
+            source_is_prop: false,
+            destination_is_prop: true,
+        }
+    }
+
+    #[test]
+    fn test_phong() {
+        let mut props = Properties70::new();
+        props.insert("DiffuseColor", [1.0, 0.5, 0.0]);
+        props.insert("DiffuseFactor", 0.5);
+        props.insert("ShininessExponent", 0.0);
+        let connections = Connections(vec![
+            texture_connection(10, 1, "DiffuseColor"),
+            texture_connection(11, 1, "NormalMap"),
+            texture_connection(12, 2, "DiffuseColor"),
+        ]);
+        let mat = StandardMaterial::from_object(&material(1, "Phong", props), &connections);
+        assert_eq!(mat.kind, MaterialKind::Phong);
+        assert_eq!(mat.base_color, [0.5, 0.25, 0.0]);
+        assert_eq!(mat.base_color_texture, Some(10));
+        assert_eq!(mat.normal_texture, Some(11));
+        assert_eq!(mat.roughness, 1.0);
+        assert_eq!(mat.opacity, 1.0);
+    }
+
+    #[test]
+    fn test_stingray_pbs() {
+        let mut props = Properties70::new();
+        props.insert("Maya|base_color", [0.2, 0.3, 0.4]);
+        props.insert("Maya|metallic", 1.0);
+        props.insert("Maya|roughness", 0.25);
+        props.insert("Maya|use_normal_map", 0.0);
+        let connections = Connections(vec![
+            texture_connection(10, 1, "Maya|TEX_color_map"),
+            texture_connection(11, 1, "Maya|TEX_normal_map"),
+        ]);
+        let mat = StandardMaterial::from_object(&material(1, "unknown", props), &connections);
+        assert_eq!(mat.kind, MaterialKind::StingrayPbs);
+        assert_eq!(mat.base_color, [0.2, 0.3, 0.4]);
+        assert_eq!(mat.metallic, 1.0);
+        assert_eq!(mat.roughness, 0.25);
+        assert_eq!(mat.base_color_texture, Some(10));
+        assert_eq!(mat.normal_texture, None);
+    }
+}
@@ -0,0 +1,86 @@
+//! Conversions into types of math crates.
+//!
+//! Matrices are converted with the same column/row semantics (no transposition is needed by
+//! callers).
+
+#[cfg(feature = "cgmath")]
+use cgmath;
+#[cfg(feature = "mint")]
+use mint;
+#[cfg(feature = "nalgebra")]
+use nalgebra;
+
+
+/// Converts a 2D vector into `mint::Vector2`.
+#[cfg(feature = "mint")]
+pub fn to_mint_vector2<T>(v: [T; 2]) -> mint::Vector2<T> {
+    v.into()
+}
+
+/// Converts a 3D vector into `mint::Vector3`.
+#[cfg(feature = "mint")]
+pub fn to_mint_vector3<T>(v: [T; 3]) -> mint::Vector3<T> {
+    v.into()
+}
+
+/// Converts a 4D vector into `mint::Vector4`.
+#[cfg(feature = "mint")]
+pub fn to_mint_vector4<T>(v: [T; 4]) -> mint::Vector4<T> {
+    v.into()
+}
+
+/// Converts a column major 4x4 matrix into `mint::ColumnMatrix4`.
+#[cfg(feature = "mint")]
+pub fn to_mint_matrix4<T>(m: [[T; 4]; 4]) -> mint::ColumnMatrix4<T> {
+    m.into()
+}
+
+
+/// Converts a 2D vector into `cgmath::Vector2`.
+#[cfg(feature = "cgmath")]
+pub fn to_cgmath_vector2<S: Copy>(v: [S; 2]) -> cgmath::Vector2<S> {
+    v.into()
+}
+
+/// Converts a 3D vector into `cgmath::Vector3`.
+#[cfg(feature = "cgmath")]
+pub fn to_cgmath_vector3<S: Copy>(v: [S; 3]) -> cgmath::Vector3<S> {
+    v.into()
+}
+
+/// Converts a 4D vector into `cgmath::Vector4`.
+#[cfg(feature = "cgmath")]
+pub fn to_cgmath_vector4<S: Copy>(v: [S; 4]) -> cgmath::Vector4<S> {
+    v.into()
+}
+
+/// Converts a column major 4x4 matrix into `cgmath::Matrix4`.
+#[cfg(feature = "cgmath")]
+pub fn to_cgmath_matrix4<S: Copy>(m: [[S; 4]; 4]) -> cgmath::Matrix4<S> {
+    m.into()
+}
+
+
+/// Converts a 2D vector into `nalgebra::Vector2`.
+#[cfg(feature = "nalgebra")]
+pub fn to_nalgebra_vector2<T: nalgebra::Scalar>(v: [T; 2]) -> nalgebra::Vector2<T> {
+    v.into()
+}
+
+/// Converts a 3D vector into `nalgebra::Vector3`.
+#[cfg(feature = "nalgebra")]
+pub fn to_nalgebra_vector3<T: nalgebra::Scalar>(v: [T; 3]) -> nalgebra::Vector3<T> {
+    v.into()
+}
+
+/// Converts a 4D vector into `nalgebra::Vector4`.
+#[cfg(feature = "nalgebra")]
+pub fn to_nalgebra_vector4<T: nalgebra::Scalar>(v: [T; 4]) -> nalgebra::Vector4<T> {
+    v.into()
+}
+
+/// Converts a column major 4x4 matrix into `nalgebra::Matrix4`.
+#[cfg(feature = "nalgebra")]
+pub fn to_nalgebra_matrix4<T: nalgebra::Scalar>(m: [[T; 4]; 4]) -> nalgebra::Matrix4<T> {
+    m.into()
+}
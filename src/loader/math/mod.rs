@@ -0,0 +1,231 @@
+//! Math types and conversions.
+//!
+//! FBX stores vectors as arrays of components, and matrices as column major arrays of columns
+//! (i.e. `mat[col][row]`).
+//! This module provides type aliases for them and (with optional features) conversions into
+//! types of math crates.
+
+#[cfg(any(feature = "mint", feature = "cgmath", feature = "nalgebra"))]
+pub use self::interop::*;
+
+#[cfg(any(feature = "mint", feature = "cgmath", feature = "nalgebra"))]
+mod interop;
+
+
+/// 2D vector.
+pub type Vector2 = [f64; 2];
+
+/// 3D vector.
+pub type Vector3 = [f64; 3];
+
+/// 4D vector.
+pub type Vector4 = [f64; 4];
+
+/// Column major 3x3 matrix.
+///
+/// `mat[i]` is the `i`-th column.
+pub type Matrix3 = [[f64; 3]; 3];
+
+/// Column major 4x4 matrix.
+///
+/// `mat[i]` is the `i`-th column.
+pub type Matrix4 = [[f64; 4]; 4];
+
+/// Quaternion (`[x, y, z, w]`).
+pub type Quaternion = [f64; 4];
+
+
+/// Order of Euler rotations (`RotationOrder` property).
+///
+/// The name lists the axes in the applied order: `Xyz` rotates around X axis first, and Z axis
+/// last.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RotationOrder {
+    /// XYZ (default).
+    #[default]
+    Xyz,
+    /// XZY.
+    Xzy,
+    /// YZX.
+    Yzx,
+    /// YXZ.
+    Yxz,
+    /// ZXY.
+    Zxy,
+    /// ZYX.
+    Zyx,
+    /// Spheric XYZ.
+    ///
+    /// FBX SDK uses this only for interpolation, and treats it as XYZ for conversion.
+    SphericXyz,
+}
+
+impl RotationOrder {
+    /// Creates a `RotationOrder` from the property value.
+    ///
+    /// Unknown values are treated as `Xyz`.
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            1 => RotationOrder::Xzy,
+            2 => RotationOrder::Yzx,
+            3 => RotationOrder::Yxz,
+            4 => RotationOrder::Zxy,
+            5 => RotationOrder::Zyx,
+            6 => RotationOrder::SphericXyz,
+            _ => RotationOrder::Xyz,
+        }
+    }
+
+    /// Returns the axes (`0` for X, `1` for Y, and `2` for Z) in the applied order.
+    pub fn axes(&self) -> [usize; 3] {
+        match *self {
+            RotationOrder::Xyz | RotationOrder::SphericXyz => [0, 1, 2],
+            RotationOrder::Xzy => [0, 2, 1],
+            RotationOrder::Yzx => [1, 2, 0],
+            RotationOrder::Yxz => [1, 0, 2],
+            RotationOrder::Zxy => [2, 0, 1],
+            RotationOrder::Zyx => [2, 1, 0],
+        }
+    }
+}
+
+
+/// Returns the quaternion of the Euler angles in degrees.
+pub fn euler_to_quaternion(angles: Vector3, order: RotationOrder) -> Quaternion {
+    order.axes().iter().fold([0.0, 0.0, 0.0, 1.0], |acc, &axis| {
+        let (s, c) = (angles[axis].to_radians() / 2.0).sin_cos();
+        let mut q = [0.0, 0.0, 0.0, c];
+        q[axis] = s;
+        quaternion_mul(&q, &acc)
+    })
+}
+
+
+/// Returns the rotation matrix of the Euler angles in degrees.
+pub fn euler_to_matrix(angles: Vector3, order: RotationOrder) -> Matrix4 {
+    quaternion_to_matrix(&euler_to_quaternion(angles, order))
+}
+
+
+/// Returns the product of the quaternions (`a` applied after `b`).
+pub fn quaternion_mul(a: &Quaternion, b: &Quaternion) -> Quaternion {
+    [
+        a[3] * b[0] + a[0] * b[3] + a[1] * b[2] - a[2] * b[1],
+        a[3] * b[1] - a[0] * b[2] + a[1] * b[3] + a[2] * b[0],
+        a[3] * b[2] + a[0] * b[1] - a[1] * b[0] + a[2] * b[3],
+        a[3] * b[3] - a[0] * b[0] - a[1] * b[1] - a[2] * b[2],
+    ]
+}
+
+
+/// Returns the rotation matrix of the unit quaternion.
+pub fn quaternion_to_matrix(q: &Quaternion) -> Matrix4 {
+    let [x, y, z, w] = *q;
+    [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + z * w), 2.0 * (x * z - y * w), 0.0],
+        [2.0 * (x * y - z * w), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + x * w), 0.0],
+        [2.0 * (x * z + y * w), 2.0 * (y * z - x * w), 1.0 - 2.0 * (x * x + y * y), 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// Minimum corner.
+    pub min: Vector3,
+    /// Maximum corner.
+    pub max: Vector3,
+}
+
+impl Aabb {
+    /// Creates the bounding box of the given points.
+    ///
+    /// Returns `None` if there are no points.
+    pub fn from_points<'a, I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = &'a Vector3>,
+    {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        let mut aabb = Aabb {
+            min: first,
+            max: first,
+        };
+        for p in points {
+            aabb.extend(*p);
+        }
+        Some(aabb)
+    }
+
+    /// Extends the box to contain the point.
+    pub fn extend(&mut self, p: Vector3) {
+        for ((min, max), &v) in self.min.iter_mut().zip(self.max.iter_mut()).zip(&p) {
+            *min = min.min(v);
+            *max = max.max(v);
+        }
+    }
+
+    /// Returns the smallest box containing both boxes.
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        let mut aabb = *self;
+        aabb.extend(other.min);
+        aabb.extend(other.max);
+        aabb
+    }
+
+    /// Returns the corners of the box.
+    pub fn corners(&self) -> [Vector3; 8] {
+        let mut corners = [[0.0; 3]; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            for (axis, v) in corner.iter_mut().enumerate() {
+                *v = if i & (1 << axis) == 0 { self.min[axis] } else { self.max[axis] };
+            }
+        }
+        corners
+    }
+
+    /// Returns the bounding box of this box transformed by the affine matrix.
+    pub fn transform(&self, m: &Matrix4) -> Aabb {
+        let corners = self.corners();
+        let transformed = corners.iter().map(|p| {
+            let mut q = m[3];
+            for (col, &v) in m.iter().zip(p) {
+                for row in 0..3 {
+                    q[row] += col[row] * v;
+                }
+            }
+            [q[0], q[1], q[2]]
+        });
+        let transformed = transformed.collect::<Vec<_>>();
+        Aabb::from_points(&transformed).expect("Corners should not be empty")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{RotationOrder, euler_to_matrix, euler_to_quaternion};
+
+    #[test]
+    fn test_euler() {
+        let approx = |a: [f64; 4], b: [f64; 4]| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-9);
+
+        // Rotating X axis by 90 degrees around Z axis gives Y axis.
+        let m = euler_to_matrix([0.0, 0.0, 90.0], RotationOrder::Xyz);
+        assert!(approx(m[0], [0.0, 1.0, 0.0, 0.0]), "{:?}", m);
+
+        // X axis is rotated to Y axis by Z, then to Z axis by X in ZYX order.
+        let angles = [90.0, 0.0, 90.0];
+        let xyz = euler_to_matrix(angles, RotationOrder::Xyz);
+        let zyx = euler_to_matrix(angles, RotationOrder::Zyx);
+        assert!(approx(xyz[0], [0.0, 1.0, 0.0, 0.0]), "{:?}", xyz);
+        assert!(approx(zyx[0], [0.0, 0.0, 1.0, 0.0]), "{:?}", zyx);
+        assert_eq!(RotationOrder::from_i64(5), RotationOrder::Zyx);
+        assert_eq!(
+            euler_to_quaternion(angles, RotationOrder::SphericXyz),
+            euler_to_quaternion(angles, RotationOrder::Xyz)
+        );
+    }
+}
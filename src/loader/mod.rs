@@ -1,4 +1,14 @@
 //! Loader.
 
+pub mod animation;
 pub mod binary;
+pub mod bounds;
+pub mod clips;
+pub mod convert;
+pub mod material;
+pub mod math;
+pub mod skeleton;
+pub mod submesh;
+pub mod time;
 pub mod utils;
+pub mod validate;
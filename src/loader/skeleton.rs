@@ -0,0 +1,164 @@
+//! Skeleton extraction.
+
+use loader::animation::local_transform;
+use loader::binary::simple::fbx7400::{Objects, Object, Connections, ModelKind};
+use loader::binary::simple::fbx7400::{NodeAttribute, NodeAttributeKind};
+use loader::math::Matrix4;
+
+
+/// Bone of a skeleton.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bone {
+    /// Object ID of the model.
+    pub id: i64,
+    /// Name of the model.
+    pub name: String,
+    /// Index of the parent bone, or `None` for the root bone.
+    pub parent: Option<usize>,
+    /// Local transform of the model in the bind (non-animated) pose.
+    pub bind_local: Matrix4,
+    /// Limb length (`Size` property of the `LimbNode` node attribute).
+    pub size: Option<f64>,
+}
+
+
+/// Skeleton, i.e. tree of bones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skeleton {
+    /// Bones in topological order (parents come before their children).
+    pub bones: Vec<Bone>,
+}
+
+impl Skeleton {
+    /// Returns the index of the bone with the given model ID.
+    pub fn index(&self, id: i64) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.id == id)
+    }
+
+    /// Returns indices of the child bones of the given bone.
+    pub fn children(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.bones
+            .iter()
+            .enumerate()
+            .filter(move |&(_, bone)| bone.parent == Some(index))
+            .map(|(i, _)| i)
+    }
+}
+
+
+/// Extracts the skeleton rooted at the given model.
+///
+/// The root model can be any model (such as `Null` or `Root`), and its descendants are
+/// collected while they are `LimbNode` models, in depth-first order of connections.
+/// Returns `None` if the root model is not found.
+pub fn extract_skeleton(
+    objects: &Objects,
+    connections: &Connections,
+    root_model_id: i64,
+) -> Option<Skeleton> {
+    let root = model(objects, root_model_id)?;
+    let mut bones = Vec::new();
+    let mut stack = vec![(root, None)];
+    while let Some((obj, parent)) = stack.pop() {
+        let index = bones.len();
+        bones.push(Bone {
+            id: obj.id(),
+            name: obj.name().to_owned(),
+            parent: parent,
+            bind_local: local_transform(obj),
+            size: limb_size(objects, connections, obj.id()),
+        });
+        let children = connections
+            .object_sources(obj.id())
+            .filter_map(|id| model(objects, id))
+            .filter(|child| ModelKind::from_subclass(child.subclass()) == ModelKind::LimbNode)
+            .collect::<Vec<_>>();
+        // Push in reverse to visit children in connection order.
+        stack.extend(children.into_iter().rev().map(|child| (child, Some(index))));
+    }
+    Some(Skeleton { bones: bones })
+}
+
+
+/// Returns the `Model` object with the given ID.
+fn model(objects: &Objects, id: i64) -> Option<&Object> {
+    objects.object(id).filter(|obj| obj.class() == "Model")
+}
+
+
+/// Returns the `Size` property of the `LimbNode` node attribute of the model.
+fn limb_size(objects: &Objects, connections: &Connections, id: i64) -> Option<f64> {
+    connections
+        .object_sources(id)
+        .filter_map(|id| objects.object(id))
+        .filter(|obj| obj.class() == "NodeAttribute")
+        .map(NodeAttribute::from_object)
+        .find(|attr| attr.kind == NodeAttributeKind::LimbNode)
+        .and_then(|attr| attr.size())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::extract_skeleton;
+
+    #[test]
+    fn test_extract_skeleton() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &(id, name_class, subclass, y) in &[
+            (1, "Armature\u{0}\u{1}Model", "Null", 0.0),
+            (2, "Hips\u{0}\u{1}Model", "LimbNode", 1.0),
+            (3, "Spine\u{0}\u{1}Model", "LimbNode", 0.5),
+            (4, "LegL\u{0}\u{1}Model", "LimbNode", -0.5),
+            (5, "Mesh\u{0}\u{1}Model", "Mesh", 0.0),
+        ]
+        {
+            b.begin_node(
+                "Model",
+                &[Attr::I64(id), Attr::String(name_class), Attr::String(subclass)],
+            );
+            let translation = [Attr::F64(0.0), Attr::F64(y), Attr::F64(0.0)];
+            write_properties70(
+                &mut b,
+                &[("Lcl Translation", "Lcl Translation", "", "A", &translation)],
+            );
+            b.end_node();
+        }
+        b.begin_node(
+            "NodeAttribute",
+            &[Attr::I64(12), Attr::String("\u{0}\u{1}NodeAttribute"), Attr::String("LimbNode")],
+        );
+        write_properties70(&mut b, &[("Size", "double", "Number", "", &[Attr::F64(33.0)])]);
+        b.end_node();
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        for &(source, destination) in &[(1, 0), (2, 1), (5, 1), (3, 2), (4, 2), (12, 2)] {
+            b.node("C", &[Attr::String("OO"), Attr::I64(source), Attr::I64(destination)]);
+        }
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let skeleton =
+            extract_skeleton(&fbx.objects, &fbx.connections, 1).expect("Skeleton should exist");
+
+        let bones = skeleton
+            .bones
+            .iter()
+            .map(|bone| (bone.name.as_str(), bone.parent))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bones,
+            vec![("Armature", None), ("Hips", Some(0)), ("Spine", Some(1)), ("LegL", Some(1))]
+        );
+        let hips = &skeleton.bones[1];
+        assert_eq!(hips.size, Some(33.0));
+        assert_eq!(hips.bind_local[3], [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(skeleton.children(1).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(skeleton.index(4), Some(3));
+        assert!(extract_skeleton(&fbx.objects, &fbx.connections, 99).is_none());
+    }
+}
@@ -0,0 +1,308 @@
+//! Mesh splitting by material.
+//!
+//! Most renderers draw one material per draw call, so a mesh with per-polygon materials is
+//! usually split into submeshes, either as polygon groups sharing the buffers of the mesh
+//! (`material_groups()`), or as separate meshes (`split_by_material()`).
+//! Material indices of the groups are resolved to material objects by `material_slots()`.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use loader::binary::simple::fbx7400::{Connections, Objects};
+use loader::binary::simple::fbx7400::{LayerElement, MappingMode, Mesh, ReferenceMode};
+
+
+/// Polygons of a mesh using the same material.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterialGroup {
+    /// Material index (index into the materials of the model).
+    ///
+    /// `None` for polygons whose material is unavailable.
+    pub material: Option<i32>,
+    /// Polygon indices, in ascending order.
+    pub polygons: Vec<usize>,
+    /// Polygon vertex indices (indices into polygon vertex indices of the mesh) of the
+    /// polygons, in ascending order.
+    pub polygon_vertices: Vec<usize>,
+}
+
+
+/// Groups polygons of the mesh by material.
+///
+/// The first `LayerElementMaterial` is used, and all polygons are in a single group if the
+/// mesh has no material layer elements.
+/// Groups are sorted by material index, and the group with `None` material comes first.
+pub fn material_groups(mesh: &Mesh) -> Vec<MaterialGroup> {
+    let materials = mesh.materials.first();
+    let mut groups: HashMap<Option<i32>, MaterialGroup> = HashMap::new();
+    for pv in mesh.polygon_vertices() {
+        let material = match materials {
+            Some(m) => m.get(pv.polygon_vertex, pv.control_point, pv.polygon).cloned(),
+            None => Some(0),
+        };
+        let group = groups.entry(material).or_insert_with(|| {
+            MaterialGroup {
+                material: material,
+                polygons: Vec::new(),
+                polygon_vertices: Vec::new(),
+            }
+        });
+        if group.polygons.last() != Some(&pv.polygon) {
+            group.polygons.push(pv.polygon);
+        }
+        group.polygon_vertices.push(pv.polygon_vertex);
+    }
+    let mut groups = groups.into_values().collect::<Vec<_>>();
+    groups.sort_by_key(|g| g.material);
+    groups
+}
+
+
+/// Returns IDs of the materials of the model, in slot order.
+///
+/// Material indices of `LayerElementMaterial` are indices into this list, which is the order
+/// of the connections from the materials to the model.
+pub fn material_slots(objects: &Objects, connections: &Connections, model: i64) -> Vec<i64> {
+    connections
+        .ordered_children_of(model)
+        .into_iter()
+        .filter(|&id| objects.object(id).is_some_and(|obj| obj.class() == "Material"))
+        .collect()
+}
+
+
+/// Groups polygons of the mesh by material, and returns the groups with the material IDs.
+///
+/// `slots` is the list of material IDs returned by `material_slots()`.
+/// The material ID is `None` if the material index is unavailable or out of range.
+pub fn material_assignments(mesh: &Mesh, slots: &[i64]) -> Vec<(MaterialGroup, Option<i64>)> {
+    material_groups(mesh)
+        .into_iter()
+        .map(|group| {
+            let material = group
+                .material
+                .and_then(|i| usize::try_from(i).ok())
+                .and_then(|i| slots.get(i).cloned());
+            (group, material)
+        })
+        .collect()
+}
+
+
+/// Splits the mesh into separate meshes by material.
+///
+/// See `material_groups()` for grouping, and `extract_polygons()` for the created meshes.
+pub fn split_by_material(mesh: &Mesh) -> Vec<(Option<i32>, Mesh)> {
+    material_groups(mesh)
+        .into_iter()
+        .map(|group| (group.material, extract_polygons(mesh, &group.polygons)))
+        .collect()
+}
+
+
+/// Creates a mesh consisting of the given polygons of the mesh.
+///
+/// Only control points used by the polygons are kept, and all layer elements are remapped
+/// to the new control points, polygon vertices, and polygons.
+/// `ByEdge` layer elements are kept as is, since edges are not remapped.
+/// `BBoxMin` and `BBoxMax` of the new mesh are `None`.
+pub fn extract_polygons(mesh: &Mesh, polygons: &[usize]) -> Mesh {
+    let mut selected = vec![false; polygons.iter().max().map_or(0, |&p| p + 1)];
+    for &p in polygons {
+        selected[p] = true;
+    }
+
+    let mut control_point_map = HashMap::new();
+    let mut control_points = Vec::new();
+    let mut polygon_vertices = Vec::new();
+    let mut polygon_vertex_index = Vec::new();
+    let mut new_polygons = Vec::new();
+    for pv in mesh.polygon_vertices() {
+        if !selected.get(pv.polygon).cloned().unwrap_or(false) {
+            continue;
+        }
+        if new_polygons.last() != Some(&pv.polygon) {
+            new_polygons.push(pv.polygon);
+        }
+        let new_cp = *control_point_map.entry(pv.control_point).or_insert_with(|| {
+            control_points.push(pv.control_point);
+            control_points.len() - 1
+        }) as i32;
+        let is_last = mesh.polygon_vertex_index[pv.polygon_vertex] < 0;
+        polygon_vertex_index.push(if is_last { !new_cp } else { new_cp });
+        polygon_vertices.push(pv.polygon_vertex);
+    }
+
+    let maps = ElementMaps {
+        control_points: &control_points,
+        polygon_vertices: &polygon_vertices,
+        polygons: &new_polygons,
+    };
+    Mesh {
+        props: mesh.props.clone(),
+        vertices: control_points
+            .iter()
+            .filter_map(|&cp| mesh.vertices.get(cp).cloned())
+            .collect(),
+        polygon_vertex_index: polygon_vertex_index,
+        normals: mesh.normals.iter().map(|e| maps.select(e)).collect(),
+        uv_sets: mesh.uv_sets.iter().map(|e| maps.select(e)).collect(),
+        colors: mesh.colors.iter().map(|e| maps.select(e)).collect(),
+        materials: mesh.materials.iter().map(|e| maps.select(e)).collect(),
+        bbox_min: None,
+        bbox_max: None,
+    }
+}
+
+
+/// Old indices of the new control points, polygon vertices, and polygons.
+struct ElementMaps<'a> {
+    /// Control points.
+    control_points: &'a [usize],
+    /// Polygon vertices.
+    polygon_vertices: &'a [usize],
+    /// Polygons.
+    polygons: &'a [usize],
+}
+
+impl<'a> ElementMaps<'a> {
+    /// Creates the layer element for the new mesh.
+    fn select<T: Clone>(&self, elem: &LayerElement<T>) -> LayerElement<T> {
+        let map = match elem.mapping {
+            MappingMode::ByControlPoint => self.control_points,
+            MappingMode::ByPolygonVertex => self.polygon_vertices,
+            MappingMode::ByPolygon => self.polygons,
+            MappingMode::AllSame | MappingMode::ByEdge => return elem.clone(),
+        };
+        let mut result = elem.clone();
+        match elem.reference {
+            ReferenceMode::Direct => {
+                result.direct = map.iter().map_while(|&i| elem.direct.get(i).cloned()).collect();
+            },
+            ReferenceMode::IndexToDirect => {
+                result.indices = map
+                    .iter()
+                    .map(|&i| elem.indices.get(i).cloned().unwrap_or(-1))
+                    .collect();
+            },
+        }
+        result
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use loader::binary::simple::fbx7400::{LayerElement, MappingMode, Mesh, ObjectProperties};
+    use loader::binary::simple::fbx7400::ReferenceMode;
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects};
+    use super::{material_assignments, material_groups, material_slots, split_by_material};
+
+    fn element<T>(mapping: MappingMode, direct: Vec<T>) -> LayerElement<T> {
+        LayerElement {
+            index: 0,
+            name: String::new(),
+            mapping: mapping,
+            reference: ReferenceMode::Direct,
+            direct: direct,
+            indices: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_split_by_material() {
+        let mesh = Mesh {
+            props: ObjectProperties {
+                id: 1,
+                name: String::new(),
+                class: "Geometry".to_owned(),
+                subclass: "Mesh".to_owned(),
+            },
+            vertices: (0..5).map(|i| [i as f64, 0.0, 0.0]).collect(),
+            polygon_vertex_index: vec![0, 1, !2, 2, 1, !3, 3, 4, !0],
+            normals: Vec::new(),
+            uv_sets: vec![
+                element(MappingMode::ByPolygonVertex, (0..9).map(|i| [i as f64, 0.0]).collect()),
+            ],
+            colors: vec![
+                element(MappingMode::ByControlPoint, (0..5).map(|i| [i as f64; 4]).collect()),
+            ],
+            materials: vec![element(MappingMode::ByPolygon, vec![1, 0, 1])],
+            bbox_min: None,
+            bbox_max: None,
+        };
+
+        let groups = material_groups(&mesh);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].material, Some(0));
+        assert_eq!(groups[0].polygons, vec![1]);
+        assert_eq!(groups[1].polygons, vec![0, 2]);
+        assert_eq!(groups[1].polygon_vertices, vec![0, 1, 2, 6, 7, 8]);
+
+        let submeshes = split_by_material(&mesh);
+        let (material, ref sub) = submeshes[1];
+        assert_eq!(material, Some(1));
+        assert_eq!(sub.polygon_vertex_index, vec![0, 1, !2, 3, 4, !0]);
+        assert_eq!(sub.vertices, mesh.vertices);
+        assert_eq!(
+            sub.uv_sets[0].direct.iter().map(|uv| uv[0]).collect::<Vec<_>>(),
+            vec![0.0, 1.0, 2.0, 6.0, 7.0, 8.0]
+        );
+        assert_eq!(sub.materials[0].direct, vec![1, 1]);
+        let (_, ref sub) = submeshes[0];
+        assert_eq!(sub.polygon_vertex_index, vec![0, 1, !2]);
+        assert_eq!(sub.colors[0].direct, vec![[2.0; 4], [1.0; 4], [3.0; 4]]);
+    }
+
+    #[test]
+    fn test_material_slots() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &(id, class, subclass) in &[
+            (1, "Model", "Mesh"),
+            (2, "Geometry", "Mesh"),
+            (10, "Material", ""),
+            (20, "Material", ""),
+        ]
+        {
+            let name_class = format!("\u{0}\u{1}{}", class);
+            b.node(class, &[Attr::I64(id), Attr::String(&name_class), Attr::String(subclass)]);
+        }
+        b.end_node();
+        b.begin_node("Connections", &[]);
+        for &(source, destination) in &[(1, 0), (20, 1), (2, 1), (10, 1), (20, 1)] {
+            b.node("C", &[Attr::String("OO"), Attr::I64(source), Attr::I64(destination)]);
+        }
+        b.end_node();
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let slots = material_slots(&fbx.objects, &fbx.connections, 1);
+        assert_eq!(slots, vec![20, 10]);
+
+        let mesh = Mesh {
+            props: ObjectProperties {
+                id: 2,
+                name: String::new(),
+                class: "Geometry".to_owned(),
+                subclass: "Mesh".to_owned(),
+            },
+            vertices: (0..4).map(|i| [i as f64, 0.0, 0.0]).collect(),
+            polygon_vertex_index: vec![0, 1, !2, 2, 1, !3, 3, 0, !1],
+            normals: Vec::new(),
+            uv_sets: Vec::new(),
+            colors: Vec::new(),
+            materials: vec![element(MappingMode::ByPolygon, vec![1, 0, 2])],
+            bbox_min: None,
+            bbox_max: None,
+        };
+        let assignments = material_assignments(&mesh, &slots)
+            .into_iter()
+            .map(|(group, material)| (group.polygons, material))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            assignments,
+            vec![(vec![1], Some(20)), (vec![0], Some(10)), (vec![2], None)]
+        );
+    }
+}
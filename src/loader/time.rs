@@ -0,0 +1,187 @@
+//! Time and frame rate.
+
+use std::ops::{Add, Sub};
+
+
+/// Time value of FBX (`KTime`), in ticks.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FbxTime(pub i64);
+
+impl FbxTime {
+    /// Number of ticks per second.
+    pub const TICKS_PER_SECOND: i64 = 46_186_158_000;
+
+    /// Creates a `FbxTime` from the number of ticks.
+    pub fn from_ticks(ticks: i64) -> Self {
+        FbxTime(ticks)
+    }
+
+    /// Creates a `FbxTime` from seconds, rounding to the nearest tick.
+    pub fn from_seconds(seconds: f64) -> Self {
+        FbxTime((seconds * FbxTime::TICKS_PER_SECOND as f64).round() as i64)
+    }
+
+    /// Returns the number of ticks.
+    pub fn ticks(self) -> i64 {
+        self.0
+    }
+
+    /// Returns the time in seconds.
+    pub fn seconds(self) -> f64 {
+        self.0 as f64 / FbxTime::TICKS_PER_SECOND as f64
+    }
+
+    /// Subtracts the time, and returns `None` if overflow occurred.
+    pub fn checked_sub(self, rhs: FbxTime) -> Option<FbxTime> {
+        self.0.checked_sub(rhs.0).map(FbxTime)
+    }
+
+    /// Returns the time in frames with the given frame rate.
+    pub fn frames(self, frame_rate: f64) -> f64 {
+        self.seconds() * frame_rate
+    }
+}
+
+impl Add for FbxTime {
+    type Output = FbxTime;
+
+    fn add(self, rhs: FbxTime) -> FbxTime {
+        FbxTime(self.0 + rhs.0)
+    }
+}
+
+impl Sub for FbxTime {
+    type Output = FbxTime;
+
+    fn sub(self, rhs: FbxTime) -> FbxTime {
+        FbxTime(self.0 - rhs.0)
+    }
+}
+
+
+/// Time mode (frame rate preset) of a scene (`TimeMode` property of `GlobalSettings`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeMode {
+    /// Default mode (`eDefaultMode`), treated as 30 frames per second.
+    Default,
+    /// 120 frames per second.
+    Frames120,
+    /// 100 frames per second.
+    Frames100,
+    /// 60 frames per second.
+    Frames60,
+    /// 50 frames per second.
+    Frames50,
+    /// 48 frames per second.
+    Frames48,
+    /// 30 frames per second.
+    Frames30,
+    /// 30 frames per second with drop frames.
+    Frames30Drop,
+    /// NTSC drop frame (29.97 frames per second).
+    NtscDropFrame,
+    /// NTSC full frame (29.97 frames per second).
+    NtscFullFrame,
+    /// PAL (25 frames per second).
+    Pal,
+    /// 24 frames per second.
+    Frames24,
+    /// 1000 frames per second.
+    Frames1000,
+    /// Film full frame (23.976 frames per second).
+    FilmFullFrame,
+    /// Custom frame rate (`CustomFrameRate` property).
+    Custom,
+    /// 96 frames per second.
+    Frames96,
+    /// 72 frames per second.
+    Frames72,
+    /// 59.94 frames per second.
+    Frames59Dot94,
+    /// 119.88 frames per second.
+    Frames119Dot88,
+    /// Unknown mode.
+    Unknown(i64),
+}
+
+impl TimeMode {
+    /// Creates a `TimeMode` from the value of `TimeMode` property.
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            0 => TimeMode::Default,
+            1 => TimeMode::Frames120,
+            2 => TimeMode::Frames100,
+            3 => TimeMode::Frames60,
+            4 => TimeMode::Frames50,
+            5 => TimeMode::Frames48,
+            6 => TimeMode::Frames30,
+            7 => TimeMode::Frames30Drop,
+            8 => TimeMode::NtscDropFrame,
+            9 => TimeMode::NtscFullFrame,
+            10 => TimeMode::Pal,
+            11 => TimeMode::Frames24,
+            12 => TimeMode::Frames1000,
+            13 => TimeMode::FilmFullFrame,
+            14 => TimeMode::Custom,
+            15 => TimeMode::Frames96,
+            16 => TimeMode::Frames72,
+            17 => TimeMode::Frames59Dot94,
+            18 => TimeMode::Frames119Dot88,
+            v => TimeMode::Unknown(v),
+        }
+    }
+
+    /// Returns the frame rate (frames per second) of the mode.
+    ///
+    /// Returns `None` for `Custom` and `Unknown(_)`.
+    pub fn frame_rate(self) -> Option<f64> {
+        Some(match self {
+            TimeMode::Frames120 => 120.0,
+            TimeMode::Frames100 => 100.0,
+            TimeMode::Frames60 => 60.0,
+            TimeMode::Frames50 => 50.0,
+            TimeMode::Frames48 => 48.0,
+            TimeMode::Default | TimeMode::Frames30 | TimeMode::Frames30Drop => 30.0,
+            TimeMode::NtscDropFrame | TimeMode::NtscFullFrame => 30_000.0 / 1001.0,
+            TimeMode::Pal => 25.0,
+            TimeMode::Frames24 => 24.0,
+            TimeMode::Frames1000 => 1000.0,
+            TimeMode::FilmFullFrame => 24_000.0 / 1001.0,
+            TimeMode::Frames96 => 96.0,
+            TimeMode::Frames72 => 72.0,
+            TimeMode::Frames59Dot94 => 60_000.0 / 1001.0,
+            TimeMode::Frames119Dot88 => 120_000.0 / 1001.0,
+            TimeMode::Custom | TimeMode::Unknown(_) => return None,
+        })
+    }
+
+    /// Returns the frame rate, using `custom` for `Custom` mode.
+    pub fn resolve_frame_rate(self, custom: Option<f64>) -> Option<f64> {
+        match self {
+            TimeMode::Custom => custom.filter(|&v| v > 0.0),
+            _ => self.frame_rate(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{FbxTime, TimeMode};
+
+    #[test]
+    fn test_time_mode() {
+        assert_eq!(TimeMode::from_i64(11), TimeMode::Frames24);
+        assert_eq!(TimeMode::from_i64(99), TimeMode::Unknown(99));
+        assert_eq!(TimeMode::Pal.frame_rate(), Some(25.0));
+        assert!((TimeMode::NtscFullFrame.frame_rate().unwrap() - 29.97).abs() < 1e-3);
+        assert_eq!(TimeMode::Custom.frame_rate(), None);
+        assert_eq!(TimeMode::Custom.resolve_frame_rate(Some(12.0)), Some(12.0));
+        assert_eq!(TimeMode::Custom.resolve_frame_rate(Some(0.0)), None);
+
+        let second = FbxTime::from_ticks(FbxTime::TICKS_PER_SECOND);
+        assert_eq!(second.seconds(), 1.0);
+        assert_eq!(FbxTime::from_seconds(0.5) + FbxTime::from_seconds(0.5), second);
+        assert_eq!(second.frames(24.0), 24.0);
+    }
+}
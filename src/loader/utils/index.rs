@@ -0,0 +1,153 @@
+//! Index buffer conversion.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+
+
+/// Number of vertices addressable by 16-bit indices.
+const U16_VERTICES: usize = 1 << 16;
+
+
+/// Error returned when an index does not fit in the destination type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IndexOverflow {
+    /// Position of the first index which does not fit.
+    pub position: usize,
+}
+
+impl fmt::Display for IndexOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Index at position {} is out of range", self.position)
+    }
+}
+
+impl error::Error for IndexOverflow {}
+
+
+/// Part of an index buffer split by `split_u16_indices()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct U16IndexChunk<T> {
+    /// Original indices of the vertices used by the chunk.
+    ///
+    /// `indices` are indices into this list.
+    pub vertices: Vec<T>,
+    /// 16-bit indices.
+    pub indices: Vec<u16>,
+}
+
+
+/// Converts indices into `u16` indices.
+///
+/// Returns `Err(_)` if any index is larger than `u16::MAX`.
+pub fn to_u16_indices<T: Copy>(src: &[T]) -> Result<Vec<u16>, IndexOverflow>
+where
+    u16: TryFrom<T>,
+{
+    convert_indices(src)
+}
+
+
+/// Converts indices into `u32` indices.
+///
+/// Returns `Err(_)` if any index is larger than `u32::MAX`.
+pub fn to_u32_indices<T: Copy>(src: &[T]) -> Result<Vec<u32>, IndexOverflow>
+where
+    u32: TryFrom<T>,
+{
+    convert_indices(src)
+}
+
+
+/// Converts indices into the destination type.
+fn convert_indices<T: Copy, U: TryFrom<T>>(src: &[T]) -> Result<Vec<U>, IndexOverflow> {
+    src.iter()
+        .enumerate()
+        .map(|(position, &i)| U::try_from(i).map_err(|_| IndexOverflow { position: position }))
+        .collect()
+}
+
+
+/// Splits indices into chunks addressable by `u16` indices.
+///
+/// Indices are processed per primitive of `primitive_len` indices (for example, `3` for
+/// triangle lists), and a primitive is never split across chunks.
+/// If all indices are addressable, a single chunk is returned.
+///
+/// # Panics
+///
+/// Panics if `primitive_len` is `0` or larger than `65536`.
+pub fn split_u16_indices<T>(src: &[T], primitive_len: usize) -> Vec<U16IndexChunk<T>>
+where
+    T: Copy + Eq + Hash,
+{
+    assert!(
+        primitive_len > 0 && primitive_len <= U16_VERTICES,
+        "Invalid primitive length: {}",
+        primitive_len
+    );
+
+    let mut chunks = Vec::new();
+    let mut current = U16IndexChunk {
+        vertices: Vec::new(),
+        indices: Vec::new(),
+    };
+    let mut remap = HashMap::new();
+    for primitive in src.chunks(primitive_len) {
+        let num_new = primitive
+            .iter()
+            .enumerate()
+            .filter(|&(pos, i)| !remap.contains_key(i) && !primitive[..pos].contains(i))
+            .count();
+        if current.vertices.len() + num_new > U16_VERTICES {
+            chunks.push(current);
+            current = U16IndexChunk {
+                vertices: Vec::new(),
+                indices: Vec::new(),
+            };
+            remap.clear();
+        }
+        for &i in primitive {
+            let index = match remap.entry(i) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    current.vertices.push(i);
+                    *entry.insert((current.vertices.len() - 1) as u16)
+                },
+            };
+            current.indices.push(index);
+        }
+    }
+    if !current.indices.is_empty() || chunks.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{IndexOverflow, split_u16_indices, to_u16_indices, to_u32_indices};
+
+    #[test]
+    fn test_index_conversion() {
+        assert_eq!(to_u16_indices(&[0u32, 65535]), Ok(vec![0u16, 65535]));
+        assert_eq!(to_u16_indices(&[0u32, 65536]), Err(IndexOverflow { position: 1 }));
+        assert_eq!(to_u32_indices(&[3usize, 1]), Ok(vec![3u32, 1]));
+
+        // 65536 distinct vertices, as 3 vertices per triangle.
+        let src = (0..65538u32).collect::<Vec<_>>();
+        let chunks = split_u16_indices(&src, 3);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].vertices.len(), 65535);
+        assert_eq!(chunks[1].vertices, vec![65535, 65536, 65537]);
+        assert_eq!(chunks[1].indices, vec![0, 1, 2]);
+        let chunks = split_u16_indices(&[5u32, 7, 5], 3);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].vertices, vec![5, 7]);
+        assert_eq!(chunks[0].indices, vec![0, 1, 0]);
+    }
+}
@@ -1,5 +1,14 @@
 //! Useful functionalities for loading FBX.
 
+pub use self::index::{IndexOverflow, U16IndexChunk};
+pub use self::index::{split_u16_indices, to_u16_indices, to_u32_indices};
+pub use self::vertex::{Rounding, f32_to_f16, f64_to_f16, unorm16, unorm8};
+pub use self::vertex::{to_f32s, to_f32_vec3s, to_f16_vec2s, to_f16_vec3s};
+pub use self::vertex::{to_unorm16s, to_unorm8s, to_unorm16_vec2s};
+
+mod index;
+mod vertex;
+
 
 /// Applies the given function to each polygon.
 ///
@@ -0,0 +1,229 @@
+//! Conversions of geometry data into compact vertex formats.
+//!
+//! FBX stores geometry data as `f64` values, but GPU vertex buffers usually use `f32`, half
+//! precision floats, or normalized integers.
+
+
+/// Rounding mode used for conversion into normalized integers.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    /// Rounds to the nearest integer (half away from zero).
+    #[default]
+    Nearest,
+    /// Rounds toward negative infinity.
+    Floor,
+    /// Rounds toward positive infinity.
+    Ceil,
+    /// Rounds toward zero (truncation).
+    TowardZero,
+}
+
+impl Rounding {
+    /// Rounds the given value.
+    fn apply(self, v: f64) -> f64 {
+        match self {
+            Rounding::Nearest => v.round(),
+            Rounding::Floor => v.floor(),
+            Rounding::Ceil => v.ceil(),
+            Rounding::TowardZero => v.trunc(),
+        }
+    }
+}
+
+
+/// Converts `f64` values into `f32` values.
+pub fn to_f32s(src: &[f64]) -> Vec<f32> {
+    src.iter().map(|&v| v as f32).collect()
+}
+
+/// Converts `f64` 3D vectors (such as positions and normals) into `f32` vectors.
+pub fn to_f32_vec3s(src: &[[f64; 3]]) -> Vec<[f32; 3]> {
+    src.iter()
+        .map(|v| [v[0] as f32, v[1] as f32, v[2] as f32])
+        .collect()
+}
+
+/// Converts `f64` 3D vectors into half precision float vectors.
+///
+/// Each component is bit pattern of IEEE 754 binary16.
+pub fn to_f16_vec3s(src: &[[f64; 3]]) -> Vec<[u16; 3]> {
+    src.iter()
+        .map(|v| [f64_to_f16(v[0]), f64_to_f16(v[1]), f64_to_f16(v[2])])
+        .collect()
+}
+
+/// Converts `f64` 2D vectors (such as UVs) into half precision float vectors.
+///
+/// Each component is bit pattern of IEEE 754 binary16.
+pub fn to_f16_vec2s(src: &[[f64; 2]]) -> Vec<[u16; 2]> {
+    src.iter()
+        .map(|v| [f64_to_f16(v[0]), f64_to_f16(v[1])])
+        .collect()
+}
+
+/// Converts `f64` values in `[0, 1]` into normalized `u16` values.
+///
+/// Values out of range are clamped.
+pub fn to_unorm16s(src: &[f64], rounding: Rounding) -> Vec<u16> {
+    src.iter().map(|&v| unorm16(v, rounding)).collect()
+}
+
+/// Converts `f64` values in `[0, 1]` (such as bone weights) into normalized `u8` values.
+///
+/// Values out of range are clamped.
+pub fn to_unorm8s(src: &[f64], rounding: Rounding) -> Vec<u8> {
+    src.iter().map(|&v| unorm8(v, rounding)).collect()
+}
+
+/// Converts `f64` 2D vectors (such as UVs) in `[0, 1]` into normalized `u16` vectors.
+///
+/// Values out of range are clamped.
+pub fn to_unorm16_vec2s(src: &[[f64; 2]], rounding: Rounding) -> Vec<[u16; 2]> {
+    src.iter()
+        .map(|v| [unorm16(v[0], rounding), unorm16(v[1], rounding)])
+        .collect()
+}
+
+/// Converts a `f64` value in `[0, 1]` into normalized `u16` value.
+///
+/// Values out of range (and NaN) are clamped.
+pub fn unorm16(v: f64, rounding: Rounding) -> u16 {
+    unorm(v, rounding, u16::MAX as f64) as u16
+}
+
+/// Converts a `f64` value in `[0, 1]` into normalized `u8` value.
+///
+/// Values out of range (and NaN) are clamped.
+pub fn unorm8(v: f64, rounding: Rounding) -> u8 {
+    unorm(v, rounding, u8::MAX as f64) as u8
+}
+
+/// Scales, rounds, and clamps the value into `[0, max]`.
+fn unorm(v: f64, rounding: Rounding, max: f64) -> f64 {
+    if v.is_nan() {
+        return 0.0;
+    }
+    let scaled = rounding.apply(v * max);
+    scaled.max(0.0).min(max)
+}
+
+/// Converts a `f64` value into bit pattern of half precision float.
+///
+/// The value is rounded to nearest, ties to even.
+pub fn f64_to_f16(v: f64) -> u16 {
+    let bits = v.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    let man = bits & 0x000f_ffff_ffff_ffff;
+
+    if exp == 0x7ff {
+        // Infinity or NaN.
+        let nan_bit = if man != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan_bit | (man >> 42) as u16;
+    }
+    finite_to_f16(sign, exp - 1023 + 15, man, 52)
+}
+
+/// Converts a `f32` value into bit pattern of half precision float.
+///
+/// The value is rounded to nearest, ties to even.
+pub fn f32_to_f16(v: f32) -> u16 {
+    let bits = v.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let man = bits & 0x007f_ffff;
+
+    if exp == 0xff {
+        // Infinity or NaN.
+        let nan_bit = if man != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan_bit | (man >> 13) as u16;
+    }
+    finite_to_f16(sign, exp - 127 + 15, man as u64, 23)
+}
+
+/// Converts a finite value into bit pattern of half precision float.
+///
+/// `half_exp` is the exponent biased for half precision, and `man` is the mantissa of
+/// `man_bits` bits without the implicit leading bit.
+fn finite_to_f16(sign: u16, half_exp: i32, man: u64, man_bits: u32) -> u16 {
+    // Number of the mantissa bits not representable in half precision.
+    let dropped = man_bits - 10;
+
+    if half_exp >= 0x1f {
+        // Overflow.
+        return sign | 0x7c00;
+    }
+    if half_exp <= 0 {
+        // Subnormal or zero.
+        if half_exp < -10 {
+            return sign;
+        }
+        let man = man | (1 << man_bits);
+        let shift = dropped + 1 + (-half_exp) as u32;
+        let round_bit = 1 << (shift - 1);
+        let mut half_man = man >> shift;
+        if (man & round_bit) != 0 && (man & (3 * round_bit - 1)) != 0 {
+            half_man += 1;
+        }
+        return sign | half_man as u16;
+    }
+
+    let round_bit = 1 << (dropped - 1);
+    // Carry from mantissa into exponent is valid (and may result in infinity).
+    let mut half = ((half_exp as u64) << 10) | (man >> dropped);
+    if (man & round_bit) != 0 && (man & (3 * round_bit - 1)) != 0 {
+        half += 1;
+    }
+    sign | half as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rounding, f32_to_f16, f64_to_f16, unorm16, unorm8};
+
+    #[test]
+    fn test_f32_to_f16() {
+        assert_eq!(f32_to_f16(0.0), 0x0000);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(0.5), 0x3800);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+        assert_eq!(f32_to_f16(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NAN) & 0x7e00, 0x7e00);
+        // Smallest subnormal.
+        assert_eq!(f32_to_f16(2.0f32.powi(-24)), 0x0001);
+        // Ties to even: 1 + 2^-11 is exactly between 1.0 and the next half value.
+        assert_eq!(f32_to_f16(1.0 + 2.0f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2.0f32.powi(-11)), 0x3c02);
+    }
+
+    #[test]
+    fn test_f64_to_f16() {
+        assert_eq!(f64_to_f16(-0.0), 0x8000);
+        assert_eq!(f64_to_f16(1.0), 0x3c00);
+        assert_eq!(f64_to_f16(65504.0), 0x7bff);
+        assert_eq!(f64_to_f16(1.0e6), 0x7c00);
+        assert_eq!(f64_to_f16(1.0e-300), 0x0000);
+        assert_eq!(f64_to_f16(f64::NAN) & 0x7e00, 0x7e00);
+        assert_eq!(f64_to_f16(2.0f64.powi(-24)), 0x0001);
+        assert_eq!(f64_to_f16(1.0 + 2.0f64.powi(-11)), 0x3c00);
+        // Slightly above the tie, which rounding through `f32` would turn into the tie.
+        assert_eq!(f64_to_f16(1.0 + 2.0f64.powi(-11) + 2.0f64.powi(-40)), 0x3c01);
+        assert_eq!(f64_to_f16(2.0f64.powi(-25) + 2.0f64.powi(-60)), 0x0001);
+    }
+
+    #[test]
+    fn test_unorm() {
+        assert_eq!(unorm16(0.0, Rounding::Nearest), 0);
+        assert_eq!(unorm16(1.0, Rounding::Nearest), 65535);
+        assert_eq!(unorm16(2.0, Rounding::Nearest), 65535);
+        assert_eq!(unorm16(-1.0, Rounding::Nearest), 0);
+        assert_eq!(unorm8(0.5, Rounding::Nearest), 128);
+        assert_eq!(unorm8(0.5, Rounding::Floor), 127);
+        assert_eq!(unorm8(0.5, Rounding::TowardZero), 127);
+        assert_eq!(unorm8(0.5, Rounding::Ceil), 128);
+        assert_eq!(unorm8(f64::NAN, Rounding::Nearest), 0);
+    }
+}
@@ -0,0 +1,208 @@
+//! Validation of assets against pipeline rules.
+//!
+//! `validate()` checks objects against `Rules` and returns findings instead of failing, so
+//! that the caller can decide which findings are fatal.
+
+use loader::binary::simple::fbx7400::{Objects, Object};
+use loader::math::Vector3;
+
+
+/// Rules to validate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules {
+    /// Maximum number of polygons per mesh geometry.
+    pub max_polygons: Option<usize>,
+    /// Whether to report polygons with more than 4 vertices.
+    pub report_ngons: bool,
+    /// Whether to report mesh geometries without UV sets.
+    pub require_uvs: bool,
+    /// Whether to report bones (`LimbNode` models) with non-uniform scaling.
+    pub require_uniform_bone_scale: bool,
+    /// Whether to report models and materials with empty names.
+    pub require_names: bool,
+}
+
+impl Default for Rules {
+    /// Returns the rules enabling all checks except the polygon limit.
+    fn default() -> Self {
+        Rules {
+            max_polygons: None,
+            report_ngons: true,
+            require_uvs: true,
+            require_uniform_bone_scale: true,
+            require_names: true,
+        }
+    }
+}
+
+
+/// Kind of a rule violation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FindingKind {
+    /// The mesh has more polygons than the limit.
+    TooManyPolygons {
+        /// Number of polygons.
+        polygons: usize,
+        /// Limit.
+        max: usize,
+    },
+    /// The mesh has polygons with more than 4 vertices.
+    Ngons {
+        /// Number of such polygons.
+        count: usize,
+    },
+    /// The mesh has no UV sets.
+    MissingUvs,
+    /// The bone has non-uniform scaling.
+    NonUniformBoneScale {
+        /// `Lcl Scaling` of the bone.
+        scaling: Vector3,
+    },
+    /// The object has an empty name.
+    Unnamed,
+}
+
+
+/// Rule violation of an object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Object ID.
+    pub object: i64,
+    /// Kind of the violation.
+    pub kind: FindingKind,
+}
+
+
+/// Validates the objects against the rules.
+///
+/// Findings are returned in the loaded order of objects.
+pub fn validate(objects: &Objects, rules: &Rules) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (id, obj) in objects.iter_ordered() {
+        let mut report = |kind| findings.push(Finding { object: id, kind: kind });
+        match (obj.class(), obj.subclass()) {
+            ("Geometry", "Mesh") => {
+                let (polygons, ngons) = polygon_counts(obj);
+                match rules.max_polygons {
+                    Some(max) if polygons > max => {
+                        report(FindingKind::TooManyPolygons { polygons: polygons, max: max })
+                    },
+                    _ => {},
+                }
+                if rules.report_ngons && ngons > 0 {
+                    report(FindingKind::Ngons { count: ngons });
+                }
+                if rules.require_uvs && obj.node("LayerElementUV").is_none() {
+                    report(FindingKind::MissingUvs);
+                }
+            },
+            ("Model", "LimbNode") if rules.require_uniform_bone_scale => {
+                let scaling = obj.properties_with_template()
+                    .get_f64_3("Lcl Scaling")
+                    .unwrap_or([1.0; 3]);
+                if !is_uniform(scaling) {
+                    report(FindingKind::NonUniformBoneScale { scaling: scaling });
+                }
+            },
+            _ => {},
+        }
+        let named_class = obj.class() == "Model" || obj.class() == "Material";
+        if rules.require_names && named_class && obj.name().is_empty() {
+            report(FindingKind::Unnamed);
+        }
+    }
+    findings
+}
+
+
+/// Returns the numbers of polygons and n-gons of the mesh geometry.
+///
+/// An incomplete polygon at the end of the indices is not counted.
+fn polygon_counts(obj: &Object) -> (usize, usize) {
+    let indices = obj.node("PolygonVertexIndex")
+        .and_then(|node| node.attributes.first())
+        .and_then(|attr| attr.as_i32_slice())
+        .unwrap_or(&[]);
+    let (mut polygons, mut ngons, mut size) = (0, 0, 0);
+    for &i in indices {
+        size += 1;
+        if i < 0 {
+            polygons += 1;
+            if size > 4 {
+                ngons += 1;
+            }
+            size = 0;
+        }
+    }
+    (polygons, ngons)
+}
+
+
+/// Returns `true` if the components of the scaling are equal, with relative tolerance.
+fn is_uniform(scaling: Vector3) -> bool {
+    let max = scaling.iter().cloned().fold(0.0, |a: f64, v| a.max(v.abs()));
+    scaling.iter().all(|v| (v - scaling[0]).abs() <= max * 1e-6)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{Attr, FbxBuilder, load_objects, write_nodes_before_objects, write_properties70};
+    use super::{Finding, FindingKind, Rules, validate};
+
+    #[test]
+    fn test_validate() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        b.begin_node(
+            "Geometry",
+            &[Attr::I64(1), Attr::String("Mesh\u{0}\u{1}Geometry"), Attr::String("Mesh")],
+        );
+        b.node("Vertices", &[Attr::ArrF64(&[0.0; 15])]);
+        b.node("PolygonVertexIndex", &[Attr::ArrI32(&[0, 1, 2, 3, !4, 0, 1, !2])]);
+        b.end_node();
+        b.begin_node(
+            "Model",
+            &[Attr::I64(2), Attr::String("\u{0}\u{1}Model"), Attr::String("LimbNode")],
+        );
+        write_properties70(
+            &mut b,
+            &[("Lcl Scaling", "Lcl Scaling", "", "A", &[1.0, 2.0, 1.0].map(Attr::F64))],
+        );
+        b.end_node();
+        b.node(
+            "Material",
+            &[Attr::I64(3), Attr::String("Skin\u{0}\u{1}Material"), Attr::String("")],
+        );
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let objects = load_objects(data).objects;
+        let rules = Rules {
+            max_polygons: Some(1),
+            ..Rules::default()
+        };
+        let finding = |object, kind| Finding { object: object, kind: kind };
+
+        assert_eq!(
+            validate(&objects, &rules),
+            vec![
+                finding(1, FindingKind::TooManyPolygons { polygons: 2, max: 1 }),
+                finding(1, FindingKind::Ngons { count: 1 }),
+                finding(1, FindingKind::MissingUvs),
+                finding(2, FindingKind::NonUniformBoneScale { scaling: [1.0, 2.0, 1.0] }),
+                finding(2, FindingKind::Unnamed),
+            ]
+        );
+        let relaxed = Rules {
+            report_ngons: false,
+            require_uvs: false,
+            require_uniform_bone_scale: false,
+            require_names: false,
+            ..Rules::default()
+        };
+        assert!(validate(&objects, &relaxed).is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+//! Array attribute codecs.
+
+use std::fmt;
+use std::io;
+use std::io::Read;
+use std::sync::Arc;
+use fnv::FnvHashMap;
+#[cfg(feature = "flate2")]
+use flate2::read::ZlibDecoder;
+#[cfg(feature = "libflate")]
+use libflate::zlib;
+
+
+/// Reader of the encoded array attribute elements.
+///
+/// This reads the whole encoded elements of an array attribute (excluding array attribute
+/// header), and never reads beyond it.
+pub type EncodedArrayReader<'a> = io::Take<&'a mut (dyn Read + 'a)>;
+
+
+/// Decoder of array attribute elements stored with a specific encoding.
+///
+/// Note that the encoding `0` always means non-compressed elements and it cannot be overridden.
+pub trait ArrayCodec: fmt::Debug + Send + Sync {
+    /// Wraps the given encoded stream and returns a reader of the decoded elements.
+    ///
+    /// Decoded elements should be little-endian values without any header.
+    fn decode<'a>(&self, encoded: EncodedArrayReader<'a>) -> io::Result<Box<dyn Read + 'a>>;
+}
+
+
+/// Zlib decoder backend.
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZlibBackend {
+    /// `flate2` crate.
+    #[cfg(feature = "flate2")]
+    Flate2,
+    /// `libflate` crate.
+    #[cfg(feature = "libflate")]
+    Libflate,
+}
+
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+impl Default for ZlibBackend {
+    /// Returns `Libflate` if available, `Flate2` otherwise.
+    #[cfg(feature = "libflate")]
+    fn default() -> Self {
+        ZlibBackend::Libflate
+    }
+
+    /// Returns `Libflate` if available, `Flate2` otherwise.
+    #[cfg(not(feature = "libflate"))]
+    fn default() -> Self {
+        ZlibBackend::Flate2
+    }
+}
+
+
+/// Zlib codec for array attributes.
+///
+/// This is registered for encoding `1` by default, with the default backend.
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZlibCodec {
+    /// Decoder backend.
+    backend: ZlibBackend,
+}
+
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+impl ZlibCodec {
+    /// Creates a new `ZlibCodec` with the given backend.
+    pub fn new(backend: ZlibBackend) -> Self {
+        ZlibCodec { backend: backend }
+    }
+
+    /// Returns the decoder backend.
+    pub fn backend(&self) -> ZlibBackend {
+        self.backend
+    }
+}
+
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+impl ArrayCodec for ZlibCodec {
+    fn decode<'a>(&self, encoded: EncodedArrayReader<'a>) -> io::Result<Box<dyn Read + 'a>> {
+        match self.backend {
+            #[cfg(feature = "flate2")]
+            ZlibBackend::Flate2 => Ok(Box::new(ZlibDecoder::new(encoded))),
+            #[cfg(feature = "libflate")]
+            ZlibBackend::Libflate => Ok(Box::new(zlib::Decoder::new(encoded)?)),
+        }
+    }
+}
+
+
+/// Registry of array attribute codecs keyed by encoding value.
+///
+/// `Default::default()` returns registry with codecs for known encodings (currently only zlib,
+/// if any zlib backend is enabled).
+#[derive(Clone)]
+pub struct ArrayCodecs {
+    /// Codecs.
+    codecs: FnvHashMap<u32, Arc<dyn ArrayCodec>>,
+}
+
+impl ArrayCodecs {
+    /// Creates a new empty `ArrayCodecs`.
+    ///
+    /// With empty registry, only non-compressed array attributes can be read.
+    pub fn new() -> Self {
+        ArrayCodecs { codecs: FnvHashMap::default() }
+    }
+
+    /// Registers the codec for the given encoding and returns the previously registered one.
+    ///
+    /// # Panics
+    /// Panics if `encoding` is `0`, which is reserved for non-compressed arrays.
+    pub fn register<C>(&mut self, encoding: u32, codec: C) -> Option<Arc<dyn ArrayCodec>>
+    where
+        C: ArrayCodec + 'static,
+    {
+        assert_ne!(
+            encoding,
+            0,
+            "Encoding 0 is reserved for non-compressed arrays"
+        );
+        self.codecs.insert(encoding, Arc::new(codec))
+    }
+
+    /// Unregisters the codec for the given encoding and returns it.
+    pub fn unregister(&mut self, encoding: u32) -> Option<Arc<dyn ArrayCodec>> {
+        self.codecs.remove(&encoding)
+    }
+
+    /// Returns the codec for the given encoding.
+    pub fn get(&self, encoding: u32) -> Option<&dyn ArrayCodec> {
+        self.codecs.get(&encoding).map(|codec| &**codec)
+    }
+}
+
+impl Default for ArrayCodecs {
+    fn default() -> Self {
+        #[cfg_attr(not(any(feature = "flate2", feature = "libflate")), allow(unused_mut))]
+        let mut codecs = ArrayCodecs::new();
+        #[cfg(any(feature = "flate2", feature = "libflate"))]
+        codecs.register(1, ZlibCodec::default());
+        codecs
+    }
+}
+
+impl fmt::Debug for ArrayCodecs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut encodings = self.codecs.keys().collect::<Vec<_>>();
+        encodings.sort();
+        f.debug_struct("ArrayCodecs")
+            .field("encodings", &encodings)
+            .finish()
+    }
+}
@@ -0,0 +1,200 @@
+//! Object-safe parser with owned events.
+
+use std::io::Read;
+use parser::binary::{Parser, ParserSource, RootParser, SubtreeParser, SubtreeGuard};
+use parser::binary::{Event, FbxHeader, FbxFooter, OwnedAttribute, OwnedAttributeOptions};
+use parser::binary::error::{Result, Warning};
+
+
+/// Parser event without borrowing the parser.
+///
+/// Emitted by `DynParser::next_owned_event()`.
+#[derive(Debug)]
+pub enum OwnedEvent {
+    /// Start of the FBX document.
+    StartFbx(FbxHeader),
+    /// End of the FBX document.
+    EndFbx(Result<FbxFooter>),
+    /// Start of a node.
+    StartNode(OwnedStartNode),
+    /// Start of a node with unparsed attributes (see `Event::RawNode`).
+    RawNode(OwnedRawNode),
+    /// End of a node.
+    EndNode,
+}
+
+
+/// Owned FBX node info.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedStartNode {
+    /// Node name.
+    pub name: String,
+    /// Node attributes.
+    pub attributes: Vec<OwnedAttribute>,
+}
+
+
+/// Owned FBX node info with unparsed attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedRawNode {
+    /// Node name.
+    pub name: String,
+    /// Number of the node attributes.
+    pub num_attributes: u64,
+    /// Raw attributes block.
+    pub attributes: Vec<u8>,
+}
+
+
+/// Object-safe parser of a FBX binary node.
+///
+/// `Parser<R>` is generic over the source and its events borrow the parser, so it cannot be
+/// used as a trait object.
+/// This is a facade which reads whole attributes into owned events, and loaders can take
+/// `&mut dyn DynParser` without being generic over sources and parsers.
+pub trait DynParser {
+    /// Parses FBX and returns the next event, with the attributes loaded into memory.
+    fn next_owned_event(&mut self) -> Result<OwnedEvent> {
+        self.next_owned_event_with(&mut OwnedAttributeOptions::default())
+    }
+
+    /// Parses FBX and returns the next event, with the attributes loaded with the given
+    /// options.
+    fn next_owned_event_with(
+        &mut self,
+        options: &mut OwnedAttributeOptions,
+    ) -> Result<OwnedEvent>;
+
+    /// Skips to the end of the current node.
+    ///
+    /// Same as `Parser::skip_current_node()`.
+    fn skip_node(&mut self) -> Result<bool>;
+
+    /// Creates subtree parser for the current node.
+    fn dyn_subtree_parser(&mut self) -> Box<dyn DynParser + '_>;
+
+    /// Returns FBX version of the reading input.
+    ///
+    /// Returns `None` if unknown yet.
+    fn fbx_version(&self) -> Option<u32>;
+
+    /// Returns names of the opened (and not closed) nodes, from the toplevel node to the most
+    /// recent opened node.
+    ///
+    /// Same as `Parser::open_node_names()`.
+    fn node_path(&self) -> &[String];
+
+    /// Returns reference to the warnings.
+    fn warnings(&self) -> &[Warning];
+}
+
+
+/// Returns the next event of the parser as an owned event.
+fn next_owned_event<R, P>(
+    parser: &mut P,
+    options: &mut OwnedAttributeOptions,
+) -> Result<OwnedEvent>
+where
+    R: ParserSource,
+    P: Parser<R>,
+{
+    Ok(match parser.next_event()? {
+        Event::StartFbx(header) => OwnedEvent::StartFbx(header),
+        Event::EndFbx(footer) => OwnedEvent::EndFbx(footer),
+        Event::StartNode(mut node) => {
+            OwnedEvent::StartNode(OwnedStartNode {
+                name: node.name.to_owned(),
+                attributes: node.attributes.collect_owned_with(options)?,
+            })
+        },
+        Event::RawNode(mut node) => {
+            let mut attributes = Vec::new();
+            node.attributes.read_to_end(&mut attributes)?;
+            OwnedEvent::RawNode(OwnedRawNode {
+                name: node.name.to_owned(),
+                num_attributes: node.num_attributes,
+                attributes: attributes,
+            })
+        },
+        Event::EndNode => OwnedEvent::EndNode,
+    })
+}
+
+
+macro_rules! impl_dyn_parser {
+    ($(impl<$($lt:lifetime,)* R> for $ty:ty;)*) => {
+        $(
+            impl<$($lt,)* R: ParserSource> DynParser for $ty {
+                fn next_owned_event_with(
+                    &mut self,
+                    options: &mut OwnedAttributeOptions,
+                ) -> Result<OwnedEvent> {
+                    next_owned_event(self, options)
+                }
+
+                fn skip_node(&mut self) -> Result<bool> {
+                    self.skip_current_node()
+                }
+
+                fn dyn_subtree_parser(&mut self) -> Box<dyn DynParser + '_> {
+                    Box::new(self.subtree_parser())
+                }
+
+                fn fbx_version(&self) -> Option<u32> {
+                    self.root_parser().fbx_version()
+                }
+
+                fn node_path(&self) -> &[String] {
+                    self.open_node_names()
+                }
+
+                fn warnings(&self) -> &[Warning] {
+                    self.root_parser().warnings()
+                }
+            }
+        )*
+    }
+}
+
+impl_dyn_parser! {
+    impl<R> for RootParser<R>;
+    impl<'a, R> for SubtreeParser<'a, R>;
+    impl<'a, R> for SubtreeGuard<'a, R>;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use parser::binary::RootParser;
+    use loader::binary::simple::GenericNode;
+    use test_util::synthetic_fbx;
+    use super::{DynParser, OwnedEvent};
+
+    #[test]
+    fn test_dyn_parser() {
+        let data = synthetic_fbx(7400, 3, 8, false);
+        let expected = GenericNode::load_from_parser(&mut RootParser::new(Cursor::new(&data)))
+            .expect("Parse error");
+
+        let mut parser = RootParser::new(Cursor::new(&data));
+        let loaded = GenericNode::load_from_dyn_parser(&mut parser).expect("Parse error");
+        assert_eq!(loaded, expected);
+        let expected = expected.0;
+        assert_eq!(DynParser::fbx_version(&parser), Some(7400));
+        assert!(DynParser::warnings(&parser).is_empty());
+
+        let mut parser = RootParser::new(Cursor::new(&data));
+        parser.set_raw_attributes(true);
+        let parser: &mut dyn DynParser = &mut parser;
+        parser.next_owned_event().expect("Parse error");
+        let raw = match parser.next_owned_event().expect("Parse error") {
+            OwnedEvent::RawNode(node) => node,
+            ev => panic!("Unexpected event: {:?}", ev),
+        };
+        assert_eq!(raw.name, expected[0].name);
+        assert_eq!(raw.num_attributes, expected[0].attributes.len() as u64);
+        assert_eq!(parser.node_path(), &[raw.name]);
+        assert!(parser.skip_node().expect("Parse error"));
+    }
+}
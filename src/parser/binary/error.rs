@@ -11,11 +11,56 @@ use std::sync::Arc;
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 
+/// Category of an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Parsing is cancelled.
+    Cancelled,
+    /// Parsing is already finished.
+    Finished,
+    /// Input data is broken or inconsistent.
+    InvalidData,
+    /// I/O error.
+    Io,
+    /// Input data uses unsupported features.
+    Unsupported,
+    /// Other errors, such as errors returned by user-defined loaders.
+    Other,
+}
+
+
 /// FBX parsing error.
+///
+/// New variants may be added in future, so use `kind()` to categorize errors.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
+    /// Number of array attribute elements differs from the expected one.
+    ArrayLengthMismatch {
+        /// Expected number of elements.
+        expected: u64,
+        /// Actual number of rest elements.
+        actual: u64,
+    },
+    /// End offset of node attributes overflows.
+    AttributesLengthOverflow {
+        /// Start offset of the attributes.
+        position: u64,
+        /// Length of the attributes told by node header.
+        bytelen: u64,
+    },
+    /// Tried to skip backward on the source which doesn't support it.
+    BackwardSeek {
+        /// Current position.
+        position: u64,
+        /// Destination position.
+        destination: u64,
+    },
     /// FBX footer is broken.
     BrokenFbxFooter,
+    /// Parsing is cancelled by the cancel flag.
+    Cancelled,
     /// Successfully finished parsing the target FBX node(s).
     Finished,
     /// Specified FBX versions mismatched in header and footer.
@@ -38,6 +83,8 @@ pub enum Error {
     NodeNameInvalidUtf8(Arc<string::FromUtf8Error>),
     /// I/O error.
     Io(io::Error),
+    /// Parsed attributes are required but the parser emitted `Event::RawNode`.
+    RawNodeUnsupported,
     /// Unknown array attribute encoding.
     UnknownArrayAttributeEncoding(u32),
     /// End offset of a node is wrong.
@@ -56,11 +103,81 @@ impl Error {
     pub fn node_name_invalid_utf8(e: string::FromUtf8Error) -> Self {
         Error::NodeNameInvalidUtf8(Arc::new(e))
     }
+
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            Error::ArrayLengthMismatch { .. } |
+            Error::AttributesLengthOverflow { .. } |
+            Error::BackwardSeek { .. } |
+            Error::BrokenFbxFooter |
+            Error::HeaderFooterVersionMismatch { .. } |
+            Error::InvalidNodeAttributeTypeCode { .. } |
+            Error::MagicNotDetected(_) |
+            Error::NodeNameInvalidUtf8(_) |
+            Error::WrongNodeEndOffset { .. } => ErrorKind::InvalidData,
+            Error::Cancelled => ErrorKind::Cancelled,
+            Error::Finished => ErrorKind::Finished,
+            Error::Io(_) => ErrorKind::Io,
+            Error::RawNodeUnsupported |
+            Error::UnknownArrayAttributeEncoding(_) => ErrorKind::Unsupported,
+        }
+    }
+
+    /// Returns the stable machine-readable code of the error (such as `"FBX-P001"`).
+    ///
+    /// Codes are never reused or changed across versions, so tools can group and suppress
+    /// known errors by code instead of the `Display` output.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Error::WrongNodeEndOffset { .. } => "FBX-P001",
+            Error::ArrayLengthMismatch { .. } => "FBX-P002",
+            Error::BackwardSeek { .. } => "FBX-P003",
+            Error::BrokenFbxFooter => "FBX-P004",
+            Error::Cancelled => "FBX-P005",
+            Error::Finished => "FBX-P006",
+            Error::HeaderFooterVersionMismatch { .. } => "FBX-P007",
+            Error::InvalidNodeAttributeTypeCode { .. } => "FBX-P008",
+            Error::MagicNotDetected(_) => "FBX-P009",
+            Error::NodeNameInvalidUtf8(_) => "FBX-P010",
+            Error::Io(_) => "FBX-P011",
+            Error::RawNodeUnsupported => "FBX-P012",
+            Error::UnknownArrayAttributeEncoding(_) => "FBX-P013",
+            Error::AttributesLengthOverflow { .. } => "FBX-P014",
+        }
+    }
+
+    /// Returns the position in the source where the error is detected, if available.
+    pub fn position(&self) -> Option<u64> {
+        match *self {
+            Error::AttributesLengthOverflow { position, .. } |
+            Error::BackwardSeek { position, .. } |
+            Error::InvalidNodeAttributeTypeCode { position, .. } => Some(position),
+            Error::WrongNodeEndOffset { real_end, .. } => Some(real_end),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            Error::ArrayLengthMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Array attribute length mismatch: expected {} elements but got {}",
+                    expected,
+                    actual
+                )
+            },
+            Error::AttributesLengthOverflow { position, bytelen } => {
+                write!(
+                    f,
+                    "End offset of node attributes overflows: position={}, bytelen={}",
+                    position,
+                    bytelen
+                )
+            },
             Error::HeaderFooterVersionMismatch { header, footer } => {
                 write!(
                     f,
@@ -77,12 +194,32 @@ impl fmt::Display for Error {
                     position
                 )
             },
+            Error::BackwardSeek {
+                position,
+                destination,
+            } => {
+                write!(
+                    f,
+                    "Tried to skip backward: position={}, destination={}",
+                    position,
+                    destination
+                )
+            },
+            Error::BrokenFbxFooter => f.write_str("FBX footer is broken"),
+            Error::Cancelled => f.write_str("Parsing is cancelled"),
+            Error::Finished => {
+                f.write_str("Successfully finished parsing and there are no more data")
+            },
+            Error::Io(ref err) => write!(f, "I/O error: {}", err),
             Error::MagicNotDetected(ref bytes) => {
                 write!(f, "Magic binary not detected: Got {:?}", bytes)
             },
             Error::NodeNameInvalidUtf8(ref err) => {
                 write!(f, "Node name is not vaiid UTF-8 string: {}", err)
             },
+            Error::RawNodeUnsupported => {
+                f.write_str("Parsed node attributes are required but got raw attributes")
+            },
             Error::UnknownArrayAttributeEncoding(val) => {
                 write!(f, "Unknown array attribute encoding: encoding={}", val)
             },
@@ -99,29 +236,12 @@ impl fmt::Display for Error {
                     real_end
                 )
             },
-            _ => write!(f, "{}", (self as &error::Error).description()),
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
-            Error::BrokenFbxFooter => "FBX footer is broken",
-            Error::Finished => "Successfully finished parsing and there are no more data",
-            Error::HeaderFooterVersionMismatch { .. } => {
-                "Specified FBX versions mismatched in header and footer"
-            },
-            Error::InvalidNodeAttributeTypeCode { .. } => "Invalid node attribute type code",
-            Error::MagicNotDetected(_) => "Magic binary not detected",
-            Error::NodeNameInvalidUtf8(_) => "Node name is not vaiid UTF-8 string",
-            Error::Io(ref err) => err.description(),
-            Error::UnknownArrayAttributeEncoding(_) => "Unknown array attribute encoding",
-            Error::WrongNodeEndOffset { .. } => "Wrong node end offset",
-        }
-    }
-
-    fn cause(&self) -> Option<&error::Error> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             Error::NodeNameInvalidUtf8(ref err) => Some(&**err),
             Error::Io(ref err) => Some(err),
@@ -133,7 +253,29 @@ impl error::Error for Error {
 impl Clone for Error {
     fn clone(&self) -> Self {
         match *self {
+            Error::ArrayLengthMismatch { expected, actual } => {
+                Error::ArrayLengthMismatch {
+                    expected: expected,
+                    actual: actual,
+                }
+            },
+            Error::AttributesLengthOverflow { position, bytelen } => {
+                Error::AttributesLengthOverflow {
+                    position: position,
+                    bytelen: bytelen,
+                }
+            },
+            Error::BackwardSeek {
+                position,
+                destination,
+            } => {
+                Error::BackwardSeek {
+                    position: position,
+                    destination: destination,
+                }
+            },
             Error::BrokenFbxFooter => Error::BrokenFbxFooter,
+            Error::Cancelled => Error::Cancelled,
             Error::Finished => Error::Finished,
             Error::InvalidNodeAttributeTypeCode { got, position } => {
                 Error::InvalidNodeAttributeTypeCode {
@@ -152,8 +294,9 @@ impl Clone for Error {
             Error::Io(ref err) => {
                 // To clone `io::Error`, convert inner error into string and use it
                 // as a new inner error.
-                Error::Io(io::Error::new(err.kind(), error::Error::description(err)))
+                Error::Io(io::Error::new(err.kind(), err.to_string()))
             },
+            Error::RawNodeUnsupported => Error::RawNodeUnsupported,
             Error::UnknownArrayAttributeEncoding(v) => Error::UnknownArrayAttributeEncoding(v),
             Error::WrongNodeEndOffset {
                 begin,
@@ -172,12 +315,81 @@ impl Clone for Error {
 
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
+        if e.get_ref().is_some_and(|inner| inner.is::<CancelledIo>()) {
+            return Error::Cancelled;
+        }
+        if let Some(inner) = e.get_ref().and_then(|inner| inner.downcast_ref::<BackwardSeekIo>()) {
+            return Error::BackwardSeek {
+                position: inner.position,
+                destination: inner.destination,
+            };
+        }
         Error::Io(e)
     }
 }
 
+
+/// Inner error of `io::Error` which indicates cancellation.
+///
+/// This is converted into `Error::Cancelled`.
+#[derive(Debug, Clone, Copy)]
+struct CancelledIo;
+
+impl fmt::Display for CancelledIo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Parsing is cancelled")
+    }
+}
+
+impl error::Error for CancelledIo {}
+
+
+/// Creates an `io::Error` which indicates cancellation.
+pub fn cancelled_io_error() -> io::Error {
+    io::Error::other(CancelledIo)
+}
+
+
+/// Inner error of `io::Error` which indicates backward skip.
+///
+/// This is converted into `Error::BackwardSeek`.
+#[derive(Debug, Clone, Copy)]
+struct BackwardSeekIo {
+    /// Current position.
+    position: u64,
+    /// Destination position.
+    destination: u64,
+}
+
+impl fmt::Display for BackwardSeekIo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Tried to skip backward: position={}, destination={}",
+            self.position,
+            self.destination
+        )
+    }
+}
+
+impl error::Error for BackwardSeekIo {}
+
+
+/// Creates an `io::Error` which indicates backward skip.
+pub fn backward_seek_io_error(position: u64, destination: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        BackwardSeekIo {
+            position: position,
+            destination: destination,
+        },
+    )
+}
+
+
 /// FBX parser warning.
 #[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
 pub enum Warning {
     /// Invalid node attribute of boolean value.
     InvalidBooleanAttributeValue {
@@ -188,6 +400,17 @@ pub enum Warning {
         /// Position of the attribute value.
         position: u64,
     },
+    /// Invalid element of boolean array attribute.
+    InvalidBooleanArrayElement {
+        /// Got value.
+        got: u8,
+        /// Assumed value.
+        assumed: bool,
+        /// Position of the array elements (possibly compressed).
+        position: u64,
+        /// Index of the element.
+        index: u64,
+    },
     /// FBX footer has invalid padding.
     InvalidPaddingInFbxFooter {
         /// Expected padding length.
@@ -195,10 +418,34 @@ pub enum Warning {
         /// Actual padding length.
         actual: u8,
     },
+    /// Corrupt data is detected and the rest of the enclosing node is skipped.
+    SkippedCorruptSubtree {
+        /// Start offset of the skipped node.
+        begin: u64,
+        /// End offset of the skipped node, where the parser resumed.
+        end: u64,
+        /// Position where the corrupt data is detected.
+        position: u64,
+    },
     /// Unknown 2 bytes right after FBX magic is unexpected.
     UnexpectedBytesAfterMagic([u8; 2]),
 }
 
+impl Warning {
+    /// Returns the stable machine-readable code of the warning (such as `"FBX-W001"`).
+    ///
+    /// Codes are never reused or changed across versions.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Warning::InvalidBooleanAttributeValue { .. } => "FBX-W001",
+            Warning::InvalidBooleanArrayElement { .. } => "FBX-W002",
+            Warning::InvalidPaddingInFbxFooter { .. } => "FBX-W003",
+            Warning::SkippedCorruptSubtree { .. } => "FBX-W004",
+            Warning::UnexpectedBytesAfterMagic(_) => "FBX-W005",
+        }
+    }
+}
+
 impl fmt::Display for Warning {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -215,6 +462,22 @@ impl fmt::Display for Warning {
                     assumed
                 )
             },
+            Warning::InvalidBooleanArrayElement {
+                got,
+                assumed,
+                position,
+                index,
+            } => {
+                write!(
+                    f,
+                    "Invalid boolean array element at index {} of the array at position {}: got \
+                        {:?}, assumed {}",
+                    index,
+                    position,
+                    got,
+                    assumed
+                )
+            },
             Warning::InvalidPaddingInFbxFooter { expected, actual } => {
                 write!(
                     f,
@@ -223,6 +486,20 @@ impl fmt::Display for Warning {
                     actual
                 )
             },
+            Warning::SkippedCorruptSubtree {
+                begin,
+                end,
+                position,
+            } => {
+                write!(
+                    f,
+                    "Skipped corrupt node (begin={}, end={}) since corrupt data is detected at \
+                        position {}",
+                    begin,
+                    end,
+                    position
+                )
+            },
             Warning::UnexpectedBytesAfterMagic(ref bytes) => {
                 write!(
                     f,
@@ -235,12 +512,4 @@ impl fmt::Display for Warning {
     }
 }
 
-impl error::Error for Warning {
-    fn description(&self) -> &str {
-        match *self {
-            Warning::InvalidBooleanAttributeValue { .. } => "Invalid boolean node attribute value",
-            Warning::InvalidPaddingInFbxFooter { .. } => "Invalid padding in FBX footer",
-            Warning::UnexpectedBytesAfterMagic(_) => "Unexpected bytes right after magic binary",
-        }
-    }
-}
+impl error::Error for Warning {}
@@ -2,22 +2,25 @@
 
 use std::fmt;
 use std::marker::PhantomData;
+use std::mem;
 use std::io;
 use std::io::Read;
-#[cfg(feature = "flate2")]
-use flate2::read::ZlibDecoder;
-#[cfg(feature = "libflate")]
-use libflate::zlib;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use parser::binary::Warnings;
-use parser::binary::error::{Result, Error};
+use parser::binary::{Warnings, Warning};
+use parser::binary::codec::ArrayCodecs;
+use parser::binary::error::{Result, Error, cancelled_io_error};
 use parser::binary::reader::{ParserSource, ReadLittleEndian};
+use parser::binary::stats::StatsRecorder;
 
 
 /// Read array type attribute from the given parser.
 pub fn read_array_attribute<'a, R>(
     source: &'a mut R,
     warnings: &'a mut Warnings,
+    array_codecs: &ArrayCodecs,
+    cancel_flag: Option<&'a AtomicBool>,
+    stats: StatsRecorder<'a>,
     type_code: u8,
 ) -> Result<(ArrayAttribute<'a, R>, u64)>
 where
@@ -25,14 +28,24 @@ where
 {
     let header = ArrayAttributeHeader::read_from_parser_source(source)?;
     let current_pos = source.position();
-    let reader = ArrayDecoder::new(source, &header)?;
+    let reader = ArrayDecoder::new(source, &header, array_codecs, cancel_flag, stats)?;
 
     let value = match type_code {
-        b'b' => ArrayAttribute::Bool(ArrayAttributeReader::new(&header, reader, warnings)),
-        b'i' => ArrayAttribute::I32(ArrayAttributeReader::new(&header, reader, warnings)),
-        b'l' => ArrayAttribute::I64(ArrayAttributeReader::new(&header, reader, warnings)),
-        b'f' => ArrayAttribute::F32(ArrayAttributeReader::new(&header, reader, warnings)),
-        b'd' => ArrayAttribute::F64(ArrayAttributeReader::new(&header, reader, warnings)),
+        b'b' => {
+            ArrayAttribute::Bool(ArrayAttributeReader::new(&header, current_pos, reader, warnings))
+        },
+        b'i' => {
+            ArrayAttribute::I32(ArrayAttributeReader::new(&header, current_pos, reader, warnings))
+        },
+        b'l' => {
+            ArrayAttribute::I64(ArrayAttributeReader::new(&header, current_pos, reader, warnings))
+        },
+        b'f' => {
+            ArrayAttribute::F32(ArrayAttributeReader::new(&header, current_pos, reader, warnings))
+        },
+        b'd' => {
+            ArrayAttribute::F64(ArrayAttributeReader::new(&header, current_pos, reader, warnings))
+        },
         _ => unreachable!(),
     };
     Ok((value, current_pos + header.bytelen_elements as u64))
@@ -84,11 +97,44 @@ pub enum ArrayAttribute<'a, R: 'a> {
 }
 
 
+/// A trait for types consuming array attribute elements chunk by chunk.
+///
+/// This allows loaders to accumulate huge arrays directly into the destination (such as a
+/// staging buffer of a renderer) without materializing the whole array as `Vec`.
+pub trait FromArrayStream<T> {
+    /// Called once before the first chunk, with the number of elements to be streamed.
+    fn begin(&mut self, num_elements: u64) {
+        let _ = num_elements;
+    }
+
+    /// Consumes the next chunk of elements.
+    fn extend_chunk(&mut self, chunk: &[T]) -> io::Result<()>;
+}
+
+impl<T: Clone> FromArrayStream<T> for Vec<T> {
+    fn begin(&mut self, num_elements: u64) {
+        self.reserve(num_elements as usize);
+    }
+
+    fn extend_chunk(&mut self, chunk: &[T]) -> io::Result<()> {
+        self.extend_from_slice(chunk);
+        Ok(())
+    }
+}
+
+
+/// Number of elements passed to `FromArrayStream::extend_chunk()` at once.
+const STREAM_CHUNK_LEN: usize = 1024;
+
+
 /// Reader of array attribute elements.
 #[derive(Debug)]
 pub struct ArrayAttributeReader<'a, R: 'a, T> {
     num_elements: u64,
     rest_elements: u64,
+    encoding: u32,
+    bytelen_encoded: u64,
+    position: u64,
     reader: ArrayDecoder<'a, R>,
     warnings: &'a mut Warnings,
     _value_type: PhantomData<T>,
@@ -97,12 +143,16 @@ pub struct ArrayAttributeReader<'a, R: 'a, T> {
 impl<'a, R: 'a + Read, T> ArrayAttributeReader<'a, R, T> {
     fn new<'b>(
         header: &'b ArrayAttributeHeader,
+        position: u64,
         reader: ArrayDecoder<'a, R>,
         warnings: &'a mut Warnings,
     ) -> Self {
         ArrayAttributeReader {
             num_elements: header.num_elements as u64,
             rest_elements: header.num_elements as u64,
+            encoding: header.encoding,
+            bytelen_encoded: header.bytelen_elements as u64,
+            position: position,
             reader: reader,
             warnings: warnings,
             _value_type: PhantomData,
@@ -116,17 +166,69 @@ impl<'a, R: 'a + Read, T> ArrayAttributeReader<'a, R, T> {
 
     /// Returns number of rest elements.
     pub fn rest_elements(&self) -> u64 {
-        self.num_elements
+        self.rest_elements
+    }
+
+    /// Returns an error if the number of rest elements is not `len`.
+    fn ensure_rest_elements(&self, len: usize) -> Result<()> {
+        if self.rest_elements == len as u64 {
+            Ok(())
+        } else {
+            Err(Error::ArrayLengthMismatch {
+                expected: len as u64,
+                actual: self.rest_elements,
+            })
+        }
+    }
+
+    /// Returns the array encoding, i.e. compression method.
+    ///
+    /// `0` means non-compressed, `1` means zlib-compressed.
+    pub fn encoding(&self) -> u32 {
+        self.encoding
+    }
+
+    /// Returns length of the encoded (possibly compressed) elements in bytes.
+    pub fn bytelen_encoded(&self) -> u64 {
+        self.bytelen_encoded
+    }
+
+    /// Returns length of the decoded (uncompressed) elements in bytes.
+    pub fn bytelen_decoded(&self) -> u64 {
+        self.num_elements * mem::size_of::<T>() as u64
     }
 }
 
 impl<'a, R: 'a + Read> ArrayAttributeReader<'a, R, bool> {
+    /// Reads the next element.
+    ///
+    /// The lowest bit is used as the value, as scalar boolean attributes are read (i.e. `b'Y'`
+    /// and `1` are `true`, and `b'T'` and `0` are `false`).
+    /// For other values, a warning is emitted.
+    fn read_bool(&mut self) -> io::Result<bool> {
+        let index = self.num_elements - self.rest_elements;
+        let raw = self.reader.read_u8()?;
+        self.rest_elements -= 1;
+        let val = (raw & 0x01) == 1;
+        match raw {
+            b'Y' | b'T' | 1 | 0 => {},
+            _ => {
+                self.warnings.warn(Warning::InvalidBooleanArrayElement {
+                    got: raw,
+                    assumed: val,
+                    position: self.position,
+                    index: index,
+                });
+            },
+        }
+        Ok(val)
+    }
+
     /// Reads elements into the given buffer and returns the read byte length.
     pub fn read_into_buf(&mut self, buf: &mut [bool]) -> io::Result<usize> {
         let size = ::std::cmp::min(buf.len(), self.rest_elements as usize);
         for elem in &mut buf[0..size] {
-            self.rest_elements -= 1;
-            *elem = (self.reader.read_u8()? & 1) == 1;
+            *elem = self.read_bool()?;
         }
         Ok(size)
     }
@@ -160,11 +262,101 @@ macro_rules! impl_attr_array_read {
     }
 }
 
+macro_rules! impl_attr_array_read_exact {
+    ($ty:ty, $zero:expr) => {
+        impl<'a, R: 'a + Read> ArrayAttributeReader<'a, R, $ty> {
+            /// Reads all rest elements into the given buffer.
+            ///
+            /// Returns `Err(Error::ArrayLengthMismatch { .. })` without reading any elements if
+            /// the buffer length differs from the number of rest elements.
+            pub fn read_exact_into(&mut self, buf: &mut [$ty]) -> Result<()> {
+                self.ensure_rest_elements(buf.len())?;
+                let size = self.read_into_buf(buf)?;
+                assert_eq!(size, buf.len());
+                Ok(())
+            }
+
+            /// Reads all elements into a fixed-size array.
+            ///
+            /// Returns `Err(Error::ArrayLengthMismatch { .. })` if the number of rest elements
+            /// is not `N`.
+            pub fn try_into_array<const N: usize>(mut self) -> Result<[$ty; N]> {
+                let mut arr = [$zero; N];
+                self.read_exact_into(&mut arr)?;
+                Ok(arr)
+            }
+        }
+    }
+}
+
+macro_rules! impl_attr_array_stream {
+    ($ty:ty, $zero:expr) => {
+        impl<'a, R: 'a + Read> ArrayAttributeReader<'a, R, $ty> {
+            /// Streams all rest elements into the given sink, chunk by chunk.
+            pub fn stream_into<S>(mut self, sink: &mut S) -> io::Result<()>
+            where
+                S: FromArrayStream<$ty> + ?Sized,
+            {
+                sink.begin(self.rest_elements);
+                let mut chunk = [$zero; STREAM_CHUNK_LEN];
+                while self.rest_elements > 0 {
+                    let size = self.read_into_buf(&mut chunk)?;
+                    sink.extend_chunk(&chunk[0..size])?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl_attr_array_read_exact!(bool, false);
+impl_attr_array_read_exact!(i32, 0);
+impl_attr_array_read_exact!(i64, 0);
+impl_attr_array_read_exact!(f32, 0.0);
+impl_attr_array_read_exact!(f64, 0.0);
+
+impl_attr_array_stream!(bool, false);
+impl_attr_array_stream!(i32, 0);
+impl_attr_array_stream!(i64, 0);
+impl_attr_array_stream!(f32, 0.0);
+impl_attr_array_stream!(f64, 0.0);
+
 impl_attr_array_read!(i32, read_i32_arr);
 impl_attr_array_read!(i64, read_i64_arr);
 impl_attr_array_read!(f32, read_f32_arr);
 impl_attr_array_read!(f64, read_f64_arr);
 
+impl<'a, R: 'a + Read> ArrayAttributeReader<'a, R, f64> {
+    /// Reads elements into the given `f32` buffer, converting them during the read, and returns
+    /// the read length.
+    ///
+    /// Elements are decoded through a small fixed-size buffer, so no `f64` buffer for the
+    /// whole array is allocated.
+    pub fn read_into_f32_buf(&mut self, buf: &mut [f32]) -> io::Result<usize> {
+        /// Number of elements converted at once.
+        const CHUNK_LEN: usize = 256;
+
+        let size = ::std::cmp::min(buf.len(), self.rest_elements as usize);
+        let mut chunk = [0.0f64; CHUNK_LEN];
+        for dest in buf[0..size].chunks_mut(CHUNK_LEN) {
+            let chunk = &mut chunk[0..dest.len()];
+            self.reader.read_f64_arr(chunk)?;
+            self.rest_elements -= dest.len() as u64;
+            for (dest, &src) in dest.iter_mut().zip(chunk.iter()) {
+                *dest = src as f32;
+            }
+        }
+        Ok(size)
+    }
+
+    /// Reads all elements into `Vec<f32>`, converting them during the read.
+    pub fn into_f32_vec(mut self) -> io::Result<Vec<f32>> {
+        let mut vec = vec![0.0; self.rest_elements as usize];
+        self.read_into_f32_buf(&mut vec[..])?;
+        Ok(vec)
+    }
+}
+
 impl<'a, R: 'a + Read> Iterator for ArrayAttributeReader<'a, R, bool> {
     type Item = io::Result<bool>;
 
@@ -172,13 +364,7 @@ impl<'a, R: 'a + Read> Iterator for ArrayAttributeReader<'a, R, bool> {
         if self.rest_elements == 0 {
             return None;
         }
-        self.rest_elements -= 1;
-        let raw = match self.reader.read_u8() {
-            Ok(val) => val,
-            Err(err) => return Some(Err(err)),
-        };
-        let val = (raw & 1) == 1;
-        Some(Ok(val))
+        Some(self.read_bool())
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -224,54 +410,54 @@ impl_attr_array_iter!(f64, read_f64);
 
 
 /// Attribute array decoder.
-enum ArrayDecoder<'a, R: 'a> {
-    /// Non-compressed stream.
-    ///
-    /// `encoding` == 0.
-    Raw(io::Take<&'a mut R>),
-    /// Zlib-compressed stream.
-    ///
-    /// `encoding` == 1.
-    #[cfg(feature = "flate2")]
-    Zlib(ZlibDecoder<io::Take<&'a mut R>>),
-    /// Zlib-compressed stream.
-    ///
-    /// `encoding` == 1.
-    #[cfg(feature = "libflate")]
-    Zlib(zlib::Decoder<io::Take<&'a mut R>>),
+struct ArrayDecoder<'a, R: 'a> {
+    /// Element stream.
+    stream: ArrayStream<'a, R>,
+    /// Cancel flag.
+    cancel_flag: Option<&'a AtomicBool>,
+    /// Parse statistics recorder.
+    stats: StatsRecorder<'a>,
 }
 
 impl<'a, R: 'a + Read> ArrayDecoder<'a, R> {
-    fn new(reader: &'a mut R, header: &ArrayAttributeHeader) -> Result<Self> {
-        match header.encoding {
-            0 => Ok(ArrayDecoder::Raw(
-                reader.take(header.bytelen_elements as u64),
-            )),
-            #[cfg(feature = "flate2")]
-            1 => {
-                Ok(ArrayDecoder::Zlib(ZlibDecoder::new(
-                    reader.take(header.bytelen_elements as u64),
-                )))
-            },
-            #[cfg(feature = "libflate")]
-            1 => {
-                Ok(ArrayDecoder::Zlib(zlib::Decoder::new(
-                    reader.take(header.bytelen_elements as u64),
-                )?))
-            },
-            _ => Err(Error::UnknownArrayAttributeEncoding(header.encoding)),
-        }
+    fn new(
+        reader: &'a mut R,
+        header: &ArrayAttributeHeader,
+        array_codecs: &ArrayCodecs,
+        cancel_flag: Option<&'a AtomicBool>,
+        stats: StatsRecorder<'a>,
+    ) -> Result<Self> {
+        let bytelen = header.bytelen_elements as u64;
+        let stream = if header.encoding == 0 {
+            ArrayStream::Raw(reader.take(bytelen))
+        } else {
+            match array_codecs.get(header.encoding) {
+                Some(codec) => {
+                    stats.record_compressed_array(bytelen);
+                    let reader: &'a mut (dyn Read + 'a) = reader;
+                    ArrayStream::Decoded(codec.decode(reader.take(bytelen))?)
+                },
+                None => return Err(Error::UnknownArrayAttributeEncoding(header.encoding)),
+            }
+        };
+        Ok(ArrayDecoder {
+            stream: stream,
+            cancel_flag: cancel_flag,
+            stats: stats,
+        })
     }
 }
 
 impl<'a, R: 'a + Read> Read for ArrayDecoder<'a, R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        match *self {
-            ArrayDecoder::Raw(ref mut reader) => reader.read(buf),
-            #[cfg(feature = "flate2")]
-            ArrayDecoder::Zlib(ref mut reader) => reader.read(buf),
-            #[cfg(feature = "libflate")]
-            ArrayDecoder::Zlib(ref mut reader) => reader.read(buf),
+        if self.cancel_flag.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            return Err(cancelled_io_error());
+        }
+        match self.stream {
+            ArrayStream::Raw(ref mut reader) => reader.read(buf),
+            ArrayStream::Decoded(ref mut reader) => {
+                self.stats.time_decompression(|| reader.read(buf))
+            },
         }
     }
 }
@@ -281,13 +467,21 @@ impl<'a, R: 'a> fmt::Debug for ArrayDecoder<'a, R> {
         write!(
             f,
             "ArrayDecoder::{}",
-            match *self {
-                ArrayDecoder::Raw(_) => "Raw",
-                #[cfg(feature = "flate2")]
-                ArrayDecoder::Zlib(_) => "Zlib",
-                #[cfg(feature = "libflate")]
-                ArrayDecoder::Zlib(_) => "Zlib",
+            match self.stream {
+                ArrayStream::Raw(_) => "Raw",
+                ArrayStream::Decoded(_) => "Decoded",
             }
         )
     }
 }
+
+
+/// Stream of array attribute elements.
+enum ArrayStream<'a, R: 'a> {
+    /// Non-compressed stream.
+    ///
+    /// `encoding` == 0.
+    Raw(io::Take<&'a mut R>),
+    /// Stream decoded by the registered codec.
+    Decoded(Box<dyn Read + 'a>),
+}
@@ -1,16 +1,22 @@
 //! Node attributes.
 
+use std::sync::atomic::AtomicBool;
 use parser::binary::Warnings;
+use parser::binary::codec::ArrayCodecs;
 use parser::binary::error::{Result, Error, Warning};
 use parser::binary::event::NodeHeader;
 use parser::binary::reader::{ParserSource, ReadLittleEndian};
+use parser::binary::stats::StatsRecorder;
 use parser::binary::utils::{AttributeValues, AttributeValue};
 use self::array::read_array_attribute;
-pub use self::array::{ArrayAttribute, ArrayAttributeReader};
+pub use self::array::{ArrayAttribute, ArrayAttributeReader, FromArrayStream};
+pub use self::owned::{OwnedAttribute, FromOwnedAttribute};
+pub use self::owned::{OwnedAttributeOptions, LargeBinaries, BinaryRef};
 use self::special::read_special_attribute;
 pub use self::special::{SpecialAttribute, SpecialAttributeType};
 
 mod array;
+mod owned;
 mod special;
 
 
@@ -27,6 +33,12 @@ pub struct Attributes<'a, R: 'a> {
     source: &'a mut R,
     /// Parser warnings.
     warnings: &'a mut Warnings,
+    /// Array attribute codecs.
+    array_codecs: &'a ArrayCodecs,
+    /// Cancel flag.
+    cancel_flag: Option<&'a AtomicBool>,
+    /// Parse statistics recorder.
+    stats: StatsRecorder<'a>,
 }
 
 impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
@@ -40,6 +52,23 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
         self.rest_attributes
     }
 
+    /// Reads all rest attributes into `OwnedAttribute`s.
+    pub fn collect_owned(&mut self) -> Result<Vec<OwnedAttribute>> {
+        self.collect_owned_with(&mut OwnedAttributeOptions::default())
+    }
+
+    /// Reads all rest attributes into `OwnedAttribute`s with the given options.
+    pub fn collect_owned_with(
+        &mut self,
+        options: &mut OwnedAttributeOptions,
+    ) -> Result<Vec<OwnedAttribute>> {
+        let mut result = Vec::with_capacity(self.rest_attributes as usize);
+        while let Some(attr) = self.next_attribute()? {
+            result.push(OwnedAttribute::load_from_parser_event_with(attr, options)?);
+        }
+        Ok(result)
+    }
+
     /// Returns the next attribute if available.
     pub fn next_attribute(&mut self) -> Result<Option<Attribute<R>>> {
         if self.rest_attributes == 0 {
@@ -55,6 +84,7 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
         self.rest_attributes -= 1;
         let type_code = self.source.read_u8()?;
         let position = self.source.position();
+        self.stats.record_attribute(type_code);
         match type_code {
             // Primitive type attributes.
             b'C' => {
@@ -93,7 +123,14 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
             // Array type attributes.
             b'b' | b'i' | b'l' | b'f' | b'd' => {
                 let (attr, end_offset) =
-                    read_array_attribute(self.source, self.warnings, type_code)?;
+                    read_array_attribute(
+                    self.source,
+                    self.warnings,
+                    self.array_codecs,
+                    self.cancel_flag,
+                    self.stats,
+                    type_code,
+                )?;
                 self.prev_attr_end = Some(end_offset);
                 Ok(Some(attr.into()))
             },
@@ -139,6 +176,9 @@ impl<'a, R: 'a + ParserSource> From<ArrayAttribute<'a, R>> for Attribute<'a, R>
 pub fn new_attributes<'a, R: 'a>(
     source: &'a mut R,
     warnings: &'a mut Warnings,
+    array_codecs: &'a ArrayCodecs,
+    cancel_flag: Option<&'a AtomicBool>,
+    stats: StatsRecorder<'a>,
     header: &NodeHeader,
 ) -> Attributes<'a, R> {
     Attributes {
@@ -147,6 +187,9 @@ pub fn new_attributes<'a, R: 'a>(
         prev_attr_end: None,
         source: source,
         warnings: warnings,
+        array_codecs: array_codecs,
+        cancel_flag: cancel_flag,
+        stats: stats,
     }
 }
 
@@ -0,0 +1,537 @@
+//! Owned node attribute.
+
+use std::fmt;
+use std::io;
+use std::io::Write;
+use parser::binary::Result;
+use parser::binary::event::attribute::{Attributes, Attribute, PrimitiveAttribute};
+use parser::binary::event::attribute::{ArrayAttribute, SpecialAttribute, SpecialAttributeType};
+use parser::binary::reader::ParserSource;
+use parser::binary::utils::{CoercionLoss, StrictCoercion};
+
+
+/// Owned node attribute.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum OwnedAttribute {
+    /// `bool`.
+    Bool(bool),
+    /// `i16`.
+    I16(i16),
+    /// `i32`.
+    I32(i32),
+    /// `i64`.
+    I64(i64),
+    /// `f32`.
+    F32(f32),
+    /// `f64`.
+    F64(f64),
+    /// `Box<[bool]>`.
+    ArrBool(Box<[bool]>),
+    /// `Box<[i32]>`.
+    ArrI32(Box<[i32]>),
+    /// `Box<[i64]>`.
+    ArrI64(Box<[i64]>),
+    /// `Box<[f32]>`.
+    ArrF32(Box<[f32]>),
+    /// `Box<[f64]>`.
+    ArrF64(Box<[f64]>),
+    /// `String`.
+    String(::std::result::Result<String, Vec<u8>>),
+    /// `Box<[u8]>`.
+    Binary(Box<[u8]>),
+    /// Binary not loaded into memory (see `LargeBinaries`).
+    BinaryRef(BinaryRef),
+}
+
+impl OwnedAttribute {
+    /// Returns the value as `T`, with strict type check.
+    pub fn get<'a, T: FromOwnedAttribute<'a>>(&'a self) -> Option<T> {
+        T::from_owned_attribute(self)
+    }
+
+    /// Returns the value converted into `T`, with loose type check.
+    ///
+    /// Conversions are the same as `AttributeValue::from_attribute_loose()`.
+    pub fn get_loose<'a, T: FromOwnedAttribute<'a>>(&'a self) -> Option<T> {
+        T::from_owned_attribute_loose(self)
+    }
+
+    /// Returns the value as `bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        self.get()
+    }
+
+    /// Returns the value as `i32`, converting from `i16`.
+    pub fn as_i32(&self) -> Option<i32> {
+        self.get_loose()
+    }
+
+    /// Returns the value as `i64`, converting from `i16` and `i32`.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.get_loose()
+    }
+
+    /// Returns the value as `f32`, converting from `f64`.
+    pub fn as_f32(&self) -> Option<f32> {
+        self.get_loose()
+    }
+
+    /// Returns the value as `f64`, converting from `f32`.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.get_loose()
+    }
+
+    /// Returns the value as string if it is a valid UTF-8 string.
+    pub fn as_str(&self) -> Option<&str> {
+        self.get()
+    }
+
+    /// Returns the value as binary.
+    pub fn as_binary(&self) -> Option<&[u8]> {
+        self.get()
+    }
+
+    /// Returns the location of the binary which is not loaded into memory.
+    pub fn as_binary_ref(&self) -> Option<BinaryRef> {
+        match *self {
+            OwnedAttribute::BinaryRef(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as `bool` array.
+    pub fn as_bool_slice(&self) -> Option<&[bool]> {
+        self.get()
+    }
+
+    /// Returns the value as `i32` array.
+    pub fn as_i32_slice(&self) -> Option<&[i32]> {
+        self.get()
+    }
+
+    /// Returns the value as `i64` array.
+    pub fn as_i64_slice(&self) -> Option<&[i64]> {
+        self.get()
+    }
+
+    /// Returns the value as `f32` array.
+    pub fn as_f32_slice(&self) -> Option<&[f32]> {
+        self.get()
+    }
+
+    /// Returns the value as `f64` array.
+    pub fn as_f64_slice(&self) -> Option<&[f64]> {
+        self.get()
+    }
+
+    /// Loads `OwnedAttribute`s from `parser::binary::Attributes`.
+    ///
+    /// This is same as `Attributes::collect_owned()`.
+    pub fn load_attrs_from_parser_event<R>(mut attrs: Attributes<R>) -> Result<Vec<Self>>
+    where
+        R: ParserSource,
+    {
+        attrs.collect_owned()
+    }
+
+    /// Loads an `OwnedAttribute` from `parser::binary::Attribute`.
+    pub fn load_from_parser_event<R>(attr: Attribute<R>) -> io::Result<Self>
+    where
+        R: ParserSource,
+    {
+        Self::load_from_parser_event_with(attr, &mut OwnedAttributeOptions::default())
+    }
+
+    /// Loads an `OwnedAttribute` from `parser::binary::Attribute` with the given options.
+    ///
+    /// If `options.strict_coercion` is set, the attribute is recorded as the next attribute of
+    /// the current node, with the loss of narrowing if any.
+    pub fn load_from_parser_event_with<R>(
+        attr: Attribute<R>,
+        options: &mut OwnedAttributeOptions,
+    ) -> io::Result<Self>
+    where
+        R: ParserSource,
+    {
+        let mut loss = None;
+        let attr = Self::load_with_loss(attr, options, &mut loss)?;
+        if let Some(ref mut strict) = options.strict_coercion {
+            strict.push_attribute(loss);
+        }
+        Ok(attr)
+    }
+
+    /// Loads an `OwnedAttribute`, and sets `loss` if the narrowing is lossy.
+    ///
+    /// The loss is checked only in the strict coercion mode, since it requires reading
+    /// elements one by one.
+    fn load_with_loss<R>(
+        attr: Attribute<R>,
+        options: &mut OwnedAttributeOptions,
+        loss: &mut Option<CoercionLoss>,
+    ) -> io::Result<Self>
+    where
+        R: ParserSource,
+    {
+        Ok(match attr {
+            Attribute::Primitive(PrimitiveAttribute::Bool(v)) => OwnedAttribute::Bool(v),
+            Attribute::Primitive(PrimitiveAttribute::I16(v)) => OwnedAttribute::I16(v),
+            Attribute::Primitive(PrimitiveAttribute::I32(v)) => OwnedAttribute::I32(v),
+            Attribute::Primitive(PrimitiveAttribute::I64(v)) => OwnedAttribute::I64(v),
+            Attribute::Primitive(PrimitiveAttribute::F32(v)) => OwnedAttribute::F32(v),
+            Attribute::Primitive(PrimitiveAttribute::F64(v)) => OwnedAttribute::F64(v),
+            Attribute::Array(ArrayAttribute::Bool(arr)) => {
+                OwnedAttribute::ArrBool(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::I32(arr)) => {
+                OwnedAttribute::ArrI32(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::I64(arr)) => {
+                OwnedAttribute::ArrI64(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::F32(arr)) => {
+                OwnedAttribute::ArrF32(arr.into_vec()?.into_boxed_slice())
+            },
+            Attribute::Array(ArrayAttribute::F64(arr)) => {
+                if options.narrow_f64_arrays && options.strict_coercion.is_some() {
+                    let arr = arr.into_iter()
+                        .map(|v| v.map(|v| CoercionLoss::narrow_f64(v, loss)))
+                        .collect::<io::Result<Vec<_>>>()?;
+                    OwnedAttribute::ArrF32(arr.into_boxed_slice())
+                } else if options.narrow_f64_arrays {
+                    OwnedAttribute::ArrF32(arr.into_f32_vec()?.into_boxed_slice())
+                } else {
+                    OwnedAttribute::ArrF64(arr.into_vec()?.into_boxed_slice())
+                }
+            },
+            Attribute::Special(v) => {
+                match v.value_type() {
+                    SpecialAttributeType::Binary if options.is_large_binary(&v) => {
+                        OwnedAttribute::BinaryRef(options.store_large_binary(v)?)
+                    },
+                    SpecialAttributeType::Binary => {
+                        OwnedAttribute::Binary(v.into_vec()?.into_boxed_slice())
+                    },
+                    SpecialAttributeType::String => {
+                        OwnedAttribute::String(match String::from_utf8(v.into_vec()?) {
+                            Ok(s) => Ok(s),
+                            Err(e) => Err(e.into_bytes()),
+                        })
+                    },
+                }
+            },
+        })
+    }
+}
+
+
+
+/// Location of a binary attribute which is not loaded into memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BinaryRef {
+    /// Offset of the content in the FBX source or in the sink (see `LargeBinaries`).
+    pub offset: u64,
+    /// Length of the content in bytes.
+    pub len: u64,
+}
+
+
+/// Handling of large binary attributes, such as embedded textures in `Video` objects.
+pub struct LargeBinaries {
+    /// Minimum length of binaries not to be loaded into memory.
+    threshold: u64,
+    /// Sink of the contents, or `None` to keep only the offsets in the FBX source.
+    sink: Option<Box<dyn Write + Send>>,
+    /// Length written to the sink.
+    written: u64,
+}
+
+impl LargeBinaries {
+    /// Creates a new `LargeBinaries` which keeps offsets and lengths of the binaries in the
+    /// FBX source, without reading the contents.
+    ///
+    /// The contents can be read later by seeking the source to the offsets.
+    pub fn reference(threshold: u64) -> Self {
+        LargeBinaries {
+            threshold: threshold,
+            sink: None,
+            written: 0,
+        }
+    }
+
+    /// Creates a new `LargeBinaries` which streams the binaries to the given sink.
+    ///
+    /// Contents are written in loaded order, and `BinaryRef::offset` is the offset in the sink
+    /// (i.e. the total length written before the content).
+    pub fn stream<W: Write + Send + 'static>(threshold: u64, sink: W) -> Self {
+        LargeBinaries {
+            threshold: threshold,
+            sink: Some(Box::new(sink)),
+            written: 0,
+        }
+    }
+
+    /// Returns the minimum length of binaries not to be loaded into memory.
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+
+    /// Returns the length written to the sink.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Returns the sink, if available.
+    pub fn into_sink(self) -> Option<Box<dyn Write + Send>> {
+        self.sink
+    }
+}
+
+impl fmt::Debug for LargeBinaries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LargeBinaries")
+            .field("threshold", &self.threshold)
+            .field("stream", &self.sink.is_some())
+            .field("written", &self.written)
+            .finish()
+    }
+}
+
+
+/// Options for loading `OwnedAttribute`s.
+#[derive(Default, Debug)]
+pub struct OwnedAttributeOptions {
+    /// Whether to load `f64` arrays as `OwnedAttribute::ArrF32`.
+    ///
+    /// If enabled, arrays are converted during the read, without allocating the whole `f64`
+    /// array.
+    pub narrow_f64_arrays: bool,
+    /// Handling of large binaries, or `None` to load all binaries into memory.
+    pub large_binaries: Option<LargeBinaries>,
+    /// Collector of lossy conversions, or `None` to convert silently.
+    ///
+    /// Loaders track the node path with `StrictCoercion::enter()` and `leave()`.
+    pub strict_coercion: Option<StrictCoercion>,
+}
+
+impl OwnedAttributeOptions {
+    /// Returns `true` if the attribute should not be loaded into memory.
+    fn is_large_binary<R: ParserSource>(&self, attr: &SpecialAttribute<R>) -> bool {
+        self.large_binaries
+            .as_ref()
+            .is_some_and(|large| attr.total_len() >= large.threshold)
+    }
+
+    /// Stores the large binary attribute, and returns its location.
+    fn store_large_binary<R: ParserSource>(
+        &mut self,
+        attr: SpecialAttribute<R>,
+    ) -> io::Result<BinaryRef> {
+        let large = self.large_binaries.as_mut().expect("Should be checked by the caller");
+        let len = attr.total_len();
+        match large.sink {
+            Some(ref mut sink) => {
+                let offset = large.written;
+                let copied = attr.copy_to(sink)?;
+                large.written += copied;
+                Ok(BinaryRef {
+                    offset: offset,
+                    len: copied,
+                })
+            },
+            None => Ok(BinaryRef {
+                offset: attr.begin_offset(),
+                len: len,
+            }),
+        }
+    }
+}
+
+
+macro_rules! impl_owned_attribute_from {
+    ($($t:ty => $variant:ident, |$v:ident| $conv:expr;)*) => {
+        $(
+            impl From<$t> for OwnedAttribute {
+                fn from($v: $t) -> Self {
+                    OwnedAttribute::$variant($conv)
+                }
+            }
+        )*
+    }
+}
+
+impl_owned_attribute_from! {
+    bool => Bool, |v| v;
+    i16 => I16, |v| v;
+    i32 => I32, |v| v;
+    i64 => I64, |v| v;
+    f32 => F32, |v| v;
+    f64 => F64, |v| v;
+    Vec<bool> => ArrBool, |v| v.into_boxed_slice();
+    Vec<i32> => ArrI32, |v| v.into_boxed_slice();
+    Vec<i64> => ArrI64, |v| v.into_boxed_slice();
+    Vec<f32> => ArrF32, |v| v.into_boxed_slice();
+    Vec<f64> => ArrF64, |v| v.into_boxed_slice();
+    Vec<u8> => Binary, |v| v.into_boxed_slice();
+    &[bool] => ArrBool, |v| v.into();
+    &[i32] => ArrI32, |v| v.into();
+    &[i64] => ArrI64, |v| v.into();
+    &[f32] => ArrF32, |v| v.into();
+    &[f64] => ArrF64, |v| v.into();
+    &[u8] => Binary, |v| v.into();
+    String => String, |v| Ok(v);
+    &str => String, |v| Ok(v.to_owned());
+}
+
+
+/// Types which can be read from an `OwnedAttribute`.
+pub trait FromOwnedAttribute<'a>: Sized {
+    /// Reads the attribute as `Self` type.
+    ///
+    /// The value type is strictly checked.
+    /// Returns `None` if the types didn't match.
+    fn from_owned_attribute(attr: &'a OwnedAttribute) -> Option<Self>;
+
+    /// Reads the attribute and converts into `Self` type.
+    ///
+    /// The value type is loosely checked, in the same manner as
+    /// `AttributeValue::from_attribute_loose()`.
+    /// Returns `None` if the types were incompatible.
+    fn from_owned_attribute_loose(attr: &'a OwnedAttribute) -> Option<Self> {
+        Self::from_owned_attribute(attr)
+    }
+}
+
+macro_rules! impl_from_owned_attribute {
+    ($($t:ty => $variant:ident $(, $loose_variant:ident)*;)*) => {
+        $(
+            impl<'a> FromOwnedAttribute<'a> for $t {
+                fn from_owned_attribute(attr: &'a OwnedAttribute) -> Option<Self> {
+                    match *attr {
+                        OwnedAttribute::$variant(ref v) => Some(v.to_owned().into()),
+                        _ => None,
+                    }
+                }
+
+                fn from_owned_attribute_loose(attr: &'a OwnedAttribute) -> Option<Self> {
+                    match *attr {
+                        OwnedAttribute::$variant(ref v) => Some(v.to_owned().into()),
+                        $(
+                            OwnedAttribute::$loose_variant(ref v) => {
+                                Some(v.iter().map(|&v| v as _).collect())
+                            },
+                        )*
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_from_owned_attribute! {
+    Vec<bool> => ArrBool;
+    Vec<i32> => ArrI32;
+    Vec<i64> => ArrI64, ArrI32;
+    Vec<f32> => ArrF32, ArrF64;
+    Vec<f64> => ArrF64, ArrF32;
+    Vec<u8> => Binary;
+}
+
+macro_rules! impl_from_owned_attribute_primitive {
+    ($($t:ty => $variant:ident $(, $loose_variant:ident)*;)*) => {
+        $(
+            impl<'a> FromOwnedAttribute<'a> for $t {
+                fn from_owned_attribute(attr: &'a OwnedAttribute) -> Option<Self> {
+                    match *attr {
+                        OwnedAttribute::$variant(v) => Some(v),
+                        _ => None,
+                    }
+                }
+
+                fn from_owned_attribute_loose(attr: &'a OwnedAttribute) -> Option<Self> {
+                    match *attr {
+                        OwnedAttribute::$variant(v) => Some(v),
+                        $(OwnedAttribute::$loose_variant(v) => Some(v as $t),)*
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_from_owned_attribute_primitive! {
+    bool => Bool;
+    i16 => I16;
+    i32 => I32, I16;
+    i64 => I64, I32, I16;
+    f32 => F32, F64;
+    f64 => F64, F32;
+}
+
+macro_rules! impl_from_owned_attribute_slice {
+    ($($t:ty => $variant:ident;)*) => {
+        $(
+            impl<'a> FromOwnedAttribute<'a> for &'a [$t] {
+                fn from_owned_attribute(attr: &'a OwnedAttribute) -> Option<Self> {
+                    match *attr {
+                        OwnedAttribute::$variant(ref v) => Some(v),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_from_owned_attribute_slice! {
+    bool => ArrBool;
+    i32 => ArrI32;
+    i64 => ArrI64;
+    f32 => ArrF32;
+    f64 => ArrF64;
+    u8 => Binary;
+}
+
+impl<'a> FromOwnedAttribute<'a> for &'a str {
+    fn from_owned_attribute(attr: &'a OwnedAttribute) -> Option<Self> {
+        match *attr {
+            OwnedAttribute::String(Ok(ref s)) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> FromOwnedAttribute<'a> for String {
+    fn from_owned_attribute(attr: &'a OwnedAttribute) -> Option<Self> {
+        <&str>::from_owned_attribute(attr).map(str::to_owned)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::OwnedAttribute;
+
+    #[test]
+    fn test_attribute_accessors() {
+        let i16_attr = OwnedAttribute::I16(-3);
+        assert_eq!(i16_attr.as_i64(), Some(-3));
+        assert_eq!(i16_attr.as_i32(), Some(-3));
+        assert_eq!(i16_attr.get::<i64>(), None);
+        assert_eq!(i16_attr.as_f64(), None);
+        assert_eq!(OwnedAttribute::F32(0.5).as_f64(), Some(0.5));
+        assert_eq!(OwnedAttribute::I64(1).as_i32(), None);
+        let arr = OwnedAttribute::from(vec![0.5f32, 1.5]);
+        assert_eq!(arr.as_f64_slice(), None);
+        assert_eq!(arr.get_loose::<Vec<f64>>(), Some(vec![0.5, 1.5]));
+        assert_eq!(
+            OwnedAttribute::from(vec![1i32, -2]).get_loose::<Vec<i64>>(),
+            Some(vec![1, -2])
+        );
+        assert_eq!(OwnedAttribute::from("s").as_str(), Some("s"));
+        assert_eq!(OwnedAttribute::String(Err(vec![0xff])).as_str(), None);
+        assert_eq!(OwnedAttribute::from(vec![1u8]).as_binary(), Some(&[1][..]));
+    }
+}
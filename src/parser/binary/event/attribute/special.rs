@@ -1,7 +1,7 @@
 //! Special type node attribute.
 
 use std::io;
-use std::io::Read;
+use std::io::{Read, Write};
 
 use parser::binary::reader::{ParserSource, ReadLittleEndian, LimitedSeekReader};
 
@@ -40,7 +40,8 @@ impl<'a, R: 'a + ParserSource> SpecialAttribute<'a, R> {
         LimitedSeekReader::new(self.source.by_ref(), current, begin, self.end_offset)
     }
 
-    fn begin_offset(&self) -> u64 {
+    /// Returns the offset of the attribute value in the source.
+    pub fn begin_offset(&self) -> u64 {
         self.end_offset - self.total_len()
     }
 
@@ -66,6 +67,13 @@ impl<'a, R: 'a + ParserSource> SpecialAttribute<'a, R> {
         Ok(buf)
     }
 
+    /// Copies the rest of the attribute to the given writer, and returns the copied length.
+    ///
+    /// The value is streamed through a small buffer, so the whole value is never allocated.
+    pub fn copy_to<W: Write + ?Sized>(mut self, sink: &mut W) -> io::Result<u64> {
+        io::copy(&mut self.reader(), sink)
+    }
+
     /// Read the attribute to the string.
     pub fn into_string(mut self) -> io::Result<String> {
         let mut buf = String::with_capacity(self.rest_len() as usize);
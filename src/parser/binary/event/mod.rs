@@ -2,12 +2,15 @@
 
 use std::io;
 
-use parser::binary::RootParser;
+use parser::binary::{RootParser, FbxVersion};
 use parser::binary::error::{Result, Error, Warning};
-use parser::binary::reader::{ParserSource, ReadLittleEndian};
+use parser::binary::reader::{ParserSource, ReadLittleEndian, LimitedSeekReader};
+use parser::binary::stats::StatsRecorder;
 pub use self::attribute::{Attributes, Attribute, SpecialAttributeType};
 pub use self::attribute::{PrimitiveAttribute, ArrayAttribute, SpecialAttribute};
-pub use self::attribute::ArrayAttributeReader;
+pub use self::attribute::{ArrayAttributeReader, FromArrayStream};
+pub use self::attribute::{OwnedAttribute, FromOwnedAttribute};
+pub use self::attribute::{OwnedAttributeOptions, LargeBinaries, BinaryRef};
 
 mod attribute;
 
@@ -21,6 +24,10 @@ pub enum Event<'a, R: 'a> {
     EndFbx(Result<FbxFooter>),
     /// Start of a node.
     StartNode(StartNode<'a, R>),
+    /// Start of a node with unparsed attributes.
+    ///
+    /// Emitted instead of `StartNode` if enabled by `RootParser::set_raw_attributes()`.
+    RawNode(RawNode<'a, R>),
     /// End of a node.
     EndNode,
 }
@@ -43,6 +50,12 @@ impl<'a, R: 'a + ParserSource> From<StartNode<'a, R>> for Event<'a, R> {
     }
 }
 
+impl<'a, R: 'a + ParserSource> From<RawNode<'a, R>> for Event<'a, R> {
+    fn from(h: RawNode<'a, R>) -> Self {
+        Event::RawNode(h)
+    }
+}
+
 
 /// FBX header.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -96,14 +109,76 @@ where
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FbxFooter {
     /// Unknown part 1.
+    ///
+    /// This is a hash derived from the file ID and the creation time by FBX SDK.
+    /// The algorithm is not public, but FBX SDK accepts `FbxFooter::DEFAULT_FILE_ID_HASH`
+    /// with the well-known file ID and creation time used by many exporters.
     pub unknown1: [u8; 16],
     /// FBX version.
     pub version: u32,
     /// Unknown part 2.
+    ///
+    /// This is a constant magic (`FbxFooter::MAGIC`).
     pub unknown2: [u8; 16],
 }
 
 impl FbxFooter {
+    /// Constant magic at the end of the footer (`unknown2`).
+    pub const MAGIC: [u8; 16] = [
+        0xf8, 0x5a, 0x8c, 0x6a, 0xde, 0xf5, 0xd9, 0x7e,
+        0xec, 0xe9, 0x0c, 0xe3, 0x75, 0x8f, 0x29, 0x0b,
+    ];
+
+    /// File ID hash (`unknown1`) written by exporters which use the fixed file ID and the
+    /// creation time `1970-01-01 10:00:00:000` (for example, Blender).
+    pub const DEFAULT_FILE_ID_HASH: [u8; 16] = [
+        0xfa, 0xbc, 0xab, 0x09, 0xd0, 0xc8, 0xd4, 0x66,
+        0xb1, 0x76, 0xfb, 0x83, 0x1c, 0xf7, 0x26, 0x7e,
+    ];
+
+    /// Creates a new `FbxFooter` with the default file ID hash.
+    pub fn new(version: u32) -> Self {
+        Self::with_file_id_hash(version, Self::DEFAULT_FILE_ID_HASH)
+    }
+
+    /// Creates a new `FbxFooter` with the given file ID hash.
+    pub fn with_file_id_hash(version: u32, file_id_hash: [u8; 16]) -> Self {
+        FbxFooter {
+            unknown1: file_id_hash,
+            version: version,
+            unknown2: Self::MAGIC,
+        }
+    }
+
+    /// Returns the file ID hash (`unknown1`).
+    pub fn file_id_hash(&self) -> &[u8; 16] {
+        &self.unknown1
+    }
+
+    /// Returns the magic (`unknown2`).
+    pub fn magic(&self) -> &[u8; 16] {
+        &self.unknown2
+    }
+
+    /// Returns `true` if the magic is `FbxFooter::MAGIC`.
+    pub fn has_valid_magic(&self) -> bool {
+        self.unknown2 == Self::MAGIC
+    }
+
+    /// Writes the footer.
+    ///
+    /// `position` is the offset of the footer from the beginning of the file (i.e. right
+    /// after the null node record terminating the toplevel nodes), and is used to write the
+    /// padding.
+    pub fn write_to<W: io::Write>(&self, sink: &mut W, position: u64) -> io::Result<()> {
+        sink.write_all(&self.unknown1)?;
+        let padding = ((16 - ((position + 16) & 0x0f)) & 0x0f) as usize;
+        sink.write_all(&[0; 19][..padding + 4])?;
+        sink.write_all(&self.version.to_le_bytes())?;
+        sink.write_all(&[0; 120])?;
+        sink.write_all(&self.unknown2)
+    }
+
     /// Reads node header from the given parser and returns it.
     pub fn read_from_parser<R>(parser: &mut RootParser<R>) -> Result<Self>
     where
@@ -138,7 +213,7 @@ impl FbxFooter {
                 count += 1;
             }
             if count > 16 {
-                error!("FBX footer should have continuous 112 bytes of zeroes, but not found");
+                debug!("FBX footer should have continuous 112 bytes of zeroes, but not found");
                 return Err(Error::BrokenFbxFooter);
             }
             count
@@ -205,6 +280,21 @@ pub struct StartNode<'a, R: 'a> {
 }
 
 
+/// FBX node info with unparsed attributes.
+#[derive(Debug)]
+pub struct RawNode<'a, R: 'a> {
+    /// Node name.
+    pub name: &'a str,
+    /// Number of the node attributes.
+    pub num_attributes: u64,
+    /// Reader of the raw attributes block.
+    ///
+    /// Each attribute consists of a type code and its value, as stored in the FBX binary.
+    /// Unread part is skipped by the parser.
+    pub attributes: LimitedSeekReader<&'a mut R>,
+}
+
+
 /// Parser event without reference to a parser.
 #[derive(Debug, Clone)]
 pub enum EventBuilder {
@@ -220,14 +310,20 @@ pub enum EventBuilder {
 
 impl EventBuilder {
     /// Creates `Event` from the `EventBuilder` and the given parser.
-    pub fn build<R>(self, parser: &mut RootParser<R>) -> Event<R>
+    pub fn build<R>(self, parser: &mut RootParser<R>) -> Event<'_, R>
     where
         R: ParserSource,
     {
         match self {
             EventBuilder::StartFbx(header) => header.into(),
             EventBuilder::EndFbx(footer) => footer.into(),
-            EventBuilder::StartNode(builder) => builder.build(parser).into(),
+            EventBuilder::StartNode(builder) => {
+                if parser.raw_attributes {
+                    builder.build_raw(parser).into()
+                } else {
+                    builder.build(parser).into()
+                }
+            },
             EventBuilder::EndNode => Event::EndNode,
         }
     }
@@ -261,7 +357,7 @@ pub struct StartNodeBuilder {
 
 impl StartNodeBuilder {
     /// Creates `StartNode` from the `StartNodeBuilder` and the given parser.
-    pub fn build<R>(self, parser: &mut RootParser<R>) -> StartNode<R>
+    pub fn build<R>(self, parser: &mut RootParser<R>) -> StartNode<'_, R>
     where
         R: ParserSource,
     {
@@ -269,13 +365,49 @@ impl StartNodeBuilder {
             ref mut source,
             ref mut warnings,
             ref recent_node_name,
+            ref array_codecs,
+            ref cancel_flag,
+            ref stats,
             ..
         } = *parser;
         StartNode {
             name: recent_node_name.as_ref().expect(
                 "`RootParser::recent_node_name` must not be empty",
             ),
-            attributes: attribute::new_attributes(source, warnings, &self.header),
+            attributes: attribute::new_attributes(
+                source,
+                warnings,
+                array_codecs,
+                cancel_flag.as_ref().map(|flag| &**flag),
+                StatsRecorder::new(stats),
+                &self.header,
+            ),
+        }
+    }
+
+    /// Creates `RawNode` from the `StartNodeBuilder` and the given parser.
+    pub fn build_raw<R>(self, parser: &mut RootParser<R>) -> RawNode<'_, R>
+    where
+        R: ParserSource,
+    {
+        let RootParser {
+            ref mut source,
+            ref recent_node_name,
+            ref open_nodes,
+            ..
+        } = *parser;
+        let begin = source.position();
+        // The end offset is checked when the node is opened.
+        let end = open_nodes
+            .last()
+            .expect("`StartNodeBuilder` is created for the opened node")
+            .attributes_end;
+        RawNode {
+            name: recent_node_name.as_ref().expect(
+                "`RootParser::recent_node_name` must not be empty",
+            ),
+            num_attributes: self.header.num_attributes,
+            attributes: LimitedSeekReader::new(source, begin, begin, end),
         }
     }
 }
@@ -306,20 +438,21 @@ impl NodeHeader {
     where
         R: ParserSource,
     {
-        let fbx_version = parser.fbx_version.expect(
+        let fbx_version = FbxVersion(parser.fbx_version.expect(
             "Attempt to read FBX node header but the parser doesn't know FBX version",
-        );
-        let (end_offset, num_attributes, bytelen_attributes) = if fbx_version < 7500 {
-            let eo = parser.source.read_u32()? as u64;
-            let na = parser.source.read_u32()? as u64;
-            let bla = parser.source.read_u32()? as u64;
-            (eo, na, bla)
-        } else {
-            let eo = parser.source.read_u64()?;
-            let na = parser.source.read_u64()?;
-            let bla = parser.source.read_u64()?;
-            (eo, na, bla)
-        };
+        ));
+        let (end_offset, num_attributes, bytelen_attributes) =
+            if fbx_version.supports_64bit_node_headers() {
+                let eo = parser.source.read_u64()?;
+                let na = parser.source.read_u64()?;
+                let bla = parser.source.read_u64()?;
+                (eo, na, bla)
+            } else {
+                let eo = parser.source.read_u32()? as u64;
+                let na = parser.source.read_u32()? as u64;
+                let bla = parser.source.read_u32()? as u64;
+                (eo, na, bla)
+            };
         let bytelen_name = parser.source.read_u8()?;
         Ok(NodeHeader {
             end_offset: end_offset,
@@ -2,20 +2,37 @@
 
 use std::io;
 use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub use self::error::{Result, Error, Warning};
-pub use self::event::{Event, FbxHeader, FbxFooter, StartNode};
+pub use self::codec::{ArrayCodec, ArrayCodecs, EncodedArrayReader};
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+pub use self::codec::{ZlibBackend, ZlibCodec};
+pub use self::dyn_parser::{DynParser, OwnedEvent, OwnedStartNode, OwnedRawNode};
+pub use self::error::{Result, Error, ErrorKind, Warning};
+pub use self::event::{Event, FbxHeader, FbxFooter, StartNode, RawNode};
 pub use self::event::{Attributes, Attribute, SpecialAttributeType};
 pub use self::event::{PrimitiveAttribute, ArrayAttribute, SpecialAttribute};
-pub use self::event::ArrayAttributeReader;
+pub use self::event::{ArrayAttributeReader, FromArrayStream};
+pub use self::event::{OwnedAttribute, FromOwnedAttribute};
+pub use self::event::{OwnedAttributeOptions, LargeBinaries, BinaryRef};
 use self::event::{EventBuilder, NodeHeader, StartNodeBuilder};
 use self::event::read_fbx_header;
 pub use self::reader::{ParserSource, BasicSource, SeekableSource, LimitedSeekReader};
+pub use self::reader::ErasedSource;
+pub use self::version::FbxVersion;
+#[cfg(feature = "stats")]
+pub use self::stats::{ParseStats, NodeStats};
+use self::stats::{StatsSlot, StatsRecorder};
 
+mod codec;
+mod dyn_parser;
 mod error;
 mod event;
 mod reader;
+mod stats;
 pub mod utils;
+mod version;
 
 
 /// Warnings store.
@@ -29,11 +46,18 @@ impl Warnings {
     }
 
     /// Add a warning.
+    ///
+    /// The warning is logged at debug level, since the user can get it from the store.
     pub fn warn(&mut self, warning: Warning) {
-        warn!("FBX binary parser warning: {}", warning);
+        debug!("FBX binary parser warning: {}", warning);
         self.0.push(warning);
     }
 
+    /// Removes all warnings, keeping the allocated capacity.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
     /// Returns the inner vector.
     pub fn inner(self) -> Vec<Warning> {
         self.0
@@ -63,6 +87,31 @@ pub trait Parser<R: ParserSource> {
     fn skip_current_node(&mut self) -> Result<bool>;
     /// Creates subtree parser for the current node.
     fn subtree_parser(&mut self) -> SubtreeParser<R>;
+
+    /// Creates subtree parser for the current node, which skips the rest of the subtree on
+    /// drop.
+    ///
+    /// See `SubtreeGuard`.
+    fn subtree_guard(&mut self) -> SubtreeGuard<'_, R> {
+        SubtreeGuard::new(self.subtree_parser())
+    }
+
+    /// Returns the name of the current node.
+    ///
+    /// See `RootParser::current_node_name()`.
+    fn current_node_name(&self) -> Option<&str>;
+
+    /// Returns the number of the opened (and not closed) nodes.
+    ///
+    /// Depth of the implicit root node is `0`, so this is `1` after `StartNode` of a toplevel
+    /// node.
+    fn depth(&self) -> usize {
+        self.root_parser().num_open_nodes()
+    }
+
+    /// Returns names of the opened (and not closed) nodes, from the toplevel node to the most
+    /// recent opened node.
+    fn open_node_names(&self) -> &[String];
 }
 
 
@@ -82,7 +131,8 @@ enum State {
     ///
     /// This status means:
     ///
-    /// - if the next event is `NodeEnd`, there must be a null node header, and
+    /// - if the next event is `NodeEnd`, there must be a null node header (unless the parser is
+    ///   resynchronized after corrupt node), and
     /// - if the parser got an extra null header, it indicates end of implicit root node.
     NodeEnded,
 }
@@ -91,6 +141,8 @@ enum State {
 /// Information about opened (but not yet closed) node.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct OpenNode {
+    /// Start offset of the node header.
+    header_begin: u64,
     /// Start offset of the node attribute.
     ///
     /// Note that this doesn't mean start offset of node header.
@@ -102,6 +154,47 @@ struct OpenNode {
 }
 
 
+/// Snapshot of the parser state.
+///
+/// This can be used to resume parsing later on a re-opened seekable stream, by
+/// `RootParser::resume()`.
+/// Warnings, array codecs, and other parser settings are not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserSnapshot {
+    /// Parser state.
+    state: State,
+    /// Position in the source.
+    position: u64,
+    /// FBX version.
+    fbx_version: Option<u32>,
+    /// Open nodes stack.
+    open_nodes: Vec<OpenNode>,
+    /// Names of the open nodes.
+    open_node_names: Vec<String>,
+    /// Name of the recent closed node.
+    ended_node_name: Option<String>,
+}
+
+impl ParserSnapshot {
+    /// Returns the position in the source, where the parser resumes reading.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns FBX version.
+    ///
+    /// Returns `None` if the snapshot was taken before reading FBX header.
+    pub fn fbx_version(&self) -> Option<u32> {
+        self.fbx_version
+    }
+
+    /// Returns the number of the opened (and not closed) node.
+    pub fn num_open_nodes(&self) -> usize {
+        self.open_nodes.len()
+    }
+}
+
+
 /// Pull parser for whole FBX with binary format.
 #[derive(Debug)]
 pub struct RootParser<R> {
@@ -118,39 +211,165 @@ pub struct RootParser<R> {
     fbx_version: Option<u32>,
     /// Open nodes stack.
     open_nodes: Vec<OpenNode>,
+    /// Names of the open nodes, followed by spare name buffers.
+    ///
+    /// Only the first `open_nodes.len()` names are of the open nodes.
+    /// The buffer next to them is the name of the recent closed node if `has_ended_node_name`
+    /// is true, and the buffers are reused for the nodes opened later.
+    open_node_names: Vec<String>,
+    /// Whether the name of the recent closed node is available.
+    has_ended_node_name: bool,
     /// Node name of the recent opened node.
     recent_node_name: Option<String>,
+    /// Array attribute codecs.
+    array_codecs: ArrayCodecs,
+    /// Whether to recover from corrupt nodes.
+    recover_corrupt_nodes: bool,
+    /// Whether to emit `RawNode` events instead of `StartNode`.
+    raw_attributes: bool,
+    /// Cancel flag.
+    cancel_flag: Option<Arc<AtomicBool>>,
+    /// Whether to dump the parser state to the debug log on errors and warnings.
+    debug_dump: bool,
+    /// Parse statistics.
+    stats: StatsSlot,
 }
 
 impl<R: Read> RootParser<BasicSource<R>> {
     /// Creates a new binary parser.
     pub fn new(source: R) -> Self {
-        RootParser {
-            source: BasicSource::new(source),
-            state: Ok(State::Header),
-            warnings: Warnings::new(),
-            fbx_version: None,
-            open_nodes: Vec::new(),
-            recent_node_name: None,
-        }
+        RootParser::with_source(BasicSource::new(source), State::Header)
+    }
+
+    /// Resets the parser to read the given new source from the beginning.
+    ///
+    /// See `reset_with()`.
+    pub fn reset(&mut self, source: R) -> BasicSource<R> {
+        self.reset_with(BasicSource::new(source))
+    }
+}
+
+impl<R: Read + io::Seek> RootParser<SeekableSource<R>> {
+    /// Resets the parser to read the given new seekable source from the beginning.
+    ///
+    /// See `reset_with()`.
+    pub fn reset_seekable(&mut self, source: R) -> SeekableSource<R> {
+        self.reset_with(SeekableSource::new(source))
     }
 }
 
 impl<R: Read + io::Seek> RootParser<SeekableSource<R>> {
     /// Creates a new binary parser.
     pub fn from_seekable(source: R) -> Self {
+        RootParser::with_source(SeekableSource::new(source), State::Header)
+    }
+}
+
+impl<R: Read + io::Seek> RootParser<SeekableSource<R>> {
+    /// Creates a new binary parser which starts reading at the given node header.
+    ///
+    /// The parser doesn't read FBX header, so the FBX version should be given.
+    /// The next event will be `StartNode` of the node at `node_offset`.
+    /// After the node (and its siblings) ended, the parser will read FBX footer, and it would
+    /// fail unless the node is at toplevel.
+    pub fn from_seekable_at(source: R, fbx_version: u32, node_offset: u64) -> io::Result<Self> {
+        let mut source = SeekableSource::new(source);
+        io::Seek::seek(&mut source, io::SeekFrom::Start(node_offset))?;
+        let mut parser = RootParser::with_source(source, State::NodeEnded);
+        parser.fbx_version = Some(fbx_version);
+        Ok(parser)
+    }
+}
+
+impl<R: Read + io::Seek> RootParser<SeekableSource<R>> {
+    /// Creates a new binary parser which resumes parsing from the given snapshot.
+    ///
+    /// The source should have the same content as the source of the parser the snapshot was
+    /// taken from.
+    /// The next event will be the one which would be returned next from the original parser.
+    pub fn resume(source: R, snapshot: &ParserSnapshot) -> io::Result<Self> {
+        let mut source = SeekableSource::new(source);
+        io::Seek::seek(&mut source, io::SeekFrom::Start(snapshot.position))?;
+        let mut parser = RootParser::with_source(source, snapshot.state);
+        parser.fbx_version = snapshot.fbx_version;
+        parser.open_nodes = snapshot.open_nodes.clone();
+        parser.open_node_names = snapshot.open_node_names.clone();
+        parser.open_node_names.extend(snapshot.ended_node_name.clone());
+        parser.has_ended_node_name = snapshot.ended_node_name.is_some();
+        Ok(parser)
+    }
+}
+
+impl<R: ParserSource> RootParser<R> {
+    /// Creates a new binary parser with the given source and state, and the default settings.
+    fn with_source(source: R, state: State) -> Self {
         RootParser {
-            source: SeekableSource::new(source),
-            state: Ok(State::Header),
+            source: source,
+            state: Ok(state),
             warnings: Warnings::new(),
             fbx_version: None,
             open_nodes: Vec::new(),
+            open_node_names: Vec::new(),
+            has_ended_node_name: false,
             recent_node_name: None,
+            array_codecs: Default::default(),
+            recover_corrupt_nodes: false,
+            raw_attributes: false,
+            cancel_flag: None,
+            debug_dump: false,
+            stats: Default::default(),
         }
     }
-}
 
-impl<R: ParserSource> RootParser<R> {
+    /// Converts the source of the parser, keeping the state and settings.
+    fn map_source<S, F: FnOnce(R) -> S>(self, f: F) -> RootParser<S> {
+        RootParser {
+            source: f(self.source),
+            state: self.state,
+            warnings: self.warnings,
+            fbx_version: self.fbx_version,
+            open_nodes: self.open_nodes,
+            open_node_names: self.open_node_names,
+            has_ended_node_name: self.has_ended_node_name,
+            recent_node_name: self.recent_node_name,
+            array_codecs: self.array_codecs,
+            recover_corrupt_nodes: self.recover_corrupt_nodes,
+            raw_attributes: self.raw_attributes,
+            cancel_flag: self.cancel_flag,
+            debug_dump: self.debug_dump,
+            stats: self.stats,
+        }
+    }
+
+    /// Converts the parser into the one with the type-erased source, keeping the state and
+    /// settings.
+    ///
+    /// Loaders instantiated for `RootParser<ErasedSource>` can be shared by all kinds of
+    /// sources, at the cost of dynamic dispatch of reads.
+    pub fn into_erased<'a>(self) -> RootParser<ErasedSource<'a>>
+    where
+        R: 'a,
+    {
+        self.map_source(|source| Box::new(source) as ErasedSource<'a>)
+    }
+
+    /// Resets the parser to read the given new source from the beginning, and returns the old
+    /// source.
+    ///
+    /// Allocated buffers (such as the node name buffer, the warnings, and the open nodes stack)
+    /// are reused, so this is cheaper than creating a new parser for each file.
+    /// Parser settings (array codecs, corrupt node recovery, raw attributes, the cancel flag,
+    /// and the debug dump flag) are kept.
+    /// Parse statistics are also kept, so use `take_stats()` to get them per source.
+    pub fn reset_with(&mut self, source: R) -> R {
+        self.state = Ok(State::Header);
+        self.warnings.clear();
+        self.fbx_version = None;
+        self.open_nodes.clear();
+        self.has_ended_node_name = false;
+        ::std::mem::replace(&mut self.source, source)
+    }
+
     /// Returns FBX version of the reading input.
     ///
     /// Returns `None` if unknown yet.
@@ -173,6 +392,168 @@ impl<R: ParserSource> RootParser<R> {
         self.open_nodes.len()
     }
 
+    /// Returns the current position in the source.
+    pub fn position(&self) -> u64 {
+        self.source.position()
+    }
+
+    /// Returns the start offset of the node header of the most recent opened node.
+    pub fn current_node_offset(&self) -> Option<u64> {
+        self.open_nodes.last().map(|node| node.header_begin)
+    }
+
+    /// Returns names of the opened (and not closed) nodes, from the toplevel node to the most
+    /// recent opened node.
+    pub fn open_node_names(&self) -> &[String] {
+        &self.open_node_names[..self.open_nodes.len()]
+    }
+
+    /// Returns the name of the current node.
+    ///
+    /// After `StartNode` (or `RawNode`) event, this returns the name of the started node.
+    /// After `EndNode` event, this returns the name of the ended node.
+    /// Otherwise (e.g. before reading any nodes, or after the parser finished), this returns
+    /// `None`.
+    pub fn current_node_name(&self) -> Option<&str> {
+        match self.state {
+            Ok(State::NodeStarted) => self.open_node_names().last().map(String::as_str),
+            Ok(State::NodeEnded) => self.ended_node_name(),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the recent closed node if available.
+    fn ended_node_name(&self) -> Option<&str> {
+        if !self.has_ended_node_name {
+            return None;
+        }
+        self.open_node_names.get(self.open_nodes.len()).map(String::as_str)
+    }
+
+    /// Takes a snapshot of the parser state.
+    ///
+    /// Returns `None` if the parser has already finished or failed.
+    pub fn snapshot(&self) -> Option<ParserSnapshot> {
+        let state = *self.state.as_ref().ok()?;
+        Some(ParserSnapshot {
+            state: state,
+            position: self.source.position(),
+            fbx_version: self.fbx_version,
+            open_nodes: self.open_nodes.clone(),
+            open_node_names: self.open_node_names().to_vec(),
+            ended_node_name: self.ended_node_name().map(str::to_owned),
+        })
+    }
+
+    /// Returns reference to the array attribute codecs.
+    pub fn array_codecs(&self) -> &ArrayCodecs {
+        &self.array_codecs
+    }
+
+    /// Returns mutable reference to the array attribute codecs.
+    ///
+    /// Codecs for nonstandard encodings can be registered through this.
+    pub fn array_codecs_mut(&mut self) -> &mut ArrayCodecs {
+        &mut self.array_codecs
+    }
+
+    /// Returns whether the parser recovers from corrupt nodes.
+    pub fn recovers_corrupt_nodes(&self) -> bool {
+        self.recover_corrupt_nodes
+    }
+
+    /// Sets whether the parser recovers from corrupt nodes.
+    ///
+    /// If enabled, when the parser detects a corrupt node (wrong node end offset or invalid node
+    /// name), it moves to the end offset of the nearest enclosing node, adds
+    /// `Warning::SkippedCorruptSubtree`, and continues parsing.
+    /// Moving backward requires seekable source (such as `SeekableSource`), and the original
+    /// error is returned if the parser cannot move.
+    ///
+    /// Disabled by default.
+    pub fn set_recover_corrupt_nodes(&mut self, recover: bool) {
+        self.recover_corrupt_nodes = recover;
+    }
+
+    /// Returns whether the parser emits `Event::RawNode` instead of `Event::StartNode`.
+    pub fn raw_attributes(&self) -> bool {
+        self.raw_attributes
+    }
+
+    /// Sets whether the parser emits `Event::RawNode` instead of `Event::StartNode`.
+    ///
+    /// If enabled, node attributes are not parsed and the user can read the raw attribute
+    /// bytes.
+    /// This is useful for tools copying nodes as is, or for user defined attribute codecs.
+    /// Note that loaders in this crate expect `StartNode` events and fail on `RawNode`.
+    ///
+    /// Disabled by default.
+    pub fn set_raw_attributes(&mut self, raw: bool) {
+        self.raw_attributes = raw;
+    }
+
+    /// Returns the cancel flag.
+    pub fn cancel_flag(&self) -> Option<&Arc<AtomicBool>> {
+        self.cancel_flag.as_ref()
+    }
+
+    /// Sets the cancel flag.
+    ///
+    /// Once `true` is stored to the flag, the parser returns `Error::Cancelled` on the next
+    /// event or the next read of array attribute elements.
+    /// I/O errors returned by array attribute readers can be converted into `Error::Cancelled`
+    /// by `From` trait.
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    /// Returns whether the parser dumps its state to the debug log on errors and warnings.
+    pub fn debug_dump(&self) -> bool {
+        self.debug_dump
+    }
+
+    /// Sets whether the parser dumps its state to the debug log on errors and warnings.
+    ///
+    /// The dump is very large, so this is intended for debugging the parser itself.
+    /// Errors and warnings are always available through the returned `Error`s and
+    /// `warnings()` regardless of this flag.
+    ///
+    /// Disabled by default.
+    pub fn set_debug_dump(&mut self, dump: bool) {
+        self.debug_dump = dump;
+    }
+
+    /// Enables collecting parse statistics.
+    ///
+    /// Statistics collected so far are kept if already enabled.
+    #[cfg(feature = "stats")]
+    pub fn enable_stats(&mut self) {
+        if self.stats.is_none() {
+            self.stats = Some(Default::default());
+        }
+    }
+
+    /// Returns a copy of the statistics collected so far.
+    ///
+    /// Returns `None` if the statistics are not enabled.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Option<ParseStats> {
+        self.stats.as_ref().map(|stats| stats.borrow().clone())
+    }
+
+    /// Takes the collected statistics and disables collecting them.
+    #[cfg(feature = "stats")]
+    pub fn take_stats(&mut self) -> Option<ParseStats> {
+        self.stats.take().map(::std::cell::RefCell::into_inner)
+    }
+
+    /// Returns whether the parsing is cancelled.
+    fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
     /// Returns the node name of the recent opened node.
     pub fn recent_node_name(&self) -> Option<&str> {
         self.recent_node_name.as_ref().map(String::as_str)
@@ -190,15 +571,20 @@ impl<R: ParserSource> RootParser<R> {
 
     /// Set the parser state as error.
     fn set_error(&mut self, err: &Error) {
-        error!("FBX binary parser error: {}", err);
-        debug!("Parser: {:#?}", self);
+        // The error is returned to the user, so this is logged only for debugging.
+        debug!("FBX binary parser error: {}", err);
+        if self.debug_dump {
+            debug!("Parser: {:#?}", self);
+        }
         self.state = Err(err.clone());
     }
 
     /// Add warning.
     fn warn(&mut self, warning: Warning) {
         self.warnings.warn(warning);
-        debug!("Parser: {:#?}", self);
+        if self.debug_dump {
+            debug!("Parser: {:#?}", self);
+        }
     }
 
     /// Reads FBX header.
@@ -216,13 +602,8 @@ impl<R: ParserSource> RootParser<R> {
         self.skip_attributes()?;
 
         // Most recent opened node might ends here without a null node header.
-        if let Some(end) = self.open_nodes.last().map(|v| v.end) {
-            if self.source.position() == end {
-                // Most recent opened node ends here (without a null node header).
-                self.state = Ok(State::NodeEnded);
-                self.open_nodes.pop();
-                return Ok(EventBuilder::EndNode);
-            }
+        if let Some(builder) = self.end_node_without_null_header() {
+            return Ok(builder);
         }
 
         let builder = self.read_node_event()?;
@@ -231,17 +612,75 @@ impl<R: ParserSource> RootParser<R> {
 
     /// Gets event after node end.
     fn read_after_node_end(&mut self) -> Result<EventBuilder> {
+        // Enclosing node might end here if the parser is resynchronized after corrupt node.
+        if let Some(builder) = self.end_node_without_null_header() {
+            return Ok(builder);
+        }
+
         self.read_node_event()
     }
 
+    /// Closes the most recent opened node if it ends at the current position.
+    fn end_node_without_null_header(&mut self) -> Option<EventBuilder> {
+        let end = self.open_nodes.last().map(|v| v.end)?;
+        if self.source.position() != end {
+            return None;
+        }
+        // Most recent opened node ends here (without a null node header).
+        self.state = Ok(State::NodeEnded);
+        self.pop_open_node();
+        Some(EventBuilder::EndNode)
+    }
+
+    /// Tries to recover from the given error by skipping the corrupt node.
+    ///
+    /// Returns `EndNode` event builder of the corrupt node on success, and the given error on
+    /// failure.
+    fn recover_from_corrupt_node(&mut self, err: Error) -> Result<EventBuilder> {
+        if !self.recover_corrupt_nodes {
+            return Err(err);
+        }
+        // For wrong node end offset, the most recent opened node itself is corrupt and the
+        // parser should skip its parent.
+        // For invalid node name, the node is not opened and the parser should skip the most
+        // recent opened node.
+        let depth = match err {
+            Error::WrongNodeEndOffset { .. } => 2,
+            Error::NodeNameInvalidUtf8(_) => 1,
+            _ => return Err(err),
+        };
+        let enclosing = match self.open_nodes.len().checked_sub(depth) {
+            Some(index) => self.open_nodes[index],
+            None => return Err(err),
+        };
+        if enclosing.end < enclosing.begin {
+            return Err(err);
+        }
+        let position = self.source.position();
+        if self.source.seek_to(enclosing.end).is_err() {
+            return Err(err);
+        }
+        self.warn(Warning::SkippedCorruptSubtree {
+            begin: enclosing.begin,
+            end: enclosing.end,
+            position: position,
+        });
+        // The rest open nodes deeper than the enclosing node are closed by
+        // `end_node_without_null_header()`.
+        self.state = Ok(State::NodeEnded);
+        self.pop_open_node();
+        Ok(EventBuilder::EndNode)
+    }
+
     /// Reads a next node-related event from the source.
     ///
     /// This always returns `Ok(EventBuilder::StartNode)`, `Ok(EventBuilder::EndNode)`,
     /// `Ok(EventBuilder::EndFbx)` or `Err(_)`.
     fn read_node_event(&mut self) -> Result<EventBuilder> {
+        let header_begin = self.source.position();
         let header = NodeHeader::read_from_parser(self)?;
         if header.is_node_end() {
-            if let Some(last_node) = self.open_nodes.pop() {
+            if let Some(last_node) = self.open_nodes.last().cloned() {
                 // There is open nodes, so this is not end of the FBX.
                 let current_pos = self.source.position();
                 if current_pos != last_node.end {
@@ -252,6 +691,7 @@ impl<R: ParserSource> RootParser<R> {
                         real_end: current_pos,
                     });
                 }
+                self.pop_open_node();
             } else {
                 assert_eq!(
                     self.state.as_ref().ok(),
@@ -296,11 +736,35 @@ impl<R: ParserSource> RootParser<R> {
             };
 
             let current_pos = self.source.position();
+            let attributes_end = current_pos.checked_add(header.bytelen_attributes).ok_or(
+                Error::AttributesLengthOverflow {
+                    position: current_pos,
+                    bytelen: header.bytelen_attributes,
+                },
+            )?;
             self.open_nodes.push(OpenNode {
+                header_begin: header_begin,
                 begin: current_pos,
                 end: header.end_offset,
-                attributes_end: current_pos + header.bytelen_attributes,
+                attributes_end: attributes_end,
             });
+            // Reuse spare name buffers.
+            let name = self.recent_node_name.as_deref().unwrap_or_default();
+            match self.open_node_names.get_mut(self.open_nodes.len() - 1) {
+                Some(buf) => {
+                    buf.clear();
+                    buf.push_str(name);
+                },
+                None => self.open_node_names.push(name.to_owned()),
+            }
+            self.has_ended_node_name = false;
+            if let Some(ref name) = self.recent_node_name {
+                StatsRecorder::new(&self.stats).record_node(
+                    self.open_nodes.len(),
+                    name,
+                    header.end_offset.saturating_sub(header_begin),
+                );
+            }
 
             // Zero or more attributes come after node start.
             self.state = Ok(State::NodeStarted);
@@ -308,6 +772,14 @@ impl<R: ParserSource> RootParser<R> {
         }
     }
 
+    /// Closes the most recent opened node and remembers its name.
+    fn pop_open_node(&mut self) -> Option<OpenNode> {
+        let node = self.open_nodes.pop();
+        // The name remains in the buffer next to the open nodes.
+        self.has_ended_node_name = node.is_some();
+        node
+    }
+
     /// Reads an FBX footer.
     fn read_fbx_footer(&mut self) -> Result<FbxFooter> {
         self.set_finish();
@@ -332,11 +804,19 @@ impl<R: ParserSource> Parser<R> for RootParser<R> {
     }
 
     fn next_event(&mut self) -> Result<Event<R>> {
+        // Don't overwrite the state if the parser has already finished or failed.
+        if self.state.is_ok() && self.is_cancelled() {
+            self.set_error(&Error::Cancelled);
+        }
         let builder = match self.state.clone()? {
             State::Header => self.read_fbx_header(),
             State::NodeStarted => self.read_after_node_start(),
             State::NodeEnded => self.read_after_node_end(),
         };
+        let builder = match builder {
+            Err(err) => self.recover_from_corrupt_node(err),
+            builder => builder,
+        };
         if let Err(ref err) = builder {
             self.set_error(err);
         }
@@ -344,7 +824,7 @@ impl<R: ParserSource> Parser<R> for RootParser<R> {
     }
 
     fn skip_current_node(&mut self) -> Result<bool> {
-        if let Some(end) = self.open_nodes.pop().map(|v| v.end) {
+        if let Some(end) = self.pop_open_node().map(|v| v.end) {
             self.source.skip_to(end)?;
             self.state = Ok(State::NodeEnded);
             Ok(true)
@@ -356,6 +836,14 @@ impl<R: ParserSource> Parser<R> for RootParser<R> {
     fn subtree_parser(&mut self) -> SubtreeParser<R> {
         SubtreeParser::new(self)
     }
+
+    fn current_node_name(&self) -> Option<&str> {
+        RootParser::current_node_name(self)
+    }
+
+    fn open_node_names(&self) -> &[String] {
+        RootParser::open_node_names(self)
+    }
 }
 
 
@@ -379,6 +867,14 @@ impl<'a, R: 'a + ParserSource> SubtreeParser<'a, R> {
         }
     }
 
+    /// Returns the depth relative to the node the subtree parser is created for.
+    ///
+    /// This is `0` before reading any child nodes and after the subtree is all read, and `1`
+    /// after `StartNode` of a child node.
+    pub fn relative_depth(&self) -> usize {
+        self.root_parser.num_open_nodes().saturating_sub(self.initial_depth)
+    }
+
     /// Checks if the subtree parser can emit more events.
     ///
     /// Returns `Ok(())` if more events can be read,
@@ -405,12 +901,19 @@ impl<'a, R: 'a + ParserSource> SubtreeParser<'a, R> {
     /// Note that this is not intended to called for `SubtreeParser` reading implicit root node.
     /// If the subtree parser can read whole data, the parser skips all events including `FbxEnd`
     /// and returns `Err(Error::Finished)`.
-    pub fn skip_to_end(self) -> Result<()> {
+    pub fn skip_to_end(mut self) -> Result<()> {
+        self.skip_rest()
+    }
+
+    /// Skip to the end of the subtree parser's readable range.
+    ///
+    /// Same as `skip_to_end()`, but doesn't consume the subtree parser.
+    fn skip_rest(&mut self) -> Result<()> {
         if self.is_finished()? {
             return Ok(());
         }
         self.root_parser.open_nodes.truncate(self.initial_depth);
-        if let Some(end) = self.root_parser.open_nodes.pop().map(|v| v.end) {
+        if let Some(end) = self.root_parser.pop_open_node().map(|v| v.end) {
             self.root_parser.source.skip_to(end)?;
             self.root_parser.state = Ok(State::NodeEnded);
             Ok(())
@@ -439,6 +942,14 @@ impl<'a, R: 'a + ParserSource> Parser<R> for SubtreeParser<'a, R> {
     fn subtree_parser(&mut self) -> SubtreeParser<R> {
         SubtreeParser::new(self.root_parser)
     }
+
+    fn current_node_name(&self) -> Option<&str> {
+        self.root_parser.current_node_name()
+    }
+
+    fn open_node_names(&self) -> &[String] {
+        self.root_parser.open_node_names()
+    }
 }
 
 impl<'a, R: ParserSource, P: Parser<R>> Parser<R> for &'a mut P {
@@ -457,4 +968,662 @@ impl<'a, R: ParserSource, P: Parser<R>> Parser<R> for &'a mut P {
     fn subtree_parser(&mut self) -> SubtreeParser<R> {
         (**self).subtree_parser()
     }
+
+    fn current_node_name(&self) -> Option<&str> {
+        (**self).current_node_name()
+    }
+
+    fn depth(&self) -> usize {
+        (**self).depth()
+    }
+
+    fn open_node_names(&self) -> &[String] {
+        (**self).open_node_names()
+    }
+}
+
+
+/// Subtree parser which skips the rest of the subtree on drop.
+///
+/// Loaders need not read the subtree until the end, and the parser is never desynchronized
+/// even if the loader returns early.
+/// If skipping on drop failed, the error is stored to the root parser and returned by the
+/// next event.
+/// Use `finish()` to get the error directly.
+pub struct SubtreeGuard<'a, R: 'a + ParserSource> {
+    /// Subtree parser, `None` if already finished.
+    parser: Option<SubtreeParser<'a, R>>,
+}
+
+impl<'a, R: 'a + ParserSource> SubtreeGuard<'a, R> {
+    /// Creates a new `SubtreeGuard`.
+    pub fn new(parser: SubtreeParser<'a, R>) -> Self {
+        SubtreeGuard { parser: Some(parser) }
+    }
+
+    /// Returns the inner subtree parser.
+    fn inner(&self) -> &SubtreeParser<'a, R> {
+        self.parser.as_ref().expect("Subtree guard should not be finished")
+    }
+
+    /// Returns the inner subtree parser.
+    fn inner_mut(&mut self) -> &mut SubtreeParser<'a, R> {
+        self.parser.as_mut().expect("Subtree guard should not be finished")
+    }
+
+    /// Skips to the end of the subtree.
+    pub fn finish(mut self) -> Result<()> {
+        match self.parser.take() {
+            Some(parser) => parser.skip_to_end(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, R: 'a + ParserSource> ::std::ops::Deref for SubtreeGuard<'a, R> {
+    type Target = SubtreeParser<'a, R>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner()
+    }
+}
+
+impl<'a, R: 'a + ParserSource> ::std::ops::DerefMut for SubtreeGuard<'a, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner_mut()
+    }
+}
+
+impl<'a, R: 'a + ParserSource> Drop for SubtreeGuard<'a, R> {
+    fn drop(&mut self) {
+        let mut parser = match self.parser.take() {
+            Some(parser) => parser,
+            None => return,
+        };
+        // Keep the error from the root parser, if already failed.
+        if parser.root_parser.error().is_some() {
+            return;
+        }
+        if let Err(err) = parser.skip_rest() {
+            if parser.root_parser.error().is_none() {
+                parser.root_parser.set_error(&err);
+            }
+        }
+    }
+}
+
+impl<'a, R: 'a + ParserSource> Parser<R> for SubtreeGuard<'a, R> {
+    fn root_parser(&self) -> &RootParser<R> {
+        self.inner().root_parser()
+    }
+
+    fn next_event(&mut self) -> Result<Event<R>> {
+        self.inner_mut().next_event()
+    }
+
+    fn skip_current_node(&mut self) -> Result<bool> {
+        self.inner_mut().skip_current_node()
+    }
+
+    fn subtree_parser(&mut self) -> SubtreeParser<R> {
+        self.inner_mut().subtree_parser()
+    }
+
+    fn current_node_name(&self) -> Option<&str> {
+        self.inner().current_node_name()
+    }
+
+    fn open_node_names(&self) -> &[String] {
+        self.inner().open_node_names()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use loader::binary::simple::GenericNode;
+    use test_util::{Attr, FbxBuilder, minimal_fbx};
+    use writer::binary::Writer;
+    use super::{RootParser, Parser, Event, Attribute, ArrayAttribute, Warning, Error};
+    use super::{FbxFooter, PrimitiveAttribute};
+    use super::ParserSource;
+    use super::utils::{AttributeValue, AttributeValues, CoercionLoss, StrictCoercion};
+
+    /// Reads events until the end of FBX and returns their short descriptions.
+    fn rest_events<R: ParserSource>(parser: &mut RootParser<R>) -> Vec<String> {
+        let mut events = Vec::new();
+        loop {
+            let desc = match parser.next_event().expect("Parse error") {
+                Event::StartNode(node) => format!("start {}", node.name),
+                Event::EndNode => "end".to_owned(),
+                Event::EndFbx(footer) => {
+                    footer.expect("Footer should be valid");
+                    break;
+                },
+                ev => panic!("Unexpected event: {:?}", ev),
+            };
+            events.push(desc);
+        }
+        events
+    }
+
+    /// Creates FBX binary with a node with wrong end offset.
+    ///
+    /// `Broken` node has wrong end offset, so the rest of `Parent` node should be skipped.
+    fn corrupt_fbx(version: u32) -> Vec<u8> {
+        let mut b = FbxBuilder::new(version);
+        b.begin_node("Root", &[]);
+        b.begin_node("Parent", &[Attr::I32(1)]);
+        let broken_pos = b.position();
+        b.begin_node("Broken", &[]);
+        b.node("Child", &[Attr::I32(2)]);
+        b.end_node();
+        b.node("Skipped", &[]);
+        b.end_node();
+        b.end_node();
+        b.node("Sibling", &[Attr::I32(3)]);
+        let mut data = b.finish();
+        // Make end offset of `Broken` node 1 byte larger.
+        data[broken_pos] = data[broken_pos].wrapping_add(1);
+        data
+    }
+
+    #[test]
+    fn test_footer() {
+        let data = minimal_fbx(7400);
+        let mut parser = RootParser::new(Cursor::new(&data[..]));
+        let footer = loop {
+            if let Event::EndFbx(footer) = parser.next_event().expect("Parse error") {
+                break footer.expect("Footer should be valid");
+            }
+        };
+        assert_eq!(footer, FbxFooter::new(7400));
+        assert!(footer.has_valid_magic());
+        assert_eq!(footer.file_id_hash(), &FbxFooter::DEFAULT_FILE_ID_HASH);
+
+        // Padding makes the version field aligned to 16 bytes.
+        for &(position, len) in &[(0, 160), (1, 175), (15, 161)] {
+            let mut written = Vec::new();
+            footer.write_to(&mut written, position).expect("Write error");
+            assert_eq!(written.len(), len);
+            assert_eq!(&written[written.len() - 16..], &FbxFooter::MAGIC);
+        }
+    }
+
+    #[test]
+    fn test_corrupt_node_error() {
+        let mut parser = RootParser::new(Cursor::new(corrupt_fbx(7400)));
+        match GenericNode::load_from_parser(&mut parser) {
+            Err(Error::WrongNodeEndOffset { .. }) => {},
+            v => panic!("Expected `WrongNodeEndOffset` error but got {:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_attributes_length_overflow() {
+        for &raw in &[false, true] {
+            let mut b = FbxBuilder::new(7500);
+            let node_pos = b.position();
+            b.node("Node", &[Attr::I32(1)]);
+            let mut data = b.finish();
+            // Make length of the attributes of `Node` overflow the end offset.
+            data[node_pos + 16..node_pos + 24].copy_from_slice(&u64::MAX.to_le_bytes());
+            let mut parser = RootParser::new(Cursor::new(data));
+            parser.set_raw_attributes(raw);
+            parser.next_event().expect("Parse error");
+            match parser.next_event() {
+                Err(Error::AttributesLengthOverflow { bytelen, .. }) => {
+                    assert_eq!(bytelen, u64::MAX)
+                },
+                v => panic!("Expected overflow error but got {:?}", v.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_corrupt_node_recovery() {
+        for &version in &[7400, 7500] {
+            let mut parser = RootParser::from_seekable(Cursor::new(corrupt_fbx(version)));
+            parser.set_recover_corrupt_nodes(true);
+            let (nodes, footer) = GenericNode::load_from_parser(&mut parser).expect("Parse error");
+            assert!(footer.is_some());
+            assert_eq!(parser.warnings().len(), 1);
+            match parser.warnings()[0] {
+                Warning::SkippedCorruptSubtree { .. } => {},
+                ref w => panic!("Unexpected warning: {:?}", w),
+            }
+            assert_eq!(nodes.len(), 2);
+            assert_eq!(nodes[1].name, "Sibling");
+            let parent = &nodes[0].children[0];
+            assert_eq!(parent.name, "Parent");
+            assert_eq!(parent.children.len(), 1);
+            assert_eq!(parent.children[0].name, "Broken");
+            assert_eq!(parent.children[0].children[0].name, "Child");
+        }
+    }
+
+    #[test]
+    fn test_snapshot_resume() {
+        for &version in &[7400, 7500] {
+            let mut b = FbxBuilder::new(version);
+            b.begin_node("Root", &[]);
+            b.begin_node("Parent", &[Attr::I32(1)]);
+            b.node("Child0", &[Attr::ArrF64(&[1.0, 2.0])]);
+            b.node("Child1", &[]);
+            b.end_node();
+            b.end_node();
+            b.node("Sibling", &[Attr::I32(2)]);
+            let data = b.finish();
+
+            let mut parser = RootParser::from_seekable(Cursor::new(data.clone()));
+            for _ in 0..4 {
+                parser.next_event().expect("Parse error");
+            }
+            // `Child0` is open and its attributes are unread.
+            let snapshot = parser.snapshot().expect("Parser should be working");
+            assert_eq!(snapshot.fbx_version(), Some(version));
+            assert_eq!(snapshot.num_open_nodes(), 3);
+            let expected = rest_events(&mut parser);
+            assert!(parser.snapshot().is_none());
+            assert_eq!(&expected[..2], &["end", "start Child1"]);
+
+            let mut resumed = RootParser::resume(Cursor::new(data), &snapshot).expect("Seek error");
+            assert_eq!(resumed.num_open_nodes(), 3);
+            assert_eq!(rest_events(&mut resumed), expected);
+        }
+    }
+
+    #[test]
+    fn test_cancel_between_events() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut parser = RootParser::new(Cursor::new(minimal_fbx(7400)));
+        parser.set_cancel_flag(Some(flag.clone()));
+        parser.next_event().expect("Parse error");
+        parser.next_event().expect("Parse error");
+        flag.store(true, Ordering::Relaxed);
+        match parser.next_event() {
+            Err(Error::Cancelled) => {},
+            v => panic!("Expected `Cancelled` error but got {:?}", v),
+        }
+        assert!(matches!(parser.error(), Some(&Error::Cancelled)));
+    }
+
+    #[test]
+    fn test_cancel_array_decoding() {
+        let mut b = FbxBuilder::new(7400);
+        b.node("Array", &[Attr::ArrF64(&[1.0, 2.0, 3.0])]);
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.set_cancel_flag(Some(flag.clone()));
+        parser.next_event().expect("Parse error");
+        let mut node = match parser.next_event().expect("Parse error") {
+            Event::StartNode(node) => node,
+            ev => panic!("Unexpected event: {:?}", ev),
+        };
+        let mut arr = match node.attributes.next_attribute().expect("Parse error") {
+            Some(Attribute::Array(ArrayAttribute::F64(arr))) => arr,
+            attr => panic!("Unexpected attribute: {:?}", attr),
+        };
+        assert_eq!(arr.next().map(|v| v.expect("Parse error")), Some(1.0));
+        flag.store(true, Ordering::Relaxed);
+        let err = arr.next().expect("Element should remain").expect_err("Should be cancelled");
+        match Error::from(err) {
+            Error::Cancelled => {},
+            err => panic!("Expected `Cancelled` error but got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_unsigned_attributes() {
+        let mut b = FbxBuilder::new(7400);
+        b.node("Node", &[Attr::I32(3), Attr::I32(-1), Attr::I64(5), Attr::I16(-2)]);
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.next_event().expect("Parse error");
+        let mut node = match parser.next_event().expect("Parse error") {
+            Event::StartNode(node) => node,
+            ev => panic!("Unexpected event: {:?}", ev),
+        };
+        macro_rules! next {
+            () => {
+                node.attributes.next_attribute().expect("Parse error").expect("Should exist")
+            };
+        }
+        assert_eq!(next!().convert_into::<u32>().expect("Parse error"), Some(3));
+        assert_eq!(next!().convert_into::<u32>().expect("Parse error"), None);
+        let loose = AttributeValue::from_attribute_loose(next!()).expect("Parse error");
+        assert_eq!(loose, Some(5u32));
+        let loose = AttributeValue::from_attribute_loose(next!()).expect("Parse error");
+        assert_eq!(loose, None::<u64>);
+    }
+
+    #[test]
+    fn test_erased_source() {
+        let data = minimal_fbx(7400);
+        let mut basic = RootParser::new(Cursor::new(&data));
+        basic.next_event().expect("Parse error");
+        let mut parsers = vec![
+            basic.into_erased(),
+            RootParser::from_seekable(Cursor::new(&data)).into_erased(),
+        ];
+        let expected = GenericNode::load_from_parser(&mut RootParser::new(Cursor::new(&data)))
+            .expect("Parse error");
+        for parser in &mut parsers {
+            let loaded = GenericNode::load_from_parser(parser).expect("Parse error");
+            assert_eq!(loaded, expected);
+            assert_eq!(parser.fbx_version(), Some(7400));
+            assert_eq!(parser.position(), data.len() as u64);
+        }
+    }
+
+    #[test]
+    fn test_strict_coercion() {
+        let mut b = FbxBuilder::new(7400);
+        b.node("Node", &[Attr::F64(0.5), Attr::F64(0.1), Attr::ArrF64(&[0.1, 1.0, 0.2])]);
+        b.node("Index", &[Attr::I32(-1)]);
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.next_event().expect("Parse error");
+        let mut strict = StrictCoercion::new();
+        for &name in &["Node", "Index"] {
+            let mut node = match parser.next_event().expect("Parse error") {
+                Event::StartNode(node) => node,
+                ev => panic!("Unexpected event: {:?}", ev),
+            };
+            strict.enter(name);
+            if name == "Node" {
+                let values = <(f32, f32, Vec<f32>)>::from_attributes_strict(
+                    &mut node.attributes,
+                    &mut strict,
+                ).expect("Parse error");
+                assert_eq!(values, Some((0.5, 0.1, vec![0.1, 1.0, 0.2])));
+            } else {
+                let index = u32::from_attributes_strict(&mut node.attributes, &mut strict);
+                assert_eq!(index.expect("Parse error"), None);
+            }
+            strict.leave();
+            parser.next_event().expect("Parse error");
+        }
+
+        let warnings = strict.warnings()
+            .iter()
+            .map(|w| (w.path.as_str(), w.attribute, w.loss))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                ("Node", 1, CoercionLoss::InexactF32 { value: 0.1, count: 1 }),
+                ("Node", 2, CoercionLoss::InexactF32 { value: 0.1, count: 2 }),
+                ("Index", 0, CoercionLoss::OutOfRange { value: -1, target: "u32" }),
+            ]
+        );
+    }
+
+    fn do_test_bool_array_variants(compress: bool) {
+        let mut b = FbxBuilder::new(7400);
+        b.set_compress_arrays(compress);
+        b.node("Node", &[Attr::ArrBoolRaw(&[b'Y', b'T', 1, 0, 3, b'?'])]);
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.next_event().expect("Parse error");
+        let mut node = match parser.next_event().expect("Parse error") {
+            Event::StartNode(node) => node,
+            ev => panic!("Unexpected event: {:?}", ev),
+        };
+        let arr = match node.attributes.next_attribute().expect("Parse error") {
+            Some(Attribute::Array(ArrayAttribute::Bool(arr))) => arr,
+            attr => panic!("Unexpected attribute: {:?}", attr),
+        };
+        assert_eq!(
+            arr.into_vec().expect("Parse error"),
+            vec![true, false, true, false, true, true]
+        );
+        let indices = parser
+            .warnings()
+            .iter()
+            .map(|w| match *w {
+                Warning::InvalidBooleanArrayElement { got, index, .. } => (got, index),
+                ref w => panic!("Unexpected warning: {:?}", w),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec![(3, 4), (b'?', 5)]);
+    }
+
+    #[test]
+    fn test_bool_scalar_and_array_agree() {
+        let values = [true, false];
+        let mut writer = Writer::new(Cursor::new(Vec::new()), 7400).expect("Write error");
+        {
+            let mut node = writer.begin_node("Node").expect("Write error");
+            node.attr(values[0]).expect("Write error");
+            node.attr(values[1]).expect("Write error");
+            node.attr(&values[..]).expect("Write error");
+            node.end_node().expect("Write error");
+        }
+        let data = writer.finish().expect("Write error").into_inner();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        parser.next_event().expect("Parse error");
+        let mut node = match parser.next_event().expect("Parse error") {
+            Event::StartNode(node) => node,
+            ev => panic!("Unexpected event: {:?}", ev),
+        };
+        let mut scalars = Vec::new();
+        for _ in 0..values.len() {
+            match node.attributes.next_attribute().expect("Parse error") {
+                Some(Attribute::Primitive(PrimitiveAttribute::Bool(v))) => scalars.push(v),
+                attr => panic!("Unexpected attribute: {:?}", attr),
+            }
+        }
+        let array = match node.attributes.next_attribute().expect("Parse error") {
+            Some(Attribute::Array(ArrayAttribute::Bool(arr))) => {
+                arr.into_vec().expect("Parse error")
+            },
+            attr => panic!("Unexpected attribute: {:?}", attr),
+        };
+        assert_eq!(scalars, values);
+        assert_eq!(array, values);
+        assert!(parser.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_bool_array_variants() {
+        do_test_bool_array_variants(false);
+    }
+
+    #[cfg(any(feature = "flate2", feature = "libflate"))]
+    #[test]
+    fn test_compressed_bool_array_variants() {
+        do_test_bool_array_variants(true);
+    }
+
+    #[test]
+    fn test_raw_attributes() {
+        let mut b = FbxBuilder::new(7400);
+        b.begin_node("Parent", &[Attr::I32(5), Attr::String("ab")]);
+        b.node("Child", &[Attr::I16(1)]);
+        b.end_node();
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.set_raw_attributes(true);
+        parser.next_event().expect("Parse error");
+        match parser.next_event().expect("Parse error") {
+            Event::RawNode(mut node) => {
+                assert_eq!(node.name, "Parent");
+                assert_eq!(node.num_attributes, 2);
+                let mut raw = Vec::new();
+                node.attributes.read_to_end(&mut raw).expect("Read error");
+                assert_eq!(raw, b"I\x05\x00\x00\x00S\x02\x00\x00\x00ab");
+            },
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        match parser.next_event().expect("Parse error") {
+            // Leave the attributes unread.
+            Event::RawNode(node) => assert_eq!(node.attributes.len(), 3),
+            ev => panic!("Unexpected event: {:?}", ev),
+        }
+        assert_eq!(rest_events(&mut parser), vec!["end", "end"]);
+    }
+
+    #[test]
+    fn test_current_node_name() {
+        let mut b = FbxBuilder::new(7400);
+        b.begin_node("Root", &[]);
+        b.node("Child", &[Attr::I32(1)]);
+        b.end_node();
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.next_event().expect("Parse error");
+        assert_eq!(parser.current_node_name(), None);
+        let mut names = Vec::new();
+        loop {
+            let desc = match parser.next_event().expect("Parse error") {
+                Event::StartNode(_) => "start",
+                Event::EndNode => "end",
+                Event::EndFbx(_) => break,
+                ev => panic!("Unexpected event: {:?}", ev),
+            };
+            names.push((desc, parser.current_node_name().expect("Should exist").to_owned()));
+        }
+        assert_eq!(parser.current_node_name(), None);
+        assert_eq!(
+            names,
+            vec![
+                ("start", "Root".to_owned()),
+                ("start", "Child".to_owned()),
+                ("end", "Child".to_owned()),
+                ("end", "Root".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_node_stack() {
+        let mut b = FbxBuilder::new(7400);
+        b.begin_node("Root", &[]);
+        b.begin_node("Parent", &[]);
+        b.node("Child", &[]);
+        b.end_node();
+        b.end_node();
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        for _ in 0..3 {
+            parser.next_event().expect("Parse error");
+        }
+        assert_eq!(parser.depth(), 2);
+        let mut subtree = parser.subtree_parser();
+        assert_eq!(subtree.relative_depth(), 0);
+        subtree.next_event().expect("Parse error");
+        assert_eq!(subtree.depth(), 3);
+        assert_eq!(subtree.relative_depth(), 1);
+        assert_eq!(subtree.open_node_names(), &["Root", "Parent", "Child"]);
+        subtree.next_event().expect("Parse error");
+        subtree.next_event().expect("Parse error");
+        assert_eq!(subtree.relative_depth(), 0);
+        assert_eq!(parser.open_node_names(), &["Root"]);
+    }
+
+    #[test]
+    fn test_open_node_names_reuse() {
+        let mut b = FbxBuilder::new(7400);
+        b.node("LongNodeName", &[]);
+        b.begin_node("A", &[]);
+        b.node("Child", &[]);
+        b.end_node();
+        b.node("B", &[]);
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.next_event().expect("Parse error");
+        let mut names = Vec::new();
+        for _ in 0..8 {
+            parser.next_event().expect("Parse error");
+            names.push((
+                parser.open_node_names().join("/"),
+                parser.current_node_name().expect("Should exist").to_owned(),
+            ));
+        }
+        let expected = [
+            ("LongNodeName", "LongNodeName"),
+            ("", "LongNodeName"),
+            ("A", "A"),
+            ("A/Child", "Child"),
+            ("A", "Child"),
+            ("", "A"),
+            ("B", "B"),
+            ("", "B"),
+        ];
+        let expected = expected
+            .iter()
+            .map(|&(open, current)| (open.to_owned(), current.to_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_subtree_guard() {
+        let mut b = FbxBuilder::new(7400);
+        b.begin_node("Root", &[]);
+        b.begin_node("Parent", &[]);
+        b.node("Child0", &[]);
+        b.node("Child1", &[]);
+        b.end_node();
+        b.node("Sibling", &[]);
+        b.end_node();
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        for _ in 0..3 {
+            parser.next_event().expect("Parse error");
+        }
+        {
+            let mut guard = parser.subtree_guard();
+            match guard.next_event().expect("Parse error") {
+                Event::StartNode(node) => assert_eq!(node.name, "Child0"),
+                ev => panic!("Unexpected event: {:?}", ev),
+            }
+        }
+        assert_eq!(rest_events(&mut parser), vec!["start Sibling", "end", "end"]);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut parser = RootParser::new(Cursor::new(corrupt_fbx(7400)));
+        assert!(GenericNode::load_from_parser(&mut parser).is_err());
+        assert!(parser.error().is_some());
+
+        let data = minimal_fbx(7500);
+        parser.reset(Cursor::new(data.clone()));
+        assert!(parser.error().is_none());
+        assert_eq!(parser.num_open_nodes(), 0);
+        let (nodes, footer) = GenericNode::load_from_parser(&mut parser).expect("Parse error");
+        assert!(footer.is_some());
+        assert_eq!(parser.fbx_version(), Some(7500));
+
+        let expected = GenericNode::load_from_parser(&mut RootParser::new(Cursor::new(data)))
+            .expect("Parse error")
+            .0;
+        assert_eq!(nodes, expected);
+    }
+
+    #[test]
+    fn test_vec_array_attributes() {
+        let mut b = FbxBuilder::new(7400);
+        b.node(
+            "Node",
+            &[
+                Attr::ArrF64(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+                Attr::ArrF32(&[1.0, 2.0, 3.0, 4.0, 5.0]),
+            ],
+        );
+        let mut parser = RootParser::new(Cursor::new(b.finish()));
+        parser.next_event().expect("Parse error");
+        let mut node = match parser.next_event().expect("Parse error") {
+            Event::StartNode(node) => node,
+            ev => panic!("Unexpected event: {:?}", ev),
+        };
+        let attr = node.attributes.next_attribute().expect("Parse error").expect("Should exist");
+        assert_eq!(
+            attr.convert_into::<Vec<[f64; 3]>>().expect("Parse error"),
+            Some(vec![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]])
+        );
+        let attr = node.attributes.next_attribute().expect("Parse error").expect("Should exist");
+        assert_eq!(
+            attr.convert_into::<Vec<[f32; 2]>>().expect("Parse error"),
+            Some(vec![[1.0, 2.0], [3.0, 4.0]])
+        );
+    }
 }
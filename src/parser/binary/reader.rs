@@ -1,86 +1,67 @@
 //! Wrapper for `std::io::Read`.
 
 use std::fmt;
-use std::mem;
 use std::io;
+use parser::binary::error::backward_seek_io_error;
 
 
-macro_rules! impl_read_little_endian_integer {
+macro_rules! impl_read_little_endian {
     ($ty:ident, $name:ident, $size:expr) => {
-/// Reads a little-endian value and returns it.
+        /// Reads a little-endian value and returns it.
         fn $name(&mut self) -> io::Result<$ty> {
-            assert_eq!($size, mem::size_of::<$ty>());
-
-            let mut data: $ty = 0;
-            let mut slice = unsafe {
-                ::std::slice::from_raw_parts_mut(&mut data as *mut $ty as *mut u8, $size)
-            };
-            self.read_exact(slice)?;
-            Ok($ty::from_le(data))
+            let mut bytes = [0u8; $size];
+            self.read_exact(&mut bytes)?;
+            Ok($ty::from_le_bytes(bytes))
         }
     }
 }
 
-macro_rules! impl_read_little_endian_integer_array {
+macro_rules! impl_read_little_endian_array {
     ($ty:ident, $name:ident, $size:expr) => {
-/// Reads a little-endian values into the given buffer.
+        /// Reads little-endian values into the given buffer.
+        ///
+        /// Values are read in bulk through a fixed-size byte buffer, and converted from
+        /// little-endian bytes, so this works on both little- and big-endian hosts.
         fn $name(&mut self, buf: &mut [$ty]) -> io::Result<()> {
-            assert_eq!($size, mem::size_of::<$ty>());
-
-            {
-                let mut slice = unsafe {
-                    ::std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, $size * buf.len())
-                };
-                self.read_exact(slice)?;
-            }
-            for elem in buf {
-                *elem = $ty::from_le(*elem);
+            /// Number of elements read at once.
+            const CHUNK_LEN: usize = ARRAY_CHUNK_BYTES / $size;
+
+            let mut bytes = [0u8; CHUNK_LEN * $size];
+            for dest in buf.chunks_mut(CHUNK_LEN) {
+                let bytes = &mut bytes[0..dest.len() * $size];
+                self.read_exact(bytes)?;
+                for (dest, src) in dest.iter_mut().zip(bytes.chunks_exact($size)) {
+                    let mut elem = [0u8; $size];
+                    elem.copy_from_slice(src);
+                    *dest = $ty::from_le_bytes(elem);
+                }
             }
             Ok(())
         }
     }
 }
 
-/// The `ReadLittleEndian` trait allows for reading little-endian primitive type values from a
-/// source.
-pub trait ReadLittleEndian: io::Read {
-    impl_read_little_endian_integer!(u8, read_u8, 1);
-    impl_read_little_endian_integer!(u32, read_u32, 4);
-    impl_read_little_endian_integer!(u64, read_u64, 8);
-    impl_read_little_endian_integer!(i16, read_i16, 2);
-    impl_read_little_endian_integer!(i32, read_i32, 4);
-    impl_read_little_endian_integer!(i64, read_i64, 8);
 
-    /// Reads a little-endian value and returns it.
-    fn read_f32(&mut self) -> io::Result<f32> {
-        let integer = self.read_u32()?;
-        let val = unsafe { mem::transmute(integer) };
-        Ok(val)
-    }
+/// Size of the buffer used by bulk array reads, in bytes.
+const ARRAY_CHUNK_BYTES: usize = 4096;
 
-    /// Reads a little-endian value and returns it.
-    fn read_f64(&mut self) -> io::Result<f64> {
-        let integer = self.read_u64()?;
-        let val = unsafe { mem::transmute(integer) };
-        Ok(val)
-    }
 
-    impl_read_little_endian_integer_array!(u8, read_u8_arr, 1);
-    impl_read_little_endian_integer_array!(u32, read_u32_arr, 4);
-    impl_read_little_endian_integer_array!(u64, read_u64_arr, 8);
-    impl_read_little_endian_integer_array!(i16, read_i16_arr, 2);
-    impl_read_little_endian_integer_array!(i32, read_i32_arr, 4);
-    impl_read_little_endian_integer_array!(i64, read_i64_arr, 8);
-
-    /// Reads a little-endian values into the given buffer.
-    fn read_f32_arr(&mut self, buf: &mut [f32]) -> io::Result<()> {
-        self.read_u32_arr(unsafe { mem::transmute(buf) })
-    }
-
-    /// Reads a little-endian values into the given buffer.
-    fn read_f64_arr(&mut self, buf: &mut [f64]) -> io::Result<()> {
-        self.read_u64_arr(unsafe { mem::transmute(buf) })
-    }
+/// The `ReadLittleEndian` trait allows for reading little-endian primitive type values from a
+/// source.
+pub trait ReadLittleEndian: io::Read {
+    impl_read_little_endian!(u8, read_u8, 1);
+    impl_read_little_endian!(u32, read_u32, 4);
+    impl_read_little_endian!(u64, read_u64, 8);
+    impl_read_little_endian!(i16, read_i16, 2);
+    impl_read_little_endian!(i32, read_i32, 4);
+    impl_read_little_endian!(i64, read_i64, 8);
+    impl_read_little_endian!(f32, read_f32, 4);
+    impl_read_little_endian!(f64, read_f64, 8);
+
+    impl_read_little_endian_array!(i32, read_i32_arr, 4);
+    impl_read_little_endian_array!(i64, read_i64_arr, 8);
+    impl_read_little_endian_array!(f32, read_f32_arr, 4);
+    impl_read_little_endian_array!(f64, read_f64_arr, 8);
 }
 
 impl<R: io::Read> ReadLittleEndian for R {}
@@ -93,12 +74,24 @@ pub trait ParserSource: fmt::Debug + io::Read {
 
     /// Skips to the given position.
     ///
-    /// # Panics
-    /// Panics if a byte at the given position has been already read.
+    /// Returns an error which is converted into `Error::BackwardSeek` if a byte at the given
+    /// position has been already read.
     fn skip_to(&mut self, dest_pos: u64) -> io::Result<()>;
+
+    /// Moves to the given position, backward or forward.
+    ///
+    /// The default implementation supports only forward moves (using `skip_to()`), and returns
+    /// an error which is converted into `Error::BackwardSeek` for backward moves.
+    fn seek_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        if dest_pos >= self.position() {
+            self.skip_to(dest_pos)
+        } else {
+            Err(backward_seek_io_error(self.position(), dest_pos))
+        }
+    }
 }
 
-impl<'a, R: ParserSource> ParserSource for &'a mut R {
+impl<'a, R: ParserSource + ?Sized> ParserSource for &'a mut R {
     fn position(&self) -> u64 {
         (**self).position()
     }
@@ -106,8 +99,34 @@ impl<'a, R: ParserSource> ParserSource for &'a mut R {
     fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
         (**self).skip_to(dest_pos)
     }
+
+    fn seek_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        (**self).seek_to(dest_pos)
+    }
 }
 
+impl<R: ParserSource + ?Sized> ParserSource for Box<R> {
+    fn position(&self) -> u64 {
+        (**self).position()
+    }
+
+    fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        (**self).skip_to(dest_pos)
+    }
+
+    fn seek_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        (**self).seek_to(dest_pos)
+    }
+}
+
+
+/// Type-erased source stream.
+///
+/// Parsers and loaders are generic over sources, and `RootParser<ErasedSource>` lets
+/// applications reading several kinds of sources instantiate them only once.
+/// See `RootParser::into_erased()`.
+pub type ErasedSource<'a> = Box<dyn ParserSource + 'a>;
+
 
 /// Reader with position info.
 pub struct BasicSource<R> {
@@ -168,12 +187,9 @@ impl<R: io::Read> ParserSource for BasicSource<R> {
     fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
         use std::io::Read;
 
-        assert!(
-            dest_pos >= self.position(),
-            "Destination position should be after current position: dest_pos={}, position={}",
-            dest_pos,
-            self.position()
-        );
+        if dest_pos < self.position() {
+            return Err(backward_seek_io_error(self.position(), dest_pos));
+        }
         const TEMP_BUF_LEN: usize = 256;
         let mut temp_buf = [0u8; TEMP_BUF_LEN];
         let mut rest_len = dest_pos - self.position();
@@ -247,17 +263,21 @@ impl<R: io::Read + io::Seek> ParserSource for SeekableSource<R> {
     fn skip_to(&mut self, dest_pos: u64) -> io::Result<()> {
         use std::io::{Seek, SeekFrom};
 
-        assert!(
-            dest_pos >= self.position(),
-            "Destination position should be after current position: dest_pos={}, position={}",
-            dest_pos,
-            self.position()
-        );
+        if dest_pos < self.position() {
+            return Err(backward_seek_io_error(self.position(), dest_pos));
+        }
         self.seek(SeekFrom::Start(dest_pos))?;
 
         assert_eq!(self.position(), dest_pos);
         Ok(())
     }
+
+    fn seek_to(&mut self, dest_pos: u64) -> io::Result<()> {
+        use std::io::{Seek, SeekFrom};
+
+        self.seek(SeekFrom::Start(dest_pos))?;
+        Ok(())
+    }
 }
 
 impl<R: io::BufRead> io::BufRead for SeekableSource<R> {
@@ -392,7 +412,8 @@ impl<R> fmt::Debug for LimitedSeekReader<R> {
 #[cfg(test)]
 mod tests {
     use std::io::{Cursor, Seek, SeekFrom};
-    use super::{ParserSource, BasicSource, SeekableSource};
+    use parser::binary::Error;
+    use super::{ParserSource, BasicSource, SeekableSource, ReadLittleEndian};
 
     fn do_test_skip_to(buf_size: usize, skip_dest: u64) {
         do_test_basic_skip_to(buf_size, skip_dest);
@@ -434,4 +455,70 @@ mod tests {
         do_test_skip_to(512, 401);
         do_test_skip_to(64, 64);
     }
+
+    #[test]
+    fn test_backward_skip() {
+        let mut reader = BasicSource::new(Cursor::new(vec![0; 16]));
+        reader.skip_to(8).expect("Failed to skip");
+        let err = reader.skip_to(4).expect_err("Backward skip should fail");
+        match Error::from(err) {
+            Error::BackwardSeek {
+                position: 8,
+                destination: 4,
+            } => {},
+            err => panic!("Expected `BackwardSeek` error but got {:?}", err),
+        }
+        assert_eq!(reader.position(), 8);
+    }
+
+    #[test]
+    fn test_read_arrays() {
+        // Longer than the chunk, to test reads across the chunk boundary.
+        let values = (0..1500).map(|v| v as f64 * -0.25).collect::<Vec<_>>();
+        let bytes = values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+        let mut buf = vec![0.0; values.len()];
+        Cursor::new(&bytes).read_f64_arr(&mut buf).expect("Read error");
+        assert_eq!(buf, values);
+
+        let mut reader = Cursor::new(&bytes);
+        assert_eq!(reader.read_f64().expect("Read error"), values[0]);
+        let mut buf = [0.0f64; 1];
+        reader.read_f64_arr(&mut buf).expect("Read error");
+        assert_eq!(buf[0], values[1]);
+
+        let mut buf = vec![0.0f64; values.len() + 1];
+        assert!(Cursor::new(&bytes).read_f64_arr(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_read_fixtures() {
+        // Values are little-endian regardless of the host endianness.
+        let bytes: &[u8] = &[
+            0xfe, 0xff, // i16: -2
+            0x78, 0x56, 0x34, 0x12, // i32: 0x12345678
+            0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x81, // i64: -0x7efd_fcfb_faf9_f8f8
+            0x00, 0x00, 0x80, 0x3f, // f32: 1.0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0xc0, // f64: -2.5
+        ];
+        let mut reader = Cursor::new(bytes);
+        assert_eq!(reader.read_i16().expect("Read error"), -2);
+        assert_eq!(reader.read_i32().expect("Read error"), 0x1234_5678);
+        assert_eq!(reader.read_i64().expect("Read error"), -0x7efd_fcfb_faf9_f8f8);
+        assert_eq!(reader.read_f32().expect("Read error"), 1.0);
+        assert_eq!(reader.read_f64().expect("Read error"), -2.5);
+
+        let bytes: &[u8] = &[0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0xc0];
+        let mut f32s = [0.0f32; 2];
+        Cursor::new(bytes).read_f32_arr(&mut f32s).expect("Read error");
+        assert_eq!(f32s, [1.0, -2.0]);
+        let mut i32s = [0i32; 2];
+        Cursor::new(bytes).read_i32_arr(&mut i32s).expect("Read error");
+        assert_eq!(i32s, [0x3f80_0000, -0x4000_0000]);
+        let mut f64s = [0.0f64; 1];
+        Cursor::new(bytes).read_f64_arr(&mut f64s).expect("Read error");
+        assert_eq!(f64s[0].to_bits(), 0xc000_0000_3f80_0000);
+        let mut i64s = [0i64; 1];
+        Cursor::new(bytes).read_i64_arr(&mut i64s).expect("Read error");
+        assert_eq!(i64s[0] as u64, 0xc000_0000_3f80_0000);
+    }
 }
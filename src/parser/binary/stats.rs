@@ -0,0 +1,260 @@
+//! Parse statistics.
+//!
+//! Statistics are collected only with `stats` feature.
+
+#[cfg(feature = "stats")]
+use std::cell::RefCell;
+#[cfg(not(feature = "stats"))]
+use std::marker::PhantomData;
+#[cfg(feature = "stats")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "stats")]
+use fnv::FnvHashMap;
+
+
+/// Statistics of a node name.
+#[cfg(feature = "stats")]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeStats {
+    /// Number of nodes.
+    pub count: u64,
+    /// Total length of nodes (including headers and children) in bytes.
+    pub bytes: u64,
+}
+
+
+/// Statistics collected during parsing.
+///
+/// Enabled by `RootParser::enable_stats()`.
+#[cfg(feature = "stats")]
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ParseStats {
+    /// Statistics per node name.
+    nodes: FnvHashMap<String, NodeStats>,
+    /// Number of attributes per type code.
+    attributes: FnvHashMap<u8, u64>,
+    /// Number of objects per node name (i.e. class).
+    objects: FnvHashMap<String, u64>,
+    /// Number of compressed array attributes.
+    compressed_arrays: u64,
+    /// Total length of compressed array attributes in bytes.
+    compressed_bytes: u64,
+    /// Total length of decompressed elements in bytes.
+    decompressed_bytes: u64,
+    /// Total time spent in decompression.
+    decompression_time: Duration,
+    /// Name of the current toplevel node.
+    toplevel: Option<String>,
+}
+
+#[cfg(feature = "stats")]
+impl ParseStats {
+    /// Creates a new empty `ParseStats`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns statistics per node name.
+    pub fn nodes(&self) -> &FnvHashMap<String, NodeStats> {
+        &self.nodes
+    }
+
+    /// Returns statistics of nodes with the given name.
+    pub fn node(&self, name: &str) -> NodeStats {
+        self.nodes.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Returns the number of attributes per type code (such as `b'I'` or `b'd'`).
+    pub fn attributes(&self) -> &FnvHashMap<u8, u64> {
+        &self.attributes
+    }
+
+    /// Returns the number of attributes with the given type code.
+    pub fn attribute_count(&self, type_code: u8) -> u64 {
+        self.attributes.get(&type_code).cloned().unwrap_or(0)
+    }
+
+    /// Returns the number of objects (children of toplevel `Objects` node) per node name.
+    pub fn objects(&self) -> &FnvHashMap<String, u64> {
+        &self.objects
+    }
+
+    /// Returns the number of objects with the given node name.
+    pub fn object_count(&self, name: &str) -> u64 {
+        self.objects.get(name).cloned().unwrap_or(0)
+    }
+
+    /// Returns the number of compressed array attributes.
+    pub fn compressed_arrays(&self) -> u64 {
+        self.compressed_arrays
+    }
+
+    /// Returns the total length of compressed array attributes in bytes.
+    pub fn compressed_bytes(&self) -> u64 {
+        self.compressed_bytes
+    }
+
+    /// Returns the total length of decompressed elements in bytes.
+    ///
+    /// Elements which are not read (e.g. skipped attributes) are not counted.
+    pub fn decompressed_bytes(&self) -> u64 {
+        self.decompressed_bytes
+    }
+
+    /// Returns the total time spent in decompression.
+    pub fn decompression_time(&self) -> Duration {
+        self.decompression_time
+    }
+}
+
+
+/// Storage of the statistics in the parser.
+#[cfg(feature = "stats")]
+pub type StatsSlot = Option<RefCell<ParseStats>>;
+
+/// Storage of the statistics in the parser.
+#[cfg(not(feature = "stats"))]
+pub type StatsSlot = ();
+
+
+/// Recorder of the statistics.
+///
+/// Without `stats` feature, this does nothing.
+#[derive(Debug, Clone, Copy)]
+pub struct StatsRecorder<'a> {
+    /// Statistics.
+    #[cfg(feature = "stats")]
+    stats: Option<&'a RefCell<ParseStats>>,
+    /// Dummy.
+    #[cfg(not(feature = "stats"))]
+    _stats: PhantomData<&'a ()>,
+}
+
+impl<'a> StatsRecorder<'a> {
+    /// Creates a new `StatsRecorder`.
+    #[cfg(feature = "stats")]
+    pub fn new(slot: &'a StatsSlot) -> Self {
+        StatsRecorder { stats: slot.as_ref() }
+    }
+
+    /// Creates a new `StatsRecorder`.
+    #[cfg(not(feature = "stats"))]
+    pub fn new(_slot: &'a StatsSlot) -> Self {
+        StatsRecorder { _stats: PhantomData }
+    }
+
+    /// Records a node start.
+    ///
+    /// `depth` is the depth of the node (`1` for toplevel nodes).
+    #[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+    pub fn record_node(&self, depth: usize, name: &str, bytes: u64) {
+        #[cfg(feature = "stats")]
+        {
+            let mut stats = match self.stats {
+                Some(stats) => stats.borrow_mut(),
+                None => return,
+            };
+            {
+                let node = stats.nodes.entry(name.to_owned()).or_default();
+                node.count += 1;
+                node.bytes += bytes;
+            }
+            if depth == 1 {
+                stats.toplevel = Some(name.to_owned());
+            } else if depth == 2 && stats.toplevel.as_ref().is_some_and(|v| v == "Objects") {
+                *stats.objects.entry(name.to_owned()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Records an attribute.
+    #[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+    pub fn record_attribute(&self, type_code: u8) {
+        #[cfg(feature = "stats")]
+        {
+            if let Some(stats) = self.stats {
+                *stats.borrow_mut().attributes.entry(type_code).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Records a compressed array attribute.
+    #[cfg_attr(not(feature = "stats"), allow(unused_variables))]
+    pub fn record_compressed_array(&self, bytes: u64) {
+        #[cfg(feature = "stats")]
+        {
+            if let Some(stats) = self.stats {
+                let mut stats = stats.borrow_mut();
+                stats.compressed_arrays += 1;
+                stats.compressed_bytes += bytes;
+            }
+        }
+    }
+
+    /// Runs the given decompression and records its time and the decompressed length.
+    pub fn time_decompression<F>(&self, f: F) -> ::std::io::Result<usize>
+    where
+        F: FnOnce() -> ::std::io::Result<usize>,
+    {
+        #[cfg(feature = "stats")]
+        {
+            if let Some(stats) = self.stats {
+                let start = Instant::now();
+                let result = f();
+                let mut stats = stats.borrow_mut();
+                stats.decompression_time += start.elapsed();
+                if let Ok(len) = result {
+                    stats.decompressed_bytes += len as u64;
+                }
+                return result;
+            }
+        }
+        f()
+    }
+}
+
+
+#[cfg(all(test, feature = "stats"))]
+mod tests {
+    use std::io::Cursor;
+    use parser::binary::RootParser;
+    use loader::binary::simple::FbxLoader;
+    use loader::binary::simple::fbx7400::ObjectsLoader;
+    use test_util::{Attr, FbxBuilder, write_nodes_before_objects};
+
+    #[test]
+    fn test_stats() {
+        let mut b = FbxBuilder::new(7400);
+        b.set_compress_arrays(true);
+        write_nodes_before_objects(&mut b);
+        b.begin_node("Objects", &[]);
+        for &id in &[1, 2] {
+            b.begin_node(
+                "Geometry",
+                &[Attr::I64(id), Attr::String("Mesh\u{0}\u{1}Geometry"), Attr::String("Mesh")],
+            );
+            b.node("Vertices", &[Attr::ArrF64(&[0.0; 30])]);
+            b.end_node();
+        }
+        b.node("Model", &[Attr::I64(3), Attr::String("Null\u{0}\u{1}Model"), Attr::String("")]);
+        b.end_node();
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let mut parser = RootParser::new(Cursor::new(data));
+        parser.enable_stats();
+        match FbxLoader::load_from_parser(&mut parser).expect("Load error") {
+            FbxLoader::Fbx7400(loader) => loader.load(ObjectsLoader::new()).expect("Load error"),
+        };
+        let stats = parser.stats().expect("Stats should be enabled");
+
+        assert_eq!(stats.node("Vertices").count, 2);
+        assert!(stats.node("Objects").bytes > stats.node("Vertices").bytes * 2);
+        assert_eq!(stats.object_count("Geometry"), 2);
+        assert_eq!(stats.object_count("Model"), 1);
+        assert_eq!(stats.object_count("Vertices"), 0);
+        assert_eq!(stats.attribute_count(b'd'), 2);
+        assert_eq!(stats.compressed_arrays(), 2);
+        assert_eq!(stats.decompressed_bytes(), 2 * 30 * 8);
+    }
+}
@@ -1,9 +1,13 @@
 //! Traits for types which can be converted from node attributes.
 
+use std::convert::TryFrom;
+use std::fmt;
 use std::io::Result as IoResult;
+use std::sync::Arc;
 
 use parser::binary::{Result, ParserSource, Attributes, Attribute, ArrayAttributeReader};
 use parser::binary::{PrimitiveAttribute, ArrayAttribute, SpecialAttributeType};
+use parser::binary::utils::{CoercionLoss, StrictCoercion};
 
 
 /// Types which can be converted from a node attribute.
@@ -29,6 +33,22 @@ pub trait AttributeValue: Sized {
     fn from_attribute_loose<R>(attr: Attribute<R>) -> Result<Option<Self>>
     where
         R: ParserSource;
+
+    /// Reads the given attribute and converts into `Self` type, detecting lossy conversions.
+    ///
+    /// Same as `from_attribute_loose()`, but sets `loss` if the conversion lost precision
+    /// (such as `f64` values which are not representable in `f32`) or the value was out of
+    /// range.
+    fn from_attribute_checked<R>(
+        attr: Attribute<R>,
+        loss: &mut Option<CoercionLoss>,
+    ) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        let _ = loss;
+        Self::from_attribute_loose(attr)
+    }
 }
 
 // Simply ignore the attribute.
@@ -137,6 +157,102 @@ impl AttributeValue for i64 {
     }
 }
 
+/// Converts the integer into the unsigned type.
+///
+/// Returns `None` and warns if the value is negative or too large.
+fn to_unsigned<S, U>(val: S, type_name: &str) -> Option<U>
+where
+    S: Copy + fmt::Display,
+    U: TryFrom<S>,
+{
+    let result = U::try_from(val).ok();
+    if result.is_none() {
+        warn!("Attribute value {} is out of range of `{}`", val, type_name);
+    }
+    result
+}
+
+/// Converts the integer into the unsigned type, and sets `loss` if it is out of range.
+fn to_unsigned_checked<S, U>(
+    val: S,
+    type_name: &'static str,
+    loss: &mut Option<CoercionLoss>,
+) -> Option<U>
+where
+    S: Copy + Into<i64>,
+    U: TryFrom<S>,
+{
+    let result = U::try_from(val).ok();
+    if result.is_none() {
+        *loss = Some(CoercionLoss::OutOfRange {
+            value: val.into(),
+            target: type_name,
+        });
+    }
+    result
+}
+
+macro_rules! impl_attribute_value_for_unsigned {
+    ($t:ident, $strict:ident) => {
+        impl AttributeValue for $t {
+            fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
+            where
+                R: ParserSource,
+            {
+                if let Attribute::Primitive(PrimitiveAttribute::$strict(val)) = attr {
+                    Ok(to_unsigned(val, stringify!($t)))
+                } else {
+                    Ok(None)
+                }
+            }
+
+            fn from_attribute_loose<R>(attr: Attribute<R>) -> Result<Option<Self>>
+            where
+                R: ParserSource,
+            {
+                match attr {
+                    Attribute::Primitive(PrimitiveAttribute::I16(val)) => {
+                        Ok(to_unsigned(val, stringify!($t)))
+                    },
+                    Attribute::Primitive(PrimitiveAttribute::I32(val)) => {
+                        Ok(to_unsigned(val, stringify!($t)))
+                    },
+                    Attribute::Primitive(PrimitiveAttribute::I64(val)) => {
+                        Ok(to_unsigned(val, stringify!($t)))
+                    },
+                    _ => Ok(None),
+                }
+            }
+
+            fn from_attribute_checked<R>(
+                attr: Attribute<R>,
+                loss: &mut Option<CoercionLoss>,
+            ) -> Result<Option<Self>>
+            where
+                R: ParserSource,
+            {
+                let ty = stringify!($t);
+                match attr {
+                    Attribute::Primitive(PrimitiveAttribute::I16(val)) => {
+                        Ok(to_unsigned_checked(val, ty, loss))
+                    },
+                    Attribute::Primitive(PrimitiveAttribute::I32(val)) => {
+                        Ok(to_unsigned_checked(val, ty, loss))
+                    },
+                    Attribute::Primitive(PrimitiveAttribute::I64(val)) => {
+                        Ok(to_unsigned_checked(val, ty, loss))
+                    },
+                    _ => Ok(None),
+                }
+            }
+        }
+    }
+}
+
+impl_attribute_value_for_unsigned!(u32, I32);
+impl_attribute_value_for_unsigned!(u64, I64);
+impl_attribute_value_for_unsigned!(usize, I64);
+
 impl AttributeValue for f32 {
     fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
     where
@@ -159,6 +275,21 @@ impl AttributeValue for f32 {
             Ok(None)
         }
     }
+
+    fn from_attribute_checked<R>(
+        attr: Attribute<R>,
+        loss: &mut Option<CoercionLoss>,
+    ) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        match attr {
+            Attribute::Primitive(PrimitiveAttribute::F64(val)) => {
+                Ok(Some(CoercionLoss::narrow_f64(val, loss)))
+            },
+            attr => Self::from_attribute_loose(attr),
+        }
+    }
 }
 
 impl AttributeValue for f64 {
@@ -185,6 +316,26 @@ impl AttributeValue for f64 {
     }
 }
 
+impl AttributeValue for Vec<bool> {
+    fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        if let Attribute::Array(ArrayAttribute::Bool(arr)) = attr {
+            Ok(Some(arr.into_vec()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn from_attribute_loose<R>(attr: Attribute<R>) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        Self::from_attribute(attr)
+    }
+}
+
 impl AttributeValue for Vec<i32> {
     fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
     where
@@ -251,12 +402,25 @@ impl AttributeValue for Vec<f32> {
     {
         match attr {
             Attribute::Array(ArrayAttribute::F32(arr)) => Ok(Some(arr.into_vec()?)),
+            Attribute::Array(ArrayAttribute::F64(arr)) => Ok(Some(arr.into_f32_vec()?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn from_attribute_checked<R>(
+        attr: Attribute<R>,
+        loss: &mut Option<CoercionLoss>,
+    ) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        match attr {
             Attribute::Array(ArrayAttribute::F64(arr)) => {
                 Ok(Some(arr.into_iter()
-                    .map(|v| v.map(|v| v as f32))
+                    .map(|v| v.map(|v| CoercionLoss::narrow_f64(v, loss)))
                     .collect::<IoResult<_>>()?))
             },
-            _ => Ok(None),
+            attr => Self::from_attribute_loose(attr),
         }
     }
 }
@@ -290,6 +454,67 @@ impl AttributeValue for Vec<f64> {
 }
 
 
+// Load as `Vec<T>` and shrink.
+impl<T> AttributeValue for Box<[T]>
+where
+    Vec<T>: AttributeValue,
+{
+    fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        Ok(<Vec<T>>::from_attribute(attr)?.map(Vec::into_boxed_slice))
+    }
+
+    fn from_attribute_loose<R>(attr: Attribute<R>) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        Ok(<Vec<T>>::from_attribute_loose(attr)?.map(Vec::into_boxed_slice))
+    }
+
+    fn from_attribute_checked<R>(
+        attr: Attribute<R>,
+        loss: &mut Option<CoercionLoss>,
+    ) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        Ok(<Vec<T>>::from_attribute_checked(attr, loss)?.map(Vec::into_boxed_slice))
+    }
+}
+
+// Load as `Vec<T>` and move into `Arc`.
+impl<T> AttributeValue for Arc<[T]>
+where
+    Vec<T>: AttributeValue,
+{
+    fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        Ok(<Vec<T>>::from_attribute(attr)?.map(Into::into))
+    }
+
+    fn from_attribute_loose<R>(attr: Attribute<R>) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        Ok(<Vec<T>>::from_attribute_loose(attr)?.map(Into::into))
+    }
+
+    fn from_attribute_checked<R>(
+        attr: Attribute<R>,
+        loss: &mut Option<CoercionLoss>,
+    ) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        Ok(<Vec<T>>::from_attribute_checked(attr, loss)?.map(Into::into))
+    }
+}
+
+
 macro_rules! def_fn_array_attr_into_vec {
     ($t:ty, $len:expr, $fn_name:ident) => {
         /// Gets `[$t; $len]` from `ArrayAttributeReader<R, $t>`.
@@ -303,7 +528,7 @@ macro_rules! def_fn_array_attr_into_vec {
                 warn!(
                     concat!(
                         "Loading data from `ArrayAttributeReader<R, ",
-                        stringify!($ty),
+                        stringify!($t),
                         ">` into `Vec<[",
                         stringify!($t),
                         "; ",
@@ -314,25 +539,19 @@ macro_rules! def_fn_array_attr_into_vec {
                     components_len,
                 );
             }
-            let mut vec = Vec::with_capacity(num_vecs as usize);
-            assert_eq!(
-                ::std::mem::align_of::<[$t; $len]>(),
-                ::std::mem::align_of::<$t>(),
-                concat!(
-                    "`[[",
-                    stringify!($t),
-                    "; ",
-                    stringify!($len),
-                    "]]` should not have padding but it has",
-                ),
-            );
-            let components_buf_len = (components_len - remainder) as usize; // Equals to `num_vecs * $len`.
-            let components_buf = unsafe {
-                ::std::slice::from_raw_parts_mut(vec.as_mut_ptr() as *mut $t, components_buf_len)
-            };
-            assert!(components_buf.len() <= vec.len() * $len);
-            let size = arr.read_into_buf(components_buf)?;
-            assert_eq!(size, components_buf_len);
+            // Read components into a flat buffer, and then group them.
+            // Note that `num_vecs * $len` equals to `components_len - remainder`.
+            let mut components = vec![0.0; (num_vecs * $len) as usize];
+            let size = arr.read_into_buf(&mut components)?;
+            assert_eq!(size, components.len());
+            let vec = components
+                .chunks_exact($len)
+                .map(|chunk| {
+                    let mut v = [0.0; $len];
+                    v.copy_from_slice(chunk);
+                    v
+                })
+                .collect();
             Ok(vec)
         }
     }
@@ -345,12 +564,11 @@ def_fn_array_attr_into_vec!(f64, 3, array_attr_f64_into_vec3);
 
 
 macro_rules! impl_attribute_value_for_array {
-    (@subvariant_load; $t:ty, 3, $variant:ident, $sub_variant:ident, $arr:ident) => {{
+    (@subvariant_load; $t:ty, 3, $variant:ident, $sub_variant:ident, $arr:ident, $cast:expr) => {{
         let arr = $arr;
         let components_len = arr.rest_elements();
         let mut buf = Vec::with_capacity((components_len / 3) as usize);
-        // Cast instead of using `Into::into` because `From<f64>` is not implemented for `f32`.
-        let mut iter = arr.into_iter().map(|v| v.map(|f| f as $t));
+        let mut iter = arr.into_iter().map(|v| v.map($cast));
         while let Some(v0r) = iter.next() {
             let v0 = v0r?;
             let v1r = iter.next();
@@ -375,12 +593,11 @@ macro_rules! impl_attribute_value_for_array {
         }
         Ok(Some(buf))
     }};
-    (@subvariant_load; $t:ty, 2, $variant:ident, $sub_variant:ident, $arr:ident) => {{
+    (@subvariant_load; $t:ty, 2, $variant:ident, $sub_variant:ident, $arr:ident, $cast:expr) => {{
         let arr = $arr;
         let components_len = arr.rest_elements();
         let mut buf = Vec::with_capacity((components_len / 2) as usize);
-        // Cast instead of using `Into::into` because `From<f64>` is not implemented for `f32`.
-        let mut iter = arr.into_iter().map(|v| v.map(|f| f as $t));
+        let mut iter = arr.into_iter().map(|v| v.map($cast));
         while let Some(v0r) = iter.next() {
             let v0 = v0r?;
             let v1 = match iter.next() {
@@ -403,7 +620,15 @@ macro_rules! impl_attribute_value_for_array {
         }
         Ok(Some(buf))
     }};
-    ($t:ty, $len:tt, $variant:ident, $sub_variant:ident, $read_fn:ident, $sub_read_fn:ident) => {
+    (
+        $t:ty,
+        $len:tt,
+        $variant:ident,
+        $sub_variant:ident,
+        $read_fn:ident,
+        $sub_read_fn:ident,
+        $checked_cast:expr
+    ) => {
         impl AttributeValue for Vec<[$t; $len]> {
             fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
             where
@@ -423,11 +648,34 @@ macro_rules! impl_attribute_value_for_array {
                 match attr {
                     Attribute::Array(ArrayAttribute::$variant(arr)) => Ok(Some($read_fn(arr)?)),
                     Attribute::Array(ArrayAttribute::$sub_variant(arr)) => {
-                        impl_attribute_value_for_array!(@subvariant_load; $t, $len, $variant, $sub_variant, arr)
+                        // Cast instead of using `Into::into` because `From<f64>` is not
+                        // implemented for `f32`.
+                        impl_attribute_value_for_array!(
+                            @subvariant_load; $t, $len, $variant, $sub_variant, arr, |f| f as $t
+                        )
                     },
                     _ => Ok(None),
                 }
             }
+
+            fn from_attribute_checked<R>(
+                attr: Attribute<R>,
+                loss: &mut Option<CoercionLoss>,
+            ) -> Result<Option<Self>>
+            where
+                R: ParserSource,
+            {
+                match attr {
+                    Attribute::Array(ArrayAttribute::$sub_variant(arr)) => {
+                        let cast = $checked_cast;
+                        impl_attribute_value_for_array!(
+                            @subvariant_load; $t, $len, $variant, $sub_variant, arr,
+                            |f| cast(f, &mut *loss)
+                        )
+                    },
+                    attr => Self::from_attribute_loose(attr),
+                }
+            }
         }
     }
 }
@@ -438,7 +686,8 @@ impl_attribute_value_for_array!(
     F32,
     F64,
     array_attr_f32_into_vec2,
-    array_attr_f64_into_vec2
+    array_attr_f64_into_vec2,
+    CoercionLoss::narrow_f64
 );
 impl_attribute_value_for_array!(
     f64,
@@ -446,7 +695,8 @@ impl_attribute_value_for_array!(
     F64,
     F32,
     array_attr_f64_into_vec2,
-    array_attr_f32_into_vec2
+    array_attr_f32_into_vec2,
+    |f: f32, _: &mut Option<CoercionLoss>| f64::from(f)
 );
 
 impl_attribute_value_for_array!(
@@ -455,7 +705,8 @@ impl_attribute_value_for_array!(
     F32,
     F64,
     array_attr_f32_into_vec3,
-    array_attr_f64_into_vec3
+    array_attr_f64_into_vec3,
+    CoercionLoss::narrow_f64
 );
 impl_attribute_value_for_array!(
     f64,
@@ -463,61 +714,38 @@ impl_attribute_value_for_array!(
     F64,
     F32,
     array_attr_f64_into_vec3,
-    array_attr_f32_into_vec3
+    array_attr_f32_into_vec3,
+    |f: f32, _: &mut Option<CoercionLoss>| f64::from(f)
 );
 
 
-macro_rules! def_fn_array_attr_into_mat4 {
-    ($t:ty, $fn_name:ident) => {
-        fn $fn_name<R>(mut arr: ArrayAttributeReader<R, $t>) -> IoResult<Option<[[$t; 4]; 4]>>
-        where
-            R: ParserSource,
-        {
-            const NUM_COLS: usize = 4;
-            const NUM_ROWS: usize = 4;
-            const NUM_ELEMS: usize = NUM_COLS * NUM_ROWS;
-            // Column major.
-            let mut mat = [[0.0; NUM_COLS]; NUM_ROWS];
-            let read_size = {
-                let buf = unsafe {
-                    ::std::slice::from_raw_parts_mut(mat.as_mut_ptr() as *mut $t, NUM_ELEMS)
-                };
-                arr.read_into_buf(buf)?
-            };
-            assert!(read_size <= NUM_ELEMS, "More than {} elements can't be read into mat{}x{}", NUM_ELEMS, NUM_ROWS, NUM_COLS);
-            if read_size == NUM_ELEMS {
-                Ok(Some(mat))
-            } else {
-                warn!(
-                    concat!(
-                        "Loading data from `ArrayAttributeReader<R, ",
-                        stringify!($t),
-                        ">` into `[[",
-                        stringify!($t),
-                        "; {}]; {}]` but array is too short (length={})"
-                    ),
-                    NUM_COLS,
-                    NUM_ROWS,
-                    read_size);
-                Ok(None)
-            }
+macro_rules! impl_attribute_value_for_fixed_array {
+    (@read; $t:ty, $variant:ident, $arr:ident) => {{
+        let arr = $arr;
+        if arr.rest_elements() != N as u64 {
+            warn!(
+                concat!(
+                    "Loading data from `ArrayAttribute::",
+                    stringify!($variant),
+                    "` into `[",
+                    stringify!($t),
+                    "; {}]` but array length ({}) differs",
+                ),
+                N,
+                arr.rest_elements()
+            );
+            return Ok(None);
         }
-    }
-}
-
-def_fn_array_attr_into_mat4!(f32, array_attr_f32_into_mat4);
-def_fn_array_attr_into_mat4!(f64, array_attr_f64_into_mat4);
-
-
-macro_rules! impl_attribute_value_for_mat4 {
-    ($t:ty, $variant:ident, $sub_variant:ident, $read_fn:ident, $sub_read_fn:ident) => {
-        impl AttributeValue for [[$t; 4]; 4] {
+        arr.try_into_array::<N>().map(Some)
+    }};
+    ($t:ty, $variant:ident $(, $sub_variant:ident => $sub_t:ty, $checked_cast:expr)*) => {
+        impl<const N: usize> AttributeValue for [$t; N] {
             fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
             where
                 R: ParserSource,
             {
                 if let Attribute::Array(ArrayAttribute::$variant(arr)) = attr {
-                    $read_fn(arr).map_err(Into::into)
+                    impl_attribute_value_for_fixed_array!(@read; $t, $variant, arr)
                 } else {
                     Ok(None)
                 }
@@ -529,65 +757,103 @@ macro_rules! impl_attribute_value_for_mat4 {
             {
                 match attr {
                     Attribute::Array(ArrayAttribute::$variant(arr)) => {
-                        $read_fn(arr).map_err(Into::into)
-                    },
-                    Attribute::Array(ArrayAttribute::$sub_variant(arr)) => {
-                        let mat = match $sub_read_fn(arr)? {
-                            Some(m) => {
-                                [
-                                    [
-                                        m[0][0] as $t,
-                                        m[0][1] as $t,
-                                        m[0][2] as $t,
-                                        m[0][3] as $t,
-                                    ],
-                                    [
-                                        m[1][0] as $t,
-                                        m[1][1] as $t,
-                                        m[1][2] as $t,
-                                        m[1][3] as $t,
-                                    ],
-                                    [
-                                        m[2][0] as $t,
-                                        m[2][1] as $t,
-                                        m[2][2] as $t,
-                                        m[2][3] as $t,
-                                    ],
-                                    [
-                                        m[3][0] as $t,
-                                        m[3][1] as $t,
-                                        m[3][2] as $t,
-                                        m[3][3] as $t,
-                                    ],
-                                ]
-                            },
-                            None => return Ok(None),
-                        };
-                        Ok(Some(mat))
+                        impl_attribute_value_for_fixed_array!(@read; $t, $variant, arr)
                     },
+                    $(
+                        Attribute::Array(ArrayAttribute::$sub_variant(arr)) => {
+                            let sub: Option<[$sub_t; N]> = impl_attribute_value_for_fixed_array!(
+                                @read; $sub_t, $sub_variant, arr
+                            )?;
+                            Ok(sub.map(|sub| sub.map(|v| v as $t)))
+                        },
+                    )*
                     _ => Ok(None),
                 }
             }
+
+            $(
+                fn from_attribute_checked<R>(
+                    attr: Attribute<R>,
+                    loss: &mut Option<CoercionLoss>,
+                ) -> Result<Option<Self>>
+                where
+                    R: ParserSource,
+                {
+                    match attr {
+                        Attribute::Array(ArrayAttribute::$sub_variant(arr)) => {
+                            let cast = $checked_cast;
+                            let sub: Option<[$sub_t; N]> = impl_attribute_value_for_fixed_array!(
+                                @read; $sub_t, $sub_variant, arr
+                            )?;
+                            Ok(sub.map(|sub| sub.map(|v| cast(v, &mut *loss))))
+                        },
+                        attr => Self::from_attribute_loose(attr),
+                    }
+                }
+            )*
         }
     }
 }
 
-impl_attribute_value_for_mat4!(
-    f32,
-    F32,
-    F64,
-    array_attr_f32_into_mat4,
-    array_attr_f64_into_mat4
+impl_attribute_value_for_fixed_array!(bool, Bool);
+impl_attribute_value_for_fixed_array!(i32, I32);
+impl_attribute_value_for_fixed_array!(
+    i64, I64, I32 => i32, |v: i32, _: &mut Option<CoercionLoss>| i64::from(v)
 );
-impl_attribute_value_for_mat4!(
-    f64,
-    F64,
-    F32,
-    array_attr_f64_into_mat4,
-    array_attr_f32_into_mat4
+impl_attribute_value_for_fixed_array!(f32, F32, F64 => f64, CoercionLoss::narrow_f64);
+impl_attribute_value_for_fixed_array!(
+    f64, F64, F32 => f32, |v: f32, _: &mut Option<CoercionLoss>| f64::from(v)
 );
 
 
+macro_rules! impl_attribute_value_for_mat4 {
+    ($t:ty) => {
+        /// Column major 4x4 matrix.
+        impl AttributeValue for [[$t; 4]; 4] {
+            fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
+            where
+                R: ParserSource,
+            {
+                Ok(<[$t; 16]>::from_attribute(attr)?.map(arr16_to_mat4))
+            }
+
+            fn from_attribute_loose<R>(attr: Attribute<R>) -> Result<Option<Self>>
+            where
+                R: ParserSource,
+            {
+                Ok(<[$t; 16]>::from_attribute_loose(attr)?.map(arr16_to_mat4))
+            }
+
+            fn from_attribute_checked<R>(
+                attr: Attribute<R>,
+                loss: &mut Option<CoercionLoss>,
+            ) -> Result<Option<Self>>
+            where
+                R: ParserSource,
+            {
+                Ok(<[$t; 16]>::from_attribute_checked(attr, loss)?.map(arr16_to_mat4))
+            }
+        }
+    }
+}
+
+impl_attribute_value_for_mat4!(f32);
+impl_attribute_value_for_mat4!(f64);
+
+
+/// Converts 16 elements into a 4x4 matrix.
+///
+/// Elements are interpreted as column major, i.e. `arr[4 * i + j]` will be `mat[i][j]`.
+fn arr16_to_mat4<T: Copy>(arr: [T; 16]) -> [[T; 4]; 4] {
+    [
+        [arr[0], arr[1], arr[2], arr[3]],
+        [arr[4], arr[5], arr[6], arr[7]],
+        [arr[8], arr[9], arr[10], arr[11]],
+        [arr[12], arr[13], arr[14], arr[15]],
+    ]
+}
+
+
 impl AttributeValue for String {
     fn from_attribute<R>(attr: Attribute<R>) -> Result<Option<Self>>
     where
@@ -645,6 +911,22 @@ pub trait AttributeValues: Sized {
     fn from_attributes<R>(attrs: &mut Attributes<R>) -> Result<Option<Self>>
     where
         R: ParserSource;
+
+    /// Construct `Self` via a conversion if possible, recording lossy conversions.
+    ///
+    /// Same as `from_attributes()`, but lossy conversions of the values are recorded into
+    /// `strict` as attributes of its current node.
+    /// The default implementation records nothing.
+    fn from_attributes_strict<R>(
+        attrs: &mut Attributes<R>,
+        strict: &mut StrictCoercion,
+    ) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        let _ = strict;
+        Self::from_attributes(attrs)
+    }
 }
 
 macro_rules! impl_attribute_values {
@@ -667,6 +949,30 @@ macro_rules! impl_attribute_values {
                 )+
                 Ok(Some(($($name),+,)))
             }
+
+            fn from_attributes_strict<R>(
+                attrs: &mut Attributes<R>,
+                strict: &mut StrictCoercion,
+            ) -> Result<Option<Self>>
+                where R: ParserSource
+            {
+                $(
+                    let $name = {
+                        let attr = match attrs.next_attribute()? {
+                            Some(attr) => attr,
+                            None => return Ok(None),
+                        };
+                        let mut loss = None;
+                        let val = $t::from_attribute_checked(attr, &mut loss)?;
+                        strict.push_attribute(loss);
+                        match val {
+                            Some(val) => val,
+                            None => return Ok(None),
+                        }
+                    };
+                )+
+                Ok(Some(($($name),+,)))
+            }
         }
     }
 }
@@ -678,6 +984,16 @@ impl<T: AttributeValue> AttributeValues for T {
     {
         <(T,)>::from_attributes(attrs).map(|v_opt| v_opt.map(|v| v.0))
     }
+
+    fn from_attributes_strict<R>(
+        attrs: &mut Attributes<R>,
+        strict: &mut StrictCoercion,
+    ) -> Result<Option<Self>>
+    where
+        R: ParserSource,
+    {
+        <(T,)>::from_attributes_strict(attrs, strict).map(|v_opt| v_opt.map(|v| v.0))
+    }
 }
 
 impl_attribute_values! {
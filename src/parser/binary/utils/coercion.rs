@@ -0,0 +1,139 @@
+//! Detection of lossy attribute value coercions.
+
+use std::fmt;
+
+
+/// Lossy conversion of an attribute value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoercionLoss {
+    /// `f64` values are not representable in `f32`.
+    InexactF32 {
+        /// First value which is not representable.
+        value: f64,
+        /// Number of values which are not representable.
+        count: u64,
+    },
+    /// Integer value is out of range of the target type.
+    OutOfRange {
+        /// Value.
+        value: i64,
+        /// Name of the target type.
+        target: &'static str,
+    },
+}
+
+impl CoercionLoss {
+    /// Narrows the value into `f32`, and accumulates the loss into `loss` if it is inexact.
+    ///
+    /// NaN is not considered as lossy.
+    pub fn narrow_f64(value: f64, loss: &mut Option<CoercionLoss>) -> f32 {
+        let narrowed = value as f32;
+        if narrowed as f64 != value && !value.is_nan() {
+            match *loss {
+                Some(CoercionLoss::InexactF32 { ref mut count, .. }) => *count += 1,
+                _ => *loss = Some(CoercionLoss::InexactF32 { value: value, count: 1 }),
+            }
+        }
+        narrowed
+    }
+}
+
+impl fmt::Display for CoercionLoss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CoercionLoss::InexactF32 { value, count } => {
+                write!(
+                    f,
+                    "{} `f64` value(s) are not representable in `f32` (first: {:?})",
+                    count,
+                    value
+                )
+            },
+            CoercionLoss::OutOfRange { value, target } => {
+                write!(f, "value {} is out of range of `{}`", value, target)
+            },
+        }
+    }
+}
+
+
+/// Lossy attribute conversion detected in the strict coercion mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoercionWarning {
+    /// Path of the node, such as `"Objects/Geometry(42)/Vertices"`.
+    pub path: String,
+    /// Index of the attribute.
+    pub attribute: usize,
+    /// Loss of the conversion.
+    pub loss: CoercionLoss,
+}
+
+impl fmt::Display for CoercionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` attribute {}: {}", self.path, self.attribute, self.loss)
+    }
+}
+
+
+/// Collector of lossy attribute conversions, with the path of the current node.
+///
+/// Loaders call `enter()` and `leave()` around each node, and `push_attribute()` for each
+/// converted attribute of the current node.
+#[derive(Default, Debug, Clone)]
+pub struct StrictCoercion {
+    /// Names of the current node and its ancestors.
+    path: Vec<String>,
+    /// Index of the next attribute of the current node.
+    next_attribute: usize,
+    /// Detected lossy conversions.
+    warnings: Vec<CoercionWarning>,
+}
+
+impl StrictCoercion {
+    /// Creates a new `StrictCoercion`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Enters the child node with the given path segment.
+    pub fn enter<S: Into<String>>(&mut self, segment: S) {
+        self.path.push(segment.into());
+        self.next_attribute = 0;
+    }
+
+    /// Leaves the current node.
+    pub fn leave(&mut self) {
+        self.path.pop();
+    }
+
+    /// Returns the path of the current node.
+    pub fn path(&self) -> String {
+        self.path.join("/")
+    }
+
+    /// Advances to the next attribute of the current node, and records the loss of its
+    /// conversion if any.
+    pub fn push_attribute(&mut self, loss: Option<CoercionLoss>) {
+        let attribute = self.next_attribute;
+        self.next_attribute += 1;
+        if let Some(loss) = loss {
+            let warning = CoercionWarning {
+                path: self.path(),
+                attribute: attribute,
+                loss: loss,
+            };
+            warn!("Lossy attribute conversion: {}", warning);
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Returns the detected lossy conversions.
+    pub fn warnings(&self) -> &[CoercionWarning] {
+        &self.warnings
+    }
+
+    /// Removes and returns the detected lossy conversions.
+    pub fn take_warnings(&mut self) -> Vec<CoercionWarning> {
+        ::std::mem::take(&mut self.warnings)
+    }
+}
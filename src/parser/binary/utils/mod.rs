@@ -1,5 +1,7 @@
 //! Utilities for parser-related stuff.
 
 pub use self::attribute_value::{AttributeValues, AttributeValue};
+pub use self::coercion::{CoercionLoss, CoercionWarning, StrictCoercion};
 
 mod attribute_value;
+mod coercion;
@@ -0,0 +1,98 @@
+//! FBX version and its capabilities.
+
+use std::fmt;
+
+
+/// FBX version (such as `7400` for FBX 7.4).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FbxVersion(pub u32);
+
+impl FbxVersion {
+    /// FBX 7.0, where `Properties70` was introduced.
+    pub const V7_0: FbxVersion = FbxVersion(7000);
+    /// FBX 7.4.
+    pub const V7_4: FbxVersion = FbxVersion(7400);
+    /// FBX 7.5, where node headers became 64-bit.
+    pub const V7_5: FbxVersion = FbxVersion(7500);
+
+    /// Returns the raw version number.
+    pub fn raw(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the major version (`7` for `7400`).
+    pub fn major(self) -> u32 {
+        self.0 / 1000
+    }
+
+    /// Returns the minor version (`4` for `7400`).
+    pub fn minor(self) -> u32 {
+        self.0 % 1000 / 100
+    }
+
+    /// Returns `true` if node headers have 64-bit fields (FBX 7.5 or later).
+    pub fn supports_64bit_node_headers(self) -> bool {
+        self >= FbxVersion::V7_5
+    }
+
+    /// Returns the length of a node header in bytes, excluding the node name.
+    ///
+    /// The header consists of the end offset, the number of attributes, the byte length of
+    /// attributes, and the byte length of the name.
+    pub fn node_header_len(self) -> usize {
+        if self.supports_64bit_node_headers() { 25 } else { 13 }
+    }
+
+    /// Returns `true` if objects have properties in `Properties70` (FBX 7.0 or later), rather
+    /// than legacy `Properties60`.
+    pub fn uses_binary_properties70(self) -> bool {
+        self >= FbxVersion::V7_0
+    }
+
+    /// Returns `true` if the file is expected to end with the footer containing the file ID
+    /// hash and the version (FBX 7.0 or later).
+    pub fn has_footer_hash(self) -> bool {
+        self >= FbxVersion::V7_0
+    }
+
+    /// Returns `true` if the version is loadable by `loader::binary::simple::FbxLoader`
+    /// (FBX 7.4 and 7.5).
+    pub fn is_supported_by_simple_loader(self) -> bool {
+        self >= FbxVersion::V7_4 && self.0 < 7600
+    }
+}
+
+impl From<u32> for FbxVersion {
+    fn from(v: u32) -> Self {
+        FbxVersion(v)
+    }
+}
+
+impl fmt::Display for FbxVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major(), self.minor())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::FbxVersion;
+
+    #[test]
+    fn test_capabilities() {
+        let v6 = FbxVersion(6100);
+        let v74 = FbxVersion(7400);
+        let v75 = FbxVersion::from(7500);
+
+        assert_eq!((v74.major(), v74.minor()), (7, 4));
+        assert_eq!(v6.to_string(), "6.1");
+        assert!(!v74.supports_64bit_node_headers() && v75.supports_64bit_node_headers());
+        assert_eq!((v74.node_header_len(), v75.node_header_len()), (13, 25));
+        assert!(!v6.uses_binary_properties70() && v74.uses_binary_properties70());
+        assert!(!v6.has_footer_hash() && v75.has_footer_hash());
+        assert!(!v6.is_supported_by_simple_loader());
+        assert!(v75.is_supported_by_simple_loader());
+        assert!(!FbxVersion(7700).is_supported_by_simple_loader());
+    }
+}
@@ -0,0 +1,31 @@
+//! Commonly used types.
+//!
+//! Glob-import this module (`use fbxcel::prelude::*;`) to use the parser and the loader
+//! without long paths.
+//! Error types are renamed to avoid conflicts between the parser and the loader.
+
+pub use parser::binary::{RootParser, Parser, ParserSource, Event, Attributes};
+pub use parser::binary::{Error as ParseError, ErrorKind, Warning as ParseWarning};
+pub use loader::binary::simple::{FbxLoader, GenericNode, Error as LoadError};
+pub use loader::binary::simple::fbx7400::{Fbx7400, Object, Objects, ObjectsLoader};
+
+
+#[cfg(test)]
+mod tests {
+    use test_util::{FbxBuilder, load_objects, write_nodes_before_objects};
+    use super::*;
+
+    #[test]
+    fn test_prelude() {
+        let mut b = FbxBuilder::new(7400);
+        write_nodes_before_objects(&mut b);
+        b.node("Objects", &[]);
+        b.node("Connections", &[]);
+        let data = b.finish();
+
+        let fbx = load_objects(data);
+        let objects: &Objects = &fbx.objects;
+        assert!(objects.is_empty());
+        assert!(objects.object(0).map(Object::id).is_none());
+    }
+}
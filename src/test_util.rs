@@ -0,0 +1,356 @@
+//! Generator of synthetic FBX binaries for tests and benchmarks.
+//!
+//! This module builds valid FBX 7.4 and 7.5 binaries programmatically, so tests don't need
+//! external FBX files.
+//!
+//! Compressed arrays are available only when `libflate` or `flate2` feature is enabled.
+//!
+//! This module is available for the tests of this crate, and with `test-util` feature for
+//! benchmarks and integration tests. It is not a part of the public API.
+
+use std::io::Cursor;
+use parser::binary::{RootParser, BasicSource};
+use loader::binary::simple::FbxLoader;
+use loader::binary::simple::fbx7400::{Fbx7400, ObjectsLoader};
+use writer::binary::{Writer, BorrowedAttribute, CompressionLevel};
+
+
+/// Node attribute to be written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Attr<'a> {
+    /// `bool`.
+    Bool(bool),
+    /// `i16`.
+    I16(i16),
+    /// `i32`.
+    I32(i32),
+    /// `i64`.
+    I64(i64),
+    /// `f32`.
+    F32(f32),
+    /// `f64`.
+    F64(f64),
+    /// Array of `bool`.
+    ArrBool(&'a [bool]),
+    /// Array of `bool` with the given raw bytes.
+    ArrBoolRaw(&'a [u8]),
+    /// Array of `i32`.
+    ArrI32(&'a [i32]),
+    /// Array of `i64`.
+    ArrI64(&'a [i64]),
+    /// Array of `f32`.
+    ArrF32(&'a [f32]),
+    /// Array of `f64`.
+    ArrF64(&'a [f64]),
+    /// String.
+    String(&'a str),
+    /// Binary.
+    Binary(&'a [u8]),
+}
+
+
+/// FBX binary builder.
+///
+/// This is a wrapper of `writer::binary::Writer` which writes into memory and panics on
+/// errors.
+#[derive(Debug)]
+pub struct FbxBuilder {
+    /// Writer.
+    writer: Writer<Cursor<Vec<u8>>>,
+    /// Whether to compress array attributes.
+    compress_arrays: bool,
+    /// Number of open nodes.
+    depth: usize,
+}
+
+impl FbxBuilder {
+    /// Creates a new builder and writes the FBX header.
+    pub fn new(version: u32) -> Self {
+        FbxBuilder {
+            writer: Writer::new(Cursor::new(Vec::new()), version)
+                .expect("Writing to `Vec` should not fail"),
+            compress_arrays: false,
+            depth: 0,
+        }
+    }
+
+    /// Returns FBX version.
+    pub fn version(&self) -> u32 {
+        self.writer.version().raw()
+    }
+
+    /// Sets whether to zlib-compress array attributes written after this call.
+    ///
+    /// # Panics
+    /// Writing compressed arrays panics if neither `libflate` nor `flate2` feature is enabled.
+    pub fn set_compress_arrays(&mut self, compress: bool) {
+        self.compress_arrays = compress;
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn position(&self) -> usize {
+        self.writer.position() as usize
+    }
+
+    /// Starts a node.
+    pub fn begin_node(&mut self, name: &str, attrs: &[Attr]) {
+        self.writer.begin_node_unguarded(name).expect("Failed to start node");
+        self.depth += 1;
+        for attr in attrs {
+            self.write_attr(attr);
+        }
+    }
+
+    /// Ends the most recent opened node.
+    ///
+    /// # Panics
+    /// Panics if no nodes are open.
+    pub fn end_node(&mut self) {
+        assert!(self.depth > 0, "No nodes are open");
+        self.depth -= 1;
+        self.writer.end_node_unguarded().expect("Failed to end node");
+    }
+
+    /// Writes a node without children.
+    pub fn node(&mut self, name: &str, attrs: &[Attr]) {
+        self.begin_node(name, attrs);
+        self.end_node();
+    }
+
+    /// Writes an attribute.
+    fn write_attr(&mut self, attr: &Attr) {
+        let compression = if self.compress_arrays {
+            Some(CompressionLevel::Default)
+        } else {
+            None
+        };
+        let attr = match *attr {
+            Attr::Bool(v) => BorrowedAttribute::Bool(v),
+            Attr::I16(v) => BorrowedAttribute::I16(v),
+            Attr::I32(v) => BorrowedAttribute::I32(v),
+            Attr::I64(v) => BorrowedAttribute::I64(v),
+            Attr::F32(v) => BorrowedAttribute::F32(v),
+            Attr::F64(v) => BorrowedAttribute::F64(v),
+            Attr::ArrBool(arr) => BorrowedAttribute::ArrBool(arr),
+            Attr::ArrBoolRaw(raw) => {
+                self.writer
+                    .raw_bool_array_unguarded(raw, compression)
+                    .expect("Failed to write attribute");
+                return;
+            },
+            Attr::ArrI32(arr) => BorrowedAttribute::ArrI32(arr),
+            Attr::ArrI64(arr) => BorrowedAttribute::ArrI64(arr),
+            Attr::ArrF32(arr) => BorrowedAttribute::ArrF32(arr),
+            Attr::ArrF64(arr) => BorrowedAttribute::ArrF64(arr),
+            Attr::String(v) => BorrowedAttribute::String(v.as_bytes()),
+            Attr::Binary(v) => BorrowedAttribute::Binary(v),
+        };
+        self.writer
+            .attr_unguarded(attr, compression)
+            .expect("Failed to write attribute");
+    }
+
+    /// Ends the implicit root node, writes the FBX footer, and returns the FBX binary.
+    ///
+    /// # Panics
+    /// Panics if some nodes are not closed.
+    pub fn finish(self) -> Vec<u8> {
+        self.writer.finish().expect("Failed to finish FBX").into_inner()
+    }
+}
+
+
+/// Loads the FBX 7.4 binary with the default objects loader.
+///
+/// # Panics
+/// Panics if the data cannot be loaded.
+pub fn load_objects(data: Vec<u8>) -> Fbx7400<ObjectsLoader<BasicSource<Cursor<Vec<u8>>>>> {
+    let parser = RootParser::new(Cursor::new(data));
+    match FbxLoader::load_from_parser(parser).expect("Load error") {
+        FbxLoader::Fbx7400(loader) => loader.load(ObjectsLoader::new()).expect("Load error"),
+    }
+}
+
+
+/// Writes `Properties70` node with the given properties.
+///
+/// Each property is (name, type, label, flags, values).
+pub fn write_properties70(b: &mut FbxBuilder, props: &[(&str, &str, &str, &str, &[Attr])]) {
+    b.begin_node("Properties70", &[]);
+    for &(name, type_, label, flags, values) in props {
+        let mut attrs = vec![
+            Attr::String(name),
+            Attr::String(type_),
+            Attr::String(label),
+            Attr::String(flags),
+        ];
+        attrs.extend_from_slice(values);
+        b.node("P", &attrs);
+    }
+    b.end_node();
+}
+
+
+/// Names of toplevel nodes required by FBX 7.4 loader before `Objects`, in usual order.
+pub const NODES_BEFORE_OBJECTS: &[&str] = &[
+    "FBXHeaderExtension",
+    "FileId",
+    "CreationTime",
+    "Creator",
+    "GlobalSettings",
+    "Documents",
+    "References",
+    "Definitions",
+];
+
+
+/// Writes toplevel nodes required by FBX 7.4 loader before `Objects`.
+///
+/// Written nodes are listed in `NODES_BEFORE_OBJECTS`.
+pub fn write_nodes_before_objects(b: &mut FbxBuilder) {
+    for name in NODES_BEFORE_OBJECTS {
+        write_toplevel_node(b, name);
+    }
+}
+
+
+/// Writes a toplevel node with typical content.
+///
+/// # Panics
+/// Panics if the node is not listed in `NODES_BEFORE_OBJECTS`.
+pub fn write_toplevel_node(b: &mut FbxBuilder, name: &str) {
+    match name {
+        "FBXHeaderExtension" => write_fbx_header_extension(b),
+        "FileId" => b.node("FileId", &[Attr::Binary(&[0; 16])]),
+        "CreationTime" => b.node("CreationTime", &[Attr::String("2017-07-30 12:00:00:000")]),
+        "Creator" => b.node("Creator", &[Attr::String("fbxcel test_util")]),
+        "GlobalSettings" => {
+            b.begin_node("GlobalSettings", &[]);
+            b.node("Version", &[Attr::I32(1000)]);
+            write_properties70(
+                b,
+                &[
+                    ("UpAxis", "int", "Integer", "", &[Attr::I32(1)]),
+                    ("UnitScaleFactor", "double", "Number", "", &[Attr::F64(1.0)]),
+                ],
+            );
+            b.end_node();
+        },
+        "Documents" => b.node("Documents", &[]),
+        "References" => b.node("References", &[]),
+        "Definitions" => {
+            b.begin_node("Definitions", &[]);
+            b.node("Version", &[Attr::I32(100)]);
+            b.node("Count", &[Attr::I32(0)]);
+            b.end_node();
+        },
+        _ => panic!("Unsupported toplevel node: {:?}", name),
+    }
+}
+
+
+/// Writes `FBXHeaderExtension` node.
+fn write_fbx_header_extension(b: &mut FbxBuilder) {
+    let version = b.version() as i32;
+    b.begin_node("FBXHeaderExtension", &[]);
+    b.node("FBXHeaderVersion", &[Attr::I32(1003)]);
+    b.node("FBXVersion", &[Attr::I32(version)]);
+    b.node("EncryptionType", &[Attr::I32(0)]);
+    b.begin_node("CreationTimeStamp", &[]);
+    for &(name, v) in &[
+        ("Version", 1000),
+        ("Year", 2017),
+        ("Month", 7),
+        ("Day", 30),
+        ("Hour", 12),
+        ("Minute", 0),
+        ("Second", 0),
+        ("Millisecond", 0),
+    ]
+    {
+        b.node(name, &[Attr::I32(v)]);
+    }
+    b.end_node();
+    b.node("Creator", &[Attr::String("fbxcel test_util")]);
+    b.begin_node(
+        "SceneInfo",
+        &[
+            Attr::String("GlobalInfo\u{0}\u{1}SceneInfo"),
+            Attr::String("UserData"),
+        ],
+    );
+    b.node("Type", &[Attr::String("UserData")]);
+    b.node("Version", &[Attr::I32(100)]);
+    b.begin_node("MetaData", &[]);
+    b.node("Version", &[Attr::I32(100)]);
+    for name in &["Title", "Subject", "Author", "Keywords", "Revision", "Comment"] {
+        b.node(name, &[Attr::String("")]);
+    }
+    b.end_node();
+    write_properties70(
+        b,
+        &[("DocumentUrl", "KString", "Url", "", &[Attr::String("test.fbx")])],
+    );
+    b.end_node();
+    b.end_node();
+}
+
+
+/// Creates a minimal valid FBX binary with empty `Objects` and `Connections`.
+pub fn minimal_fbx(version: u32) -> Vec<u8> {
+    let mut b = FbxBuilder::new(version);
+    write_nodes_before_objects(&mut b);
+    b.node("Objects", &[]);
+    b.node("Connections", &[]);
+    b.finish()
+}
+
+
+/// Creates a synthetic FBX binary with the given number of geometry objects.
+///
+/// Each geometry has `num_vertices` vertices and triangles using them, and is connected to the
+/// root object.
+pub fn synthetic_fbx(
+    version: u32,
+    num_objects: usize,
+    num_vertices: usize,
+    compress_arrays: bool,
+) -> Vec<u8> {
+    let vertices = (0..num_vertices * 3).map(|i| i as f64 * 0.5).collect::<Vec<_>>();
+    let indices = (0..num_vertices as i32)
+        .map(|i| if i % 3 == 2 { !i } else { i })
+        .collect::<Vec<_>>();
+
+    let mut b = FbxBuilder::new(version);
+    write_nodes_before_objects(&mut b);
+    b.set_compress_arrays(compress_arrays);
+    b.begin_node("Objects", &[]);
+    for i in 0..num_objects {
+        let name_class = format!("Mesh{}\u{0}\u{1}Geometry", i);
+        b.begin_node(
+            "Geometry",
+            &[
+                Attr::I64(1000 + i as i64),
+                Attr::String(&name_class),
+                Attr::String("Mesh"),
+            ],
+        );
+        write_properties70(
+            &mut b,
+            &[("Color", "ColorRGB", "Color", "", &[Attr::F64(0.8), Attr::F64(0.8), Attr::F64(0.8)])],
+        );
+        b.node("Vertices", &[Attr::ArrF64(&vertices)]);
+        b.node("PolygonVertexIndex", &[Attr::ArrI32(&indices)]);
+        b.end_node();
+    }
+    b.end_node();
+    b.begin_node("Connections", &[]);
+    for i in 0..num_objects {
+        b.node(
+            "C",
+            &[Attr::String("OO"), Attr::I64(1000 + i as i64), Attr::I64(0)],
+        );
+    }
+    b.end_node();
+    b.finish()
+}
@@ -0,0 +1,91 @@
+//! Node attribute to be written.
+
+use parser::binary::OwnedAttribute;
+
+
+/// Node attribute to be written, borrowing the value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BorrowedAttribute<'a> {
+    /// `bool`.
+    Bool(bool),
+    /// `i16`.
+    I16(i16),
+    /// `i32`.
+    I32(i32),
+    /// `i64`.
+    I64(i64),
+    /// `f32`.
+    F32(f32),
+    /// `f64`.
+    F64(f64),
+    /// Array of `bool`.
+    ArrBool(&'a [bool]),
+    /// Array of `i32`.
+    ArrI32(&'a [i32]),
+    /// Array of `i64`.
+    ArrI64(&'a [i64]),
+    /// Array of `f32`.
+    ArrF32(&'a [f32]),
+    /// Array of `f64`.
+    ArrF64(&'a [f64]),
+    /// String.
+    ///
+    /// This is raw bytes, since strings in FBX files are not always valid UTF-8.
+    String(&'a [u8]),
+    /// Binary.
+    Binary(&'a [u8]),
+}
+
+impl<'a> BorrowedAttribute<'a> {
+    /// Creates a `BorrowedAttribute` from the owned attribute.
+    ///
+    /// Returns `None` for `OwnedAttribute::BinaryRef`, since the content is not in memory.
+    pub fn from_owned(attr: &'a OwnedAttribute) -> Option<Self> {
+        Some(match *attr {
+            OwnedAttribute::Bool(v) => BorrowedAttribute::Bool(v),
+            OwnedAttribute::I16(v) => BorrowedAttribute::I16(v),
+            OwnedAttribute::I32(v) => BorrowedAttribute::I32(v),
+            OwnedAttribute::I64(v) => BorrowedAttribute::I64(v),
+            OwnedAttribute::F32(v) => BorrowedAttribute::F32(v),
+            OwnedAttribute::F64(v) => BorrowedAttribute::F64(v),
+            OwnedAttribute::ArrBool(ref v) => BorrowedAttribute::ArrBool(v),
+            OwnedAttribute::ArrI32(ref v) => BorrowedAttribute::ArrI32(v),
+            OwnedAttribute::ArrI64(ref v) => BorrowedAttribute::ArrI64(v),
+            OwnedAttribute::ArrF32(ref v) => BorrowedAttribute::ArrF32(v),
+            OwnedAttribute::ArrF64(ref v) => BorrowedAttribute::ArrF64(v),
+            OwnedAttribute::String(Ok(ref v)) => BorrowedAttribute::String(v.as_bytes()),
+            OwnedAttribute::String(Err(ref v)) => BorrowedAttribute::String(v),
+            OwnedAttribute::Binary(ref v) => BorrowedAttribute::Binary(v),
+            OwnedAttribute::BinaryRef(_) => return None,
+        })
+    }
+}
+
+
+macro_rules! impl_borrowed_attribute_from {
+    ($($t:ty => $variant:ident, |$v:ident| $conv:expr;)*) => {
+        $(
+            impl<'a> From<$t> for BorrowedAttribute<'a> {
+                fn from($v: $t) -> Self {
+                    BorrowedAttribute::$variant($conv)
+                }
+            }
+        )*
+    }
+}
+
+impl_borrowed_attribute_from! {
+    bool => Bool, |v| v;
+    i16 => I16, |v| v;
+    i32 => I32, |v| v;
+    i64 => I64, |v| v;
+    f32 => F32, |v| v;
+    f64 => F64, |v| v;
+    &'a [bool] => ArrBool, |v| v;
+    &'a [i32] => ArrI32, |v| v;
+    &'a [i64] => ArrI64, |v| v;
+    &'a [f32] => ArrF32, |v| v;
+    &'a [f64] => ArrF64, |v| v;
+    &'a [u8] => Binary, |v| v;
+    &'a str => String, |v| v.as_bytes();
+}
@@ -0,0 +1,94 @@
+//! Compression policy of array attributes.
+
+use std::fmt;
+use fnv::FnvHashMap;
+
+
+/// Compression level of zlib-compressed arrays.
+///
+/// `libflate` backend has no level control, and `Fast` uses fixed Huffman codes while the
+/// others use the default options.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompressionLevel {
+    /// Optimize for the speed.
+    Fast,
+    /// Balance between the speed and the size.
+    #[default]
+    Default,
+    /// Optimize for the size.
+    Best,
+}
+
+
+/// Array attribute to be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArrayInfo<'a> {
+    /// Name of the node which has the attribute.
+    pub node_name: &'a str,
+    /// Index of the attribute in the node.
+    pub attribute_index: usize,
+    /// Number of elements.
+    pub num_elements: usize,
+    /// Size of the uncompressed elements in bytes.
+    pub byte_len: usize,
+}
+
+
+/// Policy deciding whether to compress each array attribute.
+pub trait CompressionPolicy: fmt::Debug + Send + Sync {
+    /// Returns the compression level for the array, or `None` to write it uncompressed.
+    fn compression(&self, array: &ArrayInfo) -> Option<CompressionLevel>;
+}
+
+
+/// Compression policy by node names and array size.
+///
+/// By default, arrays of 128 bytes or larger are compressed with the default level, as FBX SDK
+/// does for small arrays being not worth compressing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayCompression {
+    /// Compression level.
+    pub level: CompressionLevel,
+    /// Minimum size of arrays to be compressed, in bytes.
+    pub min_byte_len: usize,
+    /// Overrides by node names: `true` to always compress and `false` to never compress.
+    pub nodes: FnvHashMap<String, bool>,
+}
+
+impl ArrayCompression {
+    /// Creates a new `ArrayCompression` with the given level and size threshold.
+    pub fn new(level: CompressionLevel, min_byte_len: usize) -> Self {
+        ArrayCompression {
+            level: level,
+            min_byte_len: min_byte_len,
+            nodes: FnvHashMap::default(),
+        }
+    }
+
+    /// Sets whether to compress arrays of the node with the given name regardless of the size,
+    /// and returns the policy.
+    pub fn with_node<S: Into<String>>(mut self, node_name: S, compress: bool) -> Self {
+        self.nodes.insert(node_name.into(), compress);
+        self
+    }
+}
+
+impl Default for ArrayCompression {
+    fn default() -> Self {
+        ArrayCompression::new(CompressionLevel::Default, 128)
+    }
+}
+
+impl CompressionPolicy for ArrayCompression {
+    fn compression(&self, array: &ArrayInfo) -> Option<CompressionLevel> {
+        let compress = self.nodes
+            .get(array.node_name)
+            .cloned()
+            .unwrap_or(array.byte_len >= self.min_byte_len);
+        if compress {
+            Some(self.level)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! FBX writing error.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+
+/// Result of writer function.
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+
+/// FBX writing error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An attribute is written after child nodes of the node.
+    AttributeAfterChildren,
+    /// The attribute is too large to be written (such as an array with more than `u32::MAX`
+    /// bytes).
+    AttributeTooLarge(usize),
+    /// Compressing arrays requires `libflate` or `flate2` feature.
+    CompressionUnavailable,
+    /// I/O error.
+    Io(io::Error),
+    /// Node name is longer than 255 bytes.
+    NodeNameTooLong(String),
+    /// The offset cannot be represented in the node header of the FBX version (e.g. offsets
+    /// over 4 GiB in FBX 7.4).
+    OffsetOverflow(u64),
+    /// Nodes are still open when the footer is to be written.
+    UnclosedNodes(usize),
+    /// The binary attribute is not loaded into memory (`OwnedAttribute::BinaryRef`).
+    UnloadedBinary,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::AttributeAfterChildren => {
+                f.write_str("Attributes should be written before child nodes")
+            },
+            Error::AttributeTooLarge(len) => write!(f, "Attribute is too large: {} bytes", len),
+            Error::CompressionUnavailable => {
+                f.write_str("Compressing arrays requires `libflate` or `flate2` feature")
+            },
+            Error::Io(ref err) => write!(f, "I/O error: {}", err),
+            Error::NodeNameTooLong(ref name) => write!(f, "Node name is too long: {:?}", name),
+            Error::OffsetOverflow(offset) => {
+                write!(f, "Offset {} is too large for the FBX version", offset)
+            },
+            Error::UnclosedNodes(num) => write!(f, "{} nodes are not closed", num),
+            Error::UnloadedBinary => f.write_str("Binary attribute is not loaded into memory"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
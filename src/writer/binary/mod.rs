@@ -0,0 +1,685 @@
+//! FBX binary writer.
+//!
+//! `Writer` emits nodes directly to a seekable sink, so exporters can write huge files without
+//! building the whole node tree in memory.
+
+use std::io::{Write, Seek, SeekFrom};
+#[cfg(all(feature = "flate2", not(feature = "libflate")))]
+use flate2::Compression;
+#[cfg(all(feature = "flate2", not(feature = "libflate")))]
+use flate2::write::ZlibEncoder;
+#[cfg(feature = "libflate")]
+use libflate::zlib;
+use parser::binary::{FbxFooter, FbxVersion};
+use loader::binary::simple::GenericNode;
+
+pub use self::attribute::BorrowedAttribute;
+pub use self::compression::{CompressionLevel, ArrayInfo, CompressionPolicy, ArrayCompression};
+pub use self::error::{Result, Error};
+
+mod attribute;
+mod compression;
+mod error;
+
+
+/// Magic binary of the FBX header.
+const MAGIC: &[u8] = b"Kaydara FBX Binary  \x00\x1a\x00";
+
+
+/// Node opened by the writer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpenNode {
+    /// Node name.
+    name: String,
+    /// Position of the node header.
+    header_pos: u64,
+    /// Number of attributes written so far.
+    num_attrs: u64,
+    /// Position of the first attribute.
+    attrs_begin: u64,
+    /// End position of the attributes, set when the first child node begins.
+    attrs_end: Option<u64>,
+}
+
+
+/// Streaming FBX binary writer.
+///
+/// Node headers (end offset, number of attributes, and length of attributes) are written as
+/// placeholders and backpatched when the node ends, so the sink should be seekable.
+/// Writes are not buffered, so wrap the sink with `BufWriter` for files.
+///
+/// Nodes are started by `begin_node()`, which returns a `NodeGuard` ending the node when
+/// dropped.
+///
+/// Array attributes are written uncompressed unless a compression policy is set by
+/// `set_compression_policy()`.
+#[derive(Debug)]
+pub struct Writer<W> {
+    /// Sink.
+    sink: W,
+    /// FBX version.
+    version: FbxVersion,
+    /// Position of the FBX header in the sink.
+    base: u64,
+    /// Current position relative to the FBX header.
+    position: u64,
+    /// Open nodes.
+    open_nodes: Vec<OpenNode>,
+    /// Compression policy of array attributes.
+    compression: Option<Box<dyn CompressionPolicy>>,
+    /// Error while ending a node in `NodeGuard::drop()`.
+    deferred_error: Option<Error>,
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Creates a new writer and writes the FBX header at the current position of the sink.
+    pub fn new(mut sink: W, version: u32) -> Result<Self> {
+        let base = sink.stream_position()?;
+        let mut writer = Writer {
+            sink: sink,
+            version: FbxVersion(version),
+            base: base,
+            position: 0,
+            open_nodes: Vec::new(),
+            compression: None,
+            deferred_error: None,
+        };
+        writer.write_bytes(MAGIC)?;
+        writer.write_bytes(&version.to_le_bytes())?;
+        Ok(writer)
+    }
+
+    /// Returns the FBX version.
+    pub fn version(&self) -> FbxVersion {
+        self.version
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Sets the compression policy of array attributes written after this call.
+    ///
+    /// Compressing arrays requires `libflate` or `flate2` feature, otherwise
+    /// `Error::CompressionUnavailable` is returned when an array is to be compressed.
+    pub fn set_compression_policy<P: CompressionPolicy + 'static>(&mut self, policy: P) {
+        self.compression = Some(Box::new(policy));
+    }
+
+    /// Removes the compression policy, so that array attributes are written uncompressed.
+    pub fn remove_compression_policy(&mut self) {
+        self.compression = None;
+    }
+
+    /// Starts a toplevel node.
+    pub fn begin_node(&mut self, name: &str) -> Result<NodeGuard<'_, W>> {
+        self.begin_node_impl(name)?;
+        Ok(NodeGuard {
+            writer: self,
+            ended: false,
+        })
+    }
+
+    /// Writes the generic node and its descendants as a toplevel node.
+    pub fn write_generic_node(&mut self, node: &GenericNode) -> Result<()> {
+        self.begin_node(&node.name)?.write_generic_node_content(node)
+    }
+
+    /// Writes the generic nodes as toplevel nodes.
+    pub fn write_generic_nodes(&mut self, nodes: &[GenericNode]) -> Result<()> {
+        for node in nodes {
+            self.write_generic_node(node)?;
+        }
+        Ok(())
+    }
+
+    /// Ends the implicit root node, writes the FBX footer with the default file ID hash, and
+    /// returns the sink.
+    pub fn finish(self) -> Result<W> {
+        let footer = FbxFooter::new(self.version.raw());
+        self.finish_with_footer(&footer)
+    }
+
+    /// Ends the implicit root node, writes the given FBX footer, and returns the sink.
+    ///
+    /// Returns `Error::UnclosedNodes` if some nodes are still open (e.g. a `NodeGuard` is
+    /// leaked).
+    pub fn finish_with_footer(mut self, footer: &FbxFooter) -> Result<W> {
+        self.take_deferred_error()?;
+        if !self.open_nodes.is_empty() {
+            return Err(Error::UnclosedNodes(self.open_nodes.len()));
+        }
+        let null_record = vec![0; self.version.node_header_len()];
+        self.write_bytes(&null_record)?;
+        footer.write_to(&mut self.sink, self.position)?;
+        Ok(self.sink)
+    }
+
+    /// Returns the error deferred by `NodeGuard::drop()`, if available.
+    fn take_deferred_error(&mut self) -> Result<()> {
+        match self.deferred_error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes the bytes at the current position.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        self.sink.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Starts a node.
+    fn begin_node_impl(&mut self, name: &str) -> Result<()> {
+        self.take_deferred_error()?;
+        if name.len() > 255 {
+            return Err(Error::NodeNameTooLong(name.to_owned()));
+        }
+        let position = self.position;
+        if let Some(parent) = self.open_nodes.last_mut() {
+            if parent.attrs_end.is_none() {
+                parent.attrs_end = Some(position);
+            }
+        }
+        let mut header = vec![0; self.version.node_header_len()];
+        *header.last_mut().expect("Node header should not be empty") = name.len() as u8;
+        self.write_bytes(&header)?;
+        self.write_bytes(name.as_bytes())?;
+        self.open_nodes.push(OpenNode {
+            name: name.to_owned(),
+            header_pos: position,
+            num_attrs: 0,
+            attrs_begin: self.position,
+            attrs_end: None,
+        });
+        Ok(())
+    }
+
+    /// Returns the compression level of the attribute decided by the policy.
+    fn policy_compression(&self, attr: BorrowedAttribute) -> Option<CompressionLevel> {
+        let policy = self.compression.as_ref()?;
+        let node = self.open_nodes.last()?;
+        let (num_elements, byte_len) = array_len(attr)?;
+        policy.compression(&ArrayInfo {
+            node_name: &node.name,
+            attribute_index: node.num_attrs as usize,
+            num_elements: num_elements,
+            byte_len: byte_len,
+        })
+    }
+
+    /// Writes an attribute of the current node.
+    ///
+    /// `compression` is ignored for non-array attributes.
+    fn attr_impl(
+        &mut self,
+        attr: BorrowedAttribute,
+        compression: Option<CompressionLevel>,
+    ) -> Result<()> {
+        self.write_encoded_attr(|buf| encode_attribute(attr, compression, buf))
+    }
+
+    /// Writes an attribute of the current node, encoded by the given function.
+    fn write_encoded_attr<F>(&mut self, encode: F) -> Result<()>
+    where
+        F: FnOnce(&mut Vec<u8>) -> Result<()>,
+    {
+        self.take_deferred_error()?;
+        if self.open_nodes.last().is_some_and(|node| node.attrs_end.is_some()) {
+            return Err(Error::AttributeAfterChildren);
+        }
+        let mut buf = Vec::new();
+        encode(&mut buf)?;
+        self.write_bytes(&buf)?;
+        if let Some(node) = self.open_nodes.last_mut() {
+            node.num_attrs += 1;
+        }
+        Ok(())
+    }
+
+    /// Ends the current node and backpatches its header.
+    ///
+    /// The null node record is written if the node has children.
+    fn end_node_impl(&mut self) -> Result<()> {
+        let node = match self.open_nodes.pop() {
+            Some(node) => node,
+            None => return Ok(()),
+        };
+        let attrs_end = match node.attrs_end {
+            Some(attrs_end) => {
+                let null_record = vec![0; self.version.node_header_len()];
+                self.write_bytes(&null_record)?;
+                attrs_end
+            },
+            None => self.position,
+        };
+        let mut header = Vec::with_capacity(self.version.node_header_len());
+        for &value in &[self.position, node.num_attrs, attrs_end - node.attrs_begin] {
+            if self.version.supports_64bit_node_headers() {
+                header.extend_from_slice(&value.to_le_bytes());
+            } else if value > u64::from(u32::MAX) {
+                return Err(Error::OffsetOverflow(value));
+            } else {
+                header.extend_from_slice(&(value as u32).to_le_bytes());
+            }
+        }
+        self.sink.seek(SeekFrom::Start(self.base + node.header_pos))?;
+        self.sink.write_all(&header)?;
+        self.sink.seek(SeekFrom::Start(self.base + self.position))?;
+        Ok(())
+    }
+}
+
+
+/// Node operations without guards, for `test_util::FbxBuilder`.
+#[cfg(any(test, feature = "test-util"))]
+impl<W: Write + Seek> Writer<W> {
+    /// Starts a node without a guard.
+    pub(crate) fn begin_node_unguarded(&mut self, name: &str) -> Result<()> {
+        self.begin_node_impl(name)
+    }
+
+    /// Writes an attribute of the current node.
+    pub(crate) fn attr_unguarded(
+        &mut self,
+        attr: BorrowedAttribute,
+        compression: Option<CompressionLevel>,
+    ) -> Result<()> {
+        self.attr_impl(attr, compression)
+    }
+
+    /// Writes a boolean array attribute of the current node with the given raw elements.
+    ///
+    /// This can write invalid elements, which `BorrowedAttribute::ArrBool` cannot.
+    pub(crate) fn raw_bool_array_unguarded(
+        &mut self,
+        raw: &[u8],
+        compression: Option<CompressionLevel>,
+    ) -> Result<()> {
+        self.write_encoded_attr(|buf| encode_array(buf, b'b', raw.len(), raw, compression))
+    }
+
+    /// Ends the current node started by `begin_node_unguarded()`.
+    pub(crate) fn end_node_unguarded(&mut self) -> Result<()> {
+        self.take_deferred_error()?;
+        self.end_node_impl()
+    }
+}
+
+
+/// Guard of an open node.
+///
+/// The node is ended when the guard is dropped.
+/// Errors on drop are returned by the next operation of the writer, so use `end_node()` to
+/// get errors immediately.
+#[derive(Debug)]
+pub struct NodeGuard<'a, W: 'a + Write + Seek> {
+    /// Writer.
+    writer: &'a mut Writer<W>,
+    /// Whether the node is already ended.
+    ended: bool,
+}
+
+impl<'a, W: 'a + Write + Seek> NodeGuard<'a, W> {
+    /// Writes an attribute of the node.
+    ///
+    /// Attributes should be written before child nodes, otherwise
+    /// `Error::AttributeAfterChildren` is returned.
+    ///
+    /// Array attributes are compressed as the compression policy of the writer decides.
+    pub fn attr<'b, A: Into<BorrowedAttribute<'b>>>(&mut self, attr: A) -> Result<&mut Self> {
+        let attr = attr.into();
+        let compression = self.writer.policy_compression(attr);
+        self.writer.attr_impl(attr, compression)?;
+        Ok(self)
+    }
+
+    /// Writes an attribute of the node, compressing arrays with the given level regardless of
+    /// the compression policy.
+    ///
+    /// Arrays are written uncompressed if `compression` is `None`.
+    pub fn attr_with_compression<'b, A: Into<BorrowedAttribute<'b>>>(
+        &mut self,
+        attr: A,
+        compression: Option<CompressionLevel>,
+    ) -> Result<&mut Self> {
+        self.writer.attr_impl(attr.into(), compression)?;
+        Ok(self)
+    }
+
+    /// Starts a child node.
+    pub fn begin_node(&mut self, name: &str) -> Result<NodeGuard<'_, W>> {
+        self.writer.begin_node(name)
+    }
+
+    /// Writes the generic node and its descendants as a child node.
+    pub fn write_generic_node(&mut self, node: &GenericNode) -> Result<()> {
+        self.writer.write_generic_node(node)
+    }
+
+    /// Ends the node.
+    pub fn end_node(mut self) -> Result<()> {
+        self.ended = true;
+        self.writer.end_node_impl()
+    }
+
+    /// Writes the attributes and children of the generic node, and ends the node.
+    fn write_generic_node_content(mut self, node: &GenericNode) -> Result<()> {
+        for attr in &node.attributes {
+            self.attr(BorrowedAttribute::from_owned(attr).ok_or(Error::UnloadedBinary)?)?;
+        }
+        for child in &node.children {
+            self.write_generic_node(child)?;
+        }
+        self.end_node()
+    }
+}
+
+impl<'a, W: 'a + Write + Seek> Drop for NodeGuard<'a, W> {
+    fn drop(&mut self) {
+        if self.ended {
+            return;
+        }
+        if let Err(err) = self.writer.end_node_impl() {
+            if self.writer.deferred_error.is_none() {
+                self.writer.deferred_error = Some(err);
+            }
+        }
+    }
+}
+
+
+/// Appends the encoded attribute (type code and value) to the buffer.
+///
+/// Arrays are compressed if `compression` is available.
+fn encode_attribute(
+    attr: BorrowedAttribute,
+    compression: Option<CompressionLevel>,
+    buf: &mut Vec<u8>,
+) -> Result<()> {
+    match attr {
+        BorrowedAttribute::Bool(v) => {
+            buf.push(b'C');
+            buf.push(if v { b'Y' } else { b'T' });
+        },
+        BorrowedAttribute::I16(v) => {
+            buf.push(b'Y');
+            buf.extend_from_slice(&v.to_le_bytes());
+        },
+        BorrowedAttribute::I32(v) => {
+            buf.push(b'I');
+            buf.extend_from_slice(&v.to_le_bytes());
+        },
+        BorrowedAttribute::I64(v) => {
+            buf.push(b'L');
+            buf.extend_from_slice(&v.to_le_bytes());
+        },
+        BorrowedAttribute::F32(v) => {
+            buf.push(b'F');
+            buf.extend_from_slice(&v.to_le_bytes());
+        },
+        BorrowedAttribute::F64(v) => {
+            buf.push(b'D');
+            buf.extend_from_slice(&v.to_le_bytes());
+        },
+        BorrowedAttribute::ArrBool(arr) => {
+            let raw = arr.iter().map(|&v| v as u8).collect::<Vec<_>>();
+            encode_array(buf, b'b', arr.len(), &raw, compression)?;
+        },
+        BorrowedAttribute::ArrI32(arr) => {
+            let raw = arr.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+            encode_array(buf, b'i', arr.len(), &raw, compression)?;
+        },
+        BorrowedAttribute::ArrI64(arr) => {
+            let raw = arr.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+            encode_array(buf, b'l', arr.len(), &raw, compression)?;
+        },
+        BorrowedAttribute::ArrF32(arr) => {
+            let raw = arr.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+            encode_array(buf, b'f', arr.len(), &raw, compression)?;
+        },
+        BorrowedAttribute::ArrF64(arr) => {
+            let raw = arr.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+            encode_array(buf, b'd', arr.len(), &raw, compression)?;
+        },
+        BorrowedAttribute::String(v) => encode_special(buf, b'S', v)?,
+        BorrowedAttribute::Binary(v) => encode_special(buf, b'R', v)?,
+    }
+    Ok(())
+}
+
+
+/// Converts the length into `u32`, or returns `Error::AttributeTooLarge`.
+fn len_u32(len: usize) -> Result<u32> {
+    if len > u32::MAX as usize {
+        return Err(Error::AttributeTooLarge(len));
+    }
+    Ok(len as u32)
+}
+
+
+/// Appends a special type attribute.
+fn encode_special(buf: &mut Vec<u8>, type_code: u8, bytes: &[u8]) -> Result<()> {
+    buf.push(type_code);
+    buf.extend_from_slice(&len_u32(bytes.len())?.to_le_bytes());
+    buf.extend_from_slice(bytes);
+    Ok(())
+}
+
+
+/// Appends an array type attribute, compressed if `compression` is available.
+fn encode_array(
+    buf: &mut Vec<u8>,
+    type_code: u8,
+    num_elements: usize,
+    raw: &[u8],
+    compression: Option<CompressionLevel>,
+) -> Result<()> {
+    buf.push(type_code);
+    buf.extend_from_slice(&len_u32(num_elements)?.to_le_bytes());
+    match compression {
+        Some(level) => {
+            let compressed = zlib_compress(raw, level)?;
+            buf.extend_from_slice(&1u32.to_le_bytes());
+            buf.extend_from_slice(&len_u32(compressed.len())?.to_le_bytes());
+            buf.extend_from_slice(&compressed);
+        },
+        None => {
+            buf.extend_from_slice(&0u32.to_le_bytes());
+            buf.extend_from_slice(&len_u32(raw.len())?.to_le_bytes());
+            buf.extend_from_slice(raw);
+        },
+    }
+    Ok(())
+}
+
+
+/// Returns the number of elements and the size in bytes of the array attribute.
+///
+/// Returns `None` for non-array attributes.
+fn array_len(attr: BorrowedAttribute) -> Option<(usize, usize)> {
+    match attr {
+        BorrowedAttribute::ArrBool(arr) => Some((arr.len(), arr.len())),
+        BorrowedAttribute::ArrI32(arr) => Some((arr.len(), arr.len() * 4)),
+        BorrowedAttribute::ArrI64(arr) => Some((arr.len(), arr.len() * 8)),
+        BorrowedAttribute::ArrF32(arr) => Some((arr.len(), arr.len() * 4)),
+        BorrowedAttribute::ArrF64(arr) => Some((arr.len(), arr.len() * 8)),
+        _ => None,
+    }
+}
+
+
+/// Compresses the given bytes with zlib.
+#[cfg(feature = "libflate")]
+fn zlib_compress(raw: &[u8], level: CompressionLevel) -> Result<Vec<u8>> {
+    let options = match level {
+        CompressionLevel::Fast => zlib::EncodeOptions::new().fixed_huffman_codes(),
+        CompressionLevel::Default | CompressionLevel::Best => zlib::EncodeOptions::new(),
+    };
+    let mut encoder = zlib::Encoder::with_options(Vec::new(), options)?;
+    encoder.write_all(raw)?;
+    Ok(encoder.finish().into_result()?)
+}
+
+/// Compresses the given bytes with zlib.
+#[cfg(all(feature = "flate2", not(feature = "libflate")))]
+fn zlib_compress(raw: &[u8], level: CompressionLevel) -> Result<Vec<u8>> {
+    let level = match level {
+        CompressionLevel::Fast => Compression::Fast,
+        CompressionLevel::Default => Compression::Default,
+        CompressionLevel::Best => Compression::Best,
+    };
+    let mut encoder = ZlibEncoder::new(Vec::new(), level);
+    encoder.write_all(raw)?;
+    Ok(encoder.finish()?)
+}
+
+/// Compresses the given bytes with zlib.
+#[cfg(not(any(feature = "flate2", feature = "libflate")))]
+fn zlib_compress(_raw: &[u8], _level: CompressionLevel) -> Result<Vec<u8>> {
+    Err(Error::CompressionUnavailable)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use parser::binary::{RootParser, Parser, Event, Attribute, ArrayAttribute};
+    use loader::binary::simple::GenericNode;
+    use test_util::synthetic_fbx;
+    use super::{Writer, Error, ArrayCompression, CompressionLevel};
+
+    /// Parses the FBX binary into generic nodes.
+    fn parse(data: Vec<u8>) -> Vec<GenericNode> {
+        let mut parser = RootParser::new(Cursor::new(data));
+        let (nodes, footer) = GenericNode::load_from_parser(&mut parser).expect("Parse error");
+        assert!(footer.is_some());
+        nodes
+    }
+
+    #[test]
+    fn test_streaming() {
+        for &version in &[7400, 7500] {
+            let mut writer = Writer::new(Cursor::new(Vec::new()), version).expect("Write error");
+            {
+                let mut objects = writer.begin_node("Objects").expect("Write error");
+                let mut model = objects.begin_node("Model").expect("Write error");
+                model.attr(1i64).expect("Write error");
+                model.attr("Cube\u{0}\u{1}Model").expect("Write error");
+                // Dropping the guard ends the node.
+                model.begin_node("Version").expect("Write error").attr(232).expect("Write error");
+                assert!(matches!(model.attr(0), Err(Error::AttributeAfterChildren)));
+                let mut vertices = model.begin_node("Vertices").expect("Write error");
+                vertices.attr(&[0.0, 1.0][..]).expect("Write error");
+                vertices.attr(true).expect("Write error");
+                vertices.end_node().expect("Write error");
+                model.end_node().expect("Write error");
+            }
+            writer.begin_node("Connections").expect("Write error");
+            let data = writer.finish().expect("Write error").into_inner();
+
+            let expected = vec![
+                GenericNode::named("Objects").with_child(
+                    GenericNode::named("Model")
+                        .with_attr(1i64)
+                        .with_attr("Cube\u{0}\u{1}Model")
+                        .with_child(GenericNode::named("Version").with_attr(232))
+                        .with_child(
+                            GenericNode::named("Vertices")
+                                .with_attr(vec![0.0, 1.0])
+                                .with_attr(true),
+                        ),
+                ),
+                GenericNode::named("Connections"),
+            ];
+            assert_eq!(parse(data), expected);
+        }
+    }
+
+    #[test]
+    fn test_unclosed_nodes() {
+        let mut writer = Writer::new(Cursor::new(Vec::new()), 7400).expect("Write error");
+        ::std::mem::forget(writer.begin_node("Objects").expect("Write error"));
+        assert!(matches!(writer.finish(), Err(Error::UnclosedNodes(1))));
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), 7400).expect("Write error");
+        writer.begin_node_unguarded("Objects").expect("Write error");
+        writer.begin_node_unguarded("Model").expect("Write error");
+        writer.end_node_unguarded().expect("Write error");
+        assert!(matches!(writer.finish(), Err(Error::UnclosedNodes(1))));
+    }
+
+    #[test]
+    fn test_write_generic_nodes() {
+        for &version in &[7400, 7500] {
+            let data = synthetic_fbx(version, 2, 3, false);
+            let nodes = parse(data.clone());
+            let mut writer = Writer::new(Cursor::new(Vec::new()), version).expect("Write error");
+            writer.write_generic_nodes(&nodes).expect("Write error");
+            let written = writer.finish().expect("Write error").into_inner();
+            assert_eq!(written, data);
+        }
+    }
+
+    /// Returns the node names and encodings of the first array attribute of each node.
+    fn array_encodings(data: Vec<u8>) -> Vec<(String, u32)> {
+        let mut parser = RootParser::new(Cursor::new(data));
+        let mut encodings = Vec::new();
+        loop {
+            match parser.next_event().expect("Parse error") {
+                Event::StartNode(mut node) => {
+                    let name = node.name.to_owned();
+                    if let Some(Attribute::Array(ArrayAttribute::I32(arr))) =
+                        node.attributes.next_attribute().expect("Parse error")
+                    {
+                        encodings.push((name, arr.encoding()));
+                    }
+                },
+                Event::EndFbx(_) => return encodings,
+                _ => {},
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "flate2", feature = "libflate"))]
+    fn test_compression_policy() {
+        let large = vec![0; 1024];
+        let policy = ArrayCompression::default()
+            .with_node("Forced", true)
+            .with_node("Never", false);
+        let mut writer = Writer::new(Cursor::new(Vec::new()), 7400).expect("Write error");
+        writer.set_compression_policy(policy);
+        {
+            let mut write = |name: &str, arr: &[i32]| {
+                writer.begin_node(name).and_then(|mut node| node.attr(arr).map(|_| ()))
+            };
+            write("Large", &large).expect("Write error");
+            write("Small", &[1, 2]).expect("Write error");
+            write("Forced", &[1, 2]).expect("Write error");
+            write("Never", &large).expect("Write error");
+        }
+        {
+            let mut node = writer.begin_node("Override").expect("Write error");
+            node.attr_with_compression(&[1, 2][..], Some(CompressionLevel::Fast))
+                .expect("Write error");
+        }
+        let data = writer.finish().expect("Write error").into_inner();
+
+        assert_eq!(
+            array_encodings(data.clone()),
+            vec![
+                ("Large".to_owned(), 1),
+                ("Small".to_owned(), 0),
+                ("Forced".to_owned(), 1),
+                ("Never".to_owned(), 0),
+                ("Override".to_owned(), 1),
+            ]
+        );
+        let nodes = parse(data);
+        assert_eq!(nodes[0].attributes[0].as_i32_slice(), Some(&large[..]));
+        assert_eq!(nodes[4].attributes[0].as_i32_slice(), Some(&[1, 2][..]));
+    }
+}
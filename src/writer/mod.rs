@@ -0,0 +1,3 @@
+//! FBX writers.
+
+pub mod binary;
@@ -0,0 +1,241 @@
+//! Round-trip fidelity tests of the binary parser and writer.
+//!
+//! Each test parses an FBX binary into generic nodes, writes them back with
+//! `writer::binary::Writer`, parses the output again, and compares the node trees.
+//!
+//! # Preserved
+//!
+//! * Node names, node order, and tree structure.
+//! * Attribute order, types, and values (arrays are compared by decoded elements).
+//! * Strings as raw bytes, including invalid UTF-8.
+//! * FBX version, and the footer when it is passed to `Writer::finish_with_footer()`.
+//!
+//! # Not preserved (allowed differences)
+//!
+//! * Array encodings: arrays are compressed as the compression policy of the writer decides.
+//! * Footer padding and zero fields, which are always written in the canonical form.
+//! * Null node records of nodes without children (they are written only after child nodes).
+//!
+//! Output of the writer is canonical, so writing the re-parsed nodes gives the same bytes.
+
+extern crate fbxcel;
+
+use std::io::Cursor;
+use fbxcel::parser::binary::{RootParser, FbxFooter, OwnedAttribute};
+use fbxcel::loader::binary::simple::GenericNode;
+use fbxcel::test_util::{load_objects, synthetic_fbx};
+use fbxcel::writer::binary::{Writer, ArrayCompression};
+
+
+/// Parses the FBX binary into toplevel nodes and the footer.
+fn parse(data: &[u8]) -> (Vec<GenericNode>, FbxFooter) {
+    let mut parser = RootParser::new(Cursor::new(data));
+    let (nodes, footer) = GenericNode::load_from_parser(&mut parser).expect("Parse error");
+    (nodes, footer.expect("Footer should be valid"))
+}
+
+
+/// Writes the toplevel nodes with the footer.
+fn write(nodes: &[GenericNode], footer: &FbxFooter, policy: Option<ArrayCompression>) -> Vec<u8> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), footer.version).expect("Write error");
+    if let Some(policy) = policy {
+        writer.set_compression_policy(policy);
+    }
+    writer.write_generic_nodes(nodes).expect("Write error");
+    writer.finish_with_footer(footer).expect("Write error").into_inner()
+}
+
+
+/// Returns the path and description of the first difference of the node trees.
+fn first_difference(left: &[GenericNode], right: &[GenericNode], path: &str) -> Option<String> {
+    if left.len() != right.len() {
+        return Some(format!("{}: {} nodes vs {} nodes", path, left.len(), right.len()));
+    }
+    for (index, (l, r)) in left.iter().zip(right).enumerate() {
+        let path = format!("{}/{}[{}]", path, l.name, index);
+        if l.name != r.name {
+            return Some(format!("{}: node name {:?} vs {:?}", path, l.name, r.name));
+        }
+        if l.attributes.len() != r.attributes.len() {
+            return Some(format!(
+                "{}: {} attributes vs {} attributes",
+                path,
+                l.attributes.len(),
+                r.attributes.len()
+            ));
+        }
+        for (attr_index, (la, ra)) in l.attributes.iter().zip(&r.attributes).enumerate() {
+            if la != ra {
+                return Some(format!("{}: attribute {}: {:?} vs {:?}", path, attr_index, la, ra));
+            }
+        }
+        if let Some(diff) = first_difference(&l.children, &r.children, &path) {
+            return Some(diff);
+        }
+    }
+    None
+}
+
+
+/// Checks the round trip of the FBX binary and returns the written binary.
+fn assert_round_trip(data: &[u8], policy: Option<ArrayCompression>) -> Vec<u8> {
+    let (nodes, footer) = parse(data);
+    let written = write(&nodes, &footer, policy.clone());
+    let (reparsed, reparsed_footer) = parse(&written);
+
+    if let Some(diff) = first_difference(&nodes, &reparsed, "") {
+        panic!("Round trip changed the nodes: {}", diff);
+    }
+    assert_eq!(reparsed_footer, footer);
+    assert_eq!(write(&reparsed, &reparsed_footer, policy), written, "Output should be canonical");
+    written
+}
+
+
+#[test]
+fn test_synthetic() {
+    for &version in &[7400, 7500] {
+        let data = synthetic_fbx(version, 4, 16, false);
+        assert_eq!(assert_round_trip(&data, None), data);
+    }
+}
+
+
+#[test]
+#[cfg(any(feature = "flate2", feature = "libflate"))]
+fn test_compressed_arrays() {
+    for &version in &[7400, 7500] {
+        let data = synthetic_fbx(version, 4, 64, true);
+        let uncompressed = assert_round_trip(&data, None);
+        assert!(uncompressed.len() > data.len());
+        assert_round_trip(&data, Some(ArrayCompression::default()));
+    }
+}
+
+
+#[test]
+fn test_footer() {
+    let (nodes, _) = parse(&synthetic_fbx(7400, 1, 3, false));
+    let footer = FbxFooter::with_file_id_hash(7400, [0x42; 16]);
+    let data = write(&nodes, &footer, None);
+    assert_eq!(parse(&data).1, footer);
+    assert_round_trip(&data, None);
+}
+
+
+/// Writes a node with the given encoded attributes to the FBX binary being built by hand.
+///
+/// Children are written by `children`, and the null node record is written after them if
+/// `null_record` is true, even if the node has no children.
+fn push_node<F>(
+    buf: &mut Vec<u8>,
+    version: u32,
+    name: &str,
+    attrs: (u64, &[u8]),
+    children: F,
+    null_record: bool,
+) where
+    F: FnOnce(&mut Vec<u8>),
+{
+    let field_len = if version >= 7500 { 8 } else { 4 };
+    let header_pos = buf.len();
+    buf.extend(vec![0; field_len * 3]);
+    buf.push(name.len() as u8);
+    buf.extend_from_slice(name.as_bytes());
+    buf.extend_from_slice(attrs.1);
+    children(buf);
+    if null_record {
+        buf.extend(vec![0; field_len * 3 + 1]);
+    }
+    let fields = [buf.len() as u64, attrs.0, attrs.1.len() as u64];
+    for (i, &value) in fields.iter().enumerate() {
+        let pos = header_pos + i * field_len;
+        buf[pos..pos + field_len].copy_from_slice(&value.to_le_bytes()[..field_len]);
+    }
+}
+
+
+/// Builds the FBX binary by hand.
+///
+/// If `leaf_null_records` is true, null node records are written after nodes without
+/// children.
+/// If `padded` is false, the footer is written without padding, as some exporters do.
+fn hand_built_fbx(version: u32, leaf_null_records: bool, padded: bool) -> Vec<u8> {
+    let mut buf = b"Kaydara FBX Binary  \x00\x1a\x00".to_vec();
+    buf.extend_from_slice(&version.to_le_bytes());
+    let leaf_attr = [b'I', 42, 0, 0, 0];
+    push_node(&mut buf, version, "Leaf", (1, &leaf_attr), |_| {}, leaf_null_records);
+    push_node(&mut buf, version, "Empty", (0, &[]), |_| {}, leaf_null_records);
+    push_node(&mut buf, version, "Parent", (0, &[]), |buf| {
+        let child_attr = [b'S', 2, 0, 0, 0, b'x', b'y'];
+        push_node(buf, version, "Child", (1, &child_attr), |_| {}, leaf_null_records);
+    }, true);
+    buf.extend(vec![0; if version >= 7500 { 25 } else { 13 }]);
+
+    let footer = FbxFooter::new(version);
+    let position = buf.len() as u64;
+    if padded {
+        footer.write_to(&mut buf, position).expect("Write error");
+    } else {
+        buf.extend_from_slice(footer.file_id_hash());
+        buf.extend_from_slice(&[0; 4]);
+        buf.extend_from_slice(&version.to_le_bytes());
+        buf.extend_from_slice(&[0; 120]);
+        buf.extend_from_slice(footer.magic());
+    }
+    buf
+}
+
+
+/// Non-canonical inputs (footer without padding, and null node records after leaf nodes)
+/// are accepted and written back in the canonical form.
+#[test]
+fn test_non_canonical_input() {
+    for &version in &[7400, 7500] {
+        let canonical = hand_built_fbx(version, false, true);
+        assert_eq!(assert_round_trip(&canonical, None), canonical);
+        assert_eq!(
+            parse(&canonical).0,
+            vec![
+                GenericNode::named("Leaf").with_attr(42),
+                GenericNode::named("Empty"),
+                GenericNode::named("Parent")
+                    .with_child(GenericNode::named("Child").with_attr("xy")),
+            ]
+        );
+        for &(leaf_null_records, padded) in &[(false, false), (true, true), (true, false)] {
+            let data = hand_built_fbx(version, leaf_null_records, padded);
+            assert_ne!(data, canonical);
+            assert_eq!(
+                assert_round_trip(&data, None),
+                canonical,
+                "leaf_null_records = {}, padded = {}",
+                leaf_null_records,
+                padded
+            );
+        }
+    }
+}
+
+
+#[test]
+fn test_invalid_utf8_string() {
+    let node = GenericNode::named("Comment")
+        .with_attr(OwnedAttribute::String(Err(vec![0x66, 0xff, 0x78])));
+    let data = write(&[node], &FbxFooter::new(7400), None);
+    assert_round_trip(&data, None);
+}
+
+
+#[test]
+fn test_typed_scene() {
+    let fbx = load_objects(synthetic_fbx(7400, 4, 16, false));
+    let nodes = fbx.to_generic_nodes();
+    let data = write(&nodes, &FbxFooter::new(fbx.version), None);
+    let reloaded = load_objects(data);
+
+    // Lowering loses property type names, so compare the lowered nodes of both scenes.
+    let diff = first_difference(&reloaded.to_generic_nodes(), &nodes, "");
+    assert!(diff.is_none(), "Read-modify-write changed the scene: {:?}", diff);
+    assert_eq!(reloaded.connections, fbx.connections);
+}